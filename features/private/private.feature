@@ -0,0 +1,20 @@
+
+Feature: Private API feature
+
+  Scenario: If I request open orders from a private API, it returns correct list
+    Given I have some properties concerning a private API
+    When I request all open orders
+    Then the open orders list is presented to me
+    And the "OpenOrders" responses contain only documented enum values
+
+  Scenario: If I resend a request with an already used nonce, the private API rejects it
+    Given I have some properties concerning a private API
+    When I request the "OpenOrders" endpoint
+    And I freeze the signed request
+    And I resend the frozen request
+    Then the request is rejected with error "EAPI:Invalid nonce"
+
+  Scenario: If I request open orders from a private API, it is encoded in UTF-8 with ASCII txids
+    Given I have some properties concerning a private API
+    When I request the "OpenOrders" endpoint
+    Then the "OpenOrders" response is correctly encoded
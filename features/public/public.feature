@@ -0,0 +1,114 @@
+
+Feature: Public API feature
+
+  Scenario: If I request sever time from public api, it returns correct format
+    Given I have link to a public api endpoint returning server time
+    When I request server time
+    Then the server time format is correct
+
+  Scenario: If I request info about asset pair from public api, it returns correct data
+    Given I have link to a public api endpoint returning asset pair info
+    When I request asset pair info
+    Then the asset pair info format is correct
+
+  Scenario: If I request info about asset pair from public api, it fits within the size limit
+    Given I have link to a public api endpoint returning asset pair info
+    When I request asset pair info
+    Then the "AssetPair" response of at most 1048576 bytes has correct format
+
+  Scenario: If I request info about asset pair from public api, its statuses are documented
+    Given I have link to a public api endpoint returning asset pair info
+    When I request asset pair info
+    Then the asset pair info format is correct
+    And the "AssetPair" responses contain only documented enum values
+
+  Scenario: If I request info about asset pair from public api, its numbers have no locale artifacts
+    Given I have link to a public api endpoint returning asset pair info
+    When I request asset pair info
+    Then the numeric strings of the response contain no locale artifacts
+
+  Scenario: If I request info about asset pair from public api, it is encoded in UTF-8 with ASCII pair codes
+    Given I have link to a public api endpoint returning asset pair info
+    When I request asset pair info
+    Then the "AssetPair" response is correctly encoded
+
+  Scenario: If I request server time from public api, the response has secure headers
+    Given I have link to a public api endpoint returning server time
+    When I request server time
+    Then the response has secure headers
+
+  Scenario: If a web frontend requests server time from another origin, the CORS preflight is allowed
+    Given I have link to a public api endpoint returning server time
+    When I send a CORS preflight request
+    Then the CORS preflight is allowed
+
+  Scenario: If I offer HTTP/2, the gateway selects it through ALPN
+    Given I have link to a public api endpoint returning server time
+    And the client prefers HTTP/2
+    When I request server time
+    Then the response is served over HTTP/2
+
+  Scenario: If I force HTTP/1.1, the gateway still serves the request
+    Given I have link to a public api endpoint returning server time
+    And the client forces HTTP/1.1
+    When I request server time
+    Then the response is served over HTTP/1.1
+
+  Scenario: If I request server time repeatedly, the gateway keeps the connection alive
+    Given I have link to a public api endpoint returning server time
+    When I request it 3 times with the same client
+    Then the requests open 1 connection
+
+  Scenario: If I request the asset pair endpoint named by the profile, it returns correct data
+    Given I have link to the public "${ASSET_PAIR_ENDPOINT}" endpoint
+    When I request the "${ASSET_PAIR_ENDPOINT}" endpoint
+    Then the "${ASSET_PAIR_ENDPOINT}" response format is correct
+
+  Scenario: If I request info about a single asset pair from public api, it returns correct data
+    When I request AssetPair for XBTUSD
+    Then the "AssetPair" response format is correct
+
+  Scenario: If I connect to public api, it presents a certificate valid for at least two weeks
+    Given I have link to a public api endpoint returning server time
+    Then the TLS certificate of the API expires in more than 14 days
+
+  Scenario: If I request server time over IPv4 and IPv6, both address families work
+    Given I have link to a public api endpoint returning server time
+    Then the endpoint responds over both IPv4 and IPv6
+
+  Scenario: If I request candles of an asset pair, they are ordered, gapless and within their range
+    When I request OHLC for XBTUSD
+    Then the OHLC data is internally consistent
+
+  Scenario: If I request the order book of an asset pair, its levels are sorted, uncrossed and on the tick size
+    When I request Depth for XBTUSD
+    Then the order book is well-formed
+
+  Scenario: If I request the ticker of an asset pair, its prices and volumes are consistent
+    When I request Ticker for XBTUSD
+    Then the ticker fields are consistent with each other
+
+  Scenario: If I request the ticker of an asset pair, its fields meet the declared expectations
+    When I request Ticker for XBTUSD
+    Then the response meets the expectations
+      """
+      - { path: error, op: empty }
+      - { path: result.*.a, op: length, value: 3 }
+      - { path: result.*.c.0, op: gt, value: 0 }
+      - { path: result.*.t.0, op: ge, value: 0 }
+      """
+
+  Scenario: If I poll recent trades of an asset pair, the feed is alive
+    When I request Trades for XBTUSD
+    Then the newest entry of the feed is at most 15 minutes behind server time
+
+  Scenario: If I continue recent trades from their cursor, no trade is repeated or missed
+    When I request Trades for XBTUSD
+    Then the next batch follows the cursor of the response without overlap or gap
+
+  Scenario: If I poll spreads of an asset pair twice, the cursor of the feed advances
+    When I request Spread for XBTUSD
+    Then the feed cursor advances when polled again after 10 seconds
+
+  Scenario: If asset pairs are preloaded as reference data, they carry trading rules
+    Then the reference data lists XBTUSD with its trading rules
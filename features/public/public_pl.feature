@@ -0,0 +1,11 @@
+# language: pl
+Funkcja: Publiczne API
+
+  Scenariusz: Jeśli pytam publiczne API o czas serwera, zwraca go w poprawnym formacie
+    Zakładając, że mam link do publicznego endpointu zwracającego czas serwera
+    Kiedy pytam o czas serwera
+    Wtedy format czasu serwera jest poprawny
+
+  Scenariusz: Jeśli pytam publiczne API o parę walut, zwraca poprawne dane
+    Kiedy pytam o AssetPair dla XBTUSD
+    Wtedy format odpowiedzi "AssetPair" jest poprawny
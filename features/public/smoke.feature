@@ -0,0 +1,4 @@
+Feature: Smoke check of configured endpoints
+
+  Scenario: If I request every configured endpoint, none of them is unreachable
+    Then all configured endpoints are reachable
@@ -0,0 +1,34 @@
+Feature: Changes between two versions of a schema
+
+  Scenario Outline: Narrowed types and enumerations are breaking, widened ones are not
+    Given the old schema is '<old>'
+    And the new schema is '<new>'
+    When I compare the schemas
+    Then "schema" is reported as a <kind> change: "<description>"
+
+    Examples:
+      | old                                        | new                                        | kind         | description                              |
+      | {"type": "string"}                         | {"type": ["string", "null"]}               | non-breaking | type widened                             |
+      | {"type": ["string", "null"]}               | {"type": "string"}                         | breaking     | type narrowed                            |
+      | {"type": "integer"}                        | {"type": "number"}                         | non-breaking | type widened                             |
+      | {"type": "number"}                         | {"type": "integer"}                        | breaking     | type narrowed                            |
+      | {"enum": ["online", "cancel_only"]}        | {"enum": ["online"]}                       | breaking     | enumeration narrowed                     |
+      | {"enum": ["online"]}                       | {"enum": ["online", "cancel_only"]}        | non-breaking | enumeration widened                      |
+      | {"type": "string"}                         | {"type": "string", "enum": ["online"]}     | breaking     | values were restricted to an enumeration |
+
+  Scenario: Removed fields and fields no longer required are breaking, added and newly required ones are not
+    Given the old schema is '{"required": ["altname"], "properties": {"altname": {"type": "string"}, "base": {"type": "string"}}}'
+    And the new schema is '{"required": ["base"], "properties": {"base": {"type": "string"}, "wsname": {"type": "string"}}}'
+    When I compare the schemas
+    Then "schema" is reported as a breaking change: "field altname is no longer required"
+    And "schema.altname" is reported as a breaking change: "field was removed"
+    And "schema" is reported as a non-breaking change: "field base became required"
+    And "schema.wsname" is reported as a non-breaking change: "field was added"
+    And 4 changes are reported
+
+  Scenario: Changes of nested fields are reported at their location
+    Given the old schema is '{"properties": {"result": {"additionalProperties": {"properties": {"status": {"enum": ["online", "delisted"]}}}}}}'
+    And the new schema is '{"properties": {"result": {"additionalProperties": {"properties": {"status": {"enum": ["online"]}}}}}}'
+    When I compare the schemas
+    Then "schema.result[additionalProperties].status" is reported as a breaking change: "enumeration narrowed"
+    And 1 changes are reported
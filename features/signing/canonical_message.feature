@@ -0,0 +1,32 @@
+Feature: Canonical message signed with the API secret
+
+  Scenario Outline: If the payload is not hashed, request components are signed directly
+    Given the API signs method "<method>", host "<host>" and path "<path>" with payload hash "none"
+    When I build the message for nonce "1616492376594" and payload "nonce=1616492376594"
+    Then the message is "<message>"
+
+    Examples:
+      | method | host  | path  | message                                                                          |
+      | false  | false | false | 1616492376594nonce=1616492376594                                                 |
+      | false  | false | true  | 1616492376594/0/private/Balancenonce=1616492376594                               |
+      | true   | false | true  | 1616492376594POST/0/private/Balancenonce=1616492376594                           |
+      | true   | true  | true  | 1616492376594POSTapi.example.com/0/private/Balancenonce=1616492376594            |
+
+  Scenario Outline: If the payload is hashed, request components prefix the digest
+    Given the API signs method "<method>", host "<host>" and path "<path>" with payload hash "<hash>"
+    When I build the message for nonce "1616492376594" and payload "nonce=1616492376594"
+    Then the message in hex is "<message>"
+
+    Examples:
+      | method | host  | path  | hash   | message                                                                                                                                                                                    |
+      | false  | false | true  | sha256 | 2f302f707269766174652f42616c616e636509fcef6b8f1c848c916f1c87befa425e755aefb54f968360a557c5f573b99275                                                                                       |
+      | false  | false | false | sha256 | 09fcef6b8f1c848c916f1c87befa425e755aefb54f968360a557c5f573b99275                                                                                                                           |
+      | true   | false | true  | sha256 | 504f53542f302f707269766174652f42616c616e636509fcef6b8f1c848c916f1c87befa425e755aefb54f968360a557c5f573b99275                                                                               |
+      | true   | true  | true  | sha256 | 504f53546170692e6578616d706c652e636f6d2f302f707269766174652f42616c616e636509fcef6b8f1c848c916f1c87befa425e755aefb54f968360a557c5f573b99275                                                 |
+      | false  | false | true  | sha512 | 2f302f707269766174652f42616c616e636523837b88d6f19bfcc9f0549e0de02208cb73083ea5d24d3f3519e7215d69f7dd5f5f4bfbaa70fff732d7275c28f2d95f4a3ad1004696fa3982b7f91da2f4375a |
+
+  Scenario: Signed headers follow the endpoint path, with names in lowercase
+    Given the API signs method "false", host "false" and path "true" with payload hash "none"
+    And the API signs header "X-Subaccount" with value "trading-1"
+    When I build the message for nonce "1616492376594" and payload "nonce=1616492376594"
+    Then the message is "1616492376594/0/private/Balancex-subaccount:trading-1nonce=1616492376594"
@@ -0,0 +1,231 @@
+use crate::repl::Exchange;
+use crate::scaffold;
+use api_verify::resources::config::{Auth, EndpointRegistry};
+use api_verify::resources::snapshot::RequestTranscript;
+use api_verify::resources::{naming, report_encryption};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::{fs, io, process};
+
+/// Request captured by the repl or by a run, reduced to what a drafted scenario needs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapturedRequest {
+    /// Name of the endpoint in the registry
+    pub endpoint: String,
+    pub status: Option<u16>,
+    pub parameters: Vec<(String, String)>,
+}
+
+impl CapturedRequest {
+    /// Captures the exchange of the repl; exchanges with paths outside of the registry are skipped.
+    pub fn from_exchange(exchange: &Exchange) -> Option<Self> {
+        let captured = exchange.endpoint.as_ref().map(|endpoint| Self {
+            endpoint: endpoint.clone(),
+            status: Some(exchange.status),
+            parameters: exchange.parameters.clone(),
+        });
+        captured
+    }
+
+    /// Captures the request from the transcript of a scenario snapshot;
+    /// only parameters sent in the query are known for these requests.
+    pub fn from_transcript(transcript: &RequestTranscript) -> Self {
+        let parameters = url::Url::parse(&transcript.url)
+            .map(|url| url.query_pairs().into_owned().collect())
+            .unwrap_or_default();
+        Self {
+            endpoint: transcript.endpoint.clone(),
+            status: transcript.status,
+            parameters,
+        }
+    }
+}
+
+/// Only the requests of a scenario snapshot are read from it.
+#[derive(Deserialize)]
+struct SnapshotRequests {
+    requests: Vec<RequestTranscript>,
+}
+
+/// Reads captured requests from an exchange saved by the repl or a scenario snapshot of a run.
+///
+/// # Arguments
+///
+/// * `path` - Json file in "./snapshots/repl" or "results/snapshots"
+///
+pub fn read_captures(path: &Path) -> io::Result<Vec<CapturedRequest>> {
+    let content = report_encryption::read_to_string(path)
+        .map_err(|err| io::Error::new(err.kind(), format!("Cannot read {:?}: {}", path, err)))?;
+    if let Ok(exchange) = serde_json::from_str::<Exchange>(&content) {
+        return Ok(CapturedRequest::from_exchange(&exchange)
+            .into_iter()
+            .collect());
+    }
+    let snapshot: SnapshotRequests = serde_json::from_str(&content).map_err(|err| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{:?} is neither an exchange nor a snapshot: {}", path, err),
+        )
+    })?;
+    let captures = snapshot
+        .requests
+        .iter()
+        .map(CapturedRequest::from_transcript)
+        .collect();
+    Ok(captures)
+}
+
+/// Drafts feature files from captured requests, one scenario per distinct request, in
+/// "./features/public/<name>.feature" and "./features/private/<name>.feature".
+/// Existing feature files are never overwritten.
+///
+/// # Arguments
+///
+/// * `feature_name` - Name of the feature in upper camel case, e.g. OrderLifecycle
+/// * `captures` - Requests in the order they were sent
+/// * `endpoints` - Registry of the endpoints the requests were sent to
+///
+pub fn export_feature(
+    feature_name: &str,
+    captures: &[CapturedRequest],
+    endpoints: &EndpointRegistry,
+) -> io::Result<Vec<PathBuf>> {
+    if !feature_name
+        .chars()
+        .all(|character| character.is_ascii_alphanumeric())
+        || feature_name.is_empty()
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Feature name should be alphanumeric, e.g. OrderLifecycle; got: {}",
+                feature_name
+            ),
+        ));
+    }
+    let mut distinct: Vec<&CapturedRequest> = Vec::new();
+    for captured in captures {
+        if !distinct.iter().any(|known| {
+            known.endpoint == captured.endpoint && known.parameters == captured.parameters
+        }) {
+            distinct.push(captured);
+        }
+    }
+
+    let mut created = Vec::new();
+    for auth in [Auth::Public, Auth::Private] {
+        let mut scenarios = Vec::new();
+        for captured in &distinct {
+            let endpoint = endpoints
+                .get(&captured.endpoint)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err.to_string()))?;
+            if endpoint.auth == auth {
+                scenarios.push(draft_scenario(captured, auth, endpoints));
+            }
+        }
+        if scenarios.is_empty() {
+            continue;
+        }
+        let api_type = if auth == Auth::Private {
+            "private"
+        } else {
+            "public"
+        };
+        let path = format!(
+            "./features/{}/{}.feature",
+            api_type,
+            naming::to_snake_case(feature_name)
+        );
+        let content = format!(
+            "Feature: {}\n  Drafted from captured traffic; review the scenarios before relying on them.\n{}",
+            feature_name,
+            scenarios.concat()
+        );
+        write_new_file(&path, &content)?;
+        created.push(PathBuf::from(path));
+    }
+    if created.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "None of the captured requests was sent to a configured endpoint",
+        ));
+    }
+    Ok(created)
+}
+
+/// Writes the feature to a temporary file next to its destination and renames it into place
+/// only once complete, so a failed export leaves no partial feature behind.
+fn write_new_file(path: &str, content: &str) -> io::Result<()> {
+    if Path::new(path).exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("Cannot create {}: file exists", path),
+        ));
+    }
+    let temp_path = format!("{}.{}.tmp", path, process::id());
+    let written =
+        scaffold::create_new_file(&temp_path, content).and_then(|_| fs::rename(&temp_path, path));
+    if written.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    written
+}
+
+/// Scenario verifying the captured request with the generic steps;
+/// its status and parameters not covered by the steps are noted in comments.
+fn draft_scenario(captured: &CapturedRequest, auth: Auth, endpoints: &EndpointRegistry) -> String {
+    let name = &captured.endpoint;
+    let mut lines = vec![format!(
+        "# Schema: ./schemas/{}",
+        endpoints.schema_file(name)
+    )];
+    if let Some(status) = captured.status {
+        lines.push(format!("# Captured response status: {}", status));
+    }
+    let pair = match captured.parameters.as_slice() {
+        [(parameter, value)] if parameter == "pair" && auth == Auth::Public => Some(value),
+        _ => None,
+    };
+    if pair.is_none() && !captured.parameters.is_empty() {
+        let parameters: Vec<String> = captured
+            .parameters
+            .iter()
+            .map(|(parameter, value)| format!("{}={}", parameter, value))
+            .collect();
+        lines.push(format!(
+            "# Parameters not covered by the steps: {}",
+            parameters.join(" ")
+        ));
+    }
+    match (auth, pair) {
+        (Auth::Public, Some(pair)) => {
+            lines.push(format!("When I request {} for {}", name, pair));
+        }
+        (Auth::Public, None) => {
+            lines.push(format!(
+                "Given I have link to the public \"{}\" endpoint",
+                name
+            ));
+            lines.push(format!("When I request the \"{}\" endpoint", name));
+        }
+        (Auth::Private, _) => {
+            lines.push("Given I have some properties concerning a private API".to_owned());
+            lines.push(format!("When I request the \"{}\" endpoint", name));
+        }
+    }
+    lines.push(format!("Then the \"{}\" response format is correct", name));
+
+    let title = match pair {
+        Some(pair) => format!("{} for {}", name, pair),
+        None => name.clone(),
+    };
+    let scenario = format!(
+        "\n  Scenario: Requesting {} returns correct format\n{}",
+        title,
+        lines
+            .iter()
+            .map(|line| format!("    {}\n", line))
+            .collect::<String>()
+    );
+    scenario
+}
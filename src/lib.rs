@@ -0,0 +1,4 @@
+//! Steps and resources of the api verification suite,
+//! so they can be reused by cucumber binaries of other crates.
+pub mod resources;
+pub mod steps;
@@ -0,0 +1,225 @@
+mod feature_export;
+mod openapi_smoke;
+mod repl;
+mod report_bundle;
+mod report_manifest;
+mod report_merge;
+mod scaffold;
+mod watch;
+
+use api_verify::resources::config::{ConfigError, EndpointRegistry, Profile};
+use api_verify::resources::{report_encryption, schema_diff};
+use clap::{Parser, Subcommand};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::process;
+use std::time::Duration;
+
+/// Tools supporting development of the api verification suite.
+#[derive(Parser)]
+#[clap(name = "api-verify")]
+struct Cli {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generates files needed to cover a new part of API
+    #[clap(subcommand)]
+    Scaffold(ScaffoldTarget),
+    /// Works with json schemas of responses
+    #[clap(subcommand)]
+    Schema(SchemaCommand),
+    /// Turns captured traffic into files of the suite
+    #[clap(subcommand)]
+    Export(ExportTarget),
+    /// Works with results of runs of the suites
+    #[clap(subcommand)]
+    Report(ReportCommand),
+    /// Sends requests interactively, validating responses and saving them as scenario skeletons
+    Repl,
+    /// Runs the suites repeatedly, as a standalone API monitor
+    Watch {
+        /// Delay between starts of consecutive runs, e.g. 30s, 5m or 1h
+        #[clap(long, parse(try_from_str = watch::parse_interval))]
+        interval: Duration,
+        /// Suite to run; may be repeated
+        #[clap(long = "suite", default_values = &["public", "private"])]
+        suites: Vec<String>,
+        /// Port of the HTTP health endpoint
+        #[clap(long, default_value = "80")]
+        health_port: u16,
+        /// Link the status is posted to as json after every run
+        #[clap(long)]
+        notify_url: Option<String>,
+        /// Seed of scenario order and randomized data, to reproduce a previous run
+        #[clap(long)]
+        seed: Option<u64>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ScaffoldTarget {
+    /// Generates feature file, placeholder schema and config entry for a new endpoint
+    Endpoint {
+        /// Name of the endpoint as present in its path, e.g. OpenPositions
+        name: String,
+        /// Marks the endpoint as a part of private API
+        #[clap(long)]
+        private: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ExportTarget {
+    /// Drafts a feature file from exchanges saved by the repl or scenario snapshots of a run
+    Feature {
+        /// Name of the feature in upper camel case, e.g. OrderLifecycle
+        name: String,
+        /// Json files with the captured requests
+        #[clap(required = true)]
+        captures: Vec<PathBuf>,
+    },
+    /// Generates a smoke suite calling every GET operation of an OpenAPI spec with its examples
+    Smoke {
+        /// OpenAPI 3 spec with examples of parameters, in json or yaml
+        spec: PathBuf,
+        /// Name of the generated test plan in "./features/public"
+        #[clap(default_value = "openapi_smoke")]
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ReportCommand {
+    /// Merges json results of shards or profiles into single json, JUnit and HTML reports
+    Merge {
+        /// Json results written by the suites, e.g. results/public-shard-1-of-2.json
+        #[clap(required = true)]
+        results: Vec<PathBuf>,
+        /// Catalogue the merged reports are written to
+        #[clap(short, long)]
+        output: PathBuf,
+    },
+    /// Decrypts a report artifact written with REPORT_ENCRYPTION_KEY set
+    Decrypt {
+        /// Encrypted artifact, e.g. results/public.json
+        artifact: PathBuf,
+        /// File the decrypted artifact is written to
+        #[clap(short, long)]
+        output: PathBuf,
+    },
+    /// Writes a manifest of the artifacts with their SHA-256 digests, signed with REPORT_SIGNING_KEY (Ed25519)
+    Sign {
+        /// Catalogue of the artifacts, e.g. results
+        dir: PathBuf,
+    },
+    /// Packages features, schemas, redacted configuration and results into a timestamped ZIP with a manifest
+    Bundle {
+        /// Catalogue with results of the run, e.g. results
+        results: PathBuf,
+        /// Catalogue the archive is written to
+        #[clap(short, long)]
+        output: PathBuf,
+    },
+    /// Verifies that the artifacts match their signed manifest
+    Verify {
+        /// Catalogue of the artifacts, with manifest.json and manifest.json.sig
+        dir: PathBuf,
+        /// Base64 encoded Ed25519 public key of the signer
+        #[clap(long)]
+        public_key: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum SchemaCommand {
+    /// Classifies changes between two catalogues of schemas as breaking or non-breaking
+    Diff {
+        /// Catalogue with the current schemas
+        old: PathBuf,
+        /// Catalogue with the updated schemas
+        new: PathBuf,
+    },
+}
+
+/// Reads the captured requests and drafts the feature against the registry of the profile.
+fn export_feature(name: &str, capture_files: &[PathBuf]) -> io::Result<()> {
+    let profile = Profile::load().map_err(config_error)?;
+    let endpoints = EndpointRegistry::load(&profile.api_version).map_err(config_error)?;
+    let mut captures = Vec::new();
+    for capture_file in capture_files {
+        captures.extend(feature_export::read_captures(capture_file)?);
+    }
+    for path in feature_export::export_feature(name, &captures, &endpoints)? {
+        println!("Created {}", path.display());
+    }
+    Ok(())
+}
+
+/// Decrypts the artifact with the key from REPORT_ENCRYPTION_KEY.
+fn decrypt_artifact(artifact: &PathBuf, output: &PathBuf) -> io::Result<()> {
+    let content = report_encryption::open(fs::read(artifact)?)?;
+    fs::write(output, content)?;
+    println!("Decrypted {} into {}", artifact.display(), output.display());
+    Ok(())
+}
+
+fn config_error(err: ConfigError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, err.to_string())
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Scaffold(ScaffoldTarget::Endpoint { name, private }) => {
+            scaffold::scaffold_endpoint(&name, private)
+        }
+        Command::Schema(SchemaCommand::Diff { old, new }) => {
+            schema_diff::diff_schema_dirs(&old, &new)
+        }
+        Command::Export(ExportTarget::Feature { name, captures }) => {
+            export_feature(&name, &captures)
+        }
+        Command::Export(ExportTarget::Smoke { spec, name }) => {
+            openapi_smoke::export_smoke(&spec, &name)
+        }
+        Command::Report(ReportCommand::Merge { results, output }) => {
+            report_merge::merge_reports(&results, &output)
+        }
+        Command::Report(ReportCommand::Decrypt { artifact, output }) => {
+            decrypt_artifact(&artifact, &output)
+        }
+        Command::Report(ReportCommand::Sign { dir }) => report_manifest::sign_dir(&dir),
+        Command::Report(ReportCommand::Bundle { results, output }) => {
+            report_bundle::bundle_evidence(&results, &output)
+        }
+        Command::Report(ReportCommand::Verify { dir, public_key }) => {
+            report_manifest::verify_dir(&dir, &public_key)
+        }
+        Command::Repl => {
+            tokio::runtime::Runtime::new().and_then(|runtime| runtime.block_on(repl::repl()))
+        }
+        Command::Watch {
+            interval,
+            suites,
+            health_port,
+            notify_url,
+            seed,
+        } => tokio::runtime::Runtime::new().and_then(|runtime| {
+            runtime.block_on(watch::watch(
+                interval,
+                &suites,
+                health_port,
+                notify_url.as_deref(),
+                seed,
+            ))
+        }),
+    };
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
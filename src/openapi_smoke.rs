@@ -0,0 +1,289 @@
+use crate::scaffold;
+use api_verify::resources::config::CONFIG_FILE;
+use api_verify::resources::test_plan::{PlanRequest, PlanTest, TestPlan};
+use api_verify::resources::{naming, openapi};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// GET operation of the spec with examples of all its required parameters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmokeOperation {
+    /// Name of the endpoint in the registry, derived from the operation id or the path
+    pub endpoint: String,
+    /// Path with path parameters replaced by their examples, including the base path of the server
+    pub path: String,
+    pub query: BTreeMap<String, String>,
+    /// Documented successful status, e.g. 200
+    pub status: u16,
+    /// Json schema of the successful response, with references to components inlined
+    pub schema: Value,
+}
+
+/// Reads the spec, written either as json or as yaml.
+///
+/// # Arguments
+///
+/// * `path` - OpenAPI 3 spec
+///
+pub fn read_spec(path: &Path) -> io::Result<Value> {
+    let content = fs::read_to_string(path)
+        .map_err(|err| io::Error::new(err.kind(), format!("Cannot read {:?}: {}", path, err)))?;
+    openapi::parse_spec(&content).map_err(|err| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{:?} is not a valid OpenAPI spec: {}", path, err),
+        )
+    })
+}
+
+/// Example of the parameter: its own, the one of its schema or the first of its named examples.
+fn example_of(parameter: &Value) -> Option<String> {
+    let example = parameter
+        .get("example")
+        .or_else(|| parameter.pointer("/schema/example"))
+        .or_else(|| {
+            parameter
+                .get("examples")?
+                .as_object()?
+                .values()
+                .next()?
+                .get("value")
+        })?;
+    match example {
+        Value::String(text) => Some(text.clone()),
+        Value::Array(elements) => Some(
+            elements
+                .iter()
+                .map(|element| element.as_str().map_or(element.to_string(), str::to_owned))
+                .collect::<Vec<_>>()
+                .join(","),
+        ),
+        other => Some(other.to_string()),
+    }
+}
+
+/// Name of the endpoint in upper camel case, e.g. "getTicker" or "/public/Ticker" become "GetTicker" and "Ticker".
+fn endpoint_name_of(operation: &Value, path: &str) -> String {
+    let source = operation
+        .get("operationId")
+        .and_then(Value::as_str)
+        .unwrap_or_else(|| {
+            path.rsplit('/')
+                .find(|segment| !segment.is_empty() && !segment.starts_with('{'))
+                .unwrap_or("Root")
+        });
+    source
+        .split(|character: char| !character.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut characters = word.chars();
+            let first = characters.next().unwrap().to_ascii_uppercase();
+            std::iter::once(first).chain(characters).collect::<String>()
+        })
+        .collect()
+}
+
+/// Whether the operation, or the whole spec when the operation does not say, requires credentials;
+/// an empty requirement among the alternatives makes them optional.
+fn requires_authentication(operation: &Value, spec: &Value) -> bool {
+    let requirements = operation
+        .get("security")
+        .or_else(|| spec.get("security"))
+        .and_then(Value::as_array);
+    match requirements {
+        Some(requirements) => {
+            !requirements.is_empty()
+                && !requirements
+                    .iter()
+                    .any(|requirement| requirement.as_object().map_or(false, Map::is_empty))
+        }
+        None => false,
+    }
+}
+
+/// Name of the endpoint not taken by the operations before, numbered from 2 when it is, e.g. "Ticker2".
+fn unique_name(name: String, taken: &mut BTreeSet<String>) -> String {
+    let unique = std::iter::once(name.clone())
+        .chain((2..).map(|number| format!("{}{}", name, number)))
+        .find(|candidate| !taken.contains(candidate))
+        .expect("Some numbered name should be free");
+    taken.insert(unique.clone());
+    unique
+}
+
+/// GET operations of the spec which can be called without credentials, with examples alone,
+/// and document a json response; the others are described in the second list along with the reason
+/// they are left out. Operations named alike are told apart by numbers, e.g. "Ticker" and "Ticker2".
+///
+/// # Arguments
+///
+/// * `spec` - OpenAPI 3 spec
+///
+pub fn smoke_operations(spec: &Value) -> (Vec<SmokeOperation>, Vec<String>) {
+    let base_path = openapi::base_path_of(spec);
+    let mut operations = Vec::new();
+    let mut left_out = Vec::new();
+    let mut taken_names = BTreeSet::new();
+    let paths = spec.get("paths").and_then(Value::as_object);
+    for (path, path_item) in paths.into_iter().flatten() {
+        let operation = match path_item.get("get") {
+            Some(operation) => operation,
+            None => continue,
+        };
+        if requires_authentication(operation, spec) {
+            left_out.push(format!("GET {}: requires authentication", path));
+            continue;
+        }
+        let parameters = path_item
+            .get("parameters")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .chain(
+                operation
+                    .get("parameters")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten(),
+            )
+            .map(|parameter| openapi::inline_references(parameter, spec));
+        let mut resolved_path = format!("{}{}", base_path, path);
+        let mut query = BTreeMap::new();
+        let mut missing_examples = Vec::new();
+        for parameter in parameters {
+            let name = parameter["name"].as_str().unwrap_or_default().to_owned();
+            let location = parameter["in"].as_str().unwrap_or_default();
+            let required = location == "path" || parameter["required"] == Value::Bool(true);
+            match (example_of(&parameter), location) {
+                (Some(example), "path") => {
+                    resolved_path = resolved_path.replace(&format!("{{{}}}", name), &example)
+                }
+                (Some(example), "query") if required => {
+                    query.insert(name, example);
+                }
+                (None, "path") | (None, "query") if required => missing_examples.push(name),
+                _ => {}
+            }
+        }
+        if !missing_examples.is_empty() {
+            left_out.push(format!(
+                "GET {}: no examples of {}",
+                path,
+                missing_examples.join(", ")
+            ));
+            continue;
+        }
+        let responses = operation.get("responses").and_then(Value::as_object);
+        let success = responses
+            .into_iter()
+            .flatten()
+            .find_map(|(status, response)| {
+                let status: u16 = status
+                    .parse()
+                    .ok()
+                    .filter(|status| (200..300).contains(status))?;
+                let content = response.get("content")?.as_object()?;
+                let schema = content
+                    .iter()
+                    .find(|(content_type, _)| content_type.contains("json"))?
+                    .1
+                    .get("schema")?;
+                Some((status, schema))
+            });
+        let (status, schema) = match success {
+            Some(success) => success,
+            None => {
+                left_out.push(format!("GET {}: no successful json response", path));
+                continue;
+            }
+        };
+        operations.push(SmokeOperation {
+            endpoint: unique_name(endpoint_name_of(operation, path), &mut taken_names),
+            path: resolved_path,
+            query,
+            status,
+            schema: openapi::inline_references(schema, spec),
+        });
+    }
+    (operations, left_out)
+}
+
+/// Generates a smoke suite from the examples of the spec: for every GET operation, a schema of its
+/// successful response in "./schemas", an entry in the registry and a test of the test plan
+/// "./features/public/<plan>.yaml", calling it with the examples and validating the response.
+/// Existing schemas, registry entries and plans are never overwritten.
+///
+/// # Arguments
+///
+/// * `spec_path` - OpenAPI 3 spec, in json or yaml
+/// * `plan_name` - Name of the generated plan, e.g. openapi_smoke
+///
+pub fn export_smoke(spec_path: &Path, plan_name: &str) -> io::Result<()> {
+    let spec = read_spec(spec_path)?;
+    let (operations, left_out) = smoke_operations(&spec);
+    for reason in &left_out {
+        println!("Left out {}", reason);
+    }
+    if operations.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "The spec has no GET operation callable with its examples",
+        ));
+    }
+    let mut tests = Vec::new();
+    for operation in &operations {
+        let schema_file = naming::schema_file(&operation.endpoint);
+        let schema_path = format!("./schemas/{}", schema_file);
+        let mut schema = serde_json::to_string_pretty(&operation.schema)
+            .expect("Schema should be possible to serialize");
+        schema.push('\n');
+        match scaffold::create_new_file(&schema_path, &schema) {
+            Ok(()) => println!("Created {}", schema_path),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                println!("Kept existing {}", schema_path)
+            }
+            Err(err) => return Err(err),
+        }
+        let table = format!("endpoints.{}", operation.endpoint);
+        let registry_entry = format!(
+            "[{}]\npath = \"{}\"\nauth = \"public\"\nschema = \"{}\"\n",
+            table, operation.path, schema_file
+        );
+        if scaffold::append_registry_entry(CONFIG_FILE, &table, &registry_entry)? {
+            println!("Added endpoint {} to {}", operation.endpoint, CONFIG_FILE);
+        } else {
+            println!(
+                "Kept existing endpoint {} of {}; its test calls it there instead of {}",
+                operation.endpoint, CONFIG_FILE, operation.path
+            );
+        }
+        tests.push(PlanTest {
+            name: format!(
+                "If I call {} with its examples, it responds according to the spec",
+                operation.endpoint
+            ),
+            tags: Vec::new(),
+            requests: vec![PlanRequest {
+                endpoint: operation.endpoint.clone(),
+                parameters: operation.query.clone(),
+                status: Some(operation.status),
+                schema: true,
+                assertions: Vec::new(),
+                extract: BTreeMap::new(),
+            }],
+        });
+    }
+    let plan = TestPlan {
+        name: format!("Smoke checks generated from {}", spec_path.display()),
+        tags: vec!["smoke".to_owned()],
+        tests,
+    };
+    let plan_path = format!("./features/public/{}.yaml", plan_name);
+    let content = serde_yaml::to_string(&plan).expect("Test plan should be possible to serialize");
+    scaffold::create_new_file(&plan_path, &content)?;
+    println!("Created {} with {} tests", plan_path, operations.len());
+    Ok(())
+}
@@ -0,0 +1,320 @@
+use crate::feature_export::{self, CapturedRequest};
+use crate::{config_error, scaffold};
+use api_verify::resources::config::{
+    Auth, ConfigError, EndpointRegistry, PrivateApiProperties, Profile,
+};
+use api_verify::resources::{
+    access, accounts, http_client, naming, report_encryption, request_handler, schema, scrubbing,
+};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+/// Catalogue the exchanges saved by the `save` command are written to.
+const EXCHANGES_DIR: &str = "./snapshots/repl";
+
+const HELP: &str = "Commands:
+  get <path|Endpoint> [name=value ...]           GET from public API, parameters in the query
+  post public <path|Endpoint> [name=value ...]   POST to public API, parameters in the body
+  post private <path|Endpoint> [name=value ...]  signed POST to private API
+  validate [schema file|Endpoint]                validates the last response against the schema
+  save <Endpoint>                                saves the last exchange and a scenario skeleton
+  export <FeatureName>                           drafts a feature from all exchanges of the session
+  help                                           prints this message
+  exit                                           ends the session";
+
+/// Request and response exchanged with the API, as saved by the `save` command.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Exchange {
+    /// Name of the endpoint in the registry, when the request was sent to one
+    pub endpoint: Option<String>,
+    pub auth: Auth,
+    pub method: String,
+    pub url: String,
+    pub parameters: Vec<(String, String)>,
+    pub status: u16,
+    pub response: serde_json::Value,
+}
+
+/// State of the interactive session.
+struct Session {
+    endpoints: EndpointRegistry,
+    client: reqwest::Client,
+    api_link: String,
+    /// Loaded on the first request to private API, so public API can be explored without keys
+    private_api_properties: Option<PrivateApiProperties>,
+    /// Exchanges of the session, in the order they were made
+    exchanges: Vec<Exchange>,
+    /// Profile of the session, deciding which endpoints may be requested and how saved responses are scrubbed
+    profile: Profile,
+}
+
+/// Runs the interactive session, reading commands from the standard input until "exit" or its end.
+/// Errors of single commands are printed and do not end the session.
+pub async fn repl() -> io::Result<()> {
+    let profile = Profile::load().map_err(config_error)?;
+    let endpoints = EndpointRegistry::load(&profile.api_version).map_err(config_error)?;
+    let (client, _) = http_client::build_client(&profile);
+    let api_link = env::var("API_LINK")
+        .map_err(|_| config_error(ConfigError::Missing("API_LINK".to_owned())))?;
+    let mut session = Session {
+        endpoints,
+        client,
+        api_link,
+        private_api_properties: None,
+        exchanges: Vec::new(),
+        profile,
+    };
+
+    println!("{}", HELP);
+    // Read without blocking the runtime, which keeps serving requests of the session
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    loop {
+        print!("api-verify> ");
+        io::stdout().flush()?;
+        let line = match lines.next_line().await? {
+            Some(line) => line,
+            None => break,
+        };
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let result = match words.as_slice() {
+            [] => Ok(()),
+            ["exit"] | ["quit"] => break,
+            ["help"] => {
+                println!("{}", HELP);
+                Ok(())
+            }
+            ["get", target, parameters @ ..] => {
+                session.send(Auth::Public, "GET", target, parameters).await
+            }
+            ["post", "public", target, parameters @ ..] => {
+                session.send(Auth::Public, "POST", target, parameters).await
+            }
+            ["post", "private", target, parameters @ ..] => {
+                session
+                    .send(Auth::Private, "POST", target, parameters)
+                    .await
+            }
+            ["validate"] => session.validate(None),
+            ["validate", schema_source] => session.validate(Some(schema_source)),
+            ["save", endpoint_name] => session.save(endpoint_name),
+            ["export", feature_name] => session.export(feature_name),
+            _ => Err(format!(
+                "Unknown command: {}; type help for the list",
+                line.trim()
+            )),
+        };
+        if let Err(err) = result {
+            eprintln!("{}", err);
+        }
+    }
+    Ok(())
+}
+
+impl Session {
+    /// Sends the request and prints the response, remembering the exchange.
+    ///
+    /// # Arguments
+    ///
+    /// * `auth` - Part of API the request is sent to
+    /// * `method` - HTTP method, GET or POST
+    /// * `target` - Path of the endpoint, e.g. /0/public/Time, or its name in the registry
+    /// * `parameters` - Parameters in the form name=value
+    ///
+    async fn send(
+        &mut self,
+        auth: Auth,
+        method: &str,
+        target: &str,
+        parameters: &[&str],
+    ) -> Result<(), String> {
+        let (endpoint, path) = self.resolve_target(target, auth)?;
+        let parameters = parse_parameters(parameters)?;
+        let pairs: Vec<(&str, &str)> = parameters
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+        let url = [self.api_link.as_str(), path.as_str()].concat();
+
+        let result = match (auth, method) {
+            (Auth::Private, _) => {
+                if self.private_api_properties.is_none() {
+                    let properties = accounts::properties_for(&self.profile, None)
+                        .map_err(|err| err.to_string())?;
+                    self.private_api_properties = Some(properties);
+                }
+                let properties = self.private_api_properties.as_ref().unwrap();
+                let signed_request = request_handler::sign_private_request_with(
+                    &properties.api_key,
+                    &properties.api_secret,
+                    &properties.otp_secret,
+                    &properties.api_link,
+                    &path,
+                    &properties.signer_config,
+                    0,
+                    &pairs,
+                );
+                request_handler::send_signed_request(&self.client, &signed_request).await
+            }
+            (Auth::Public, "POST") => self.client.post(&url).form(&pairs).send().await,
+            (Auth::Public, _) => self.client.get(&url).query(&pairs).send().await,
+        };
+        let response = result.map_err(|err| format!("Request failed: {}", err))?;
+        let status = response.status().as_u16();
+        let text = response
+            .text()
+            .await
+            .map_err(|err| format!("Reading the response failed: {}", err))?;
+        let json = serde_json::from_str(&text).unwrap_or(serde_json::Value::String(text));
+        println!("{} {}", method, url);
+        println!("Status: {}", status);
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&json).expect("Json should be possible to serialize")
+        );
+
+        self.exchanges.push(Exchange {
+            endpoint,
+            auth,
+            method: method.to_owned(),
+            url,
+            parameters,
+            status,
+            response: json,
+        });
+        Ok(())
+    }
+
+    /// Finds the path of the target; endpoints of the registry have to belong to the part of API
+    /// and be allowed by the profile, also when they are given by path.
+    fn resolve_target(&self, target: &str, auth: Auth) -> Result<(Option<String>, String), String> {
+        if target.starts_with('/') {
+            if let Some((endpoint_name, endpoint)) = self
+                .endpoints
+                .iter()
+                .find(|(_, endpoint)| endpoint.path == target)
+            {
+                access::check(&self.profile, endpoint_name, endpoint)
+                    .map_err(|denied| denied.to_string())?;
+            }
+            return Ok((None, target.to_owned()));
+        }
+        let endpoint = self.endpoints.get(target).map_err(|err| err.to_string())?;
+        if endpoint.auth != auth {
+            return Err(format!(
+                "Endpoint {} is a part of {:?} API",
+                target, endpoint.auth
+            ));
+        }
+        access::check(&self.profile, target, endpoint).map_err(|denied| denied.to_string())?;
+        Ok((Some(target.to_owned()), endpoint.path.clone()))
+    }
+
+    /// Validates the last response against the given schema file, the schema of the given endpoint,
+    /// or the schema of the endpoint the last request was sent to.
+    fn validate(&self, schema_source: Option<&str>) -> Result<(), String> {
+        let exchange = self.last_exchange()?;
+        let schema_file = match schema_source.or(exchange.endpoint.as_deref()) {
+            Some(source) if source.ends_with(".json") => source.to_owned(),
+            Some(endpoint_name) => {
+                self.endpoints
+                    .get(endpoint_name)
+                    .map_err(|err| err.to_string())?;
+                self.endpoints.schema_file(endpoint_name)
+            }
+            None => {
+                return Err("Give the schema file or the endpoint to validate against".to_owned())
+            }
+        };
+        if !Path::new("./schemas").join(&schema_file).exists() {
+            return Err(format!("Schema ./schemas/{} does not exist", schema_file));
+        }
+        let compiled = schema::compile_schema(&schema_file)?;
+        match schema::validation_errors(&compiled, &exchange.response) {
+            Some(errors) => println!("Response does not match {}: {}", schema_file, errors),
+            None => println!("Response matches {}", schema_file),
+        }
+        Ok(())
+    }
+
+    /// Writes the last exchange to "./snapshots/repl/<endpoint>.json", with sensitive values scrubbed,
+    /// and, unless it exists, a feature file verifying the endpoint with the generic steps.
+    fn save(&self, endpoint_name: &str) -> Result<(), String> {
+        let exchange = self.last_exchange()?;
+        let endpoint = self.endpoints.get(endpoint_name).map_err(|err| {
+            format!(
+                "{}; add it with `api-verify scaffold endpoint {}` first",
+                err, endpoint_name
+            )
+        })?;
+        let snake_case_name = naming::to_snake_case(endpoint_name);
+
+        let exchange_path = format!("{}/{}.json", EXCHANGES_DIR, snake_case_name);
+        let mut exchange = exchange.clone();
+        scrubbing::apply(&mut exchange.response, &self.profile.scrub);
+        let content = serde_json::to_string_pretty(&exchange)
+            .expect("Exchange should be possible to serialize");
+        fs::create_dir_all(EXCHANGES_DIR)
+            .and_then(|_| {
+                fs::write(
+                    &exchange_path,
+                    report_encryption::seal(content.into_bytes()),
+                )
+            })
+            .map_err(|err| format!("Cannot write {}: {}", exchange_path, err))?;
+        println!("Saved the exchange to {}", exchange_path);
+
+        let private = endpoint.auth == Auth::Private;
+        let api_type = if private { "private" } else { "public" };
+        let feature_path = format!("./features/{}/{}.feature", api_type, snake_case_name);
+        if Path::new(&feature_path).exists() {
+            println!("{} already exists", feature_path);
+        } else {
+            scaffold::create_new_file(
+                &feature_path,
+                &scaffold::feature_skeleton(endpoint_name, private),
+            )
+            .map_err(|err| err.to_string())?;
+            println!("Created {}", feature_path);
+        }
+        Ok(())
+    }
+
+    /// Drafts a feature file with a scenario for every distinct request of the session.
+    fn export(&self, feature_name: &str) -> Result<(), String> {
+        let captures: Vec<CapturedRequest> = self
+            .exchanges
+            .iter()
+            .filter_map(CapturedRequest::from_exchange)
+            .collect();
+        let created = feature_export::export_feature(feature_name, &captures, &self.endpoints)
+            .map_err(|err| err.to_string())?;
+        for path in created {
+            println!("Created {}", path.display());
+        }
+        Ok(())
+    }
+
+    fn last_exchange(&self) -> Result<&Exchange, String> {
+        self.exchanges
+            .last()
+            .ok_or_else(|| "No request was sent yet".to_owned())
+    }
+}
+
+/// Splits parameters given in the form name=value.
+fn parse_parameters(parameters: &[&str]) -> Result<Vec<(String, String)>, String> {
+    parameters
+        .iter()
+        .map(|parameter| match parameter.split_once('=') {
+            Some((name, value)) if !name.is_empty() => Ok((name.to_owned(), value.to_owned())),
+            _ => Err(format!(
+                "Parameter should be given as name=value, got: {}",
+                parameter
+            )),
+        })
+        .collect()
+}
@@ -0,0 +1,125 @@
+use crate::report_manifest::{self, Manifest, ManifestEntry};
+use api_verify::resources::baseline;
+use api_verify::resources::redaction;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Catalogues of the suite bundled as they are.
+const SOURCE_DIRS: [&str; 2] = ["./features", "./schemas"];
+
+/// Configuration files bundled with secrets redacted.
+const CONFIG_FILES: [&str; 3] = [
+    "./api_verify.toml",
+    "./baseline.toml",
+    "./docker-compose.yml",
+];
+
+/// Timestamp of the bundle in UTC, e.g. "2026-10-16T143005Z", usable in file names.
+fn timestamp() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("System time should be after unix epoch")
+        .as_secs() as i64;
+    let second_of_day = seconds % 86_400;
+    format!(
+        "{}T{:02}{:02}{:02}Z",
+        baseline::date_of(seconds / 86_400),
+        second_of_day / 3600,
+        second_of_day % 3600 / 60,
+        second_of_day % 60
+    )
+}
+
+/// Files of the catalogue along with their paths in the archive, e.g. "features/public/time.feature".
+fn files_of(dir: &Path, archive_dir: &str) -> io::Result<Vec<(PathBuf, String)>> {
+    let mut files = Vec::new();
+    for artifact in Manifest::of_dir(dir)? {
+        files.push((
+            dir.join(&artifact.path),
+            format!("{}/{}", archive_dir, artifact.path),
+        ));
+    }
+    Ok(files)
+}
+
+/// Packages features, schemas, configuration with secrets redacted, and results of the run including
+/// snapshots with request transcripts into a timestamped ZIP archive with a manifest of SHA-256 digests
+/// of its entries, signed when REPORT_SIGNING_KEY is set.
+///
+/// # Arguments
+///
+/// * `results_dir` - Catalogue with results of the run, e.g. "results"
+/// * `output_dir` - Catalogue the archive is written to
+///
+pub fn bundle_evidence(results_dir: &Path, output_dir: &Path) -> io::Result<()> {
+    let mut entries: Vec<(String, Vec<u8>)> = Vec::new();
+    let mut files = Vec::new();
+    for source_dir in SOURCE_DIRS {
+        let source_dir = Path::new(source_dir);
+        if source_dir.is_dir() {
+            let archive_dir = source_dir.file_name().unwrap().to_string_lossy();
+            files.extend(files_of(source_dir, &archive_dir)?);
+        }
+    }
+    files.extend(files_of(results_dir, "results")?);
+    for (path, archive_path) in files {
+        entries.push((archive_path, fs::read(path)?));
+    }
+    for config_file in CONFIG_FILES {
+        let path = Path::new(config_file);
+        if path.is_file() {
+            let (redacted, _) = redaction::redact(&fs::read_to_string(path)?);
+            let archive_path = format!("config/{}", path.file_name().unwrap().to_string_lossy());
+            entries.push((archive_path, redacted.into_bytes()));
+        }
+    }
+
+    let artifacts: Vec<ManifestEntry> = entries
+        .iter()
+        .map(|(path, content)| ManifestEntry::of(path.clone(), content))
+        .collect();
+    if env::var(report_manifest::SIGNING_KEY_ENV_VAR).is_ok() {
+        let (manifest, signature) = report_manifest::signed_manifest(artifacts)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err.to_string()))?;
+        entries.push((
+            report_manifest::MANIFEST_FILE.to_owned(),
+            manifest.into_bytes(),
+        ));
+        entries.push((
+            report_manifest::SIGNATURE_FILE.to_owned(),
+            signature.into_bytes(),
+        ));
+    } else {
+        let manifest = Manifest {
+            public_key: String::new(),
+            artifacts,
+        };
+        let content =
+            serde_json::to_string_pretty(&manifest).expect("Manifest should be serializable");
+        entries.push((
+            report_manifest::MANIFEST_FILE.to_owned(),
+            content.into_bytes(),
+        ));
+    }
+
+    fs::create_dir_all(output_dir)?;
+    let bundle_path = output_dir.join(format!("api-verify-evidence-{}.zip", timestamp()));
+    let mut archive = ZipWriter::new(File::create(&bundle_path)?);
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (path, content) in &entries {
+        archive.start_file(path.as_str(), options)?;
+        archive.write_all(content)?;
+    }
+    archive.finish()?;
+    println!(
+        "Bundled {} files into {}",
+        entries.len(),
+        bundle_path.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,235 @@
+use api_verify::resources::config::ConfigError;
+use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signature, Signer, Verifier};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Environment variable with the base64 encoded 32-byte Ed25519 secret key manifests are signed with.
+pub const SIGNING_KEY_ENV_VAR: &str = "REPORT_SIGNING_KEY";
+
+/// Name of the manifest written to the catalogue of artifacts.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Name of the file with the base64 encoded signature of the manifest.
+pub const SIGNATURE_FILE: &str = "manifest.json.sig";
+
+/// Artifact listed in the manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Path relative to the catalogue, with "/" as separator
+    pub path: String,
+    pub sha256: String,
+    pub size: u64,
+}
+
+impl ManifestEntry {
+    /// Describes the artifact with the content.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the artifact, relative to the catalogue or archive
+    /// * `content` - Content of the artifact
+    ///
+    pub fn of(path: String, content: &[u8]) -> Self {
+        Self {
+            path,
+            sha256: hex::encode(Sha256::digest(content)),
+            size: content.len() as u64,
+        }
+    }
+}
+
+/// Digests of all artifacts of a run, signed so they can be proven unmodified.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Base64 encoded public key of the signer, for information only; verification uses the trusted key.
+    /// Empty when the manifest is not signed
+    pub public_key: String,
+    pub artifacts: Vec<ManifestEntry>,
+}
+
+impl Manifest {
+    /// Lists the artifacts of the catalogue, except of the manifest and its signature, in the order of their paths.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - Catalogue of the artifacts, e.g. "results"
+    ///
+    pub fn of_dir(dir: &Path) -> io::Result<Vec<ManifestEntry>> {
+        let mut files = Vec::new();
+        collect_files(dir, &mut files)?;
+        let mut artifacts = Vec::new();
+        for file in files {
+            let path = file
+                .strip_prefix(dir)
+                .expect("Listed file should be inside the catalogue")
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+                .join("/");
+            if path == MANIFEST_FILE || path == SIGNATURE_FILE {
+                continue;
+            }
+            artifacts.push(ManifestEntry::of(path, &fs::read(&file)?));
+        }
+        artifacts.sort_by(|first, second| first.path.cmp(&second.path));
+        Ok(artifacts)
+    }
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn invalid_input(err: ConfigError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, err.to_string())
+}
+
+fn decode_key(variable: &str, value: &str) -> Result<Vec<u8>, ConfigError> {
+    let key = base64::decode(value.trim()).map_err(|err| ConfigError::Malformed {
+        variable: variable.to_owned(),
+        reason: format!("value is not valid base64 ({})", err),
+    })?;
+    if key.len() != 32 {
+        return Err(ConfigError::Malformed {
+            variable: variable.to_owned(),
+            reason: format!("key should have 32 bytes, got {}", key.len()),
+        });
+    }
+    Ok(key)
+}
+
+/// Reads the signing key pair from REPORT_SIGNING_KEY.
+fn keypair_from_env() -> Result<Keypair, ConfigError> {
+    let value = env::var(SIGNING_KEY_ENV_VAR)
+        .map_err(|_| ConfigError::Missing(SIGNING_KEY_ENV_VAR.to_owned()))?;
+    let key = decode_key(SIGNING_KEY_ENV_VAR, &value)?;
+    let secret = SecretKey::from_bytes(&key).expect("Key of 32 bytes should be a valid secret key");
+    let public = PublicKey::from(&secret);
+    Ok(Keypair { secret, public })
+}
+
+/// Writes the manifest of all artifacts of the catalogue with their SHA-256 digests,
+/// along with its Ed25519 signature made with the key from REPORT_SIGNING_KEY.
+///
+/// # Arguments
+///
+/// * `dir` - Catalogue of the artifacts, e.g. "results"
+///
+pub fn sign_dir(dir: &Path) -> io::Result<()> {
+    let artifacts = Manifest::of_dir(dir)?;
+    let artifact_count = artifacts.len();
+    let (content, signature) = signed_manifest(artifacts).map_err(invalid_input)?;
+    fs::write(dir.join(MANIFEST_FILE), &content)?;
+    fs::write(dir.join(SIGNATURE_FILE), signature)?;
+    println!(
+        "Signed manifest of {} artifacts written to {}",
+        artifact_count,
+        dir.join(MANIFEST_FILE).display()
+    );
+    Ok(())
+}
+
+/// Serializes the manifest of the artifacts and signs it with the key from REPORT_SIGNING_KEY,
+/// returning the content of the manifest and its base64 encoded signature.
+///
+/// # Arguments
+///
+/// * `artifacts` - Artifacts listed in the manifest
+///
+pub fn signed_manifest(artifacts: Vec<ManifestEntry>) -> Result<(String, String), ConfigError> {
+    let keypair = keypair_from_env()?;
+    let manifest = Manifest {
+        public_key: base64::encode(keypair.public.as_bytes()),
+        artifacts,
+    };
+    let content = serde_json::to_string_pretty(&manifest).expect("Manifest should be serializable");
+    let signature = keypair.sign(content.as_bytes());
+    Ok((content, base64::encode(signature.to_bytes())))
+}
+
+/// Problems found by verification of the manifest, empty when the artifacts are unmodified.
+///
+/// # Arguments
+///
+/// * `dir` - Catalogue of the artifacts, with the manifest and its signature
+/// * `public_key` - Base64 encoded public key the manifest is expected to be signed with
+///
+pub fn manifest_problems(dir: &Path, public_key: &str) -> io::Result<Vec<String>> {
+    let key = decode_key("public key", public_key).map_err(invalid_input)?;
+    let public_key = PublicKey::from_bytes(&key)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err.to_string()))?;
+    let content = fs::read(dir.join(MANIFEST_FILE))?;
+    let signature = base64::decode(fs::read_to_string(dir.join(SIGNATURE_FILE))?.trim())
+        .ok()
+        .and_then(|bytes| Signature::try_from(bytes.as_slice()).ok());
+    let signature_is_valid = signature.map_or(false, |signature| {
+        public_key.verify(&content, &signature).is_ok()
+    });
+    if !signature_is_valid {
+        return Ok(vec![
+            "The signature does not match the manifest or the public key".to_owned(),
+        ]);
+    }
+
+    let manifest: Manifest = serde_json::from_slice(&content)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    let artifacts = Manifest::of_dir(dir)?;
+    let mut problems = Vec::new();
+    for listed in &manifest.artifacts {
+        match artifacts
+            .iter()
+            .find(|artifact| artifact.path == listed.path)
+        {
+            None => problems.push(format!("{} is missing", listed.path)),
+            Some(artifact) if artifact != listed => {
+                problems.push(format!("{} was modified", listed.path))
+            }
+            Some(_) => {}
+        }
+    }
+    for artifact in &artifacts {
+        if !manifest
+            .artifacts
+            .iter()
+            .any(|listed| listed.path == artifact.path)
+        {
+            problems.push(format!("{} is not listed in the manifest", artifact.path));
+        }
+    }
+    Ok(problems)
+}
+
+/// Verifies the signed manifest of the catalogue, failing when any artifact was modified, removed or added.
+///
+/// # Arguments
+///
+/// * `dir` - Catalogue of the artifacts, with the manifest and its signature
+/// * `public_key` - Base64 encoded public key the manifest is expected to be signed with
+///
+pub fn verify_dir(dir: &Path, public_key: &str) -> io::Result<()> {
+    let problems = manifest_problems(dir, public_key)?;
+    if !problems.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Artifacts of {} do not match the manifest:\n{}",
+                dir.display(),
+                problems.join("\n")
+            ),
+        ));
+    }
+    println!("Artifacts of {} match the signed manifest", dir.display());
+    Ok(())
+}
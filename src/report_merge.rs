@@ -0,0 +1,396 @@
+use api_verify::resources::report_encryption;
+use api_verify::resources::results::{
+    self, RunResult, RunSection, ScenarioOutcome, ScenarioStatus,
+};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Outcome of a scenario along with the suite and profile it was run in.
+#[derive(Debug, Clone, Serialize)]
+pub struct MergedOutcome {
+    pub suite: String,
+    pub profile: String,
+    #[serde(flatten)]
+    pub outcome: ScenarioOutcome,
+}
+
+/// Results of many runs, e.g. shards of a suite or runs with different profiles,
+/// with their metadata de-duplicated.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MergedReport {
+    pub suites: BTreeSet<String>,
+    pub profiles: BTreeSet<String>,
+    pub run_ids: BTreeSet<String>,
+    pub seeds: BTreeSet<u64>,
+    pub shards: BTreeSet<String>,
+    /// States of the provider attached to runs with failed scenarios
+    pub provider_statuses: BTreeSet<String>,
+    /// Whether any of the runs was interrupted
+    pub cancelled: bool,
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub known_issues: usize,
+    pub scenarios: Vec<MergedOutcome>,
+    /// Reports about the runs as a whole, named after the run they come from, e.g. "public [default] 1/2: integrity"
+    pub sections: Vec<RunSection>,
+}
+
+/// Name of the section of the run in the merged report, e.g. "public [default] 1/2: integrity".
+fn section_name_of(run_result: &RunResult, section: &RunSection) -> String {
+    let shard = run_result
+        .shard
+        .as_ref()
+        .map(|shard| format!(" {}", shard))
+        .unwrap_or_default();
+    format!(
+        "{} [{}]{}: {}",
+        run_result.suite, run_result.profile, shard, section.name
+    )
+}
+
+impl MergedReport {
+    /// Merges the results; a scenario present in many of them with the same run ID,
+    /// e.g. in a result and in the one resuming it, is kept once.
+    ///
+    /// # Arguments
+    ///
+    /// * `run_results` - Results of the runs, in the order they are listed in the report
+    ///
+    pub fn merge(run_results: &[RunResult]) -> Self {
+        let mut merged = MergedReport::default();
+        for run_result in run_results {
+            merged.suites.insert(run_result.suite.clone());
+            merged.profiles.insert(run_result.profile.clone());
+            merged.run_ids.insert(run_result.run_id.clone());
+            merged.seeds.insert(run_result.seed);
+            merged.shards.extend(run_result.shard.clone());
+            merged.cancelled |= run_result.cancelled;
+            merged
+                .provider_statuses
+                .extend(run_result.provider_status.as_ref().map(ToString::to_string));
+            for section in &run_result.sections {
+                let section = RunSection {
+                    name: section_name_of(run_result, section),
+                    lines: section.lines.clone(),
+                };
+                let is_duplicate = merged
+                    .sections
+                    .iter()
+                    .any(|known| known.name == section.name && known.lines == section.lines);
+                if !is_duplicate {
+                    merged.sections.push(section);
+                }
+            }
+            for outcome in &run_result.scenarios {
+                let is_duplicate = merged.scenarios.iter().any(|known| {
+                    known.suite == run_result.suite
+                        && known.profile == run_result.profile
+                        && known.outcome.feature == outcome.feature
+                        && known.outcome.scenario == outcome.scenario
+                        && known.outcome.run_id == outcome.run_id
+                });
+                if !is_duplicate {
+                    merged.scenarios.push(MergedOutcome {
+                        suite: run_result.suite.clone(),
+                        profile: run_result.profile.clone(),
+                        outcome: outcome.clone(),
+                    });
+                }
+            }
+        }
+        let count = |status| {
+            merged
+                .scenarios
+                .iter()
+                .filter(|merged_outcome| merged_outcome.outcome.status == status)
+                .count()
+        };
+        merged.passed = count(ScenarioStatus::Passed);
+        merged.failed = count(ScenarioStatus::Failed);
+        merged.skipped = count(ScenarioStatus::Skipped);
+        merged.known_issues = count(ScenarioStatus::KnownIssue);
+        merged
+    }
+
+    /// Name of the test suite of the outcome, e.g. "public: AssetPair endpoint";
+    /// the profile is included when the runs used many of them.
+    fn test_suite_of(&self, merged_outcome: &MergedOutcome) -> String {
+        let test_suite = if self.profiles.len() > 1 {
+            format!(
+                "{} [{}]: {}",
+                merged_outcome.suite, merged_outcome.profile, merged_outcome.outcome.feature
+            )
+        } else {
+            format!(
+                "{}: {}",
+                merged_outcome.suite, merged_outcome.outcome.feature
+            )
+        };
+        test_suite
+    }
+
+    fn write_junit(&self, report: &mut impl Write) -> io::Result<()> {
+        let mut test_suites: Vec<(String, Vec<&ScenarioOutcome>)> = Vec::new();
+        for merged_outcome in &self.scenarios {
+            let name = self.test_suite_of(merged_outcome);
+            match test_suites
+                .iter_mut()
+                .find(|(test_suite, _)| *test_suite == name)
+            {
+                Some((_, outcomes)) => outcomes.push(&merged_outcome.outcome),
+                None => test_suites.push((name, vec![&merged_outcome.outcome])),
+            }
+        }
+        let comments: Vec<String> = self
+            .run_ids
+            .iter()
+            .map(|run_id| format!("RUN_ID={}", run_id))
+            .chain(self.seeds.iter().map(|seed| format!("SEED={}", seed)))
+            .chain(
+                self.provider_statuses
+                    .iter()
+                    .map(|provider_status| format!("PROVIDER_STATUS={}", provider_status)),
+            )
+            .collect();
+        results::write_junit_suites(report, "combined", &comments, &test_suites, &self.sections)
+    }
+
+    fn write_html(&self, report: &mut impl Write) -> io::Result<()> {
+        let join =
+            |values: &BTreeSet<String>| values.iter().cloned().collect::<Vec<_>>().join(", ");
+        writeln!(report, "<!DOCTYPE html>")?;
+        writeln!(
+            report,
+            "<html><head><meta charset=\"utf-8\"><title>API verification report</title>"
+        )?;
+        writeln!(
+            report,
+            "<style>td, th {{ padding: 2px 8px; text-align: left }} .passed {{ color: green }} \
+             .failed {{ color: red }} .skipped, .known {{ color: orange }}</style></head><body>"
+        )?;
+        writeln!(report, "<h1>API verification report</h1>")?;
+        writeln!(
+            report,
+            "<p>Suites: {}; profiles: {}; runs: {}</p>",
+            results::escape(&join(&self.suites)),
+            results::escape(&join(&self.profiles)),
+            results::escape(&join(&self.run_ids))
+        )?;
+        writeln!(
+            report,
+            "<p class=\"summary\">{} passed, {} failed, {} skipped, {} known issues{}</p>",
+            self.passed,
+            self.failed,
+            self.skipped,
+            self.known_issues,
+            if self.cancelled {
+                "; some runs were cancelled"
+            } else {
+                ""
+            }
+        )?;
+        for provider_status in &self.provider_statuses {
+            writeln!(
+                report,
+                "<p class=\"provider\">Provider status: {}</p>",
+                results::escape(provider_status)
+            )?;
+        }
+        writeln!(
+            report,
+            "<table><tr><th>Suite</th><th>Feature</th><th>Scenario</th><th>Status</th>\
+             <th>Duration</th><th>Failure</th><th>Warnings</th></tr>"
+        )?;
+        for merged_outcome in &self.scenarios {
+            let outcome = &merged_outcome.outcome;
+            let status = match outcome.status {
+                ScenarioStatus::Passed => "passed",
+                ScenarioStatus::Failed => "failed",
+                ScenarioStatus::Skipped => "skipped",
+                ScenarioStatus::KnownIssue => "known issue",
+            };
+            writeln!(
+                report,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td class=\"{}\">{}</td><td>{} ms</td><td>{}</td><td>{}</td></tr>",
+                results::escape(&merged_outcome.suite),
+                results::escape(&outcome.feature),
+                results::escape(&outcome.scenario),
+                status,
+                status,
+                outcome.duration_ms,
+                results::escape(
+                    outcome
+                        .failure
+                        .as_deref()
+                        .or(outcome.skip_reason.as_deref())
+                        .unwrap_or_default()
+                ),
+                results::escape(&outcome.warnings.join("; "))
+            )?;
+        }
+        writeln!(report, "</table>")?;
+        for section in &self.sections {
+            writeln!(
+                report,
+                "<h2>{}</h2><pre class=\"section\">{}</pre>",
+                results::escape(&section.name),
+                results::escape(&section.lines.join("\n"))
+            )?;
+        }
+        writeln!(report, "</body></html>")
+    }
+}
+
+/// Merges run results, e.g. of shards or profiles, into "report.json", "report.xml" (JUnit)
+/// and "report.html" in the output catalogue, encrypted when a report key is set.
+///
+/// # Arguments
+///
+/// * `result_files` - Json results written by the suites, e.g. "results/public-shard-1-of-2.json"
+/// * `output_dir` - Catalogue the merged reports are written to
+///
+pub fn merge_reports(result_files: &[PathBuf], output_dir: &Path) -> io::Result<()> {
+    let mut run_results = Vec::new();
+    for result_file in result_files {
+        let path = result_file.to_string_lossy();
+        run_results.push(RunResult::read(&path)?);
+    }
+    let merged = MergedReport::merge(&run_results);
+
+    fs::create_dir_all(output_dir)?;
+    let json = serde_json::to_string_pretty(&merged).expect("Merged report should be serializable");
+    fs::write(
+        output_dir.join("report.json"),
+        report_encryption::seal(json.into_bytes()),
+    )?;
+    let mut junit = Vec::new();
+    merged.write_junit(&mut junit)?;
+    fs::write(
+        output_dir.join("report.xml"),
+        report_encryption::seal(junit),
+    )?;
+    let mut html = Vec::new();
+    merged.write_html(&mut html)?;
+    fs::write(
+        output_dir.join("report.html"),
+        report_encryption::seal(html),
+    )?;
+
+    println!(
+        "Merged {} results into {}: {} passed, {} failed, {} skipped",
+        run_results.len(),
+        output_dir.display(),
+        merged.passed,
+        merged.failed,
+        merged.skipped
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome(scenario: &str, status: ScenarioStatus, run_id: &str) -> ScenarioOutcome {
+        ScenarioOutcome {
+            feature: "Time endpoint".to_owned(),
+            scenario: scenario.to_owned(),
+            status,
+            duration_ms: 10,
+            failure: None,
+            run_id: run_id.to_owned(),
+            known_issue: None,
+            retried: false,
+            warnings: Vec::new(),
+            skip_reason: None,
+        }
+    }
+
+    fn run_result(shard: &str, scenarios: Vec<ScenarioOutcome>) -> RunResult {
+        RunResult {
+            suite: "public".to_owned(),
+            profile: "default".to_owned(),
+            seed: 42,
+            run_id: "run-1".to_owned(),
+            shard: Some(shard.to_owned()),
+            scenarios,
+            sections: vec![RunSection {
+                name: "integrity".to_owned(),
+                lines: vec!["No violations".to_owned()],
+            }],
+            ..RunResult::default()
+        }
+    }
+
+    #[test]
+    fn merge_keeps_scenarios_of_the_same_run_once() {
+        let first = run_result(
+            "1/2",
+            vec![
+                outcome("Server time is current", ScenarioStatus::Passed, "run-1"),
+                outcome("Time is in seconds", ScenarioStatus::Failed, "run-1"),
+            ],
+        );
+        let resumed = run_result(
+            "1/2",
+            vec![
+                outcome("Server time is current", ScenarioStatus::Passed, "run-1"),
+                outcome("Time is in seconds", ScenarioStatus::Passed, "run-2"),
+            ],
+        );
+        let merged = MergedReport::merge(&[first, resumed]);
+        assert_eq!(merged.scenarios.len(), 3);
+        assert_eq!((merged.passed, merged.failed, merged.skipped), (2, 1, 0));
+        assert_eq!(merged.seeds.len(), 1);
+    }
+
+    #[test]
+    fn merge_names_sections_after_their_run() {
+        let merged = MergedReport::merge(&[
+            run_result("1/2", Vec::new()),
+            run_result("1/2", Vec::new()),
+            run_result("2/2", Vec::new()),
+        ]);
+        let names: Vec<&str> = merged
+            .sections
+            .iter()
+            .map(|section| section.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "public [default] 1/2: integrity",
+                "public [default] 2/2: integrity"
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_include_sections_and_warnings() {
+        let mut warned = outcome("Server time is current", ScenarioStatus::Passed, "run-1");
+        warned.warnings = vec!["Header <Server> reveals the version".to_owned()];
+        let merged = MergedReport::merge(&[run_result("1/2", vec![warned])]);
+
+        let mut junit = Vec::new();
+        merged.write_junit(&mut junit).unwrap();
+        let junit = String::from_utf8(junit).unwrap();
+        assert!(
+            junit.contains(r#"<testcase name="public [default] 1/2: integrity" classname="run""#)
+        );
+        assert!(
+            junit.contains("<system-out>Header &lt;Server&gt; reveals the version</system-out>")
+        );
+
+        let mut html = Vec::new();
+        merged.write_html(&mut html).unwrap();
+        let html = String::from_utf8(html).unwrap();
+        assert!(html.contains("<td>Header &lt;Server&gt; reveals the version</td>"));
+        assert!(html.contains(
+            "<h2>public [default] 1/2: integrity</h2><pre class=\"section\">No violations</pre>"
+        ));
+    }
+}
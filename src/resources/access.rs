@@ -0,0 +1,83 @@
+use super::config::{Endpoint, Profile};
+use super::skipping;
+use std::fmt;
+
+/// Reason of refusing to send a request to an endpoint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccessDenied {
+    /// The endpoint changes state of the account, while the profile is read-only
+    ReadOnly(String),
+    /// The endpoint is denied, or not allowed, by the endpoint lists of the profile
+    Denied(String),
+}
+
+impl fmt::Display for AccessDenied {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AccessDenied::ReadOnly(endpoint_name) => write!(
+                f,
+                "Refusing to send a request to {}: the endpoint is mutating and the profile is read-only",
+                endpoint_name
+            ),
+            AccessDenied::Denied(endpoint_name) => {
+                write!(f, "endpoint {} is denied by the profile", endpoint_name)
+            }
+        }
+    }
+}
+
+/// Checks whether the profile allows sending requests to the endpoint.
+///
+/// # Arguments
+///
+/// * `profile` - Profile of the run
+/// * `endpoint_name` - Name of the endpoint in the registry
+/// * `endpoint` - The endpoint
+///
+pub fn check(
+    profile: &Profile,
+    endpoint_name: &str,
+    endpoint: &Endpoint,
+) -> Result<(), AccessDenied> {
+    let is_allowed = profile.allowed_endpoints.is_empty()
+        || profile
+            .allowed_endpoints
+            .iter()
+            .any(|allowed| allowed == endpoint_name);
+    let is_denied = profile
+        .denied_endpoints
+        .iter()
+        .any(|denied| denied == endpoint_name);
+    if !is_allowed || is_denied {
+        return Err(AccessDenied::Denied(endpoint_name.to_owned()));
+    }
+    if profile.read_only && endpoint.mutating {
+        return Err(AccessDenied::ReadOnly(endpoint_name.to_owned()));
+    }
+    Ok(())
+}
+
+/// Stops the scenario unless the profile allows sending requests to the endpoint:
+/// mutating endpoints of a read-only profile fail the step, denied endpoints skip the scenario.
+///
+/// # Arguments
+///
+/// * `profile` - Profile of the run
+/// * `scenario_key` - Key of the scenario sending the request
+/// * `endpoint_name` - Name of the endpoint in the registry
+/// * `endpoint` - The endpoint
+///
+pub fn ensure_allowed(
+    profile: &Profile,
+    scenario_key: &str,
+    endpoint_name: &str,
+    endpoint: &Endpoint,
+) {
+    match check(profile, endpoint_name, endpoint) {
+        Ok(()) => {}
+        Err(denied @ AccessDenied::Denied(_)) => {
+            skipping::skip_scenario(scenario_key, &denied.to_string())
+        }
+        Err(denied) => panic!("{}", denied),
+    }
+}
@@ -0,0 +1,72 @@
+use super::config::{ConfigError, PrivateApiProperties, Profile};
+use super::signing::SignerConfig;
+use super::tags;
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+
+/// Account the whole run uses when scenarios do not select one, given with `--account`.
+static RUN_ACCOUNT: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+
+/// Selects the account the run uses, making sure the profile defines it.
+///
+/// # Arguments
+///
+/// * `profile` - Profile of the run
+/// * `account` - Name of the account in `accounts` of the profile; keys without prefix are used when not set
+///
+pub fn set_run_account(profile: &Profile, account: Option<&str>) -> Result<(), ConfigError> {
+    if let Some(account) = account {
+        prefix_of(profile, account)?;
+    }
+    *RUN_ACCOUNT
+        .lock()
+        .expect("Account of the run should not be poisoned") = account.map(str::to_owned);
+    Ok(())
+}
+
+/// Account selected by the `@account(name)` tag of the scenario, if any.
+///
+/// # Arguments
+///
+/// * `tags` - Tags of the scenario, including the ones inherited from its rule and feature, without "@"
+///
+pub fn account_of<'a>(tags: impl IntoIterator<Item = &'a String>) -> Option<String> {
+    tags.into_iter()
+        .filter_map(|tag| tags::tag_argument(tag, "account"))
+        .map(|account| account.trim().to_owned())
+        .last()
+}
+
+fn prefix_of<'a>(profile: &'a Profile, account: &str) -> Result<&'a str, ConfigError> {
+    profile
+        .accounts
+        .get(account)
+        .map(String::as_str)
+        .ok_or_else(|| ConfigError::UnknownAccount(account.to_owned()))
+}
+
+/// Loads credentials of the account selected by the scenario, or of the one of the run,
+/// from environment variables with the prefix of the account, e.g. SUBA_API_KEY,
+/// signing requests with the conventions and signed headers of the profile.
+///
+/// # Arguments
+///
+/// * `profile` - Profile of the run
+/// * `account` - Account selected by the scenario, if any
+///
+pub fn properties_for(
+    profile: &Profile,
+    account: Option<&str>,
+) -> Result<PrivateApiProperties, ConfigError> {
+    let run_account = RUN_ACCOUNT
+        .lock()
+        .expect("Account of the run should not be poisoned")
+        .clone();
+    let signer_config = SignerConfig::from_profile(profile)?;
+    match account.or(run_account.as_deref()) {
+        Some(account) => {
+            PrivateApiProperties::from_env_with_prefix(prefix_of(profile, account)?, signer_config)
+        }
+        None => PrivateApiProperties::from_env(signer_config),
+    }
+}
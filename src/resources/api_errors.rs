@@ -0,0 +1,57 @@
+/// Reads errors reported by the API in the "error" array of the response body.
+///
+/// # Arguments
+///
+/// * `json_response` - Response body parsed to json
+///
+pub fn errors_of(json_response: &serde_json::Value) -> Vec<String> {
+    json_response["error"]
+        .as_array()
+        .map(|errors| {
+            errors
+                .iter()
+                .map(|error| match error {
+                    serde_json::Value::String(text) => text.clone(),
+                    other => other.to_string(),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Outcome of the request, as seen by the API.
+#[derive(Debug)]
+pub struct RequestOutcome {
+    pub status: reqwest::StatusCode,
+    pub errors: Vec<String>,
+}
+
+impl RequestOutcome {
+    /// Reads status and errors of the response, consuming it.
+    pub async fn from_response(response: reqwest::Response) -> reqwest::Result<Self> {
+        let status = response.status();
+        let body = response.text().await?;
+        let errors = match serde_json::from_str::<serde_json::Value>(&body) {
+            Ok(json_response) => errors_of(&json_response),
+            Err(_) if status.is_success() => vec![format!("response is not json: {}", body)],
+            Err(_) => Vec::new(),
+        };
+        Ok(Self { status, errors })
+    }
+
+    /// Panics unless the request was rejected with the expected error among reported ones.
+    pub fn verify_rejected_with(&self, expected_error: &str) {
+        assert!(
+            self.errors.iter().any(|error| error == expected_error),
+            "Expected the request to be rejected with {}, got status {} with errors {:?}",
+            expected_error,
+            self.status,
+            self.errors
+        );
+    }
+
+    /// Request is accepted when it succeeded and no errors were reported.
+    pub fn is_accepted(&self) -> bool {
+        self.status.is_success() && self.errors.is_empty()
+    }
+}
@@ -0,0 +1,81 @@
+use super::streaming::{self, CappedBody, StreamError};
+use reqwest::header::CONTENT_TYPE;
+
+/// Binary response body, e.g. PDF or ZIP export, along with its declared content type.
+#[derive(Debug)]
+pub struct Attachment {
+    pub content_type: Option<String>,
+    pub body: CappedBody,
+}
+
+/// Reads the whole binary response within the size limit.
+///
+/// # Arguments
+///
+/// * `response` - Response whose body has not been read yet
+/// * `max_bytes` - Maximal accepted size of the body
+///
+pub async fn read_attachment(
+    response: reqwest::Response,
+    max_bytes: u64,
+) -> Result<Attachment, StreamError> {
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_owned);
+    let body = streaming::read_capped(response, max_bytes).await?;
+    Ok(Attachment { content_type, body })
+}
+
+impl Attachment {
+    /// Panics unless the declared media type, ignoring parameters such as charset,
+    /// is equal to the expected one.
+    pub fn verify_content_type(&self, expected_content_type: &str) {
+        let media_type = self
+            .content_type
+            .as_deref()
+            .map(|content_type| content_type.split(';').next().unwrap_or("").trim());
+        assert_eq!(
+            media_type,
+            Some(expected_content_type),
+            "Unexpected content type of the attachment"
+        );
+    }
+
+    /// Panics unless the size of the attachment is within the inclusive range.
+    pub fn verify_size_between(&self, min_bytes: u64, max_bytes: u64) {
+        let size = self.body.summary.size;
+        assert!(
+            (min_bytes..=max_bytes).contains(&size),
+            "Expected attachment of {} to {} bytes, got {} bytes",
+            min_bytes,
+            max_bytes,
+            size
+        );
+    }
+
+    /// Panics unless the attachment starts with the given bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `magic_bytes_hex` - Expected first bytes in hex, e.g. "504B0304" for ZIP
+    ///
+    pub fn verify_magic_bytes(&self, magic_bytes_hex: &str) {
+        let magic_bytes = hex::decode(magic_bytes_hex).expect("Magic bytes should be given in hex");
+        assert!(
+            self.body.bytes.starts_with(&magic_bytes),
+            "Attachment does not start with bytes {}",
+            magic_bytes_hex
+        );
+    }
+
+    /// Panics unless SHA-256 of the attachment is equal to the expected hex digest.
+    pub fn verify_sha256(&self, expected_sha256: &str) {
+        assert_eq!(
+            self.body.summary.sha256,
+            expected_sha256.to_lowercase(),
+            "Unexpected SHA-256 of the attachment"
+        );
+    }
+}
@@ -0,0 +1,123 @@
+use super::config::{EndpointRegistry, Profile};
+use super::orders::DerivedOrder;
+use super::parameters::OrderSide;
+use super::reference::PairInfo;
+use super::{api_errors, fixtures, skipping};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Protection of real accounts from test orders worth a noticeable part of their balance.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BalanceGuardSettings {
+    /// Private endpoint listing balances by asset, e.g. "Balance"; orders are not guarded when not set
+    pub endpoint: Option<String>,
+    /// Largest part of the balance of the spent asset an order may be worth
+    pub max_fraction: f64,
+}
+
+impl Default for BalanceGuardSettings {
+    fn default() -> Self {
+        Self {
+            endpoint: None,
+            max_fraction: 0.01,
+        }
+    }
+}
+
+/// Balances by asset, queried once per account on the first guarded order; the run account is keyed by None.
+static BALANCES: Lazy<Mutex<HashMap<Option<String>, HashMap<String, f64>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+async fn balances_of(
+    profile: &Profile,
+    endpoints: &EndpointRegistry,
+    endpoint_name: &str,
+    account: Option<&str>,
+) -> HashMap<String, f64> {
+    let account = account.map(str::to_owned);
+    if let Some(balances) = BALANCES
+        .lock()
+        .expect("Balances should not be poisoned")
+        .get(&account)
+    {
+        return balances.clone();
+    }
+    let json_response =
+        fixtures::send_private_as(account.as_deref(), endpoint_name, &[], profile, endpoints)
+            .await
+            .unwrap_or_else(|err| panic!("{}", err));
+    let errors = api_errors::errors_of(&json_response);
+    assert!(
+        errors.is_empty(),
+        "Balance cannot be queried from {}: {:?}",
+        endpoint_name,
+        errors
+    );
+    let balances: HashMap<String, f64> = json_response
+        .get("result")
+        .and_then(serde_json::Value::as_object)
+        .unwrap_or_else(|| panic!("Response of {} has no result object", endpoint_name))
+        .iter()
+        .filter_map(|(asset, amount)| {
+            let amount = match amount {
+                serde_json::Value::String(amount) => amount.parse().ok(),
+                amount => amount.as_f64(),
+            };
+            amount.map(|amount| (asset.clone(), amount))
+        })
+        .collect();
+    BALANCES
+        .lock()
+        .expect("Balances should not be poisoned")
+        .insert(account, balances.clone());
+    balances
+}
+
+/// Skips the scenario when the order is worth more than the allowed fraction of the balance of the asset it spends:
+/// the quote asset of buy orders and the base asset of sell orders.
+///
+/// # Arguments
+///
+/// * `profile` - Profile of the run
+/// * `endpoints` - Endpoints known to the suite
+/// * `scenario_key` - Key of the scenario about to send the order
+/// * `account` - Account whose keys sign the order, if selected by the scenario
+/// * `pair` - Trading rules of the pair of the order
+/// * `order` - The order
+///
+pub async fn skip_if_unaffordable(
+    profile: &Profile,
+    endpoints: &EndpointRegistry,
+    scenario_key: &str,
+    account: Option<&str>,
+    pair: &PairInfo,
+    order: &DerivedOrder,
+) {
+    let settings = &profile.balance_guard;
+    let endpoint_name = match &settings.endpoint {
+        Some(endpoint_name) => endpoint_name,
+        None => return,
+    };
+    let (asset, amount) = match order.side {
+        OrderSide::Buy => (&pair.quote, order.cost()),
+        OrderSide::Sell => (&pair.base, order.volume.parse().unwrap_or_default()),
+    };
+    let balances = balances_of(profile, endpoints, endpoint_name, account).await;
+    let balance = balances.get(asset).copied().unwrap_or_default();
+    if amount > balance * settings.max_fraction {
+        skipping::skip_scenario(
+            scenario_key,
+            &format!(
+                "order worth {} {} exceeds {}% of the balance of {} {}",
+                amount,
+                asset,
+                settings.max_fraction * 100.0,
+                balance,
+                asset
+            ),
+        )
+    }
+}
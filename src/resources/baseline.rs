@@ -0,0 +1,174 @@
+use super::config::ConfigError;
+use super::results::{RunResult, ScenarioStatus};
+use serde::Deserialize;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Checked-in file listing currently known failures.
+pub const BASELINE_FILE: &str = "./baseline.toml";
+
+/// Scenario known to fail until the issue behind it is fixed.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct KnownFailure {
+    pub feature: String,
+    pub scenario: String,
+    /// Date after which the failure fails the run again, e.g. "2026-12-31"
+    pub expires: String,
+    /// Description of the issue, e.g. a link to the ticket
+    #[serde(default)]
+    pub reason: String,
+}
+
+/// Failures accepted for now, kept in the `[[known_failures]]` tables of the baseline file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Baseline {
+    pub known_failures: Vec<KnownFailure>,
+}
+
+/// Effect of the baseline on the result of a run.
+#[derive(Debug, Clone, Default)]
+pub struct BaselineReport {
+    /// Failures reported as known issues
+    pub known_issues: Vec<KnownFailure>,
+    /// Baselined scenarios which passed, so they can be removed from the baseline
+    pub passing: Vec<KnownFailure>,
+    /// Baselined scenarios which failed after their entry expired
+    pub expired: Vec<KnownFailure>,
+}
+
+impl Baseline {
+    /// Loads the baseline file; a missing file is treated as an empty baseline.
+    pub fn load() -> Result<Self, ConfigError> {
+        let baseline = match fs::read_to_string(BASELINE_FILE) {
+            Ok(content) => {
+                toml::from_str(&content).map_err(|err| ConfigError::InvalidFile(err.to_string()))?
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Baseline::default(),
+            Err(err) => return Err(ConfigError::InvalidFile(err.to_string())),
+        };
+        Ok(baseline)
+    }
+
+    /// Turns failures of baselined scenarios into known issues, unless their entries expired.
+    ///
+    /// # Arguments
+    ///
+    /// * `run_result` - Result of the run
+    /// * `today` - Current date, e.g. "2026-10-16"
+    ///
+    pub fn apply(&self, run_result: &mut RunResult, today: &str) -> BaselineReport {
+        let mut report = BaselineReport::default();
+        for known_failure in &self.known_failures {
+            let outcomes: Vec<_> = run_result
+                .scenarios
+                .iter_mut()
+                .filter(|outcome| {
+                    outcome.feature == known_failure.feature
+                        && outcome.scenario == known_failure.scenario
+                })
+                .collect();
+            if outcomes.is_empty() {
+                continue;
+            }
+            if outcomes
+                .iter()
+                .all(|outcome| outcome.status == ScenarioStatus::Passed)
+            {
+                report.passing.push(known_failure.clone());
+                continue;
+            }
+            if known_failure.expires.as_str() < today {
+                report.expired.push(known_failure.clone());
+                continue;
+            }
+            for outcome in outcomes {
+                if outcome.status == ScenarioStatus::Failed {
+                    outcome.status = ScenarioStatus::KnownIssue;
+                    outcome.known_issue = Some(known_failure.reason.clone());
+                }
+            }
+            report.known_issues.push(known_failure.clone());
+        }
+        report
+    }
+}
+
+impl fmt::Display for BaselineReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for known_failure in &self.known_issues {
+            writeln!(
+                f,
+                "Known issue: {}: {} ({}, until {})",
+                known_failure.feature,
+                known_failure.scenario,
+                known_failure.reason,
+                known_failure.expires
+            )?;
+        }
+        for known_failure in &self.passing {
+            writeln!(
+                f,
+                "Baselined scenario passes, remove it from {}: {}: {}",
+                BASELINE_FILE, known_failure.feature, known_failure.scenario
+            )?;
+        }
+        for known_failure in &self.expired {
+            writeln!(
+                f,
+                "Baseline entry expired on {}, the failure counts again: {}: {}",
+                known_failure.expires, known_failure.feature, known_failure.scenario
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Current date in UTC, e.g. "2026-10-16", comparable with expiry dates of the baseline.
+pub fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("System time should be after unix epoch")
+        .as_secs() as i64
+        / 86_400;
+    date_of(days)
+}
+
+/// Civil date in UTC, e.g. "2026-10-16", of the day since the unix epoch.
+///
+/// # Arguments
+///
+/// * `days` - Number of days since the unix epoch
+///
+pub fn date_of(days: i64) -> String {
+    let (year, month, day) = civil_date(days);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Year, month and day of month in UTC of the day since the unix epoch.
+///
+/// # Arguments
+///
+/// * `days` - Number of days since the unix epoch
+///
+pub fn civil_date(days: i64) -> (i64, i64, i64) {
+    // Conversion of days since the unix epoch into the civil date, by Howard Hinnant
+    let shifted = days + 719_468;
+    let era = shifted.div_euclid(146_097);
+    let day_of_era = shifted.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Caps on the number of API calls, protecting the rate limit of the account
+/// against e.g. endless pagination loops; unset caps are not enforced.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RequestBudget {
+    /// Maximal number of API calls in the whole run
+    pub per_run: Option<usize>,
+    /// Maximal number of API calls in a single scenario
+    pub per_scenario: Option<usize>,
+}
+
+/// API calls made so far in the run, shared by all scenarios.
+static RUN_REQUESTS: AtomicUsize = AtomicUsize::new(0);
+
+/// Counts an API call against the budget, failing the step when the budget is exhausted.
+///
+/// # Arguments
+///
+/// * `budget` - Caps of the profile
+/// * `scenario_requests` - API calls made so far in the scenario
+///
+pub fn consume(budget: &RequestBudget, scenario_requests: &mut usize) {
+    if let Some(per_scenario) = budget.per_scenario {
+        if *scenario_requests >= per_scenario {
+            panic!(
+                "request budget exhausted: scenario is limited to {} API calls",
+                per_scenario
+            );
+        }
+    }
+    if let Err(err) = consume_run(budget) {
+        panic!("{}", err);
+    }
+    *scenario_requests += 1;
+}
+
+/// Counts an API call against the budget of the run only, e.g. a call made by fixtures or by checks
+/// before the scenarios; fails when the budget is exhausted.
+///
+/// # Arguments
+///
+/// * `budget` - Caps of the profile
+///
+pub fn consume_run(budget: &RequestBudget) -> Result<(), String> {
+    let within_run_budget = RUN_REQUESTS
+        .fetch_update(
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+            |run_requests| match budget.per_run {
+                Some(per_run) if run_requests >= per_run => None,
+                _ => Some(run_requests + 1),
+            },
+        )
+        .is_ok();
+    match budget.per_run {
+        Some(per_run) if !within_run_budget => Err(format!(
+            "request budget exhausted: run is limited to {} API calls",
+            per_run
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// API calls made so far in the run.
+pub fn run_requests() -> usize {
+    RUN_REQUESTS.load(Ordering::SeqCst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn consume_counts_the_call_in_the_scenario_and_the_run() {
+        let run_requests_before = run_requests();
+        let mut scenario_requests = 0;
+        consume(&RequestBudget::default(), &mut scenario_requests);
+        assert_eq!(scenario_requests, 1);
+        assert!(run_requests() > run_requests_before);
+    }
+
+    #[test]
+    #[should_panic(expected = "request budget exhausted: scenario is limited to 2 API calls")]
+    fn consume_fails_once_the_scenario_budget_is_spent() {
+        let budget = RequestBudget {
+            per_run: None,
+            per_scenario: Some(2),
+        };
+        let mut scenario_requests = 2;
+        consume(&budget, &mut scenario_requests);
+    }
+
+    #[test]
+    fn consume_run_fails_once_the_run_budget_is_spent() {
+        let budget = RequestBudget {
+            per_run: Some(run_requests()),
+            per_scenario: None,
+        };
+        let spent = consume_run(&budget);
+        assert_eq!(
+            spent,
+            Err(format!(
+                "request budget exhausted: run is limited to {} API calls",
+                budget.per_run.unwrap()
+            ))
+        );
+    }
+
+    #[test]
+    fn consume_run_is_unlimited_without_a_cap() {
+        assert_eq!(consume_run(&RequestBudget::default()), Ok(()));
+    }
+}
@@ -0,0 +1,111 @@
+use super::redaction::{self, ReportBuffer};
+use super::results;
+use super::sharding::Shard;
+use std::process;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Exit code of a run cancelled with Ctrl-C, distinct from the one of failed runs.
+pub const CANCELLED_EXIT_CODE: i32 = 130;
+
+/// Whether the run was cancelled; scenarios are not started afterwards.
+static CANCELLED: AtomicBool = AtomicBool::new(false);
+
+/// Number of steps in progress, awaited before the partial report is written.
+static STEPS_IN_PROGRESS: AtomicUsize = AtomicUsize::new(0);
+
+/// Whether the run was cancelled with Ctrl-C.
+pub fn is_cancelled() -> bool {
+    CANCELLED.load(Ordering::SeqCst)
+}
+
+/// Fails the scenario about to start when the run was cancelled, so it sends no requests.
+pub fn refuse_if_cancelled() {
+    if is_cancelled() {
+        panic!("The run was cancelled");
+    }
+}
+
+pub fn step_started() {
+    STEPS_IN_PROGRESS.fetch_add(1, Ordering::SeqCst);
+}
+
+pub fn step_finished() {
+    STEPS_IN_PROGRESS.fetch_sub(1, Ordering::SeqCst);
+}
+
+/// Exits with `CANCELLED_EXIT_CODE` when the run was cancelled, once its reports are written.
+pub fn exit_if_cancelled() {
+    if is_cancelled() {
+        process::exit(CANCELLED_EXIT_CODE);
+    }
+}
+
+/// Handles Ctrl-C: no new scenarios are started, so the run finishes once the steps in progress do
+/// and writes its reports as usual, marked as cancelled.
+/// Steps still in progress after the grace period, or at a second Ctrl-C, are abandoned:
+/// the JUnit report and the json result of the finished scenarios are written
+/// and the process exits with `CANCELLED_EXIT_CODE` at once.
+///
+/// # Arguments
+///
+/// * `suite` - Name of the suite, e.g. public
+/// * `report_path` - Path the JUnit report of the suite is written to
+/// * `result_path` - Path the json result of the suite is written to
+/// * `seed` - Seed of the run, recorded in the report
+/// * `shard` - Part of the scenarios run, if the suite is split across executors
+/// * `grace_seconds` - Longest time steps in progress are awaited
+///
+pub fn install(
+    suite: &str,
+    report_path: &str,
+    result_path: &str,
+    seed: u64,
+    shard: Option<Shard>,
+    grace_seconds: u64,
+) {
+    let suite = suite.to_owned();
+    let report_path = report_path.to_owned();
+    let result_path = result_path.to_owned();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_err() {
+            return;
+        }
+        CANCELLED.store(true, Ordering::SeqCst);
+        eprintln!(
+            "Cancelling the run; waiting up to {} s for steps in progress (Ctrl-C to stop now)",
+            grace_seconds
+        );
+        let steps_finished = async {
+            while STEPS_IN_PROGRESS.load(Ordering::SeqCst) > 0 {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        };
+        let finished = tokio::select! {
+            finished = tokio::time::timeout(Duration::from_secs(grace_seconds), steps_finished) => {
+                finished.is_ok()
+            }
+            _ = tokio::signal::ctrl_c() => false,
+        };
+        if finished {
+            return;
+        }
+
+        let run_result = results::RunResult {
+            cancelled: true,
+            ..results::run_result(&suite, seed, shard)
+        };
+        let report = ReportBuffer::default();
+        run_result
+            .write_junit(&mut report.clone())
+            .expect("Writing to report buffer should not fail");
+        redaction::write_redacted_report(&report_path, &report);
+        run_result.write(&result_path);
+        eprintln!(
+            "Run cancelled after {} scenarios; partial report written to {}",
+            run_result.scenarios.len(),
+            report_path
+        );
+        process::exit(CANCELLED_EXIT_CODE);
+    });
+}
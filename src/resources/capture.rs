@@ -0,0 +1,20 @@
+/// Extracts value of the field pointed by dot separated path, e.g. "result.txid.0".
+/// String values are returned without quotes, other values as json.
+///
+/// # Arguments
+///
+/// * `json_response` - Response body parsed to json
+/// * `field_path` - Dot separated keys or array indexes leading to the field
+///
+pub fn extract_field(json_response: &serde_json::Value, field_path: &str) -> Option<String> {
+    let json_pointer: String = field_path
+        .split('.')
+        .map(|key| format!("/{}", key.replace('~', "~0").replace('/', "~1")))
+        .collect();
+    let value = json_response.pointer(&json_pointer)?;
+    let extracted = match value {
+        serde_json::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    };
+    Some(extracted)
+}
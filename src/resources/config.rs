@@ -0,0 +1,756 @@
+use super::balance_guard::BalanceGuardSettings;
+use super::budget::RequestBudget;
+use super::correlation::CorrelationSettings;
+use super::cors::CorsPreflight;
+use super::cursor::CursorConfig;
+use super::deprecation::DeprecationSettings;
+use super::feed::FeedSettings;
+use super::fixtures::FixtureDefinition;
+use super::flakiness::FlakinessSettings;
+use super::generators::GeneratorSettings;
+use super::http_client::HttpVersionPreference;
+use super::integrity::IntegrityRule;
+use super::key_permissions::KeyPermissionSettings;
+use super::latency_history::RegressionSettings;
+use super::maintenance::MaintenanceSettings;
+use super::masking::MaskRule;
+use super::monitor::MonitorSettings;
+use super::naming;
+use super::openapi::OpenApiSettings;
+use super::orders::OrderSettings;
+use super::permissions::PermissionSettings;
+use super::rate_limit::RateLimit;
+use super::reconciliation::ReconciliationSettings;
+use super::reference::ReferenceSettings;
+use super::scripting::ScriptSettings;
+use super::scrubbing::ScrubRule;
+use super::security_headers::SecurityHeaderSettings;
+use super::signing::{SecretEncoding, SignerConfig, SigningSettings};
+use super::status_page::StatusPageSettings;
+use super::streaming;
+use super::templating::TemplateSettings;
+use super::throughput::ThroughputSettings;
+use super::ticker::TickerSettings;
+use super::translation::StepTranslation;
+use super::verdict::VerdictPolicy;
+use super::version_negotiation::VersionNegotiationSettings;
+use super::wasm_plugins::WasmPluginSettings;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+/// File with settings of profiles, optional when running with the default profile.
+pub const CONFIG_FILE: &str = "./api_verify.toml";
+
+/// Profile used when API_PROFILE is not set.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// API version used when the profile does not select one.
+pub const DEFAULT_API_VERSION: &str = "0";
+
+/// Problems with configuration found while loading it.
+#[derive(Debug)]
+pub enum ConfigError {
+    Missing(String),
+    Malformed { variable: String, reason: String },
+    InvalidFile(String),
+    UnknownProfile(String),
+    UnknownEndpoint(String),
+    UnknownVersion(String),
+    UnknownAccount(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Missing(variable) => {
+                write!(f, "Missing environment variable: {}", variable)
+            }
+            ConfigError::Malformed { variable, reason } => {
+                write!(f, "Malformed environment variable {}: {}", variable, reason)
+            }
+            ConfigError::InvalidFile(reason) => {
+                write!(f, "Invalid config file {}: {}", CONFIG_FILE, reason)
+            }
+            ConfigError::UnknownProfile(profile) => {
+                write!(f, "Profile {} is not defined in {}", profile, CONFIG_FILE)
+            }
+            ConfigError::UnknownEndpoint(endpoint) => {
+                write!(f, "Endpoint {} is not defined in {}", endpoint, CONFIG_FILE)
+            }
+            ConfigError::UnknownVersion(version) => {
+                write!(
+                    f,
+                    "API version {} is not defined in {}",
+                    version, CONFIG_FILE
+                )
+            }
+            ConfigError::UnknownAccount(account) => {
+                write!(
+                    f,
+                    "Account {} is not defined in the profile of {}",
+                    account, CONFIG_FILE
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Settings which vary between environments the suite is run against.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    /// Version of the API, e.g. "0" for paths starting with "/0/"
+    pub api_version: String,
+    /// Headers asking for and indicating a version, for APIs versioned by headers instead of paths
+    pub version_negotiation: VersionNegotiationSettings,
+    /// Refuses to send requests to endpoints flagged as mutating, so the suite can be safely run against production
+    pub read_only: bool,
+    /// Endpoints scenarios may request; all are allowed when empty
+    pub allowed_endpoints: Vec<String>,
+    /// Endpoints scenarios may not request; scenarios requesting them are skipped
+    pub denied_endpoints: Vec<String>,
+    /// Maximal number of followed redirects; 0 disables following them
+    pub max_redirects: usize,
+    /// HTTP versions offered to the server
+    pub http_version: HttpVersionPreference,
+    /// Limit of the decompressed size of response bodies
+    pub max_body_bytes: u64,
+    /// Addresses used instead of DNS resolution, by host
+    pub resolve: HashMap<String, IpAddr>,
+    /// Call counter the requests are throttled with
+    pub rate_limit: RateLimit,
+    /// Caps on the number of API calls per run and per scenario
+    pub request_budget: RequestBudget,
+    /// Warm-up and cool-down used when the suite is run as a synthetic monitor
+    pub monitor: MonitorSettings,
+    /// Blackout windows of announced downtime, during which scenarios are delayed or skipped
+    pub maintenance: MaintenanceSettings,
+    /// Status page of the provider, whose state is attached to the report when scenarios fail
+    pub status_page: StatusPageSettings,
+    /// Public endpoints asset pairs and assets are preloaded from before the suite, for steps composing orders
+    pub reference: ReferenceSettings,
+    /// Composition of validate-only test orders from the trading rules of their pair
+    pub orders: OrderSettings,
+    /// Skipping of order scenarios whose order is worth too much of the account balance
+    pub balance_guard: BalanceGuardSettings,
+    /// Endpoints of trades and ledger entries reconciled with each other, and the precision of amounts
+    pub reconciliation: ReconciliationSettings,
+    /// Tolerance of the invariants between fields of tickers
+    pub ticker: TickerSettings,
+    /// Server time endpoint and timestamp positions of market data feeds whose recency is verified
+    pub feeds: FeedSettings,
+    /// Rules of referential integrity between responses of different endpoints
+    pub integrity_rules: Vec<IntegrityRule>,
+    /// Detection of median latency regressions against the previous runs
+    pub latency_regression: RegressionSettings,
+    /// Masking of volatile response fields, applied before responses are kept in snapshots
+    pub masks: Vec<MaskRule>,
+    /// Scrubbing of sensitive response values, e.g. balances, before they reach snapshots or console output
+    pub scrub: Vec<ScrubRule>,
+    /// Expectations towards security headers of responses
+    pub security_headers: SecurityHeaderSettings,
+    /// OpenAPI spec responses are checked against, besides the schemas of the registry
+    pub openapi: OpenApiSettings,
+    /// Preflight sent by "I send a CORS preflight request"
+    pub cors: CorsPreflight,
+    /// Limits of throughput tests, which override the rate limit
+    pub throughput: ThroughputSettings,
+    /// Values of `${NAME}` placeholders in steps of features, by name; environment variables are used otherwise
+    pub variables: HashMap<String, String>,
+    /// Headers correlating requests with logs of the provider
+    pub correlation: CorrelationSettings,
+    /// Handling of Deprecation and Sunset headers of responses, which are always reported after the run
+    pub deprecation: DeprecationSettings,
+    /// Validators compiled to WebAssembly, registered before the run
+    pub wasm_plugins: WasmPluginSettings,
+    /// Rhai scripts defining checks, request payloads and steps
+    pub scripts: ScriptSettings,
+    /// Tera templates of request parameters
+    pub templates: TemplateSettings,
+    /// Synthetic values generated for request parameters by templates and steps
+    pub generators: GeneratorSettings,
+    /// Longest time steps in progress are awaited after Ctrl-C, before writing the partial report
+    pub cancel_grace_seconds: u64,
+    /// Classification of scenarios as flaky by the previous runs, and their quarantine
+    pub flakiness: FlakinessSettings,
+    /// Policy deciding whether the run fails, e.g. tolerating a number of undeclared fields
+    pub verdict: VerdictPolicy,
+    /// Handling of requests rejected for insufficient permissions of the API key
+    pub permissions: PermissionSettings,
+    /// Query of permissions of the API key, gating scenarios tagged with the permissions they require
+    pub key_permissions: KeyPermissionSettings,
+    /// Credential sets of subaccounts, by account name, as prefixes of their environment variables,
+    /// e.g. "SUBA_" for SUBA_API_KEY, SUBA_API_SECRET and SUBA_OTP_SECRET
+    pub accounts: HashMap<String, String>,
+    /// Headers sent with every request to private API and included in its signature, by name,
+    /// e.g. the one selecting the subaccount the keys act on; signed in the order of their names
+    pub signed_headers: BTreeMap<String, String>,
+    /// Conventions the API uses to verify signatures of private requests
+    pub signing: SigningSettings,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            api_version: DEFAULT_API_VERSION.to_owned(),
+            version_negotiation: VersionNegotiationSettings::default(),
+            read_only: false,
+            allowed_endpoints: Vec::new(),
+            denied_endpoints: Vec::new(),
+            max_redirects: 10,
+            http_version: HttpVersionPreference::default(),
+            max_body_bytes: streaming::DEFAULT_MAX_BYTES,
+            resolve: HashMap::new(),
+            rate_limit: RateLimit::default(),
+            request_budget: RequestBudget::default(),
+            monitor: MonitorSettings::default(),
+            maintenance: MaintenanceSettings::default(),
+            status_page: StatusPageSettings::default(),
+            reference: ReferenceSettings::default(),
+            orders: OrderSettings::default(),
+            balance_guard: BalanceGuardSettings::default(),
+            reconciliation: ReconciliationSettings::default(),
+            ticker: TickerSettings::default(),
+            feeds: FeedSettings::default(),
+            integrity_rules: Vec::new(),
+            latency_regression: RegressionSettings::default(),
+            masks: Vec::new(),
+            scrub: Vec::new(),
+            security_headers: SecurityHeaderSettings::default(),
+            openapi: OpenApiSettings::default(),
+            cors: CorsPreflight::default(),
+            throughput: ThroughputSettings::default(),
+            variables: HashMap::new(),
+            correlation: CorrelationSettings::default(),
+            deprecation: DeprecationSettings::default(),
+            wasm_plugins: WasmPluginSettings::default(),
+            scripts: ScriptSettings::default(),
+            templates: TemplateSettings::default(),
+            generators: GeneratorSettings::default(),
+            cancel_grace_seconds: 10,
+            flakiness: FlakinessSettings::default(),
+            verdict: VerdictPolicy::default(),
+            permissions: PermissionSettings::default(),
+            key_permissions: KeyPermissionSettings::default(),
+            accounts: HashMap::new(),
+            signed_headers: BTreeMap::new(),
+            signing: SigningSettings::default(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    profiles: HashMap<String, Profile>,
+    endpoints: HashMap<String, Endpoint>,
+    fixtures: HashMap<String, FixtureDefinition>,
+    versions: HashMap<String, VersionConfig>,
+    translations: HashMap<String, Vec<StepTranslation>>,
+}
+
+/// Settings specific to a single API version, kept in the `[versions.<version>]` table.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct VersionConfig {
+    endpoints: HashMap<String, Endpoint>,
+}
+
+/// Reads the config file; a missing file is treated as an empty one.
+fn read_config_file() -> Result<ConfigFile, ConfigError> {
+    let config_file = match fs::read_to_string(CONFIG_FILE) {
+        Ok(content) => {
+            toml::from_str(&content).map_err(|err| ConfigError::InvalidFile(err.to_string()))?
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => ConfigFile::default(),
+        Err(err) => return Err(ConfigError::InvalidFile(err.to_string())),
+    };
+    Ok(config_file)
+}
+
+impl Profile {
+    /// Loads the profile named by API_PROFILE, or the default one, from the config file.
+    /// Without the config file, only the default profile is available, with default settings.
+    pub fn load() -> Result<Self, ConfigError> {
+        let profile_name = env::var("API_PROFILE").ok();
+        let mut config_file = read_config_file()?;
+        match profile_name {
+            Some(profile_name) => config_file
+                .profiles
+                .remove(&profile_name)
+                .ok_or(ConfigError::UnknownProfile(profile_name)),
+            None => Ok(config_file
+                .profiles
+                .remove(DEFAULT_PROFILE)
+                .unwrap_or_default()),
+        }
+    }
+}
+
+/// Authentication required by an endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Auth {
+    Public,
+    Private,
+}
+
+/// Description of a single endpoint, kept in the `[endpoints.<Name>]` table of the config file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Endpoint {
+    /// Path appended to API_LINK, e.g. "/0/public/Time"
+    pub path: String,
+    pub auth: Auth,
+    /// HTTP method; GET for public and POST for private endpoints when not set
+    pub method: Option<String>,
+    /// File in "./schemas" describing the response; derived from the name when not set
+    pub schema: Option<String>,
+    /// Cost of a single request in terms of the API rate limit
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+    /// Time within which the response headers should be received, in milliseconds
+    pub latency_budget_ms: Option<u64>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Documented values of enum-like fields of the response, by field name, e.g. "status"
+    #[serde(default)]
+    pub enums: HashMap<String, Vec<String>>,
+    /// Paths of identifiers expected to be ASCII, e.g. "$.result.*.altname"; keys of objects matched by a path are checked too
+    #[serde(default)]
+    pub ascii: Vec<String>,
+    /// Checks of responses of the endpoint reported as warnings instead of failing the step, e.g. "enum_values"
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    /// Whether requests to the endpoint change state of the account, e.g. place or cancel orders
+    #[serde(default)]
+    pub mutating: bool,
+    /// Time for which responses are reused within the run, for reference data such as asset pairs;
+    /// cached responses do not consume the rate limit
+    pub cache_ttl_seconds: Option<u64>,
+    /// Cursor continuing the listing of an incremental endpoint, e.g. recent trades
+    pub cursor: Option<CursorConfig>,
+    /// Schemas of responses with given statuses and content types, e.g. error bodies of 4XX responses;
+    /// `schema` describes responses matched by none of them
+    #[serde(default)]
+    pub responses: Vec<ResponseSchema>,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+/// Schema of the responses of an endpoint with the given status and content type.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ResponseSchema {
+    /// Status, e.g. "200", or range of statuses, e.g. "4XX"
+    pub status: String,
+    /// Media type of the response, e.g. "application/problem+json"; responses of any type match when not set
+    pub content_type: Option<String>,
+    /// File in "./schemas" describing the response
+    pub schema: String,
+}
+
+impl ResponseSchema {
+    /// How specifically the rule matches the response, or None when it does not:
+    /// exact statuses before ranges, and rules naming the content type before the others.
+    fn specificity(&self, status: u16, content_type: Option<&str>) -> Option<u8> {
+        let status_specificity = if self.status == status.to_string() {
+            2
+        } else if self
+            .status
+            .eq_ignore_ascii_case(&format!("{}XX", status / 100))
+        {
+            0
+        } else {
+            return None;
+        };
+        let media_type = content_type
+            .and_then(|content_type| content_type.split(';').next())
+            .map(str::trim);
+        match (&self.content_type, media_type) {
+            (None, _) => Some(status_specificity),
+            (Some(expected), Some(media_type)) if expected.eq_ignore_ascii_case(media_type) => {
+                Some(status_specificity + 1)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Endpoint {
+    /// HTTP method used to request the endpoint.
+    pub fn method(&self) -> reqwest::Method {
+        let method = match (&self.method, self.auth) {
+            (Some(method), _) => method.to_uppercase(),
+            (None, Auth::Public) => "GET".to_owned(),
+            (None, Auth::Private) => "POST".to_owned(),
+        };
+        reqwest::Method::from_bytes(method.as_bytes())
+            .unwrap_or_else(|_| panic!("Invalid HTTP method: {}", method))
+    }
+}
+
+/// Endpoints known to the suite, by logical name (e.g. "OpenOrders").
+#[derive(Debug, Clone, Default)]
+pub struct EndpointRegistry {
+    endpoints: HashMap<String, Endpoint>,
+    /// Catalogue within "./schemas" holding schemas of the API version, with trailing slash
+    schema_dir: String,
+}
+
+impl EndpointRegistry {
+    /// Loads endpoints of the API version defined in the config file.
+    /// Endpoints of the default version are kept in the top-level `[endpoints]` table,
+    /// and their schemas directly in "./schemas"; endpoints of other versions are kept
+    /// in `[versions.<version>.endpoints]` tables, and their schemas in "./schemas/v<version>".
+    ///
+    /// # Arguments
+    ///
+    /// * `api_version` - Version of the API selected by the profile
+    ///
+    pub fn load(api_version: &str) -> Result<Self, ConfigError> {
+        let mut config_file = read_config_file()?;
+        let versioned_endpoints = config_file
+            .versions
+            .remove(api_version)
+            .map(|version_config| version_config.endpoints);
+        let registry = if api_version == DEFAULT_API_VERSION {
+            let mut endpoints = config_file.endpoints;
+            endpoints.extend(versioned_endpoints.unwrap_or_default());
+            Self {
+                endpoints,
+                schema_dir: String::new(),
+            }
+        } else {
+            Self {
+                endpoints: versioned_endpoints
+                    .ok_or_else(|| ConfigError::UnknownVersion(api_version.to_owned()))?,
+                schema_dir: format!("v{}/", api_version),
+            }
+        };
+        Ok(registry)
+    }
+
+    /// Finds the endpoint by its logical name.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint_name` - Name of the endpoint in upper camel case
+    ///
+    pub fn get(&self, endpoint_name: &str) -> Result<&Endpoint, ConfigError> {
+        self.endpoints
+            .get(endpoint_name)
+            .ok_or_else(|| ConfigError::UnknownEndpoint(endpoint_name.to_owned()))
+    }
+
+    /// Name of the file in "./schemas" describing responses of the endpoint.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint_name` - Name of the endpoint in upper camel case
+    ///
+    pub fn schema_file(&self, endpoint_name: &str) -> String {
+        match self.endpoints.get(endpoint_name) {
+            Some(Endpoint {
+                schema: Some(schema),
+                ..
+            }) => format!("{}{}", self.schema_dir, schema),
+            _ => format!("{}{}", self.schema_dir, naming::schema_file(endpoint_name)),
+        }
+    }
+
+    /// Name of the file in "./schemas" describing the response of the endpoint with the status and content type,
+    /// chosen among `responses` of the endpoint; the schema of the endpoint when none of them matches.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint_name` - Name of the endpoint in upper camel case
+    /// * `status` - Status of the response
+    /// * `content_type` - Content-Type header of the response
+    ///
+    pub fn response_schema_file(
+        &self,
+        endpoint_name: &str,
+        status: u16,
+        content_type: Option<&str>,
+    ) -> String {
+        let selected = self.endpoints.get(endpoint_name).and_then(|endpoint| {
+            endpoint
+                .responses
+                .iter()
+                .filter_map(|response| {
+                    Some((response.specificity(status, content_type)?, response))
+                })
+                // The first of equally specific rules wins
+                .fold(
+                    None,
+                    |best: Option<(u8, &ResponseSchema)>, candidate| match best {
+                        Some(best) if best.0 >= candidate.0 => Some(best),
+                        _ => Some(candidate),
+                    },
+                )
+        });
+        match selected {
+            Some((_, response)) => format!("{}{}", self.schema_dir, response.schema),
+            None => self.schema_file(endpoint_name),
+        }
+    }
+
+    /// Name of the file in "./schemas" with rules for csv responses of the endpoint.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint_name` - Name of the endpoint in upper camel case
+    ///
+    pub fn csv_rules_file(&self, endpoint_name: &str) -> String {
+        format!(
+            "{}{}",
+            self.schema_dir,
+            naming::csv_rules_file(endpoint_name)
+        )
+    }
+
+    /// All endpoints, by logical name.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Endpoint)> {
+        self.endpoints.iter()
+    }
+
+    /// Finds the endpoint a request was sent to by the path of its URL, which may include the base path
+    /// of API_LINK; the endpoint with the longest matching path wins.
+    ///
+    /// # Arguments
+    ///
+    /// * `url_path` - Path of the requested URL, e.g. "/0/public/Time"
+    ///
+    pub fn find_by_path(&self, url_path: &str) -> Option<(&String, &Endpoint)> {
+        self.endpoints
+            .iter()
+            .filter(|(_, endpoint)| !endpoint.path.is_empty() && url_path.ends_with(&endpoint.path))
+            .max_by_key(|(_, endpoint)| endpoint.path.len())
+    }
+}
+
+/// Loads fixtures defined in the config file, by name.
+pub fn load_fixture_definitions() -> Result<HashMap<String, FixtureDefinition>, ConfigError> {
+    let definitions = read_config_file()?.fixtures;
+    Ok(definitions)
+}
+
+/// Loads translations of step phrases defined in the config file, by language.
+pub fn load_translations() -> Result<HashMap<String, Vec<StepTranslation>>, ConfigError> {
+    let translations = read_config_file()?.translations;
+    Ok(translations)
+}
+
+/// Properties needed to send requests to private API.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PrivateApiProperties {
+    pub otp_secret: String,
+    pub api_link: String,
+    pub api_key: String,
+    pub api_secret: String,
+    pub signer_config: SignerConfig,
+}
+
+impl PrivateApiProperties {
+    /// Loads the properties from environment variables
+    /// and validates the key material, so malformed keys are reported
+    /// before any request is assembled.
+    ///
+    /// # Arguments
+    ///
+    /// * `signer_config` - Signing conventions of the profile, telling how the secret is encoded
+    ///
+    pub fn from_env(signer_config: SignerConfig) -> Result<Self, ConfigError> {
+        Self::from_env_with_prefix("", signer_config)
+    }
+
+    /// Loads the properties of an account whose keys are kept in environment variables
+    /// with the prefix, e.g. SUBA_API_KEY; API_LINK and signing conventions are shared by all accounts.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - Prefix of the variables with keys of the account, e.g. "SUBA_"
+    /// * `signer_config` - Signing conventions of the profile, telling how the secret is encoded
+    ///
+    pub fn from_env_with_prefix(
+        prefix: &str,
+        signer_config: SignerConfig,
+    ) -> Result<Self, ConfigError> {
+        let otp_secret_var = format!("{}OTP_SECRET", prefix);
+        let api_secret_var = format!("{}API_SECRET", prefix);
+        let properties = Self {
+            otp_secret: read_env_var(&otp_secret_var)?,
+            api_link: read_env_var("API_LINK")?,
+            api_key: read_env_var(&format!("{}API_KEY", prefix))?,
+            api_secret: read_env_var(&api_secret_var)?,
+            signer_config,
+        };
+        match properties.signer_config.secret_encoding {
+            SecretEncoding::Base64 => validate_base64(&api_secret_var, &properties.api_secret)?,
+            SecretEncoding::Hex => validate_hex(&api_secret_var, &properties.api_secret)?,
+            SecretEncoding::Raw => validate_no_whitespace(&api_secret_var, &properties.api_secret)?,
+        }
+        validate_base32(&otp_secret_var, &properties.otp_secret)?;
+        Ok(properties)
+    }
+}
+
+impl SignerConfig {
+    /// Signing conventions from `signing` and `signed_headers` of the profile;
+    /// optional environment variables, e.g. PAYLOAD_HASH, override the conventions of the profile.
+    ///
+    /// # Arguments
+    ///
+    /// * `profile` - Profile of the run
+    ///
+    pub fn from_profile(profile: &Profile) -> Result<Self, ConfigError> {
+        let settings = &profile.signing;
+        let secret_encoding =
+            read_optional_env_var("API_SECRET_ENCODING")?.unwrap_or(settings.secret_encoding);
+        let signature_encoding =
+            read_optional_env_var("SIGNATURE_ENCODING")?.unwrap_or(settings.signature_encoding);
+        let payload_hash = read_optional_env_var("PAYLOAD_HASH")?.unwrap_or(settings.payload_hash);
+        let sign_path =
+            read_optional_env_var::<Flag>("SIGN_PATH")?.map_or(settings.sign_path, |flag| flag.0);
+        let sign_method = read_optional_env_var::<Flag>("SIGN_METHOD")?
+            .map_or(settings.sign_method, |flag| flag.0);
+        let sign_host =
+            read_optional_env_var::<Flag>("SIGN_HOST")?.map_or(settings.sign_host, |flag| flag.0);
+        Ok(Self {
+            secret_encoding,
+            signature_encoding,
+            payload_hash,
+            sign_path,
+            sign_method,
+            sign_host,
+            signed_headers: profile
+                .signed_headers
+                .iter()
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect(),
+        })
+    }
+}
+
+/// Boolean value of an environment variable.
+struct Flag(bool);
+
+impl FromStr for Flag {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "true" | "1" => Ok(Flag(true)),
+            "false" | "0" => Ok(Flag(false)),
+            _ => Err(format!("expected true or false, got {:?}", value)),
+        }
+    }
+}
+
+fn read_env_var(variable: &str) -> Result<String, ConfigError> {
+    env::var(variable).map_err(|_| ConfigError::Missing(variable.to_owned()))
+}
+
+/// Reads and parses variable which may be absent.
+fn read_optional_env_var<T: FromStr<Err = String>>(
+    variable: &str,
+) -> Result<Option<T>, ConfigError> {
+    match env::var(variable) {
+        Ok(value) => value
+            .parse()
+            .map(Some)
+            .map_err(|reason| malformed(variable, reason)),
+        Err(_) => Ok(None),
+    }
+}
+
+fn malformed(variable: &str, reason: String) -> ConfigError {
+    ConfigError::Malformed {
+        variable: variable.to_owned(),
+        reason,
+    }
+}
+
+/// Whitespace is the most common result of pasting a key, so it is reported separately.
+fn validate_no_whitespace(variable: &str, value: &str) -> Result<(), ConfigError> {
+    if value.trim() != value {
+        return Err(malformed(
+            variable,
+            "value has leading or trailing whitespace; remove it".to_owned(),
+        ));
+    }
+    if let Some(position) = value.find(char::is_whitespace) {
+        return Err(malformed(
+            variable,
+            format!("value contains whitespace at position {}", position),
+        ));
+    }
+    Ok(())
+}
+
+/// Checks that the value can be decoded as standard base64.
+///
+/// # Arguments
+///
+/// * `variable` - Name of the environment variable, used in the diagnostics
+/// * `value` - Value of the environment variable
+///
+pub fn validate_base64(variable: &str, value: &str) -> Result<(), ConfigError> {
+    validate_no_whitespace(variable, value)?;
+    base64::decode(value)
+        .map(|_| ())
+        .map_err(|err| malformed(variable, format!("value is not valid base64 ({})", err)))
+}
+
+/// Checks that the value consists of base32 (RFC 4648) alphabet with optional padding.
+///
+/// # Arguments
+///
+/// * `variable` - Name of the environment variable, used in the diagnostics
+/// * `value` - Value of the environment variable
+///
+pub fn validate_base32(variable: &str, value: &str) -> Result<(), ConfigError> {
+    validate_no_whitespace(variable, value)?;
+    if value.is_empty() {
+        return Err(malformed(variable, "value is empty".to_owned()));
+    }
+    let unpadded = value.trim_end_matches('=');
+    if let Some((position, character)) = unpadded
+        .char_indices()
+        .find(|(_, character)| !matches!(character.to_ascii_uppercase(), 'A'..='Z' | '2'..='7'))
+    {
+        return Err(malformed(
+            variable,
+            format!(
+                "character {:?} at position {} is not in base32 alphabet (A-Z, 2-7)",
+                character, position
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Checks that the value can be decoded as hex.
+///
+/// # Arguments
+///
+/// * `variable` - Name of the environment variable, used in the diagnostics
+/// * `value` - Value of the environment variable
+///
+pub fn validate_hex(variable: &str, value: &str) -> Result<(), ConfigError> {
+    validate_no_whitespace(variable, value)?;
+    hex::decode(value)
+        .map(|_| ())
+        .map_err(|err| malformed(variable, format!("value is not valid hex ({})", err)))
+}
@@ -0,0 +1,284 @@
+use super::cancellation;
+use super::correlation;
+use super::redaction;
+use super::results::{self, ScenarioOutcome, ScenarioStatus};
+use super::scrubbing;
+use super::severity;
+use super::skipping;
+use super::snapshot::{self, RequestTranscript};
+use async_trait::async_trait;
+use cucumber::{event, gherkin, parser, Event, World, Writer};
+use std::collections::HashMap;
+use std::env;
+use std::fmt::{self, Debug};
+use std::time::{Duration, Instant};
+
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+const YELLOW: &str = "\x1b[33m";
+const GREY: &str = "\x1b[90m";
+const RESET: &str = "\x1b[0m";
+
+/// Options of the console output, given after `--` to `cargo test`.
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct ConsoleCli {
+    /// Prints only failed steps and the summary, e.g. on CI
+    #[clap(long)]
+    pub quiet: bool,
+}
+
+/// Writer printing every finished step with its duration and the requests it sent,
+/// color-coded by outcome, before handing events over to the wrapped writer, e.g. the JUnit one.
+/// Colors are disabled when the NO_COLOR environment variable is set.
+/// Outcomes of finished scenarios are recorded for the json result of the run.
+pub struct ConsoleWriter<Wr> {
+    inner: Wr,
+    colored: bool,
+    /// Start of steps in progress, along with the number of requests the scenario sent before them,
+    /// by scenario key and line of the step
+    started: HashMap<(String, usize), (Instant, usize)>,
+    /// Scenarios in progress, by scenario key
+    scenarios: HashMap<String, ScenarioProgress>,
+    passed: usize,
+    failed: usize,
+    skipped: usize,
+}
+
+/// Progress of a scenario, turned into its outcome once it finishes.
+struct ScenarioProgress {
+    start: Instant,
+    /// Description of the failed step or hook
+    failure: Option<String>,
+    skipped: bool,
+    finished_steps: usize,
+}
+
+impl<Wr> ConsoleWriter<Wr> {
+    pub fn new(inner: Wr) -> Self {
+        Self {
+            inner,
+            colored: env::var_os("NO_COLOR").is_none(),
+            started: HashMap::new(),
+            scenarios: HashMap::new(),
+            passed: 0,
+            failed: 0,
+            skipped: 0,
+        }
+    }
+
+    fn paint(&self, color: &str, text: &str) -> String {
+        if self.colored {
+            format!("{}{}{}", color, text, RESET)
+        } else {
+            text.to_owned()
+        }
+    }
+
+    fn on_step<W>(
+        &mut self,
+        feature: &gherkin::Feature,
+        scenario: &gherkin::Scenario,
+        step: &gherkin::Step,
+        step_event: &event::Step<W>,
+        quiet: bool,
+    ) {
+        let scenario_key = snapshot::scenario_key(feature, scenario);
+        let step_key = (scenario_key.clone(), step.position.line);
+        let (label, color) = match step_event {
+            event::Step::Started => {
+                let sent_before = snapshot::requests_of(&scenario_key).len();
+                self.started.insert(step_key, (Instant::now(), sent_before));
+                cancellation::step_started();
+                return;
+            }
+            event::Step::Passed(..) => {
+                self.passed += 1;
+                ("passed", GREEN)
+            }
+            event::Step::Skipped => {
+                self.skipped += 1;
+                if let Some(progress) = self.scenarios.get_mut(&scenario_key) {
+                    progress.skipped = true;
+                }
+                ("skipped", YELLOW)
+            }
+            // Steps stopping the scenario on purpose are reported as skipped, not failed
+            event::Step::Failed(..) if skipping::skip_reason(&scenario_key).is_some() => {
+                self.skipped += 1;
+                ("skipped", YELLOW)
+            }
+            event::Step::Failed(.., err) => {
+                self.failed += 1;
+                if let Some(progress) = self.scenarios.get_mut(&scenario_key) {
+                    progress.failure = Some(format!("{}{}: {}", step.keyword, step.value, err));
+                }
+                ("failed", RED)
+            }
+        };
+        if let Some(progress) = self.scenarios.get_mut(&scenario_key) {
+            progress.finished_steps += 1;
+        }
+        let (elapsed, requests) = match self.started.remove(&step_key) {
+            Some((start, sent_before)) => {
+                cancellation::step_finished();
+                (
+                    start.elapsed(),
+                    snapshot::requests_of(&scenario_key).split_off(sent_before),
+                )
+            }
+            None => (Duration::default(), Vec::new()),
+        };
+        if quiet && label != "failed" {
+            return;
+        }
+        println!(
+            "{} {}{} {} {}",
+            self.paint(color, &format!("{:<7}", label)),
+            step.keyword,
+            step.value,
+            self.paint(GREY, &format!("({} ms)", elapsed.as_millis())),
+            self.paint(GREY, &describe_requests(&requests)),
+        );
+        if label == "failed" {
+            println!(
+                "        in {}: {}, line {}",
+                feature.name, scenario.name, step.position.line
+            );
+        }
+    }
+
+    fn on_scenario<W>(
+        &mut self,
+        feature: &gherkin::Feature,
+        scenario: &gherkin::Scenario,
+        scenario_event: &event::Scenario<W>,
+        quiet: bool,
+    ) {
+        let scenario_key = snapshot::scenario_key(feature, scenario);
+        match scenario_event {
+            event::Scenario::Started => {
+                let progress = ScenarioProgress {
+                    start: Instant::now(),
+                    failure: None,
+                    skipped: false,
+                    finished_steps: 0,
+                };
+                self.scenarios.insert(scenario_key, progress);
+            }
+            // Hooks stopping the scenario on purpose leave it skipped, not failed
+            event::Scenario::Hook(_, event::Hook::Failed(..))
+                if skipping::skip_reason(&scenario_key).is_some() => {}
+            event::Scenario::Hook(hook_type, event::Hook::Failed(..)) => {
+                if let Some(progress) = self.scenarios.get_mut(&scenario_key) {
+                    progress.failure = Some(format!("{:?} hook failed", hook_type));
+                }
+            }
+            event::Scenario::Finished => {
+                let progress = match self.scenarios.remove(&scenario_key) {
+                    Some(progress) => progress,
+                    None => return,
+                };
+                // Scenarios refused after cancellation did not run any step
+                if cancellation::is_cancelled() && progress.finished_steps == 0 {
+                    return;
+                }
+                let warnings = severity::take_warnings(&scenario_key);
+                if !quiet {
+                    for warning in &warnings {
+                        println!("{} {}", self.paint(YELLOW, "warning"), warning);
+                    }
+                }
+                let skip_reason = skipping::take_skip_reason(&scenario_key);
+                if let (Some(reason), false) = (&skip_reason, quiet) {
+                    println!("{} {}", self.paint(YELLOW, "skipped"), reason);
+                }
+                let status = match (&progress.failure, progress.skipped || skip_reason.is_some()) {
+                    (Some(_), _) => ScenarioStatus::Failed,
+                    (None, true) => ScenarioStatus::Skipped,
+                    (None, false) => ScenarioStatus::Passed,
+                };
+                results::record_outcome(ScenarioOutcome {
+                    feature: feature.name.clone(),
+                    scenario: scenario.name.clone(),
+                    status,
+                    duration_ms: progress.start.elapsed().as_millis() as u64,
+                    failure: progress.failure,
+                    run_id: correlation::run_id().to_owned(),
+                    known_issue: None,
+                    retried: false,
+                    warnings,
+                    skip_reason,
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Prints the report of a step with sensitive values scrubbed and secrets masked,
+/// as they are in the written reports.
+///
+/// # Arguments
+///
+/// * `report` - Report of the step, e.g. the reachability matrix
+///
+pub fn print_report(report: &impl fmt::Display) {
+    let (redacted, _) = redaction::redact(&scrubbing::scrub_known_values(&report.to_string()));
+    println!("{}", redacted);
+}
+
+/// Endpoints and statuses of the requests, e.g. "AssetPair 200, OpenOrders error".
+fn describe_requests(requests: &[RequestTranscript]) -> String {
+    requests
+        .iter()
+        .map(|request| match request.status {
+            Some(status) => format!("{} {}", request.endpoint, status),
+            None => format!("{} error", request.endpoint),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[async_trait(?Send)]
+impl<W, Wr> Writer<W> for ConsoleWriter<Wr>
+where
+    W: World + Debug,
+    Wr: Writer<W>,
+    Wr::Cli: Default,
+{
+    type Cli = ConsoleCli;
+
+    async fn handle_event(
+        &mut self,
+        ev: parser::Result<Event<event::Cucumber<W>>>,
+        cli: &Self::Cli,
+    ) {
+        if let Ok(event) = &ev {
+            match &event.value {
+                event::Cucumber::Feature(
+                    feature,
+                    event::Feature::Scenario(scenario, scenario_event),
+                )
+                | event::Cucumber::Feature(
+                    feature,
+                    event::Feature::Rule(_, event::Rule::Scenario(scenario, scenario_event)),
+                ) => {
+                    if let event::Scenario::Step(step, step_event)
+                    | event::Scenario::Background(step, step_event) = scenario_event
+                    {
+                        self.on_step(feature, scenario, step, step_event, cli.quiet);
+                    }
+                    self.on_scenario(feature, scenario, scenario_event, cli.quiet);
+                }
+                event::Cucumber::Finished => println!(
+                    "{} passed, {} failed, {} skipped steps",
+                    self.paint(GREEN, &self.passed.to_string()),
+                    self.paint(RED, &self.failed.to_string()),
+                    self.paint(YELLOW, &self.skipped.to_string()),
+                ),
+                _ => {}
+            }
+        }
+        self.inner.handle_event(ev, &Wr::Cli::default()).await;
+    }
+}
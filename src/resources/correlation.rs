@@ -0,0 +1,128 @@
+use super::config::ConfigError;
+use super::seed::SeededRng;
+use once_cell::sync::Lazy;
+use reqwest::header::{HeaderMap, HeaderName};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::process;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Headers correlating requests of the suite with logs of the provider.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CorrelationSettings {
+    /// Header carrying the ID of the run in every request; empty disables it
+    pub run_header: String,
+    /// Header carrying a new ID in every request, which responses echo where documented;
+    /// empty disables it
+    pub request_header: String,
+}
+
+impl Default for CorrelationSettings {
+    fn default() -> Self {
+        Self {
+            run_header: "X-Correlation-Id".to_owned(),
+            request_header: "X-Request-Id".to_owned(),
+        }
+    }
+}
+
+/// ID of the run, taken from the RUN_ID environment variable or generated at startup.
+static RUN_ID: Lazy<String> = Lazy::new(|| env::var("RUN_ID").unwrap_or_else(|_| new_run_id()));
+
+/// ID of the run, attached to every request and stamped into every report of the run.
+pub fn run_id() -> &'static str {
+    RUN_ID.as_str()
+}
+
+/// Checks the ID of the run taken from RUN_ID before the suite starts, since it is sent as a header value
+/// of every request; it should be non-empty printable ASCII without whitespace, e.g. "nightly-2026-10-16".
+pub fn validate_run_id() -> Result<(), ConfigError> {
+    let malformed = |reason: &str| ConfigError::Malformed {
+        variable: "RUN_ID".to_owned(),
+        reason: reason.to_owned(),
+    };
+    if run_id().is_empty() {
+        return Err(malformed("value is empty"));
+    }
+    if !run_id().chars().all(|c| c.is_ascii_graphic()) {
+        return Err(malformed(
+            "value should consist of printable ASCII characters without whitespace",
+        ));
+    }
+    Ok(())
+}
+
+/// Number of IDs generated by the process, mixed into new IDs,
+/// so IDs generated at the same time differ.
+static GENERATED_IDS: AtomicU64 = AtomicU64::new(0);
+
+/// Generates a new ID of a run.
+pub fn new_run_id() -> String {
+    new_id()
+}
+
+/// Header with a new ID of a single request, unless the settings disable it.
+///
+/// # Arguments
+///
+/// * `settings` - Correlation settings of the profile
+///
+pub fn request_id_header(settings: &CorrelationSettings) -> Option<(HeaderName, String)> {
+    if settings.request_header.is_empty() {
+        return None;
+    }
+    let name = HeaderName::from_bytes(settings.request_header.as_bytes())
+        .expect("Request ID header should be a valid header name");
+    Some((name, new_id()))
+}
+
+/// Describes how the echoed request ID differs from the sent one, if it does.
+///
+/// # Arguments
+///
+/// * `sent` - ID of the request, if one was sent
+/// * `echoed` - Value echoed by the API
+/// * `location` - Header or field the value was read from, for the description
+///
+pub fn echo_problem(sent: Option<&str>, echoed: Option<&str>, location: &str) -> Option<String> {
+    let problem = match (sent, echoed) {
+        (None, _) => Some("No request ID was sent; check request_header of the profile".to_owned()),
+        (Some(_), None) => Some(format!("Request ID is not echoed in {}", location)),
+        (Some(sent), Some(echoed)) if sent != echoed => Some(format!(
+            "{} echoes {:?}, while the request ID was {:?}",
+            location, echoed, sent
+        )),
+        _ => None,
+    };
+    problem
+}
+
+/// Value of the header as text, if present.
+pub fn header_value<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+/// Generates a random ID in the format of UUID version 4.
+/// It does not depend on the seed of the run, so reproduced runs can still be told apart.
+pub fn new_id() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("System time should be after unix epoch")
+        .as_nanos() as u64;
+    let generated = GENERATED_IDS.fetch_add(1, Ordering::SeqCst);
+    let mut rng =
+        SeededRng::new(nanos ^ (u64::from(process::id()) << 32) ^ generated.rotate_right(16));
+    let high = rng.next_u64();
+    let low = rng.next_u64();
+    let id = format!(
+        "{:08x}-{:04x}-4{:03x}-{:04x}-{:012x}",
+        high >> 32,
+        (high >> 16) & 0xffff,
+        high & 0x0fff,
+        (low >> 48) & 0x3fff | 0x8000,
+        low & 0xffff_ffff_ffff
+    );
+    id
+}
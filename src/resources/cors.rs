@@ -0,0 +1,101 @@
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+
+/// Preflight request sent on behalf of a web frontend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CorsPreflight {
+    /// Origin of the frontend, sent in the Origin header
+    pub origin: String,
+    /// Method of the actual request, sent in Access-Control-Request-Method
+    pub method: String,
+    /// Headers of the actual request, sent in Access-Control-Request-Headers
+    pub request_headers: Vec<String>,
+}
+
+impl Default for CorsPreflight {
+    fn default() -> Self {
+        Self {
+            origin: "https://www.kraken.com".to_owned(),
+            method: "GET".to_owned(),
+            request_headers: Vec::new(),
+        }
+    }
+}
+
+impl CorsPreflight {
+    /// Builds OPTIONS request carrying the preflight headers.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - Client the request is sent with
+    /// * `url` - Url of the endpoint
+    ///
+    pub fn request(&self, client: &reqwest::Client, url: &str) -> reqwest::RequestBuilder {
+        let mut request = client
+            .request(reqwest::Method::OPTIONS, url)
+            .header(reqwest::header::ORIGIN, &self.origin)
+            .header(reqwest::header::ACCESS_CONTROL_REQUEST_METHOD, &self.method);
+        if !self.request_headers.is_empty() {
+            request = request.header(
+                reqwest::header::ACCESS_CONTROL_REQUEST_HEADERS,
+                self.request_headers.join(", "),
+            );
+        }
+        request
+    }
+
+    /// Checks whether CORS headers of the response allow the actual request,
+    /// returning descriptions of what is not allowed.
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - Status of the preflight response
+    /// * `headers` - Headers of the preflight response
+    ///
+    pub fn denials(&self, status: reqwest::StatusCode, headers: &HeaderMap) -> Vec<String> {
+        let header = |name: reqwest::header::HeaderName| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::trim)
+        };
+        let listed = |list: Option<&str>, item: &str| {
+            list.map_or(false, |list| {
+                list.split(',')
+                    .map(str::trim)
+                    .any(|listed| listed == "*" || listed.eq_ignore_ascii_case(item))
+            })
+        };
+        let mut denials = Vec::new();
+
+        if !status.is_success() {
+            denials.push(format!("preflight responded with status {}", status));
+        }
+        match header(reqwest::header::ACCESS_CONTROL_ALLOW_ORIGIN) {
+            Some(allowed) if allowed == "*" || allowed == self.origin => {}
+            Some(allowed) => denials.push(format!(
+                "Access-Control-Allow-Origin is {}, not {}",
+                allowed, self.origin
+            )),
+            None => denials.push("Access-Control-Allow-Origin is missing".to_owned()),
+        }
+        let allowed_methods = header(reqwest::header::ACCESS_CONTROL_ALLOW_METHODS);
+        if !listed(allowed_methods, &self.method) {
+            denials.push(format!(
+                "method {} is not in Access-Control-Allow-Methods {:?}",
+                self.method, allowed_methods
+            ));
+        }
+        let allowed_headers = header(reqwest::header::ACCESS_CONTROL_ALLOW_HEADERS);
+        for request_header in &self.request_headers {
+            if !listed(allowed_headers, request_header) {
+                denials.push(format!(
+                    "header {} is not in Access-Control-Allow-Headers {:?}",
+                    request_header, allowed_headers
+                ));
+            }
+        }
+        denials
+    }
+}
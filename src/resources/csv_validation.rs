@@ -0,0 +1,212 @@
+use super::number_format;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+
+/// Expectations towards csv response, read from a rules file in the "./schemas" catalogue.
+#[derive(Debug, Deserialize)]
+pub struct CsvRules {
+    /// Exact expected header row
+    pub header: Vec<String>,
+    /// Rules for values of the columns, by column name
+    #[serde(default)]
+    pub columns: HashMap<String, ColumnRule>,
+    /// Bounds of the number of rows, excluding the header
+    pub min_rows: Option<usize>,
+    pub max_rows: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ColumnRule {
+    #[serde(rename = "type", default)]
+    pub column_type: ColumnType,
+    /// Regular expression the whole value has to match
+    pub pattern: Option<String>,
+    /// Whether the value may be empty
+    #[serde(default)]
+    pub optional: bool,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnType {
+    String,
+    Integer,
+    Number,
+    /// Decimal number without locale artifacts, e.g. "1234.5"
+    Decimal,
+    Boolean,
+}
+
+impl Default for ColumnType {
+    fn default() -> Self {
+        ColumnType::String
+    }
+}
+
+/// Reads csv rules from the "./schemas" catalogue.
+///
+/// # Arguments
+///
+/// * `rules_file` - Name of the file containing the rules
+///
+pub fn load_rules(rules_file: &str) -> CsvRules {
+    let rules: CsvRules = serde_json::from_str(
+        fs::read_to_string(format!("./schemas/{}", rules_file))
+            .expect("Something went wrong reading the file")
+            .as_str(),
+    )
+    .expect("Csv rules should be possible to parse");
+    rules
+}
+
+impl ColumnType {
+    fn accepts(&self, value: &str) -> bool {
+        match self {
+            ColumnType::String => true,
+            ColumnType::Integer => value.parse::<i64>().is_ok(),
+            ColumnType::Number => {
+                !value.contains(|character: char| character.is_alphabetic())
+                    && value.parse::<f64>().is_ok()
+            }
+            ColumnType::Decimal => number_format::is_plain_decimal(value),
+            ColumnType::Boolean => value == "true" || value == "false",
+        }
+    }
+}
+
+/// Compiled rule of a single column, identified by its position in the header.
+struct CompiledColumnRule<'a> {
+    column_index: usize,
+    column_name: &'a str,
+    rule: &'a ColumnRule,
+    pattern: Option<Regex>,
+}
+
+/// Validates csv body against the rules, returning descriptions of all violations.
+/// Rows are numbered from 1, the header being the first row; columns are numbered from 1.
+///
+/// # Arguments
+///
+/// * `body` - Whole csv response body
+/// * `rules` - Expectations towards the body
+///
+pub fn validate_csv(body: &[u8], rules: &CsvRules) -> Vec<String> {
+    let mut violations = Vec::new();
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(body);
+    let mut records = reader.records();
+
+    let header = match records.next() {
+        Some(Ok(header)) => header,
+        Some(Err(err)) => return vec![format!("row 1: invalid csv ({})", err)],
+        None => return vec!["response is empty, header row is missing".to_owned()],
+    };
+    let header: Vec<&str> = header.iter().collect();
+    if header != rules.header {
+        violations.push(format!(
+            "row 1: expected header {:?}, got {:?}",
+            rules.header, header
+        ));
+    }
+
+    let column_rules: Vec<CompiledColumnRule> = rules
+        .columns
+        .iter()
+        .map(|(column_name, rule)| {
+            let column_index = rules
+                .header
+                .iter()
+                .position(|name| name == column_name)
+                .unwrap_or_else(|| {
+                    panic!(
+                        "Csv rules refer to column {} absent from the header",
+                        column_name
+                    )
+                });
+            let pattern = rule.pattern.as_ref().map(|pattern| {
+                Regex::new(&format!("^(?:{})$", pattern))
+                    .expect("Csv rules should contain valid patterns")
+            });
+            CompiledColumnRule {
+                column_index,
+                column_name,
+                rule,
+                pattern,
+            }
+        })
+        .collect();
+
+    let mut row_count = 0;
+    for (index, record) in records.enumerate() {
+        let row_number = index + 2;
+        let record = match record {
+            Ok(record) => record,
+            Err(err) => {
+                violations.push(format!("row {}: invalid csv ({})", row_number, err));
+                continue;
+            }
+        };
+        row_count += 1;
+        if record.len() != rules.header.len() {
+            violations.push(format!(
+                "row {}: expected {} columns, got {}",
+                row_number,
+                rules.header.len(),
+                record.len()
+            ));
+        }
+        for compiled in &column_rules {
+            let value = record.get(compiled.column_index).unwrap_or("");
+            let coordinates = format!(
+                "row {}, column {} ({})",
+                row_number,
+                compiled.column_index + 1,
+                compiled.column_name
+            );
+            if value.is_empty() {
+                if !compiled.rule.optional {
+                    violations.push(format!("{}: value is empty", coordinates));
+                }
+                continue;
+            }
+            if !compiled.rule.column_type.accepts(value) {
+                violations.push(format!(
+                    "{}: {:?} is not of type {:?}",
+                    coordinates, value, compiled.rule.column_type
+                ));
+            }
+            if let Some(pattern) = &compiled.pattern {
+                if !pattern.is_match(value) {
+                    violations.push(format!(
+                        "{}: {:?} does not match pattern {}",
+                        coordinates,
+                        value,
+                        pattern.as_str()
+                    ));
+                }
+            }
+        }
+    }
+
+    if let Some(min_rows) = rules.min_rows {
+        if row_count < min_rows {
+            violations.push(format!(
+                "expected at least {} rows, got {}",
+                min_rows, row_count
+            ));
+        }
+    }
+    if let Some(max_rows) = rules.max_rows {
+        if row_count > max_rows {
+            violations.push(format!(
+                "expected at most {} rows, got {}",
+                max_rows, row_count
+            ));
+        }
+    }
+    violations
+}
@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+
+/// Incremental listing of an endpoint, continued from the cursor returned with the previous batch;
+/// kept in `cursor` of the endpoint in the registry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CursorConfig {
+    /// Request parameter carrying the cursor, e.g. "since"
+    #[serde(default = "default_parameter")]
+    pub parameter: String,
+    /// Field of the result holding the cursor of the next batch, e.g. "last"
+    #[serde(default = "default_field")]
+    pub field: String,
+    /// Position of a sequence number within entries, consecutive across batches, e.g. 6 for the id of a trade;
+    /// without it, batches are checked for overlap only
+    pub sequence_index: Option<usize>,
+}
+
+fn default_parameter() -> String {
+    "since".to_owned()
+}
+
+fn default_field() -> String {
+    "last".to_owned()
+}
+
+/// The cursor of the batch, as sent back in the parameter of the next request.
+///
+/// # Arguments
+///
+/// * `result` - Result of the endpoint
+/// * `config` - Cursor configuration of the endpoint
+///
+pub fn cursor_of(result: &serde_json::Value, config: &CursorConfig) -> Option<String> {
+    match result.get(&config.field)? {
+        serde_json::Value::String(cursor) => Some(cursor.clone()),
+        serde_json::Value::Number(cursor) => Some(cursor.to_string()),
+        _ => None,
+    }
+}
+
+/// Link requesting the batch following the cursor, with the cursor parameter added to the query.
+///
+/// # Arguments
+///
+/// * `api_link` - Link the previous batch was requested from
+/// * `config` - Cursor configuration of the endpoint
+/// * `cursor` - Cursor returned with the previous batch
+///
+pub fn link_after(api_link: &str, config: &CursorConfig, cursor: &str) -> String {
+    let mut url = url::Url::parse(api_link).expect("API link should be a valid URL");
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(name, _)| name != &config.parameter)
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect();
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(pairs)
+        .append_pair(&config.parameter, cursor);
+    url.to_string()
+}
+
+/// Entries of a batch, listed by pair next to the cursor.
+fn entries_of(result: &serde_json::Value) -> Vec<&serde_json::Value> {
+    result
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter_map(|(_, entries)| entries.as_array())
+        .flatten()
+        .collect()
+}
+
+fn sequence_of(entry: &serde_json::Value, index: usize) -> Option<i64> {
+    match entry.get(index)? {
+        serde_json::Value::String(sequence) => sequence.parse().ok(),
+        sequence => sequence.as_i64(),
+    }
+}
+
+/// Describes discontinuities between two consecutive batches: with sequence numbers configured, numbers
+/// repeated or skipped between the batches or within the second one; otherwise entries of the first batch
+/// repeated in the second one.
+///
+/// # Arguments
+///
+/// * `first` - Result of the first request
+/// * `second` - Result of the request following the cursor of the first one
+/// * `config` - Cursor configuration of the endpoint
+///
+pub fn discontinuities(
+    first: &serde_json::Value,
+    second: &serde_json::Value,
+    config: &CursorConfig,
+) -> Vec<String> {
+    let first_entries = entries_of(first);
+    let second_entries = entries_of(second);
+    let index = match config.sequence_index {
+        Some(index) => index,
+        None => {
+            return second_entries
+                .iter()
+                .filter(|entry| first_entries.contains(entry))
+                .map(|entry| format!("entry {} is listed by both batches", entry))
+                .collect()
+        }
+    };
+    let mut discontinuities = Vec::new();
+    let mut previous = first_entries
+        .iter()
+        .filter_map(|entry| sequence_of(entry, index))
+        .max();
+    for entry in second_entries {
+        let sequence = match sequence_of(entry, index) {
+            Some(sequence) => sequence,
+            None => {
+                discontinuities.push(format!("entry {} has no sequence number", entry));
+                continue;
+            }
+        };
+        match previous {
+            Some(previous) if sequence <= previous => discontinuities.push(format!(
+                "entry {} overlaps: its sequence number follows {}",
+                entry, previous
+            )),
+            Some(previous) if sequence > previous + 1 => discontinuities.push(format!(
+                "sequence numbers {} to {} are missing before entry {}",
+                previous + 1,
+                sequence - 1,
+                entry
+            )),
+            _ => {}
+        }
+        previous = Some(sequence);
+    }
+    discontinuities
+}
@@ -0,0 +1,189 @@
+use super::baseline;
+use once_cell::sync::Lazy;
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::sync::Mutex;
+
+/// Handling of endpoints announced for removal by Deprecation and Sunset headers of their responses.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DeprecationSettings {
+    /// Date, e.g. "2027-01-01", before which a Sunset of a requested endpoint fails the scenario;
+    /// signals are only reported when not set
+    pub fail_sunset_before: Option<String>,
+}
+
+/// Deprecation signals sent with responses of an endpoint during the run.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeprecationSignal {
+    /// Value of the Deprecation header, e.g. "@1688169599" or "true"
+    pub deprecation: Option<String>,
+    /// Value of the Sunset header, e.g. "Sat, 31 Dec 2027 23:59:59 GMT"
+    pub sunset: Option<String>,
+    /// Date of the Sunset header, e.g. "2027-12-31"
+    pub sunset_date: Option<String>,
+    /// Distinct values of Warning headers
+    pub warnings: BTreeSet<String>,
+}
+
+impl DeprecationSignal {
+    fn is_empty(&self) -> bool {
+        self.deprecation.is_none() && self.sunset.is_none() && self.warnings.is_empty()
+    }
+}
+
+/// Signals observed during the run, by endpoint name.
+static SIGNALS: Lazy<Mutex<BTreeMap<String, DeprecationSignal>>> =
+    Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+/// Date in UTC, e.g. "2027-12-31", of an HTTP date such as "Sat, 31 Dec 2027 23:59:59 GMT",
+/// or of a unix timestamp written as "@1830297599".
+///
+/// # Arguments
+///
+/// * `value` - Value of a Sunset or Deprecation header
+///
+pub fn date_of(value: &str) -> Option<String> {
+    let value = value.trim();
+    if let Some(timestamp) = value.strip_prefix('@') {
+        let seconds: i64 = timestamp.trim().parse().ok()?;
+        return Some(baseline::date_of(seconds.div_euclid(86_400)));
+    }
+    let parts: Vec<&str> = value
+        .split(|character: char| character == ',' || character.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .collect();
+    match parts.as_slice() {
+        [_, day, month, year, ..] => {
+            let day: u32 = day.parse().ok()?;
+            let month = MONTHS
+                .iter()
+                .position(|name| name.eq_ignore_ascii_case(month))?
+                + 1;
+            let year: u32 = year.parse().ok()?;
+            Some(format!("{:04}-{:02}-{:02}", year, month, day))
+        }
+        _ => None,
+    }
+}
+
+/// Reads the deprecation signals of the response, adding them to the signals of the endpoint observed during the run.
+/// Returns the signals of this response.
+///
+/// # Arguments
+///
+/// * `endpoint_name` - Name of the endpoint which returned the response
+/// * `headers` - Headers of the response
+///
+pub fn observe(endpoint_name: &str, headers: &HeaderMap) -> DeprecationSignal {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.trim().to_owned())
+    };
+    let sunset = header("sunset");
+    let signal = DeprecationSignal {
+        deprecation: header("deprecation"),
+        sunset_date: sunset.as_deref().and_then(date_of),
+        sunset,
+        warnings: headers
+            .get_all("warning")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .map(|value| value.trim().to_owned())
+            .collect(),
+    };
+    if !signal.is_empty() {
+        let mut signals = SIGNALS
+            .lock()
+            .expect("Deprecation signals should not be poisoned");
+        let observed = signals.entry(endpoint_name.to_owned()).or_default();
+        if signal.deprecation.is_some() {
+            observed.deprecation = signal.deprecation.clone();
+        }
+        if signal.sunset.is_some() {
+            observed.sunset = signal.sunset.clone();
+            observed.sunset_date = signal.sunset_date.clone();
+        }
+        observed.warnings.extend(signal.warnings.iter().cloned());
+    }
+    signal
+}
+
+/// Describes the removal of the endpoint announced before the date of the settings, if it is.
+///
+/// # Arguments
+///
+/// * `endpoint_name` - Name of the requested endpoint
+/// * `signal` - Deprecation signals of its response
+/// * `settings` - Deprecation settings of the profile
+///
+pub fn sunset_violation(
+    endpoint_name: &str,
+    signal: &DeprecationSignal,
+    settings: &DeprecationSettings,
+) -> Option<String> {
+    let limit = settings.fail_sunset_before.as_ref()?;
+    let sunset = signal.sunset.as_ref()?;
+    match &signal.sunset_date {
+        Some(sunset_date) if sunset_date < limit => Some(format!(
+            "{} is announced for removal on {} (Sunset: {}), before {}",
+            endpoint_name, sunset_date, sunset, limit
+        )),
+        Some(_) => None,
+        None => Some(format!(
+            "{} is announced for removal on an unreadable date (Sunset: {})",
+            endpoint_name, sunset
+        )),
+    }
+}
+
+/// Deprecation signals of the endpoints requested during the run.
+#[derive(Debug, Clone, Default)]
+pub struct DeprecationReport {
+    pub signals: BTreeMap<String, DeprecationSignal>,
+}
+
+impl fmt::Display for DeprecationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.signals.is_empty() {
+            return Ok(());
+        }
+        writeln!(f, "Deprecation signals:")?;
+        for (endpoint_name, signal) in &self.signals {
+            let mut parts = Vec::new();
+            if let Some(deprecation) = &signal.deprecation {
+                match date_of(deprecation) {
+                    Some(date) => parts.push(format!("deprecated since {}", date)),
+                    None => parts.push(format!("deprecated ({})", deprecation)),
+                }
+            }
+            if let Some(sunset) = &signal.sunset {
+                let date = signal.sunset_date.as_deref().unwrap_or(sunset);
+                parts.push(format!("removed on {}", date));
+            }
+            for warning in &signal.warnings {
+                parts.push(format!("warning {}", warning));
+            }
+            writeln!(f, "  {}: {}", endpoint_name, parts.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Deprecation signals observed during the run.
+pub fn deprecation_report() -> DeprecationReport {
+    DeprecationReport {
+        signals: SIGNALS
+            .lock()
+            .expect("Deprecation signals should not be poisoned")
+            .clone(),
+    }
+}
@@ -0,0 +1,265 @@
+use super::openapi;
+use once_cell::sync::Lazy;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::sync::Mutex;
+
+/// Fields of responses observed during the run, compared with the fields declared in the schema.
+#[derive(Debug, Clone, Default)]
+pub struct SchemaDrift {
+    /// Paths of fields declared in the schema, e.g. "result.unixtime"
+    pub declared: BTreeSet<String>,
+    /// Paths of declared fields present in at least one response
+    pub seen: BTreeSet<String>,
+    /// Paths of fields present in responses, but absent from the schema
+    pub undeclared: BTreeSet<String>,
+    /// Values observed outside of declared enumerations, by path
+    pub outside_enum: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl SchemaDrift {
+    /// Declared fields which were never present in responses.
+    pub fn never_seen(&self) -> impl Iterator<Item = &String> {
+        self.declared.difference(&self.seen)
+    }
+
+    fn has_drift(&self) -> bool {
+        self.never_seen().next().is_some()
+            || !self.undeclared.is_empty()
+            || !self.outside_enum.is_empty()
+    }
+}
+
+/// Schema drift of the run, by schema file.
+#[derive(Debug, Clone, Default)]
+pub struct DriftReport {
+    pub schemas: BTreeMap<String, SchemaDrift>,
+}
+
+impl fmt::Display for DriftReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (schema_file, drift) in &self.schemas {
+            if !drift.has_drift() {
+                continue;
+            }
+            writeln!(f, "Schema drift of {}:", schema_file)?;
+            for path in drift.never_seen() {
+                writeln!(f, "  never seen: {}", path)?;
+            }
+            for path in &drift.undeclared {
+                writeln!(f, "  undeclared: {}", path)?;
+            }
+            for (path, values) in &drift.outside_enum {
+                let values: Vec<&str> = values.iter().map(String::as_str).collect();
+                writeln!(f, "  outside enumeration: {} = {}", path, values.join(", "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Drift collected so far in the run, by schema file.
+static DRIFT: Lazy<Mutex<BTreeMap<String, SchemaDrift>>> =
+    Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+/// Compares fields of the response with fields declared in the schema, accumulating drift of the run.
+/// Array elements are denoted by "[]" and keys of maps described by `additionalProperties` by "*".
+///
+/// # Arguments
+///
+/// * `schema_file` - Name of the file in "./schemas" containing the schema
+/// * `schema` - Content of the schema
+/// * `json_response` - Response body parsed to json
+///
+pub fn observe(schema_file: &str, schema: &Value, json_response: &Value) {
+    let schema = flattened(schema);
+    let mut drift_by_schema = DRIFT.lock().expect("Schema drift should not be poisoned");
+    let drift = drift_by_schema
+        .entry(schema_file.to_owned())
+        .or_insert_with(|| {
+            let mut drift = SchemaDrift::default();
+            collect_declared(&schema, "", &mut drift.declared);
+            drift
+        });
+    collect_observed(&schema, json_response, "", drift);
+}
+
+/// Paths of fields of the response absent from the schema, e.g. "result.extra".
+///
+/// # Arguments
+///
+/// * `schema` - Content of the schema
+/// * `json_response` - Response body parsed to json
+///
+pub fn undeclared_fields(schema: &Value, json_response: &Value) -> Vec<String> {
+    let mut drift = SchemaDrift::default();
+    collect_observed(&flattened(schema), json_response, "", &mut drift);
+    drift.undeclared.into_iter().collect()
+}
+
+/// Drift collected so far in the run.
+pub fn drift_report() -> DriftReport {
+    DriftReport {
+        schemas: DRIFT
+            .lock()
+            .expect("Schema drift should not be poisoned")
+            .clone(),
+    }
+}
+
+/// Schema with its local `$ref`s inlined and its `allOf`, `oneOf` and `anyOf` merged into a single schema,
+/// so fields declared by any of the composed schemas count as declared.
+fn flattened(schema: &Value) -> Value {
+    merged(&openapi::inline_references(schema, schema))
+}
+
+/// Schema with its compositions merged, recursively.
+fn merged(schema: &Value) -> Value {
+    let fields = match schema.as_object() {
+        Some(fields) => fields,
+        None => return schema.clone(),
+    };
+    let mut base = fields.clone();
+    let mut parts = Vec::new();
+    let mut alternatives_without_enum = false;
+    for keyword in ["allOf", "oneOf", "anyOf"] {
+        if let Some(Value::Array(composed)) = base.remove(keyword) {
+            for part in &composed {
+                let part = merged(part);
+                alternatives_without_enum |= keyword != "allOf" && part.get("enum").is_none();
+                parts.push(part);
+            }
+        }
+    }
+    let mut flat = Map::new();
+    for (key, value) in base {
+        let value = match key.as_str() {
+            "properties" => Value::Object(
+                value
+                    .as_object()
+                    .into_iter()
+                    .flatten()
+                    .map(|(field, property)| (field.clone(), merged_subschema(property)))
+                    .collect(),
+            ),
+            "additionalProperties" | "items" => merged_subschema(&value),
+            _ => value,
+        };
+        flat.insert(key, value);
+    }
+    for part in parts {
+        merge_into(&mut flat, part);
+    }
+    if alternatives_without_enum {
+        flat.remove("enum");
+    }
+    Value::Object(flat)
+}
+
+fn merged_subschema(schema: &Value) -> Value {
+    match schema {
+        Value::Object(_) => merged(schema),
+        other => other.clone(),
+    }
+}
+
+/// Adds properties, items, additional properties and enumerated values of the part to the schema.
+fn merge_into(schema: &mut Map<String, Value>, part: Value) {
+    let part = match part {
+        Value::Object(part) => part,
+        _ => return,
+    };
+    for (key, value) in part {
+        match (key.as_str(), schema.get_mut(&key)) {
+            ("properties", Some(Value::Object(properties))) => {
+                for (field, property) in value.as_object().cloned().unwrap_or_default() {
+                    match properties.get_mut(&field) {
+                        Some(Value::Object(existing)) => merge_into(existing, property),
+                        _ => {
+                            properties.insert(field, property);
+                        }
+                    }
+                }
+            }
+            ("additionalProperties" | "items", Some(Value::Object(existing))) => {
+                merge_into(existing, value)
+            }
+            ("enum", Some(Value::Array(allowed))) => {
+                for allowed_value in value.as_array().cloned().unwrap_or_default() {
+                    if !allowed.contains(&allowed_value) {
+                        allowed.push(allowed_value);
+                    }
+                }
+            }
+            (_, Some(_)) => {}
+            (_, None) => {
+                schema.insert(key, value);
+            }
+        }
+    }
+}
+
+fn join(path: &str, segment: &str) -> String {
+    if path.is_empty() {
+        segment.to_owned()
+    } else if segment == "[]" {
+        format!("{}{}", path, segment)
+    } else {
+        format!("{}.{}", path, segment)
+    }
+}
+
+fn collect_declared(schema: &Value, path: &str, declared: &mut BTreeSet<String>) {
+    if let Some(properties) = schema["properties"].as_object() {
+        for (field, property) in properties {
+            let field_path = join(path, field);
+            declared.insert(field_path.clone());
+            collect_declared(property, &field_path, declared);
+        }
+    }
+    if schema["additionalProperties"].is_object() {
+        collect_declared(&schema["additionalProperties"], &join(path, "*"), declared);
+    }
+    if schema["items"].is_object() {
+        collect_declared(&schema["items"], &join(path, "[]"), declared);
+    }
+}
+
+fn collect_observed(schema: &Value, value: &Value, path: &str, drift: &mut SchemaDrift) {
+    if let Some(allowed) = schema["enum"].as_array() {
+        if !allowed.contains(value) {
+            drift
+                .outside_enum
+                .entry(path.to_owned())
+                .or_default()
+                .insert(value.to_string());
+        }
+    }
+    match value {
+        Value::Object(fields) => {
+            for (field, field_value) in fields {
+                if let Some(property) = schema["properties"].get(field) {
+                    let field_path = join(path, field);
+                    drift.seen.insert(field_path.clone());
+                    collect_observed(property, field_value, &field_path, drift);
+                } else if schema["additionalProperties"].is_object() {
+                    collect_observed(
+                        &schema["additionalProperties"],
+                        field_value,
+                        &join(path, "*"),
+                        drift,
+                    );
+                } else {
+                    drift.undeclared.insert(join(path, field));
+                }
+            }
+        }
+        Value::Array(elements) => {
+            for element in elements {
+                collect_observed(&schema["items"], element, &join(path, "[]"), drift);
+            }
+        }
+        _ => {}
+    }
+}
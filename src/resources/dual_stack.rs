@@ -0,0 +1,154 @@
+use super::config::Profile;
+use super::http_client;
+use std::fmt;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+/// Result of sending the request over a single address family.
+#[derive(Debug)]
+pub enum FamilyOutcome {
+    /// Host has no address of the family
+    Unavailable,
+    Responded {
+        address: IpAddr,
+        status: reqwest::StatusCode,
+        latency: Duration,
+    },
+    Failed {
+        address: IpAddr,
+        error: String,
+    },
+}
+
+impl fmt::Display for FamilyOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FamilyOutcome::Unavailable => write!(f, "not available"),
+            FamilyOutcome::Responded {
+                address,
+                status,
+                latency,
+            } => write!(
+                f,
+                "{} responded with {} in {} ms",
+                address,
+                status,
+                latency.as_millis()
+            ),
+            FamilyOutcome::Failed { address, error } => write!(f, "{} failed: {}", address, error),
+        }
+    }
+}
+
+/// Outcomes of sending the same request over IPv4 and IPv6.
+#[derive(Debug)]
+pub struct DualStackReport {
+    pub ipv4: FamilyOutcome,
+    pub ipv6: FamilyOutcome,
+}
+
+impl DualStackReport {
+    /// Panics if the request failed over any available address family, or was answered
+    /// with a status other than the expected one, or than a successful one when none is expected.
+    ///
+    /// # Arguments
+    ///
+    /// * `expected_status` - Status both address families should respond with, if not a successful one
+    ///
+    pub fn verify(&self, expected_status: Option<reqwest::StatusCode>) {
+        let is_broken = |outcome: &FamilyOutcome| match outcome {
+            FamilyOutcome::Unavailable => false,
+            FamilyOutcome::Responded { status, .. } => match expected_status {
+                Some(expected_status) => *status != expected_status,
+                None => !status.is_success(),
+            },
+            FamilyOutcome::Failed { .. } => true,
+        };
+        assert!(
+            !is_broken(&self.ipv4) && !is_broken(&self.ipv6),
+            "Request failed over one of address families:\n{}",
+            self
+        );
+    }
+}
+
+impl fmt::Display for DualStackReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "IPv4: {}", self.ipv4)?;
+        write!(f, "IPv6: {}", self.ipv6)
+    }
+}
+
+/// Host of the link along with its first resolved address of each family.
+#[derive(Debug, Clone)]
+pub struct FamilyAddresses {
+    pub host: String,
+    pub ipv4: Option<IpAddr>,
+    pub ipv6: Option<IpAddr>,
+}
+
+/// Resolves the host of the link to its first address of each family.
+///
+/// # Arguments
+///
+/// * `link` - Full link to the endpoint
+///
+pub async fn resolve_families(link: &str) -> FamilyAddresses {
+    let url = url::Url::parse(link).expect("Link to the endpoint should be a valid url");
+    let host = url.host_str().expect("Link should contain host").to_owned();
+    let port = url.port_or_known_default().unwrap_or(443);
+    let addresses: Vec<IpAddr> = match tokio::net::lookup_host((host.as_str(), port)).await {
+        Ok(addresses) => addresses.map(|address| address.ip()).collect(),
+        Err(err) => panic!("Resolving {} failed: {}", host, err),
+    };
+
+    let first_of_family = |is_v4: bool| {
+        addresses
+            .iter()
+            .find(|address| address.is_ipv4() == is_v4)
+            .copied()
+    };
+    FamilyAddresses {
+        ipv4: first_of_family(true),
+        ipv6: first_of_family(false),
+        host,
+    }
+}
+
+/// Sends GET request to the link over the address family, by pinning the host to the address;
+/// callers throttle the request, which is sent only when the host has an address of the family.
+///
+/// # Arguments
+///
+/// * `profile` - Settings of the environment the suite is run against
+/// * `link` - Full link to the endpoint
+/// * `host` - Host of the link
+/// * `address` - Address of the family, if the host has one
+///
+pub async fn request_over(
+    profile: &Profile,
+    link: &str,
+    host: &str,
+    address: Option<IpAddr>,
+) -> FamilyOutcome {
+    let address = match address {
+        Some(address) => address,
+        None => return FamilyOutcome::Unavailable,
+    };
+    let mut pinned_profile = profile.clone();
+    pinned_profile.resolve.insert(host.to_owned(), address);
+    let (client, _) = http_client::build_client(&pinned_profile);
+
+    let start = Instant::now();
+    match client.get(link).send().await {
+        Ok(response) => FamilyOutcome::Responded {
+            address,
+            status: response.status(),
+            latency: start.elapsed(),
+        },
+        Err(err) => FamilyOutcome::Failed {
+            address,
+            error: err.to_string(),
+        },
+    }
+}
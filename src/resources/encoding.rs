@@ -0,0 +1,104 @@
+use super::masking;
+use serde_json::Value;
+
+/// Byte order mark of UTF-8.
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Checks that the response declares UTF-8 (or no charset, which means UTF-8 for json),
+/// and that its body is valid UTF-8 without byte order mark; returns descriptions of the problems.
+///
+/// # Arguments
+///
+/// * `content_type` - Value of the Content-Type header, if present
+/// * `body` - Whole response body
+///
+pub fn encoding_problems(content_type: Option<&str>, body: &[u8]) -> Vec<String> {
+    let mut problems = Vec::new();
+    let charset = content_type.and_then(|content_type| {
+        content_type
+            .split(';')
+            .skip(1)
+            .filter_map(|parameter| parameter.trim().split_once('='))
+            .find(|(name, _)| name.eq_ignore_ascii_case("charset"))
+            .map(|(_, charset)| charset.trim_matches('"').to_owned())
+    });
+    if let Some(charset) = charset {
+        if !charset.eq_ignore_ascii_case("utf-8") && !charset.eq_ignore_ascii_case("utf8") {
+            problems.push(format!("declared charset is {}, not UTF-8", charset));
+        }
+    }
+    if body.starts_with(UTF8_BOM) {
+        problems.push("body starts with byte order mark".to_owned());
+    }
+    if let Err(err) = std::str::from_utf8(body) {
+        problems.push(format!(
+            "body contains invalid UTF-8 sequence at byte {}",
+            err.valid_up_to()
+        ));
+    }
+    problems
+}
+
+/// Finds non-ASCII identifiers among values matched by the paths, e.g. `$.result.*.altname`.
+/// Matched strings have to be ASCII; matched objects have to have ASCII keys,
+/// e.g. `$.result` of asset pairs, keyed by pair codes.
+///
+/// # Arguments
+///
+/// * `json_response` - Response body parsed to json
+/// * `paths` - Paths of identifiers, in the form used by masks
+///
+pub fn non_ascii_identifiers(json_response: &Value, paths: &[String]) -> Vec<String> {
+    let mut problems = Vec::new();
+    for path in paths {
+        collect_non_ascii(
+            json_response,
+            &masking::segments_of(path),
+            "$",
+            &mut problems,
+        );
+    }
+    problems
+}
+
+fn collect_non_ascii(
+    value: &Value,
+    segments: &[String],
+    location: &str,
+    problems: &mut Vec<String>,
+) {
+    let (segment, rest) = match segments.split_first() {
+        Some(split) => split,
+        None => {
+            match value {
+                Value::String(text) if !text.is_ascii() => {
+                    problems.push(format!("{}: non-ASCII value {:?}", location, text))
+                }
+                Value::Object(fields) => {
+                    for key in fields.keys().filter(|key| !key.is_ascii()) {
+                        problems.push(format!("{}: non-ASCII key {:?}", location, key));
+                    }
+                }
+                _ => {}
+            }
+            return;
+        }
+    };
+    match value {
+        Value::Object(fields) => {
+            for (key, field) in fields {
+                if segment == "*" || segment == key {
+                    collect_non_ascii(field, rest, &format!("{}.{}", location, key), problems);
+                }
+            }
+        }
+        Value::Array(elements) => {
+            for (index, element) in elements.iter().enumerate() {
+                if segment == "*" || segment.parse::<usize>().ok() == Some(index) {
+                    collect_non_ascii(element, rest, &format!("{}[{}]", location, index), problems);
+                }
+            }
+        }
+        _ => {}
+    }
+}
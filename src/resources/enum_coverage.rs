@@ -0,0 +1,158 @@
+use itertools::Itertools;
+use once_cell::sync::Lazy;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::sync::Mutex;
+
+/// Values of enum-like fields observed in responses during the run, by endpoint name and field name.
+static OBSERVED: Lazy<Mutex<BTreeMap<String, BTreeMap<String, BTreeSet<String>>>>> =
+    Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+/// Collects values of the enum-like fields found at any depth of the response.
+///
+/// # Arguments
+///
+/// * `endpoint_name` - Name of the endpoint which returned the response
+/// * `documented` - Documented values of enum-like fields of the endpoint, by field name
+/// * `json_response` - Response body parsed to json
+///
+pub fn observe(
+    endpoint_name: &str,
+    documented: &HashMap<String, Vec<String>>,
+    json_response: &Value,
+) {
+    if documented.is_empty() {
+        return;
+    }
+    let mut observed = OBSERVED
+        .lock()
+        .expect("Observed enum values should not be poisoned");
+    let fields = observed.entry(endpoint_name.to_owned()).or_default();
+    collect_values(json_response, documented, fields);
+}
+
+fn collect_values(
+    value: &Value,
+    documented: &HashMap<String, Vec<String>>,
+    fields: &mut BTreeMap<String, BTreeSet<String>>,
+) {
+    match value {
+        Value::Object(object) => {
+            for (key, field_value) in object {
+                if documented.contains_key(key) {
+                    if let Some(text) = field_value.as_str() {
+                        fields
+                            .entry(key.clone())
+                            .or_default()
+                            .insert(text.to_owned());
+                    }
+                }
+                collect_values(field_value, documented, fields);
+            }
+        }
+        Value::Array(elements) => {
+            for element in elements {
+                collect_values(element, documented, fields);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Values observed in responses of the endpoint so far, which are not documented, by field name.
+///
+/// # Arguments
+///
+/// * `endpoint_name` - Name of the endpoint
+/// * `documented` - Documented values of enum-like fields of the endpoint, by field name
+///
+pub fn undocumented_values(
+    endpoint_name: &str,
+    documented: &HashMap<String, Vec<String>>,
+) -> BTreeMap<String, BTreeSet<String>> {
+    let observed = OBSERVED
+        .lock()
+        .expect("Observed enum values should not be poisoned");
+    let mut undocumented = BTreeMap::new();
+    for (field, values) in observed.get(endpoint_name).into_iter().flatten() {
+        let new_values: BTreeSet<String> = values
+            .iter()
+            .filter(|value| !documented[field].contains(value))
+            .cloned()
+            .collect();
+        if !new_values.is_empty() {
+            undocumented.insert(field.clone(), new_values);
+        }
+    }
+    undocumented
+}
+
+/// Panics listing values of enum-like fields, observed so far in responses of the endpoint,
+/// which are not documented.
+///
+/// # Arguments
+///
+/// * `endpoint_name` - Name of the endpoint
+/// * `documented` - Documented values of enum-like fields of the endpoint, by field name
+///
+pub fn verify_documented(endpoint_name: &str, documented: &HashMap<String, Vec<String>>) {
+    let problems = undocumented_problems(endpoint_name, documented);
+    if !problems.is_empty() {
+        panic!(
+            "Responses of {} contain undocumented values:\n{}",
+            endpoint_name,
+            problems.join("\n")
+        )
+    }
+}
+
+/// Undocumented values observed so far in responses of the endpoint, a line per field, e.g. "status: paused".
+///
+/// # Arguments
+///
+/// * `endpoint_name` - Name of the endpoint
+/// * `documented` - Documented values of enum-like fields of the endpoint, by field name
+///
+pub fn undocumented_problems(
+    endpoint_name: &str,
+    documented: &HashMap<String, Vec<String>>,
+) -> Vec<String> {
+    undocumented_values(endpoint_name, documented)
+        .iter()
+        .map(|(field, values)| format!("{}: {}", field, values.iter().join(", ")))
+        .collect()
+}
+
+/// Values of enum-like fields observed during the run, by endpoint name and field name.
+#[derive(Debug, Clone, Default)]
+pub struct EnumCoverageReport {
+    pub endpoints: BTreeMap<String, BTreeMap<String, BTreeSet<String>>>,
+}
+
+impl fmt::Display for EnumCoverageReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (endpoint_name, fields) in &self.endpoints {
+            for (field, values) in fields {
+                writeln!(
+                    f,
+                    "Values of {}.{}: {}",
+                    endpoint_name,
+                    field,
+                    values.iter().join(", ")
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Values of enum-like fields observed so far in the run.
+pub fn enum_coverage_report() -> EnumCoverageReport {
+    EnumCoverageReport {
+        endpoints: OBSERVED
+            .lock()
+            .expect("Observed enum values should not be poisoned")
+            .clone(),
+    }
+}
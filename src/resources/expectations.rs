@@ -0,0 +1,224 @@
+use super::masking;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Comparison of a field of the response with the value of an expectation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Operator {
+    /// The path matches at least one field
+    Exists,
+    /// The path matches no field
+    Absent,
+    Eq,
+    Ne,
+    /// Numeric comparisons; numeric strings, e.g. "0.1", are compared as numbers
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    /// The field is a string matching the regular expression
+    Matches,
+    /// The field equals one of the listed values
+    In,
+    /// The field is a string containing the value, or an array containing an element equal to it
+    Contains,
+    /// The field is a string, array or object with the given number of characters, elements or keys
+    Length,
+    /// The field has the json type, e.g. "string", "number", "array" or "null"
+    Type,
+    /// The field is an empty string, array or object
+    Empty,
+    NotEmpty,
+}
+
+/// Expectation towards the fields matched by a path of the response, declared in a doc string of
+/// "the response meets the expectations", e.g. `{ path: result.*.status, op: in, value: [open, closed] }`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Expectation {
+    /// Path in the form used by masks, e.g. "result.XXBTZUSD.a.0" or "$.result.*.status"
+    pub path: String,
+    pub op: Operator,
+    /// Value compared with the fields; not needed by exists, absent, empty and not_empty
+    #[serde(default)]
+    pub value: Value,
+}
+
+/// Parses expectations written as a yaml or json list.
+///
+/// # Arguments
+///
+/// * `document` - Doc string of the step
+///
+pub fn parse(document: &str) -> Result<Vec<Expectation>, serde_yaml::Error> {
+    serde_yaml::from_str(document)
+}
+
+fn collect<'a>(value: &'a Value, segments: &[String], found: &mut Vec<&'a Value>) {
+    let (segment, rest) = match segments.split_first() {
+        Some(split) => split,
+        None => {
+            found.push(value);
+            return;
+        }
+    };
+    match value {
+        Value::Object(fields) => {
+            for (key, field) in fields {
+                if segment == "*" || segment == key {
+                    collect(field, rest, found);
+                }
+            }
+        }
+        Value::Array(elements) => {
+            for (index, element) in elements.iter().enumerate() {
+                if segment == "*" || segment.parse::<usize>().ok() == Some(index) {
+                    collect(element, rest, found);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn number_of(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Equality of json values, where a string equals a number or boolean written the same way, e.g. "1" and 1.
+fn loosely_equal(field: &Value, expected: &Value) -> bool {
+    match (field, expected) {
+        (Value::String(text), Value::Number(_)) | (Value::String(text), Value::Bool(_)) => {
+            text == &expected.to_string()
+        }
+        _ => field == expected,
+    }
+}
+
+fn type_of(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn length_of(value: &Value) -> Option<usize> {
+    match value {
+        Value::String(text) => Some(text.chars().count()),
+        Value::Array(elements) => Some(elements.len()),
+        Value::Object(fields) => Some(fields.len()),
+        _ => None,
+    }
+}
+
+impl Expectation {
+    /// Whether the single field meets the expectation.
+    fn holds_for(&self, field: &Value) -> bool {
+        let compare = |holds: fn(f64, f64) -> bool| match (number_of(field), number_of(&self.value))
+        {
+            (Some(actual), Some(expected)) => holds(actual, expected),
+            _ => false,
+        };
+        match self.op {
+            Operator::Exists => true,
+            Operator::Absent => false,
+            Operator::Eq => loosely_equal(field, &self.value),
+            Operator::Ne => !loosely_equal(field, &self.value),
+            Operator::Gt => compare(|actual, expected| actual > expected),
+            Operator::Ge => compare(|actual, expected| actual >= expected),
+            Operator::Lt => compare(|actual, expected| actual < expected),
+            Operator::Le => compare(|actual, expected| actual <= expected),
+            Operator::Matches => {
+                let pattern = self.value.as_str().unwrap_or_default();
+                let regex = Regex::new(pattern)
+                    .unwrap_or_else(|err| panic!("Pattern {} is invalid: {}", pattern, err));
+                field.as_str().map_or(false, |text| regex.is_match(text))
+            }
+            Operator::In => self.value.as_array().map_or(false, |allowed| {
+                allowed.iter().any(|value| loosely_equal(field, value))
+            }),
+            Operator::Contains => match (field, &self.value) {
+                (Value::String(text), Value::String(part)) => text.contains(part.as_str()),
+                (Value::Array(elements), expected) => elements
+                    .iter()
+                    .any(|element| loosely_equal(element, expected)),
+                _ => false,
+            },
+            Operator::Length => length_of(field).map(|length| length as u64) == self.value.as_u64(),
+            Operator::Type => Some(type_of(field)) == self.value.as_str(),
+            Operator::Empty => length_of(field) == Some(0),
+            Operator::NotEmpty => length_of(field).map_or(false, |length| length > 0),
+        }
+    }
+
+    /// Describes how the response breaks the expectation, if it does: every field matched by the path
+    /// has to meet it, and the path has to match at least one field unless it is expected absent.
+    ///
+    /// # Arguments
+    ///
+    /// * `json_response` - Response body parsed to json
+    ///
+    pub fn failure(&self, json_response: &Value) -> Option<String> {
+        let mut fields = Vec::new();
+        collect(
+            json_response,
+            &masking::segments_of(&self.path),
+            &mut fields,
+        );
+        if self.op == Operator::Absent {
+            return fields
+                .first()
+                .map(|field| format!("{} should be absent, found {}", self.path, field));
+        }
+        if fields.is_empty() {
+            return Some(format!("{} is missing", self.path));
+        }
+        let broken: Vec<String> = fields
+            .iter()
+            .filter(|field| !self.holds_for(field))
+            .map(|field| field.to_string())
+            .collect();
+        if broken.is_empty() {
+            return None;
+        }
+        let operator = serde_json::to_value(self.op)
+            .ok()
+            .and_then(|operator| operator.as_str().map(str::to_owned))
+            .unwrap_or_default();
+        let expected = if self.value.is_null() {
+            operator
+        } else {
+            format!("{} {}", operator, self.value)
+        };
+        Some(format!(
+            "{} should be {}, found {}",
+            self.path,
+            expected,
+            broken.join(", ")
+        ))
+    }
+}
+
+/// Describes the expectations broken by the response.
+///
+/// # Arguments
+///
+/// * `expectations` - Expectations parsed from the doc string
+/// * `json_response` - Response body parsed to json
+///
+pub fn failures(expectations: &[Expectation], json_response: &Value) -> Vec<String> {
+    expectations
+        .iter()
+        .filter_map(|expectation| expectation.failure(json_response))
+        .collect()
+}
@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Layout of market data feeds whose recency is verified, e.g. recent trades and spreads.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FeedSettings {
+    /// Public endpoint returning the server time in "result.unixtime"
+    pub server_time_endpoint: String,
+    /// Position of the timestamp within the entries of a feed, by endpoint name
+    pub timestamp_index: BTreeMap<String, usize>,
+}
+
+impl Default for FeedSettings {
+    fn default() -> Self {
+        Self {
+            server_time_endpoint: "ServerTime".to_owned(),
+            timestamp_index: BTreeMap::from([("Trades".to_owned(), 2), ("Spread".to_owned(), 0)]),
+        }
+    }
+}
+
+/// Timestamp of the newest entry of the feed, in seconds, when any entry carries one.
+/// Entries are listed by pair, next to the "last" cursor.
+///
+/// # Arguments
+///
+/// * `result` - Result of the feed endpoint
+/// * `timestamp_index` - Position of the timestamp within an entry
+///
+pub fn newest_timestamp(result: &serde_json::Value, timestamp_index: usize) -> Option<f64> {
+    result
+        .as_object()?
+        .values()
+        .filter_map(serde_json::Value::as_array)
+        .flatten()
+        .filter_map(|entry| match entry.get(timestamp_index)? {
+            serde_json::Value::String(timestamp) => timestamp.parse().ok(),
+            timestamp => timestamp.as_f64(),
+        })
+        .reduce(f64::max)
+}
+
+/// The "last" cursor of the feed, sent either as a number or as a string of digits, e.g. nanoseconds.
+///
+/// # Arguments
+///
+/// * `result` - Result of the feed endpoint
+///
+pub fn cursor_of(result: &serde_json::Value) -> Option<u128> {
+    match result.get("last")? {
+        serde_json::Value::String(cursor) => cursor.parse().ok(),
+        cursor => cursor.as_u64().map(u128::from),
+    }
+}
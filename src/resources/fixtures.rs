@@ -0,0 +1,204 @@
+use super::config::{Auth, EndpointRegistry, Profile};
+use super::{
+    access, accounts, api_errors, budget, capture, http_client, rate_limit, request_handler, tags,
+};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use tokio::sync::OnceCell;
+
+/// Account state a scenario depends on, kept in the `[fixtures.<name>]` table of the config file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FixtureDefinition {
+    /// Name of the private endpoint creating the fixture, e.g. "AddOrder"
+    pub setup: String,
+    /// Parameters of the setup request
+    #[serde(default)]
+    pub parameters: BTreeMap<String, String>,
+    /// Field of the setup response identifying the fixture, e.g. "result.txid.0"
+    pub id_field: Option<String>,
+    /// Name of the private endpoint removing the fixture at the end of the run, e.g. "CancelOrder"
+    pub teardown: Option<String>,
+    /// Parameter of the teardown request holding the fixture id
+    #[serde(default = "default_teardown_parameter")]
+    pub teardown_parameter: String,
+}
+
+fn default_teardown_parameter() -> String {
+    "txid".to_owned()
+}
+
+/// Fixtures of the run, by name, set along with their ids once created; the map is locked only
+/// to look a fixture up, so creating one does not hold up scenarios needing other fixtures.
+static CREATED_FIXTURES: Lazy<Mutex<HashMap<String, Arc<OnceCell<Option<String>>>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Names of the fixtures from `@fixture(name)` tags of the scenario, its rule or feature.
+///
+/// # Arguments
+///
+/// * `tags` - Tags of the scenario, its rule and feature, without "@"
+///
+pub fn required_fixtures<'a>(tags: impl Iterator<Item = &'a String>) -> Vec<String> {
+    tags.filter_map(|tag| tags::tag_argument(tag, "fixture"))
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Makes sure the fixture exists, creating it on first use; later calls reuse the created one,
+/// while a failed creation is retried by the next scenario needing the fixture.
+/// Returns id of the fixture, if its definition points to one.
+///
+/// # Arguments
+///
+/// * `name` - Name of the fixture
+/// * `definitions` - Fixtures defined in the config file
+/// * `profile` - Settings of the environment the suite is run against
+/// * `endpoints` - Endpoints known to the suite
+///
+pub async fn ensure(
+    name: &str,
+    definitions: &HashMap<String, FixtureDefinition>,
+    profile: &Profile,
+    endpoints: &EndpointRegistry,
+) -> Result<Option<String>, String> {
+    let fixture = CREATED_FIXTURES
+        .lock()
+        .expect("Created fixtures should not be poisoned")
+        .entry(name.to_owned())
+        .or_default()
+        .clone();
+    fixture
+        .get_or_try_init(|| create(name, definitions, profile, endpoints))
+        .await
+        .cloned()
+}
+
+/// Sends the setup request of the fixture, returning its id.
+async fn create(
+    name: &str,
+    definitions: &HashMap<String, FixtureDefinition>,
+    profile: &Profile,
+    endpoints: &EndpointRegistry,
+) -> Result<Option<String>, String> {
+    let definition = definitions
+        .get(name)
+        .ok_or_else(|| format!("Fixture {} is not defined in api_verify.toml", name))?;
+
+    let parameters: Vec<(&str, &str)> = definition
+        .parameters
+        .iter()
+        .map(|(key, value)| (key.as_str(), value.as_str()))
+        .collect();
+    let json_response = send_private(&definition.setup, &parameters, profile, endpoints).await?;
+    let errors = api_errors::errors_of(&json_response);
+    if !errors.is_empty() {
+        return Err(format!("Creating fixture {} failed: {:?}", name, errors));
+    }
+    definition
+        .id_field
+        .as_ref()
+        .map(|id_field| {
+            capture::extract_field(&json_response, id_field).ok_or_else(|| {
+                format!(
+                    "Response creating fixture {} does not contain field {}",
+                    name, id_field
+                )
+            })
+        })
+        .transpose()
+}
+
+/// Removes fixtures created during the run which define a teardown endpoint;
+/// failures are reported, but do not stop removing the remaining fixtures.
+///
+/// # Arguments
+///
+/// * `definitions` - Fixtures defined in the config file
+/// * `profile` - Settings of the environment the suite is run against
+/// * `endpoints` - Endpoints known to the suite
+///
+pub async fn teardown_all(
+    definitions: &HashMap<String, FixtureDefinition>,
+    profile: &Profile,
+    endpoints: &EndpointRegistry,
+) {
+    let created_fixtures: Vec<_> = CREATED_FIXTURES
+        .lock()
+        .expect("Created fixtures should not be poisoned")
+        .drain()
+        .collect();
+    for (name, fixture) in created_fixtures {
+        let definition = &definitions[&name];
+        let (teardown, id) = match (&definition.teardown, fixture.get()) {
+            (Some(teardown), Some(Some(id))) => (teardown, id),
+            _ => continue,
+        };
+        let parameters = [(definition.teardown_parameter.as_str(), id.as_str())];
+        let errors = send_private(teardown, &parameters, profile, endpoints)
+            .await
+            .map(|json_response| api_errors::errors_of(&json_response));
+        match errors {
+            Ok(errors) if errors.is_empty() => {}
+            Ok(errors) => eprintln!("Removing fixture {} failed: {:?}", name, errors),
+            Err(err) => eprintln!("Removing fixture {} failed: {}", name, err),
+        }
+    }
+}
+
+/// Signs and sends a request with the parameters to the private endpoint, outside of any scenario;
+/// fails unless the response is json.
+pub(crate) async fn send_private(
+    endpoint_name: &str,
+    parameters: &[(&str, &str)],
+    profile: &Profile,
+    endpoints: &EndpointRegistry,
+) -> Result<serde_json::Value, String> {
+    send_private_as(None, endpoint_name, parameters, profile, endpoints).await
+}
+
+/// Signs and sends a request like `send_private`, with the keys of the account, or of the one of the run;
+/// the call is counted against the request budget of the run.
+pub(crate) async fn send_private_as(
+    account: Option<&str>,
+    endpoint_name: &str,
+    parameters: &[(&str, &str)],
+    profile: &Profile,
+    endpoints: &EndpointRegistry,
+) -> Result<serde_json::Value, String> {
+    let properties = accounts::properties_for(profile, account).map_err(|err| err.to_string())?;
+    let endpoint = endpoints
+        .get(endpoint_name)
+        .map_err(|err| err.to_string())?;
+    if endpoint.auth != Auth::Private {
+        return Err(format!(
+            "Fixture endpoint {} is not a part of private API",
+            endpoint_name
+        ));
+    }
+    if let Err(denied) = access::check(profile, endpoint_name, endpoint) {
+        return Err(format!("Fixture cannot be managed: {}", denied));
+    }
+    budget::consume_run(&profile.request_budget)?;
+    rate_limit::acquire(&profile.rate_limit, endpoint_name, endpoint.weight).await;
+    let signed_request = request_handler::sign_private_request_with(
+        &properties.api_key,
+        &properties.api_secret,
+        &properties.otp_secret,
+        &properties.api_link,
+        &endpoint.path,
+        &properties.signer_config,
+        0,
+        parameters,
+    );
+    let (client, _) = http_client::build_client(profile);
+    let response = request_handler::send_signed_request(&client, &signed_request)
+        .await
+        .map_err(|err| format!("Request to {} failed: {}", endpoint_name, err))?;
+    response
+        .json()
+        .await
+        .map_err(|err| format!("Response of {} is not json: {}", endpoint_name, err))
+}
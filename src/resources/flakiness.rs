@@ -0,0 +1,316 @@
+use super::correlation;
+use super::report_encryption;
+use super::results::{RunResult, ScenarioStatus};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// What is done with scenarios quarantined as flaky by the previous runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuarantineAction {
+    /// Flaky scenarios are run as usual and only listed after the run
+    Report,
+    /// Flaky scenarios which failed are run once more, and the second attempt counts
+    Retry,
+    /// Flaky scenarios are run and kept in the history, but their failures are reported
+    /// as known issues and do not fail the run
+    Exclude,
+}
+
+impl Default for QuarantineAction {
+    fn default() -> Self {
+        QuarantineAction::Report
+    }
+}
+
+/// Classification of scenarios as flaky by their failure rate over the previous runs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FlakinessSettings {
+    /// Number of the last runs of a scenario its failure rate is computed from
+    pub window_runs: usize,
+    /// Scenario which passed in the window, but failed in more than this part of its runs, is flaky
+    pub max_failure_rate: f64,
+    /// What is done with the quarantined scenarios in the next run
+    pub quarantine: QuarantineAction,
+}
+
+impl Default for FlakinessSettings {
+    fn default() -> Self {
+        Self {
+            window_runs: 20,
+            max_failure_rate: 0.1,
+            quarantine: QuarantineAction::default(),
+        }
+    }
+}
+
+/// Known issue reported for failures of the quarantined scenarios excluded from the outcome of the run.
+const QUARANTINE_REASON: &str = "quarantined as flaky";
+
+/// Statuses of the scenarios of a single run; kept as a line of the history file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct RunStatuses {
+    run_id: String,
+    scenarios: Vec<RecordedStatus>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedStatus {
+    feature: String,
+    scenario: String,
+    /// Whether the scenario failed, at least in its first attempt
+    failed: bool,
+}
+
+/// Scenario classified as flaky, listed in the quarantine file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlakyScenario {
+    pub feature: String,
+    pub scenario: String,
+    pub failed_runs: usize,
+    pub runs: usize,
+}
+
+/// Scenarios quarantined as flaky, kept in the quarantine file generated after every run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Quarantine {
+    pub quarantined: Vec<FlakyScenario>,
+}
+
+impl Quarantine {
+    /// Reads the quarantine file generated by the previous run; a missing file quarantines nothing.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the quarantine file
+    ///
+    pub fn read(path: &str) -> Self {
+        match report_encryption::read_to_string(path) {
+            Ok(content) => toml::from_str(&content)
+                .unwrap_or_else(|err| panic!("Invalid quarantine file {}: {}", path, err)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Quarantine::default(),
+            Err(err) => panic!("Quarantine file {} cannot be read: {}", path, err),
+        }
+    }
+
+    /// Feature and scenario names of the quarantined scenarios.
+    pub fn scenarios(&self) -> HashSet<(String, String)> {
+        self.quarantined
+            .iter()
+            .map(|flaky| (flaky.feature.clone(), flaky.scenario.clone()))
+            .collect()
+    }
+
+    /// Reports failures of the quarantined scenarios as known issues, when the settings exclude them
+    /// from the outcome of the run. Returns the number of excused failures.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - Settings of the classification from the profile
+    /// * `run_result` - Result of the run
+    ///
+    pub fn excuse_failures(
+        &self,
+        settings: &FlakinessSettings,
+        run_result: &mut RunResult,
+    ) -> usize {
+        if settings.quarantine != QuarantineAction::Exclude {
+            return 0;
+        }
+        let quarantined = self.scenarios();
+        let mut excused = 0;
+        for outcome in &mut run_result.scenarios {
+            if outcome.status == ScenarioStatus::Failed
+                && quarantined.contains(&(outcome.feature.clone(), outcome.scenario.clone()))
+            {
+                outcome.status = ScenarioStatus::KnownIssue;
+                outcome.known_issue = Some(QUARANTINE_REASON.to_owned());
+                excused += 1;
+            }
+        }
+        excused
+    }
+
+    /// Quarantined scenarios which failed in the run and are run once more, according to the settings.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - Settings of the classification from the profile
+    /// * `run_result` - Result of the first attempt of the run
+    ///
+    pub fn to_retry(
+        &self,
+        settings: &FlakinessSettings,
+        run_result: &RunResult,
+    ) -> HashSet<(String, String)> {
+        if settings.quarantine != QuarantineAction::Retry {
+            return HashSet::new();
+        }
+        let quarantined = self.scenarios();
+        run_result
+            .scenarios
+            .iter()
+            .filter(|outcome| outcome.status == ScenarioStatus::Failed)
+            .map(|outcome| (outcome.feature.clone(), outcome.scenario.clone()))
+            .filter(|scenario| quarantined.contains(scenario))
+            .collect()
+    }
+
+    /// Writes the quarantine file, read by the next run; failures are reported without failing the run.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the quarantine file
+    ///
+    pub fn write(&self, path: &str) {
+        let content = format!(
+            "# Generated after every run; scenarios listed here are quarantined as flaky in the next one\n{}",
+            toml::to_string(self).expect("Quarantine should be serializable")
+        );
+        let written = Path::new(path)
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(path, report_encryption::seal(content.into_bytes())));
+        if let Err(err) = written {
+            eprintln!("Writing quarantine file {} failed: {}", path, err);
+        }
+    }
+}
+
+impl fmt::Display for Quarantine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.quarantined.is_empty() {
+            return Ok(());
+        }
+        writeln!(f, "Flaky scenarios:")?;
+        for flaky in &self.quarantined {
+            writeln!(
+                f,
+                "  {}: {} failed in {} of its last {} runs",
+                flaky.feature, flaky.scenario, flaky.failed_runs, flaky.runs
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Replaces outcomes of the failed scenarios run once more with the outcomes of the second attempt,
+/// marked as retried. Returns the number of replaced outcomes.
+///
+/// # Arguments
+///
+/// * `run_result` - Result of the run, with the outcomes of the second attempts at the end
+/// * `first_attempts` - Number of outcomes recorded before the second attempts
+///
+pub fn apply_retries(run_result: &mut RunResult, first_attempts: usize) -> usize {
+    let retries = run_result.scenarios.split_off(first_attempts);
+    let mut replaced = 0;
+    for mut retry in retries {
+        let first_attempt = run_result.scenarios.iter_mut().find(|outcome| {
+            !outcome.retried
+                && outcome.status == ScenarioStatus::Failed
+                && outcome.feature == retry.feature
+                && outcome.scenario == retry.scenario
+        });
+        if let Some(first_attempt) = first_attempt {
+            retry.retried = true;
+            *first_attempt = retry;
+            replaced += 1;
+        }
+    }
+    replaced
+}
+
+/// Appends statuses of the scenarios run in this run to the history file, then classifies as flaky
+/// the scenarios whose failure rate over their last runs kept in the history exceeds the limit.
+/// Scenarios which failed in every run of the window are broken rather than flaky, so they are not quarantined.
+///
+/// # Arguments
+///
+/// * `run_result` - Result of the run; scenarios taken over from a resumed run are skipped
+/// * `settings` - Settings of the classification from the profile
+/// * `history_file` - File with statuses of previous runs, one json line per run
+///
+pub fn check_flakiness(
+    run_result: &RunResult,
+    settings: &FlakinessSettings,
+    history_file: &str,
+) -> Quarantine {
+    let run = RunStatuses {
+        run_id: correlation::run_id().to_owned(),
+        scenarios: run_result
+            .scenarios
+            .iter()
+            .filter(|outcome| outcome.run_id == correlation::run_id())
+            .filter(|outcome| outcome.status != ScenarioStatus::Skipped)
+            .map(|outcome| RecordedStatus {
+                feature: outcome.feature.clone(),
+                scenario: outcome.scenario.clone(),
+                failed: outcome.retried || outcome.status != ScenarioStatus::Passed,
+            })
+            .collect(),
+    };
+    if let Err(err) = append_run(history_file, &run) {
+        eprintln!("Writing scenario history {} failed: {}", history_file, err);
+    }
+    let history: Vec<RunStatuses> = report_encryption::read_to_string(history_file)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+
+    // Statuses of every scenario, newest run first; outlines reported many times count once per run
+    let mut statuses: BTreeMap<(String, String), Vec<bool>> = BTreeMap::new();
+    for run in history.iter().rev() {
+        let mut run_statuses: BTreeMap<(String, String), bool> = BTreeMap::new();
+        for recorded in &run.scenarios {
+            *run_statuses
+                .entry((recorded.feature.clone(), recorded.scenario.clone()))
+                .or_default() |= recorded.failed;
+        }
+        for (scenario, failed) in run_statuses {
+            statuses.entry(scenario).or_default().push(failed);
+        }
+    }
+    let quarantined = statuses
+        .into_iter()
+        .filter_map(|((feature, scenario), failed)| {
+            let window = &failed[..failed.len().min(settings.window_runs)];
+            let failed_runs = window.iter().filter(|failed| **failed).count();
+            let failure_rate = failed_runs as f64 / window.len() as f64;
+            (failure_rate > settings.max_failure_rate && failed_runs < window.len()).then(|| {
+                FlakyScenario {
+                    feature,
+                    scenario,
+                    failed_runs,
+                    runs: window.len(),
+                }
+            })
+        })
+        .collect();
+    Quarantine { quarantined }
+}
+
+/// Rewrites the history file with the run appended, since an encrypted file cannot be appended to.
+fn append_run(history_file: &str, run: &RunStatuses) -> io::Result<()> {
+    if let Some(dir) = Path::new(history_file).parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut content = match fs::read(history_file) {
+        Ok(content) => report_encryption::open(content)?,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(err) => return Err(err),
+    };
+    if !content.is_empty() && !content.ends_with(b"\n") {
+        content.push(b'\n');
+    }
+    serde_json::to_writer(&mut content, run).expect("Run statuses should be serializable");
+    content.push(b'\n');
+    fs::write(history_file, report_encryption::seal(content))
+}
@@ -0,0 +1,217 @@
+use super::baseline;
+use super::reference::{self, PairInfo};
+use super::seed::{Seed, SeededRng};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Shapes of synthetic values generated for request parameters.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GeneratorSettings {
+    /// Prefix of client order IDs, telling orders of the suite apart; IDs are 18 characters long at most
+    pub client_order_prefix: String,
+    /// Prefix of reference strings, e.g. "AV" for "AV-20261016-K3XQ9P"
+    pub reference_prefix: String,
+    /// Largest amount, as a multiple of the minimal volume of the pair
+    pub amount_max_multiple: f64,
+    /// Oldest timestamp, in seconds before now
+    pub timestamp_max_age_seconds: u64,
+}
+
+impl Default for GeneratorSettings {
+    fn default() -> Self {
+        Self {
+            client_order_prefix: "av".to_owned(),
+            reference_prefix: "AV".to_owned(),
+            amount_max_multiple: 10.0,
+            timestamp_max_age_seconds: 86_400,
+        }
+    }
+}
+
+/// Longest free text client order ID accepted by the API.
+const MAX_CLIENT_ORDER_ID_LEN: usize = 18;
+
+const ALPHANUMERIC: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Generator of all synthetic values, following the seed of the run, so a fixed SEED reproduces them.
+static RNG: Lazy<Mutex<SeededRng>> =
+    Lazy::new(|| Mutex::new(SeededRng::new(Seed::from_env().value)));
+
+/// Number from `start` (inclusive) to `end` (exclusive); fails unless `end` is above `start`
+/// and the range fits in the generator.
+pub fn integer_in_range(start: i64, end: i64) -> Result<i64, String> {
+    let span = end
+        .checked_sub(start)
+        .filter(|span| *span > 0)
+        .and_then(|span| usize::try_from(span).ok())
+        .ok_or_else(|| format!("No number can be generated from {} to {}", start, end))?;
+    let offset = RNG
+        .lock()
+        .expect("Random generator should not be poisoned")
+        .below(span);
+    Ok(start + offset as i64)
+}
+
+/// Text of random characters of the alphabet.
+fn random_text(alphabet: &[u8], len: usize) -> String {
+    let mut rng = RNG.lock().expect("Random generator should not be poisoned");
+    (0..len)
+        .map(|_| alphabet[rng.below(alphabet.len())] as char)
+        .collect()
+}
+
+fn now_seconds() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("System time should be after unix epoch")
+        .as_secs()
+}
+
+/// Client order ID of the prefix followed by random alphanumeric characters, e.g. "av7Qm2Lx0cT9bE4kWz".
+///
+/// # Arguments
+///
+/// * `settings` - Generator settings of the profile
+///
+pub fn client_order_id(settings: &GeneratorSettings) -> String {
+    let prefix: String = settings
+        .client_order_prefix
+        .chars()
+        .take(MAX_CLIENT_ORDER_ID_LEN / 2)
+        .collect();
+    let len = MAX_CLIENT_ORDER_ID_LEN - prefix.len();
+    format!("{}{}", prefix, random_text(ALPHANUMERIC, len))
+}
+
+/// Reference string of the prefix, today's date and random characters, e.g. "AV-20261016-K3XQ9P".
+///
+/// # Arguments
+///
+/// * `settings` - Generator settings of the profile
+///
+pub fn reference(settings: &GeneratorSettings) -> String {
+    format!(
+        "{}-{}-{}",
+        settings.reference_prefix,
+        baseline::today().replace('-', ""),
+        random_text(&ALPHANUMERIC[..36], 6)
+    )
+}
+
+/// Volume of the pair with its lot precision, at least its minimal volume (and minimal cost at the price, if given)
+/// and at most `amount_max_multiple` times that.
+///
+/// # Arguments
+///
+/// * `pair` - Trading rules of the pair, from the reference data
+/// * `price` - Price the volume would be traded at, if known
+/// * `settings` - Generator settings of the profile
+///
+pub fn amount(
+    pair: &PairInfo,
+    price: Option<f64>,
+    settings: &GeneratorSettings,
+) -> Result<String, String> {
+    let min_volume = match (pair.min_cost(), price) {
+        (Some(min_cost), Some(price)) if price > 0.0 => pair.min_volume().max(min_cost / price),
+        _ => pair.min_volume(),
+    };
+    let scale = 10f64.powi(pair.lot_decimals as i32);
+    let min_lots = (min_volume * scale - 1e-6).ceil().max(1.0) as i64;
+    let max_lots = ((min_lots as f64) * settings.amount_max_multiple.max(1.0)).floor() as i64;
+    let end = max_lots
+        .checked_add(1)
+        .ok_or_else(|| format!("Amount of {} lots is too large to generate", max_lots))?;
+    let lots = integer_in_range(min_lots, end)?;
+    Ok(format!(
+        "{:.*}",
+        pair.lot_decimals as usize,
+        lots as f64 / scale
+    ))
+}
+
+/// Unix timestamp in seconds, up to `timestamp_max_age_seconds` before now.
+///
+/// # Arguments
+///
+/// * `settings` - Generator settings of the profile
+///
+pub fn timestamp(settings: &GeneratorSettings) -> Result<u64, String> {
+    let end = i64::try_from(settings.timestamp_max_age_seconds)
+        .ok()
+        .and_then(|max_age| max_age.checked_add(1))
+        .ok_or_else(|| {
+            format!(
+                "timestamp_max_age_seconds of {} is too large",
+                settings.timestamp_max_age_seconds
+            )
+        })?;
+    let age = integer_in_range(0, end)? as u64;
+    Ok(now_seconds().saturating_sub(age))
+}
+
+/// Timestamp in UTC in the RFC 3339 format, e.g. "2026-10-16T08:30:00Z".
+///
+/// # Arguments
+///
+/// * `seconds` - Unix timestamp in seconds
+///
+pub fn rfc3339_of(seconds: u64) -> String {
+    let time_of_day = seconds % 86_400;
+    format!(
+        "{}T{:02}:{:02}:{:02}Z",
+        baseline::date_of((seconds / 86_400) as i64),
+        time_of_day / 3600,
+        time_of_day % 3600 / 60,
+        time_of_day % 60
+    )
+}
+
+/// Kind of value generated by "I generate <kind> as "<name>"".
+#[derive(Debug, Clone, PartialEq)]
+pub enum Generator {
+    ClientOrderId,
+    Reference,
+    /// Amount of the named pair
+    Amount(String),
+    Timestamp,
+}
+
+impl FromStr for Generator {
+    type Err = String;
+
+    fn from_str(kind: &str) -> Result<Self, String> {
+        match kind {
+            "a client order ID" => Ok(Generator::ClientOrderId),
+            "a reference" => Ok(Generator::Reference),
+            "a timestamp" => Ok(Generator::Timestamp),
+            _ => kind
+                .strip_prefix("an amount of ")
+                .map(|pair| Generator::Amount(pair.to_owned()))
+                .ok_or_else(|| format!("{} cannot be generated", kind)),
+        }
+    }
+}
+
+impl Generator {
+    /// Generates a value of the kind; amounts are generated for pairs preloaded to the reference data.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - Generator settings of the profile
+    ///
+    pub fn generate(&self, settings: &GeneratorSettings) -> Result<String, String> {
+        match self {
+            Generator::ClientOrderId => Ok(client_order_id(settings)),
+            Generator::Reference => Ok(reference(settings)),
+            Generator::Amount(pair) => {
+                amount(reference::loaded().require_pair(pair), None, settings)
+            }
+            Generator::Timestamp => timestamp(settings).map(|seconds| seconds.to_string()),
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use super::config::Profile;
+use super::correlation;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::redirect;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use url::Url;
+
+/// Urls the request was redirected to, in the order of redirects.
+#[derive(Debug, Clone, Default)]
+pub struct RedirectChain(Arc<Mutex<Vec<Url>>>);
+
+impl RedirectChain {
+    pub fn urls(&self) -> Vec<Url> {
+        self.0
+            .lock()
+            .expect("Redirect chain should not be poisoned")
+            .clone()
+    }
+
+    fn record(&self, url: &Url) {
+        self.0
+            .lock()
+            .expect("Redirect chain should not be poisoned")
+            .push(url.clone());
+    }
+}
+
+/// HTTP versions the client offers to the server.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HttpVersionPreference {
+    /// Only HTTP/1.1 is used
+    Http1Only,
+    /// HTTP/2 is offered through ALPN, falling back to HTTP/1.1 when the server does not select it
+    #[default]
+    PreferHttp2,
+    /// HTTP/2 is used without negotiation
+    Http2Only,
+}
+
+/// Counts connections opened by a client; every new connection resolves the host,
+/// while requests sent over pooled connections do not.
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionCounter(Arc<AtomicUsize>);
+
+impl ConnectionCounter {
+    pub fn new_connections(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Resolver counting resolutions, which applies addresses of the profile before DNS.
+struct CountingResolver {
+    counter: ConnectionCounter,
+    overrides: HashMap<String, IpAddr>,
+}
+
+impl Resolve for CountingResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        self.counter.0.fetch_add(1, Ordering::SeqCst);
+        let host = name.as_str().to_owned();
+        let overridden = self.overrides.get(&host).copied();
+        Box::pin(async move {
+            // Port is ignored by reqwest; the one from the requested url is used
+            let addresses: Vec<SocketAddr> = match overridden {
+                Some(address) => vec![SocketAddr::new(address, 0)],
+                None => tokio::net::lookup_host((host.as_str(), 0)).await?.collect(),
+            };
+            let addresses: Addrs = Box::new(addresses.into_iter());
+            Ok(addresses)
+        })
+    }
+}
+
+/// Builds client configured according to the profile,
+/// along with the chain recording redirects of requests sent by the client.
+/// A new client should be built for each request, so the chain refers to a single request.
+///
+/// # Arguments
+///
+/// * `profile` - Settings of the environment the suite is run against
+///
+pub fn build_client(profile: &Profile) -> (reqwest::Client, RedirectChain) {
+    let (mut builder, redirect_chain) = configured_builder(profile);
+    for (host, address) in &profile.resolve {
+        // Port is ignored by reqwest; the one from the requested url is used
+        builder = builder.resolve(host, SocketAddr::new(*address, 0));
+    }
+    let client = builder.build().expect("Client should be possible to build");
+    (client, redirect_chain)
+}
+
+/// Builds client configured according to the profile, along with the counter of connections it opens,
+/// so reuse of connections by many requests sent with the client can be verified.
+///
+/// # Arguments
+///
+/// * `profile` - Settings of the environment the suite is run against
+///
+pub fn build_counting_client(profile: &Profile) -> (reqwest::Client, ConnectionCounter) {
+    let (builder, _) = configured_builder(profile);
+    let counter = ConnectionCounter::default();
+    let resolver = CountingResolver {
+        counter: counter.clone(),
+        overrides: profile.resolve.clone(),
+    };
+    let client = builder
+        .dns_resolver(Arc::new(resolver))
+        .build()
+        .expect("Client should be possible to build");
+    (client, counter)
+}
+
+/// Client builder with redirect policy, HTTP versions and correlation header of the profile.
+fn configured_builder(profile: &Profile) -> (reqwest::ClientBuilder, RedirectChain) {
+    let redirect_chain = RedirectChain::default();
+    let recorded_chain = redirect_chain.clone();
+    let max_redirects = profile.max_redirects;
+    let policy = redirect::Policy::custom(move |attempt| {
+        if max_redirects == 0 {
+            attempt.stop()
+        } else if attempt.previous().len() > max_redirects {
+            attempt.error(format!("more than {} redirects", max_redirects))
+        } else {
+            recorded_chain.record(attempt.url());
+            attempt.follow()
+        }
+    });
+    let mut default_headers = HeaderMap::new();
+    if !profile.correlation.run_header.is_empty() {
+        let name = HeaderName::from_bytes(profile.correlation.run_header.as_bytes())
+            .expect("Correlation header should be a valid header name");
+        let value = HeaderValue::from_str(correlation::run_id())
+            .expect("Run ID should be a valid header value");
+        default_headers.insert(name, value);
+    }
+    let builder = reqwest::Client::builder()
+        .redirect(policy)
+        .default_headers(default_headers);
+    let builder = match profile.http_version {
+        HttpVersionPreference::Http1Only => builder.http1_only(),
+        HttpVersionPreference::PreferHttp2 => builder,
+        HttpVersionPreference::Http2Only => builder.http2_prior_knowledge(),
+    };
+    (builder, redirect_chain)
+}
@@ -0,0 +1,201 @@
+use super::config::EndpointRegistry;
+use super::test_plan::{Assertion, PlanRequest, PlanTest, TestPlan};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Problem of a request of an HTTP file, along with the line it starts at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpFileError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for HttpFileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// Annotations and lines of a single request of the file, i.e. the text between `###` separators.
+#[derive(Debug, Default)]
+struct RequestBlock {
+    first_line: usize,
+    annotations: Vec<(String, String)>,
+    request_line: Option<String>,
+    body: Vec<String>,
+}
+
+fn blocks_of(content: &str) -> Vec<RequestBlock> {
+    let mut blocks = vec![RequestBlock {
+        first_line: 1,
+        ..RequestBlock::default()
+    }];
+    let mut in_body = false;
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("###") {
+            blocks.push(RequestBlock {
+                first_line: index + 2,
+                ..RequestBlock::default()
+            });
+            in_body = false;
+            continue;
+        }
+        let block = blocks.last_mut().unwrap();
+        let comment = trimmed
+            .strip_prefix('#')
+            .or_else(|| trimmed.strip_prefix("//"));
+        if let Some(comment) = comment {
+            if let Some(annotation) = comment.trim().strip_prefix('@') {
+                let (name, value) = annotation.split_once(' ').unwrap_or((annotation, ""));
+                block
+                    .annotations
+                    .push((name.to_owned(), value.trim().to_owned()));
+            }
+            continue;
+        }
+        if block.request_line.is_none() {
+            if !trimmed.is_empty() {
+                block.request_line = Some(trimmed.to_owned());
+            }
+        } else if in_body {
+            block.body.push(line.to_owned());
+        } else if trimmed.is_empty() {
+            // Headers end with the first empty line; they are not sent, as the suite signs its own requests
+            in_body = true;
+        }
+    }
+    blocks
+        .into_iter()
+        .filter(|block| block.request_line.is_some())
+        .collect()
+}
+
+/// Path and query of the target of the request line, without the host, which is usually a `{{host}}` variable.
+fn path_and_query(target: &str) -> &str {
+    if target.starts_with("{{") {
+        return target.find("}}").map_or(target, |end| &target[end + 2..]);
+    }
+    match target.find("://") {
+        Some(scheme_end) => {
+            let rest = &target[scheme_end + 3..];
+            rest.find('/').map_or("/", |path_start| &rest[path_start..])
+        }
+        None => target,
+    }
+}
+
+fn form_parameters(encoded: &str) -> Vec<(String, String)> {
+    url::form_urlencoded::parse(encoded.as_bytes())
+        .into_owned()
+        .collect()
+}
+
+fn request_of(
+    block: &RequestBlock,
+    endpoints: &EndpointRegistry,
+) -> Result<PlanRequest, HttpFileError> {
+    let error = |message: String| HttpFileError {
+        line: block.first_line,
+        message,
+    };
+    let request_line = block.request_line.as_deref().unwrap_or_default();
+    let mut words = request_line.split_whitespace();
+    let target = match (words.next(), words.next()) {
+        (Some(_method), Some(target)) => target,
+        (Some(target), None) => target,
+        _ => return Err(error("the request has no target".to_owned())),
+    };
+    let (path, query) = path_and_query(target)
+        .split_once('?')
+        .unwrap_or((path_and_query(target), ""));
+    let endpoint = endpoints
+        .iter()
+        .find(|(_, endpoint)| endpoint.path == path)
+        .map(|(name, _)| name.clone())
+        .ok_or_else(|| error(format!("no endpoint of the registry has path {}", path)))?;
+    let mut parameters: BTreeMap<String, String> = form_parameters(query).into_iter().collect();
+    parameters.extend(form_parameters(block.body.join("").trim()));
+
+    let mut request = PlanRequest {
+        endpoint,
+        parameters,
+        status: None,
+        schema: false,
+        assertions: Vec::new(),
+        extract: BTreeMap::new(),
+    };
+    for (name, value) in &block.annotations {
+        match name.as_str() {
+            "name" => {}
+            "status" => {
+                let status = value
+                    .parse()
+                    .map_err(|_| error(format!("@status {} is not a status code", value)))?;
+                request.status = Some(status);
+            }
+            "schema" => request.schema = true,
+            "capture" => {
+                let (name, field) = value.split_once('=').ok_or_else(|| {
+                    error(format!("@capture {} should read <name> = <field>", value))
+                })?;
+                request
+                    .extract
+                    .insert(name.trim().to_owned(), field.trim().to_owned());
+            }
+            "assert" => {
+                let (field, expected) = value.split_once("==").ok_or_else(|| {
+                    error(format!("@assert {} should read <field> == <value>", value))
+                })?;
+                request.assertions.push(Assertion {
+                    field: field.trim().to_owned(),
+                    exists: None,
+                    equals: Some(expected.trim().to_owned()),
+                    matches: None,
+                });
+            }
+            _ => return Err(error(format!("@{} is not a known annotation", name))),
+        }
+    }
+    Ok(request)
+}
+
+/// Reads a JetBrains or VS Code HTTP file as a test plan with a single test sending its requests in order.
+/// Requests are separated by `###` and their targets are matched with paths of the registry, e.g.
+/// `GET {{host}}/0/public/Time`; parameters are read from the query and a form encoded body.
+/// Expectations are annotated in comments above the request line:
+/// `# @status 200`, `# @schema`, `# @assert result.status == online` and `# @capture txid = result.txid.0`,
+/// where a captured value is referred to by later requests as `{{txid}}`.
+/// Headers are not sent, as the suite authenticates its requests itself.
+///
+/// # Arguments
+///
+/// * `name` - Name of the file, used as the name of the plan and of its test
+/// * `content` - Content of the file
+/// * `endpoints` - Endpoints known to the suite
+///
+pub fn plan_of(
+    name: &str,
+    content: &str,
+    endpoints: &EndpointRegistry,
+) -> Result<TestPlan, HttpFileError> {
+    let requests = blocks_of(content)
+        .iter()
+        .map(|block| request_of(block, endpoints))
+        .collect::<Result<Vec<_>, _>>()?;
+    if requests.is_empty() {
+        return Err(HttpFileError {
+            line: 1,
+            message: "the file has no requests".to_owned(),
+        });
+    }
+    Ok(TestPlan {
+        name: name.to_owned(),
+        tags: Vec::new(),
+        tests: vec![PlanTest {
+            name: format!("Requests of {} meet their annotations", name),
+            tags: Vec::new(),
+            requests,
+        }],
+    })
+}
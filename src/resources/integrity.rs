@@ -0,0 +1,223 @@
+use super::masking;
+use itertools::Itertools;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::sync::Mutex;
+
+/// What a value of the source has to match within the target response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TargetKind {
+    /// Keys of the objects matched by the target path, e.g. pair names of `$.result`
+    Keys,
+    /// String or number values matched by the target path
+    Values,
+}
+
+impl Default for TargetKind {
+    fn default() -> Self {
+        TargetKind::Keys
+    }
+}
+
+/// Rule of referential integrity between responses of two endpoints, kept in `integrity_rules` of the profile,
+/// e.g. every `$.result.trades.*.ordertxid` of TradesHistory should be a key of `$.result.closed` of ClosedOrders.
+/// Paths are written in the form used by masks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IntegrityRule {
+    /// Name of the rule in reported violations
+    pub name: String,
+    /// Endpoint whose responses refer to the target
+    pub source_endpoint: String,
+    /// Path of the referring values
+    pub source_path: String,
+    /// Endpoint whose responses are referred to
+    pub target_endpoint: String,
+    /// Path of the objects or values referred to
+    pub target_path: String,
+    /// Whether the referring values are keys or values found at the target path; keys by default
+    #[serde(default)]
+    pub target: TargetKind,
+}
+
+/// Values referring to the target and values referred to, collected from responses of a rule.
+#[derive(Debug, Clone, Default)]
+struct RuleState {
+    sources: BTreeSet<String>,
+    targets: BTreeSet<String>,
+    target_observed: bool,
+}
+
+/// States of the rules by scenario key and rule name; the whole run is kept under the empty key.
+static OBSERVED: Lazy<Mutex<HashMap<String, HashMap<String, RuleState>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn collect(value: &Value, segments: &[String], kind: TargetKind, found: &mut BTreeSet<String>) {
+    let (segment, rest) = match segments.split_first() {
+        Some(split) => split,
+        None => {
+            match (value, kind) {
+                (Value::Object(fields), TargetKind::Keys) => found.extend(fields.keys().cloned()),
+                (Value::String(text), TargetKind::Values) => {
+                    found.insert(text.clone());
+                }
+                (Value::Number(number), TargetKind::Values) => {
+                    found.insert(number.to_string());
+                }
+                _ => {}
+            }
+            return;
+        }
+    };
+    match value {
+        Value::Object(fields) => {
+            for (key, field) in fields {
+                if segment == "*" || segment == key {
+                    collect(field, rest, kind, found);
+                }
+            }
+        }
+        Value::Array(elements) => {
+            for (index, element) in elements.iter().enumerate() {
+                if segment == "*" || segment.parse::<usize>().ok() == Some(index) {
+                    collect(element, rest, kind, found);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Collects values of the response referring to or referred to by the rules, for the scenario and the run.
+///
+/// # Arguments
+///
+/// * `scenario_key` - Key of the scenario which received the response
+/// * `endpoint_name` - Name of the endpoint which returned the response
+/// * `rules` - Integrity rules of the profile
+/// * `json_response` - Response body parsed to json
+///
+pub fn observe(
+    scenario_key: &str,
+    endpoint_name: &str,
+    rules: &[IntegrityRule],
+    json_response: &Value,
+) {
+    if rules.is_empty() {
+        return;
+    }
+    let mut observed = OBSERVED
+        .lock()
+        .expect("Observed values should not be poisoned");
+    for scope in [scenario_key, ""] {
+        let states = observed.entry(scope.to_owned()).or_default();
+        for rule in rules {
+            let state = states.entry(rule.name.clone()).or_default();
+            if rule.source_endpoint == endpoint_name {
+                let segments = masking::segments_of(&rule.source_path);
+                collect(
+                    json_response,
+                    &segments,
+                    TargetKind::Values,
+                    &mut state.sources,
+                );
+            }
+            if rule.target_endpoint == endpoint_name {
+                let segments = masking::segments_of(&rule.target_path);
+                collect(json_response, &segments, rule.target, &mut state.targets);
+                state.target_observed = true;
+            }
+        }
+    }
+}
+
+/// Violations of the rules among the observed responses, a line per rule, e.g.
+/// "orders_of_trades: OQCLML-BW3P3-BUCMWZ of TradesHistory missing from ClosedOrders".
+/// Rules whose target endpoint was not requested are not evaluated.
+fn violations_of(states: &HashMap<String, RuleState>, rules: &[IntegrityRule]) -> Vec<String> {
+    rules
+        .iter()
+        .filter_map(|rule| {
+            let state = states.get(&rule.name)?;
+            if !state.target_observed {
+                return None;
+            }
+            let missing: Vec<&String> = state.sources.difference(&state.targets).collect();
+            if missing.is_empty() {
+                return None;
+            }
+            Some(format!(
+                "{}: {} of {} missing from {}",
+                rule.name,
+                missing.iter().join(", "),
+                rule.source_endpoint,
+                rule.target_endpoint
+            ))
+        })
+        .collect()
+}
+
+/// Removes the responses observed by the scenario and returns violations of the rules among them.
+///
+/// # Arguments
+///
+/// * `scenario_key` - Key of the scenario
+/// * `rules` - Integrity rules of the profile
+///
+pub fn scenario_violations(scenario_key: &str, rules: &[IntegrityRule]) -> Vec<String> {
+    let states = OBSERVED
+        .lock()
+        .expect("Observed values should not be poisoned")
+        .remove(scenario_key)
+        .unwrap_or_default();
+    violations_of(&states, rules)
+}
+
+/// Removes the responses observed by the finished scenario.
+///
+/// # Arguments
+///
+/// * `scenario_key` - Key of the finished scenario
+///
+pub fn forget(scenario_key: &str) {
+    OBSERVED
+        .lock()
+        .expect("Observed values should not be poisoned")
+        .remove(scenario_key);
+}
+
+/// Violations of the rules among the responses observed during the run.
+#[derive(Debug, Clone, Default)]
+pub struct IntegrityReport {
+    pub violations: Vec<String>,
+}
+
+impl fmt::Display for IntegrityReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for violation in &self.violations {
+            writeln!(f, "Integrity violation of {}", violation)?;
+        }
+        Ok(())
+    }
+}
+
+/// Evaluates the rules over all responses observed during the run.
+///
+/// # Arguments
+///
+/// * `rules` - Integrity rules of the profile
+///
+pub fn integrity_report(rules: &[IntegrityRule]) -> IntegrityReport {
+    let observed = OBSERVED
+        .lock()
+        .expect("Observed values should not be poisoned");
+    let violations = observed
+        .get("")
+        .map(|states| violations_of(states, rules))
+        .unwrap_or_default();
+    IntegrityReport { violations }
+}
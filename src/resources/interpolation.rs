@@ -0,0 +1,89 @@
+use cucumber::gherkin;
+use std::collections::HashMap;
+use std::env;
+
+/// Replaces `${NAME}` placeholders in the text with values of the variables of the profile,
+/// or of the environment variables when the profile does not define them.
+/// Returns the name of the first unresolved variable as an error.
+///
+/// # Arguments
+///
+/// * `text` - Text containing placeholders, e.g. step of a feature
+/// * `variables` - Variables of the profile, by name
+///
+pub fn interpolate(text: &str, variables: &HashMap<String, String>) -> Result<String, String> {
+    let mut interpolated = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => break,
+        };
+        let name = &rest[start + 2..end];
+        let value = match variables.get(name) {
+            Some(value) => value.clone(),
+            None => env::var(name).map_err(|_| name.to_owned())?,
+        };
+        interpolated.push_str(&rest[..start]);
+        interpolated.push_str(&value);
+        rest = &rest[end + 1..];
+    }
+    interpolated.push_str(rest);
+    Ok(interpolated)
+}
+
+/// Interpolates texts, docstrings and tables of all steps of the feature,
+/// panicking when a placeholder cannot be resolved.
+///
+/// # Arguments
+///
+/// * `feature` - Parsed feature, with scenario outlines already expanded
+/// * `variables` - Variables of the profile, by name
+///
+pub fn interpolate_feature(feature: &mut gherkin::Feature, variables: &HashMap<String, String>) {
+    let path = feature
+        .path
+        .as_ref()
+        .map(|path| path.display().to_string())
+        .unwrap_or_default();
+    let mut steps: Vec<&mut gherkin::Step> = Vec::new();
+    if let Some(background) = feature.background.as_mut() {
+        steps.extend(background.steps.iter_mut());
+    }
+    for scenario in feature.scenarios.iter_mut() {
+        steps.extend(scenario.steps.iter_mut());
+    }
+    for rule in feature.rules.iter_mut() {
+        if let Some(background) = rule.background.as_mut() {
+            steps.extend(background.steps.iter_mut());
+        }
+        for scenario in rule.scenarios.iter_mut() {
+            steps.extend(scenario.steps.iter_mut());
+        }
+    }
+    for step in steps {
+        let line = step.position.line;
+        let resolve = |text: &str| {
+            interpolate(text, variables).unwrap_or_else(|name| {
+                panic!(
+                    "Variable {} used in {}:{} is defined neither in the profile nor in the environment",
+                    name, path, line
+                )
+            })
+        };
+        let value = resolve(&step.value);
+        let docstring = step.docstring.as_deref().map(resolve);
+        let rows: Option<Vec<Vec<String>>> = step.table.as_ref().map(|table| {
+            table
+                .rows
+                .iter()
+                .map(|row| row.iter().map(|cell| resolve(cell)).collect())
+                .collect()
+        });
+        step.value = value;
+        step.docstring = docstring;
+        if let (Some(table), Some(rows)) = (step.table.as_mut(), rows) {
+            table.rows = rows;
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use super::config::{EndpointRegistry, Profile};
+use super::{fixtures, skipping};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+
+/// Query of permissions of the API key, sent before the private suite where the API exposes key metadata.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeyPermissionSettings {
+    /// Private endpoint describing the key, e.g. "GetApiKeyInfo"; scenarios are not gated when not set
+    pub endpoint: Option<String>,
+    /// Field of the response listing the permissions, as an array of names or an object of flags
+    pub field: String,
+}
+
+impl Default for KeyPermissionSettings {
+    fn default() -> Self {
+        Self {
+            endpoint: None,
+            field: "result.permissions".to_owned(),
+        }
+    }
+}
+
+/// Permissions of the API key, normalized; not known until the preflight succeeded.
+static KEY_PERMISSIONS: Lazy<Mutex<Option<BTreeSet<String>>>> = Lazy::new(|| Mutex::new(None));
+
+/// Normalizes a permission name, so "Query Funds", "query_funds" and "query-funds" are the same.
+fn normalize(permission: &str) -> String {
+    permission
+        .trim()
+        .to_lowercase()
+        .replace(|c| c == ' ' || c == '_', "-")
+}
+
+/// Reads permission names from an array of names, or from an object of flags, keeping the ones set.
+fn permissions_of(value: &serde_json::Value) -> Option<BTreeSet<String>> {
+    match value {
+        serde_json::Value::Array(names) => Some(
+            names
+                .iter()
+                .filter_map(|name| name.as_str())
+                .map(normalize)
+                .collect(),
+        ),
+        serde_json::Value::Object(flags) => Some(
+            flags
+                .iter()
+                .filter(|(_, granted)| granted.as_bool().unwrap_or(false))
+                .map(|(name, _)| normalize(name))
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+/// Queries permissions of the API key, when the profile names the endpoint describing it,
+/// so scenarios tagged with permissions the key lacks are skipped.
+///
+/// # Arguments
+///
+/// * `profile` - Profile of the run
+/// * `endpoints` - Endpoints known to the suite
+///
+pub async fn preflight(profile: &Profile, endpoints: &EndpointRegistry) {
+    let settings = &profile.key_permissions;
+    let endpoint_name = match &settings.endpoint {
+        Some(endpoint_name) => endpoint_name,
+        None => return,
+    };
+    let json_response = fixtures::send_private(endpoint_name, &[], profile, endpoints)
+        .await
+        .unwrap_or_else(|err| panic!("{}", err));
+    let pointer = format!("/{}", settings.field.replace('.', "/"));
+    let permissions = json_response
+        .pointer(&pointer)
+        .and_then(permissions_of)
+        .unwrap_or_else(|| {
+            panic!(
+                "Response of {} does not list permissions in {}: {}",
+                endpoint_name, settings.field, json_response
+            )
+        });
+    println!(
+        "API key permissions: {}",
+        permissions.iter().cloned().collect::<Vec<_>>().join(", ")
+    );
+    *KEY_PERMISSIONS
+        .lock()
+        .expect("Key permissions should not be poisoned") = Some(permissions);
+}
+
+/// Permissions required by `@requires-<permission>` tags, e.g. "trade" for `@requires-trade`.
+///
+/// # Arguments
+///
+/// * `tags` - Tags of the scenario, including the ones inherited from its rule and feature, without "@"
+///
+pub fn required_permissions<'a>(tags: impl IntoIterator<Item = &'a String>) -> Vec<String> {
+    tags.into_iter()
+        .filter_map(|tag| tag.strip_prefix("requires-"))
+        .map(normalize)
+        .collect()
+}
+
+/// Skips the scenario when the key is known to lack any of the permissions its tags require.
+///
+/// # Arguments
+///
+/// * `scenario_key` - Key of the scenario
+/// * `tags` - Tags of the scenario, including the ones inherited from its rule and feature, without "@"
+///
+pub fn skip_unless_permitted<'a>(scenario_key: &str, tags: impl IntoIterator<Item = &'a String>) {
+    let missing: Vec<String> = match &*KEY_PERMISSIONS
+        .lock()
+        .expect("Key permissions should not be poisoned")
+    {
+        Some(permissions) => required_permissions(tags)
+            .into_iter()
+            .filter(|required| !permissions.contains(required))
+            .collect(),
+        None => return,
+    };
+    if !missing.is_empty() {
+        skipping::skip_scenario(
+            scenario_key,
+            &format!(
+                "the API key lacks the permissions required by the scenario: {}",
+                missing.join(", ")
+            ),
+        )
+    }
+}
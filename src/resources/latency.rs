@@ -0,0 +1,303 @@
+use super::config::EndpointRegistry;
+use super::correlation;
+use super::report_encryption;
+use hdrhistogram::serialization::{Serializer, V2DeflateSerializer};
+use hdrhistogram::Histogram;
+use once_cell::sync::Lazy;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Highest latency tracked by histograms, in microseconds; higher ones are recorded as this value.
+const MAX_TRACKED_MICROS: u64 = 3_600_000_000;
+
+/// Histograms of latencies of requests sent during the run, in microseconds, by endpoint name.
+/// Their size does not depend on the number of samples, so long soak runs keep memory bounded.
+static LATENCIES: Lazy<Mutex<BTreeMap<String, Histogram<u64>>>> =
+    Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+/// Creates histogram of latencies in microseconds, from 1 µs to an hour, with 3 significant digits.
+pub fn new_histogram() -> Histogram<u64> {
+    Histogram::new_with_bounds(1, MAX_TRACKED_MICROS, 3).expect("Histogram bounds should be valid")
+}
+
+/// Adds latency to the histogram of latencies in microseconds.
+///
+/// # Arguments
+///
+/// * `histogram` - Histogram created by `new_histogram`
+/// * `latency` - Latency of a request
+///
+pub fn record_into(histogram: &mut Histogram<u64>, latency: Duration) {
+    let micros = (latency.as_micros() as u64).clamp(1, MAX_TRACKED_MICROS);
+    histogram.saturating_record(micros);
+}
+
+/// Latency budgets of endpoints, by endpoint name.
+static BUDGETS: Lazy<Mutex<HashMap<String, Duration>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Requests of an endpoint which exceeded its latency budget, counted rather than kept,
+/// so long runs do not accumulate them.
+#[derive(Debug, Clone, Copy, Default)]
+struct OverBudget {
+    count: usize,
+    worst: Duration,
+}
+
+/// Requests which exceeded the latency budget of their endpoint, by endpoint name.
+static OVER_BUDGET: Lazy<Mutex<BTreeMap<String, OverBudget>>> =
+    Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+/// Registers latency budgets declared by the endpoints, so every recorded request is checked against them.
+///
+/// # Arguments
+///
+/// * `endpoints` - Endpoints known to the suite
+///
+pub fn set_budgets(endpoints: &EndpointRegistry) {
+    let mut budgets = BUDGETS
+        .lock()
+        .expect("Latency budgets should not be poisoned");
+    for (endpoint_name, endpoint) in endpoints.iter() {
+        if let Some(budget_ms) = endpoint.latency_budget_ms {
+            budgets.insert(endpoint_name.clone(), Duration::from_millis(budget_ms));
+        }
+    }
+}
+
+/// Records latency of a request counted in the statistics of the run.
+///
+/// # Arguments
+///
+/// * `endpoint_name` - Name of the requested endpoint
+/// * `latency` - Time from sending the request until receiving the response headers
+///
+pub fn record(endpoint_name: &str, latency: Duration) {
+    let mut latencies = LATENCIES.lock().expect("Latencies should not be poisoned");
+    let histogram = latencies
+        .entry(endpoint_name.to_owned())
+        .or_insert_with(new_histogram);
+    record_into(histogram, latency);
+    drop(latencies);
+    let budget = BUDGETS
+        .lock()
+        .expect("Latency budgets should not be poisoned")
+        .get(endpoint_name)
+        .copied();
+    if budget.map_or(false, |budget| latency > budget) {
+        let mut over_budget = OVER_BUDGET
+            .lock()
+            .expect("Requests over budget should not be poisoned");
+        let over_budget = over_budget.entry(endpoint_name.to_owned()).or_default();
+        over_budget.count += 1;
+        over_budget.worst = over_budget.worst.max(latency);
+    }
+}
+
+/// Awaits sending of the request, recording its latency when a response is received.
+/// Returns the response along with its latency.
+///
+/// # Arguments
+///
+/// * `endpoint_name` - Name of the requested endpoint
+/// * `sending` - Future sending the request
+///
+pub async fn timed<F>(
+    endpoint_name: &str,
+    sending: F,
+) -> reqwest::Result<(reqwest::Response, Duration)>
+where
+    F: Future<Output = reqwest::Result<reqwest::Response>>,
+{
+    let start = Instant::now();
+    let response = sending.await?;
+    let latency = start.elapsed();
+    record(endpoint_name, latency);
+    Ok((response, latency))
+}
+
+/// Problem of a request which exceeded the latency budget of its endpoint, if it did.
+///
+/// # Arguments
+///
+/// * `endpoint_name` - Name of the requested endpoint
+/// * `latency` - Latency of the request
+///
+pub fn budget_problem(endpoint_name: &str, latency: Duration) -> Option<String> {
+    let budget = BUDGETS
+        .lock()
+        .expect("Latency budgets should not be poisoned")
+        .get(endpoint_name)
+        .copied()?;
+    (latency > budget).then(|| {
+        format!(
+            "{} took {} ms, over its budget of {} ms",
+            endpoint_name,
+            latency.as_millis(),
+            budget.as_millis()
+        )
+    })
+}
+
+/// Latency percentiles of a single endpoint.
+#[derive(Debug, Clone)]
+pub struct EndpointLatency {
+    pub samples: usize,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    /// Histogram in the HdrHistogram V2 format, compressed and encoded in base64,
+    /// so percentiles can be recomputed or merged across runs by HdrHistogram tools
+    pub histogram: String,
+}
+
+impl EndpointLatency {
+    pub fn from_histogram(histogram: &Histogram<u64>) -> Self {
+        let percentile =
+            |percent: f64| Duration::from_micros(histogram.value_at_quantile(percent / 100.0));
+        let mut serialized = Vec::new();
+        V2DeflateSerializer::new()
+            .serialize(histogram, &mut serialized)
+            .expect("Histogram should be serializable");
+        Self {
+            samples: histogram.len() as usize,
+            p50: percentile(50.0),
+            p90: percentile(90.0),
+            p99: percentile(99.0),
+            histogram: base64::encode(serialized),
+        }
+    }
+}
+
+/// Latency statistics of the run, by endpoint name.
+#[derive(Debug, Clone, Default)]
+pub struct LatencyReport {
+    pub endpoints: BTreeMap<String, EndpointLatency>,
+}
+
+impl fmt::Display for LatencyReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Latency (p50 / p90 / p99):")?;
+        for (endpoint_name, latency) in &self.endpoints {
+            writeln!(
+                f,
+                "  {}: {} / {} / {} ms in {} requests",
+                endpoint_name,
+                latency.p50.as_millis(),
+                latency.p90.as_millis(),
+                latency.p99.as_millis(),
+                latency.samples
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Latency statistics of requests recorded so far in the run.
+pub fn latency_report() -> LatencyReport {
+    let endpoints = LATENCIES
+        .lock()
+        .expect("Latencies should not be poisoned")
+        .iter()
+        .filter(|(_, histogram)| !histogram.is_empty())
+        .map(|(endpoint_name, histogram)| {
+            (
+                endpoint_name.clone(),
+                EndpointLatency::from_histogram(histogram),
+            )
+        })
+        .collect();
+    LatencyReport { endpoints }
+}
+
+impl LatencyReport {
+    /// Writes serialized histograms of the endpoints to a json file, by endpoint name.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the written file
+    ///
+    pub fn write_histograms(&self, path: &str) {
+        let histograms: BTreeMap<&String, &String> = self
+            .endpoints
+            .iter()
+            .map(|(endpoint_name, latency)| (endpoint_name, &latency.histogram))
+            .collect();
+        let content = serde_json::to_string_pretty(&serde_json::json!({
+            "run_id": correlation::run_id(),
+            "histograms": histograms,
+        }))
+        .expect("Histograms should be serializable");
+        let written = std::path::Path::new(path)
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(path, report_encryption::seal(content.into_bytes())));
+        if let Err(err) = written {
+            eprintln!("Writing latency histograms {} failed: {}", path, err);
+        }
+    }
+}
+
+/// Requests which exceeded the latency budget of a single endpoint.
+#[derive(Debug, Clone)]
+pub struct BudgetViolations {
+    pub budget: Duration,
+    pub requests: usize,
+    pub violations: usize,
+    pub worst: Duration,
+}
+
+/// Latency budget violations of the run, by endpoint name.
+#[derive(Debug, Clone, Default)]
+pub struct BudgetReport {
+    pub endpoints: BTreeMap<String, BudgetViolations>,
+}
+
+impl fmt::Display for BudgetReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.endpoints.is_empty() {
+            return Ok(());
+        }
+        writeln!(f, "Latency budget violations:")?;
+        for (endpoint_name, violations) in &self.endpoints {
+            writeln!(
+                f,
+                "  {}: {} of {} requests exceeded {} ms, the slowest took {} ms",
+                endpoint_name,
+                violations.violations,
+                violations.requests,
+                violations.budget.as_millis(),
+                violations.worst.as_millis()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Latency budget violations recorded so far in the run.
+pub fn budget_report() -> BudgetReport {
+    let budgets = BUDGETS
+        .lock()
+        .expect("Latency budgets should not be poisoned");
+    let latencies = LATENCIES.lock().expect("Latencies should not be poisoned");
+    let endpoints = OVER_BUDGET
+        .lock()
+        .expect("Requests over budget should not be poisoned")
+        .iter()
+        .map(|(endpoint_name, over_budget)| {
+            let violations = BudgetViolations {
+                budget: budgets[endpoint_name],
+                requests: latencies
+                    .get(endpoint_name)
+                    .map_or(0, |histogram| histogram.len() as usize),
+                violations: over_budget.count,
+                worst: over_budget.worst,
+            };
+            (endpoint_name.clone(), violations)
+        })
+        .collect();
+    BudgetReport { endpoints }
+}
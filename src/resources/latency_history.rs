@@ -0,0 +1,220 @@
+use super::correlation;
+use super::latency::LatencyReport;
+use super::report_encryption;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tempfile::NamedTempFile;
+
+/// Detection of median latency regressions against the previous runs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RegressionSettings {
+    /// Regression is flagged when the median of the run exceeds the trailing median multiplied by the factor
+    pub factor: f64,
+    /// Number of previous runs the trailing median is computed from
+    pub trailing_runs: usize,
+    /// Whether regressions fail the run instead of being printed as warnings
+    pub fail: bool,
+}
+
+impl Default for RegressionSettings {
+    fn default() -> Self {
+        Self {
+            factor: 1.5,
+            trailing_runs: 7,
+            fail: false,
+        }
+    }
+}
+
+/// Tables of the results store: runs in the order they were recorded, and median latencies
+/// of every run, in milliseconds, by endpoint name.
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS runs (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        run_id TEXT NOT NULL,
+        recorded_at INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS latency_medians (
+        run INTEGER NOT NULL REFERENCES runs (id),
+        endpoint TEXT NOT NULL,
+        median_ms INTEGER NOT NULL,
+        PRIMARY KEY (run, endpoint)
+    );";
+
+/// Opens the SQLite database, creating its tables when missing.
+fn open_store(path: &Path) -> Result<Connection, String> {
+    let connection = Connection::open(path).map_err(|err| err.to_string())?;
+    connection
+        .execute_batch(SCHEMA)
+        .map_err(|err| err.to_string())?;
+    Ok(connection)
+}
+
+/// Decrypted copy of an encrypted results store, in the temporary catalogue, readable by the owner only;
+/// the copy is removed when dropped, also when the queries against it panic.
+fn working_copy(history_file: &str) -> Result<NamedTempFile, String> {
+    let content = match fs::read(history_file) {
+        Ok(content) => report_encryption::open(content).map_err(|err| err.to_string())?,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(err) => return Err(err.to_string()),
+    };
+    let mut working_file = tempfile::Builder::new()
+        .prefix("api-verify-latency-")
+        .tempfile()
+        .map_err(|err| err.to_string())?;
+    working_file
+        .write_all(&content)
+        .and_then(|_| working_file.flush())
+        .map_err(|err| err.to_string())?;
+    Ok(working_file)
+}
+
+/// Runs the queries against the results store, creating it along with its catalogue when missing.
+/// With REPORT_ENCRYPTION_KEY set the store is kept encrypted, so the queries run against a decrypted
+/// working copy, which is sealed back into the store and removed afterwards.
+fn with_store<T>(
+    history_file: &str,
+    queries: impl FnOnce(&mut Connection) -> T,
+) -> Result<T, String> {
+    if let Some(dir) = Path::new(history_file).parent() {
+        fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+    }
+    if !report_encryption::is_enabled() {
+        return Ok(queries(&mut open_store(Path::new(history_file))?));
+    }
+    let working_file = working_copy(history_file)?;
+    let result = open_store(working_file.path()).map(|mut connection| queries(&mut connection));
+    let sealed = result.is_ok().then(|| fs::read(working_file.path()));
+    drop(working_file);
+    if let Some(content) = sealed {
+        let content = content.map_err(|err| err.to_string())?;
+        fs::write(history_file, report_encryption::seal(content)).map_err(|err| err.to_string())?;
+    }
+    result
+}
+
+/// Medians of the last runs kept in the store, by endpoint name.
+fn trailing_medians(
+    connection: &Connection,
+    trailing_runs: usize,
+) -> rusqlite::Result<BTreeMap<String, Vec<u64>>> {
+    let mut statement = connection.prepare(
+        "SELECT endpoint, median_ms FROM latency_medians
+         WHERE run IN (SELECT id FROM runs ORDER BY id DESC LIMIT ?1)",
+    )?;
+    let rows = statement.query_map(params![trailing_runs as i64], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+    })?;
+    let mut medians: BTreeMap<String, Vec<u64>> = BTreeMap::new();
+    for row in rows {
+        let (endpoint_name, median_ms) = row?;
+        medians
+            .entry(endpoint_name)
+            .or_default()
+            .push(median_ms.max(0) as u64);
+    }
+    Ok(medians)
+}
+
+/// Adds medians of the run to the store, as a whole or not at all.
+fn record_run(connection: &mut Connection, report: &LatencyReport) -> rusqlite::Result<()> {
+    let recorded_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("System time should be after unix epoch")
+        .as_secs() as i64;
+    let transaction = connection.transaction()?;
+    transaction.execute(
+        "INSERT INTO runs (run_id, recorded_at) VALUES (?1, ?2)",
+        params![correlation::run_id(), recorded_at],
+    )?;
+    let run = transaction.last_insert_rowid();
+    for (endpoint_name, latency) in &report.endpoints {
+        transaction.execute(
+            "INSERT INTO latency_medians (run, endpoint, median_ms) VALUES (?1, ?2, ?3)",
+            params![run, endpoint_name, latency.p50.as_millis() as i64],
+        )?;
+    }
+    transaction.commit()
+}
+
+/// Compares median latencies of the run with the trailing medians of previous runs kept in the results store,
+/// then adds medians of the run to the store. Returns descriptions of the regressions;
+/// a store which cannot be opened is reported without failing the run.
+///
+/// # Arguments
+///
+/// * `report` - Latency statistics of the run
+/// * `settings` - Settings of the detection from the profile
+/// * `history_file` - SQLite database with medians of previous runs
+///
+pub fn check_regressions(
+    report: &LatencyReport,
+    settings: &RegressionSettings,
+    history_file: &str,
+) -> Vec<String> {
+    let stored = with_store(history_file, |connection| {
+        let trailing = trailing_medians(connection, settings.trailing_runs).unwrap_or_else(|err| {
+            eprintln!("Reading latency history {} failed: {}", history_file, err);
+            BTreeMap::new()
+        });
+        if let Err(err) = record_run(connection, report) {
+            eprintln!("Writing latency history {} failed: {}", history_file, err);
+        }
+        trailing
+    });
+    let trailing = match stored {
+        Ok(trailing) => trailing,
+        Err(err) => {
+            eprintln!("Opening latency history {} failed: {}", history_file, err);
+            return Vec::new();
+        }
+    };
+
+    let mut regressions = Vec::new();
+    for (endpoint_name, latency) in &report.endpoints {
+        let mut previous = match trailing.get(endpoint_name) {
+            Some(previous) => previous.clone(),
+            None => continue,
+        };
+        previous.sort_unstable();
+        let trailing_median = previous[(previous.len() - 1) / 2];
+        let median = latency.p50.as_millis() as u64;
+        if median as f64 > trailing_median as f64 * settings.factor {
+            regressions.push(format!(
+                "{}: median latency {} ms exceeds {} times the median of the last {} runs ({} ms)",
+                endpoint_name,
+                median,
+                settings.factor,
+                previous.len(),
+                trailing_median
+            ));
+        }
+    }
+
+    regressions
+}
+
+/// Prints the regressions as warnings, or panics with them when the settings say so.
+///
+/// # Arguments
+///
+/// * `regressions` - Descriptions of the regressions
+/// * `settings` - Settings of the detection from the profile
+///
+pub fn report_regressions(regressions: &[String], settings: &RegressionSettings) {
+    if regressions.is_empty() {
+        return;
+    }
+    if settings.fail {
+        panic!("Latency regressions:\n{}", regressions.join("\n"))
+    }
+    for regression in regressions {
+        println!("Warning: latency regression of {}", regression);
+    }
+}
@@ -0,0 +1,209 @@
+use super::baseline;
+use super::skipping;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Reason scenarios started during a blackout window are skipped with.
+pub const SKIP_REASON: &str = "maintenance window";
+
+/// Handling of scenarios started during a blackout window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BlackoutAction {
+    /// Scenarios are reported as skipped with "maintenance window"
+    Skip,
+    /// Scenarios wait for the end of the window, or are skipped when it ends later than `max_delay_minutes`
+    Delay,
+}
+
+/// Announced downtime of the provider, starting at times matching the cron expression.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BlackoutWindow {
+    /// Start of the window in UTC, as minute, hour, day of month, month and day of week, e.g. "0 2 * * 0"
+    pub cron: CronSchedule,
+    /// Length of the window
+    pub duration_minutes: i64,
+}
+
+/// Blackout windows during which scenarios are not run, so scheduled monitor runs do not raise false alarms.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MaintenanceSettings {
+    /// Windows of announced downtime; scenarios are run at any time when empty
+    pub windows: Vec<BlackoutWindow>,
+    /// Whether scenarios started during a window are skipped or delayed
+    pub action: BlackoutAction,
+    /// Longest wait for the end of a window when scenarios are delayed
+    pub max_delay_minutes: i64,
+}
+
+impl Default for MaintenanceSettings {
+    fn default() -> Self {
+        Self {
+            windows: Vec::new(),
+            action: BlackoutAction::Skip,
+            max_delay_minutes: 30,
+        }
+    }
+}
+
+/// Cron expression of five fields: minute, hour, day of month, month and day of week (0 or 7 for Sunday).
+/// Fields are "*", values, ranges "1-5" and steps "*/15" or "0-30/10", joined with commas.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct CronSchedule {
+    expression: String,
+    minutes: Vec<bool>,
+    hours: Vec<bool>,
+    days: Vec<bool>,
+    months: Vec<bool>,
+    weekdays: Vec<bool>,
+    /// Whether both day fields are restricted, so either of them matching is enough, as in cron
+    either_day: bool,
+}
+
+/// Parses a single field of a cron expression into flags of the values it matches, indexed by value.
+fn parse_field(field: &str, min: usize, max: usize) -> Result<Vec<bool>, String> {
+    let mut matched = vec![false; max + 1];
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (
+                range,
+                step.parse::<usize>()
+                    .ok()
+                    .filter(|step| *step > 0)
+                    .ok_or_else(|| format!("invalid step in {:?}", part))?,
+            ),
+            None => (part, 1),
+        };
+        let parse_value = |value: &str| {
+            value
+                .parse::<usize>()
+                .ok()
+                .filter(|value| (min..=max).contains(value))
+                .ok_or_else(|| format!("{:?} is not a value from {} to {}", value, min, max))
+        };
+        let (first, last) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((first, last)) => (parse_value(first)?, parse_value(last)?),
+            None if step > 1 => (parse_value(range)?, max),
+            None => (parse_value(range)?, parse_value(range)?),
+        };
+        for value in (first..=last).step_by(step) {
+            matched[value] = true;
+        }
+    }
+    Ok(matched)
+}
+
+impl TryFrom<String> for CronSchedule {
+    type Error = String;
+
+    fn try_from(expression: String) -> Result<Self, Self::Error> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!(
+                "cron expression {:?} should have 5 fields: minute, hour, day of month, month and day of week",
+                expression
+            ));
+        }
+        let mut weekdays = parse_field(fields[4], 0, 7)?;
+        weekdays[0] |= weekdays[7];
+        Ok(Self {
+            minutes: parse_field(fields[0], 0, 59)?,
+            hours: parse_field(fields[1], 0, 23)?,
+            days: parse_field(fields[2], 1, 31)?,
+            months: parse_field(fields[3], 1, 12)?,
+            weekdays,
+            either_day: fields[2] != "*" && fields[4] != "*",
+            expression,
+        })
+    }
+}
+
+impl From<CronSchedule> for String {
+    fn from(schedule: CronSchedule) -> Self {
+        schedule.expression
+    }
+}
+
+impl CronSchedule {
+    /// Whether the expression matches the minute.
+    ///
+    /// # Arguments
+    ///
+    /// * `minute` - Number of minutes since the unix epoch
+    ///
+    pub fn matches(&self, minute: i64) -> bool {
+        let days = minute.div_euclid(1440);
+        let minute_of_day = minute.rem_euclid(1440) as usize;
+        let (_, month, day) = baseline::civil_date(days);
+        // The unix epoch was a Thursday
+        let weekday = (days + 4).rem_euclid(7) as usize;
+        let day_matches = if self.either_day {
+            self.days[day as usize] || self.weekdays[weekday]
+        } else {
+            self.days[day as usize] && self.weekdays[weekday]
+        };
+        self.minutes[minute_of_day % 60]
+            && self.hours[minute_of_day / 60]
+            && self.months[month as usize]
+            && day_matches
+    }
+}
+
+/// End, in minutes since the unix epoch, of the blackout window the minute falls into, if any;
+/// the latest end is returned when windows overlap.
+///
+/// # Arguments
+///
+/// * `settings` - Blackout windows of the profile
+/// * `minute` - Number of minutes since the unix epoch
+///
+pub fn window_end(settings: &MaintenanceSettings, minute: i64) -> Option<i64> {
+    settings
+        .windows
+        .iter()
+        .filter_map(|window| {
+            (0..window.duration_minutes)
+                .map(|elapsed| minute - elapsed)
+                .find(|start| window.cron.matches(*start))
+                .map(|start| start + window.duration_minutes)
+        })
+        .max()
+}
+
+fn now_seconds() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("System time should be after unix epoch")
+        .as_secs() as i64
+}
+
+/// Delays the scenario until the blackout window in progress ends, or skips it with "maintenance window",
+/// according to the settings; scenarios started outside of windows are not affected.
+///
+/// # Arguments
+///
+/// * `settings` - Blackout windows of the profile
+/// * `scenario_key` - Key of the scenario about to be run
+///
+pub async fn guard(settings: &MaintenanceSettings, scenario_key: &str) {
+    loop {
+        let now = now_seconds();
+        let end = match window_end(settings, now.div_euclid(60)) {
+            Some(end) => end * 60,
+            None => return,
+        };
+        if settings.action == BlackoutAction::Skip || end - now > settings.max_delay_minutes * 60 {
+            skipping::skip_scenario(scenario_key, SKIP_REASON)
+        }
+        println!(
+            "Maintenance window in progress, {} waits {} seconds",
+            scenario_key,
+            end - now
+        );
+        tokio::time::sleep(Duration::from_secs((end - now) as u64)).await;
+    }
+}
@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// How a volatile value is masked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MaskStrategy {
+    /// Removes the field or array element
+    Drop,
+    /// Replaces the value with zero value of its type, e.g. 0, "" or false
+    Zero,
+    /// Replaces the value with a short digest, so equal values stay equal
+    Hash,
+}
+
+/// Masking of values matched by a path, e.g. `$.result.*.opentm`.
+/// Paths consist of object keys and array indexes separated by dots or in brackets;
+/// `*` matches every key or index.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MaskRule {
+    pub path: String,
+    pub strategy: MaskStrategy,
+}
+
+/// Splits the path into segments, e.g. `$.result[0].*` into ["result", "0", "*"].
+pub fn segments_of(path: &str) -> Vec<String> {
+    path.trim_start_matches('$')
+        .replace('[', ".")
+        .replace(']', "")
+        .split('.')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| segment.trim_matches(|c| c == '\'' || c == '"').to_owned())
+        .collect()
+}
+
+/// Applies the rules to the json value, in the order they are given.
+///
+/// # Arguments
+///
+/// * `value` - Json value, e.g. response body, which is masked in place
+/// * `rules` - Rules of masking
+///
+pub fn apply(value: &mut Value, rules: &[MaskRule]) {
+    for rule in rules {
+        mask_at(value, &segments_of(&rule.path), rule.strategy);
+    }
+}
+
+fn mask_at(value: &mut Value, segments: &[String], strategy: MaskStrategy) {
+    let (segment, rest) = match segments.split_first() {
+        Some(split) => split,
+        None => {
+            mask_value(value, strategy);
+            return;
+        }
+    };
+    let is_last = rest.is_empty();
+    match value {
+        Value::Object(fields) => {
+            if is_last && strategy == MaskStrategy::Drop {
+                if segment == "*" {
+                    fields.clear();
+                } else {
+                    fields.remove(segment);
+                }
+                return;
+            }
+            for (key, field) in fields.iter_mut() {
+                if segment == "*" || segment == key {
+                    mask_at(field, rest, strategy);
+                }
+            }
+        }
+        Value::Array(elements) => {
+            if is_last && strategy == MaskStrategy::Drop {
+                match segment.parse::<usize>() {
+                    Ok(index) if index < elements.len() => {
+                        elements.remove(index);
+                    }
+                    _ if segment == "*" => elements.clear(),
+                    _ => {}
+                }
+                return;
+            }
+            for (index, element) in elements.iter_mut().enumerate() {
+                if segment == "*" || segment.parse::<usize>().ok() == Some(index) {
+                    mask_at(element, rest, strategy);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn mask_value(value: &mut Value, strategy: MaskStrategy) {
+    *value = match strategy {
+        MaskStrategy::Drop => Value::Null,
+        MaskStrategy::Zero => match value {
+            Value::Number(_) => Value::from(0),
+            Value::String(text) if text.parse::<f64>().is_ok() => Value::from("0"),
+            Value::String(_) => Value::from(""),
+            Value::Bool(_) => Value::from(false),
+            Value::Array(_) => Value::Array(Vec::new()),
+            Value::Object(_) => Value::Object(serde_json::Map::new()),
+            Value::Null => Value::Null,
+        },
+        MaskStrategy::Hash => {
+            let digest = hex::encode(Sha256::digest(value.to_string().as_bytes()));
+            Value::from(format!("sha256:{}", &digest[..16]))
+        }
+    };
+}
@@ -0,0 +1,82 @@
+//! Building blocks of the steps: configuration, requests, validation and reporting.
+pub mod access;
+pub mod accounts;
+pub mod api_errors;
+pub mod attachment;
+pub mod balance_guard;
+pub mod baseline;
+pub mod budget;
+pub mod cancellation;
+pub mod capture;
+pub mod conditional;
+pub mod config;
+pub mod console;
+pub mod correlation;
+pub mod cors;
+pub mod csv_validation;
+pub mod cursor;
+pub mod deprecation;
+pub mod drift;
+pub mod dual_stack;
+pub mod encoding;
+pub mod enum_coverage;
+pub mod expectations;
+pub mod feed;
+pub mod fixtures;
+pub mod flakiness;
+pub mod generators;
+pub mod http_client;
+pub mod http_file;
+pub mod integrity;
+pub mod interpolation;
+pub mod key_permissions;
+pub mod latency;
+pub mod latency_history;
+pub mod maintenance;
+pub mod masking;
+pub mod monitor;
+pub mod naming;
+pub mod ndjson;
+pub mod number_format;
+pub mod ohlc;
+pub mod openapi;
+pub mod order_book;
+pub mod order_consistency;
+pub mod orders;
+pub mod parameters;
+pub mod permissions;
+pub mod rate_limit;
+pub mod reachability;
+pub mod reconciliation;
+pub mod redaction;
+pub mod reference;
+pub mod report_encryption;
+pub mod request_handler;
+pub mod response_cache;
+pub mod results;
+pub mod run_options;
+pub mod scheduling;
+pub mod schema;
+pub mod schema_diff;
+pub mod scripting;
+pub mod scrubbing;
+pub mod security_headers;
+pub mod seed;
+pub mod severity;
+pub mod sharding;
+pub mod signing;
+pub mod skipping;
+pub mod snapshot;
+pub mod status_page;
+pub mod streaming;
+pub mod tags;
+pub mod templating;
+pub mod test_plan;
+pub mod throughput;
+pub mod ticker;
+pub mod tls;
+pub mod translation;
+pub mod validators;
+pub mod verdict;
+pub mod version_negotiation;
+pub mod wasm_plugins;
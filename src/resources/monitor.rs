@@ -0,0 +1,56 @@
+use super::config::{Auth, EndpointRegistry, Profile};
+use super::{access, http_client, rate_limit};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::time::Duration;
+
+/// Settings used when the suite is run repeatedly as a synthetic monitor.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MonitorSettings {
+    /// Requests sent to every public endpoint before the suite,
+    /// so cold DNS and TLS caches do not skew latency statistics
+    pub warm_up_requests: usize,
+    /// Delay after the suite, before the next one is started
+    pub cool_down_seconds: u64,
+}
+
+/// Sends warm-up requests to the public endpoints of the registry.
+/// Their latency is not recorded and their failures are ignored,
+/// but they are throttled like any other request.
+///
+/// # Arguments
+///
+/// * `profile` - Settings of the environment the suite is run against
+/// * `endpoints` - Endpoints known to the suite
+///
+pub async fn warm_up(profile: &Profile, endpoints: &EndpointRegistry) {
+    if profile.monitor.warm_up_requests == 0 {
+        return;
+    }
+    let api_link = env::var("API_LINK").expect("Missing secret value: API_LINK");
+    let (client, _) = http_client::build_client(profile);
+    for (endpoint_name, endpoint) in endpoints.iter() {
+        if endpoint.auth != Auth::Public || access::check(profile, endpoint_name, endpoint).is_err()
+        {
+            continue;
+        }
+        let link = [api_link.as_str(), endpoint.path.as_str()].concat();
+        for _ in 0..profile.monitor.warm_up_requests {
+            rate_limit::acquire(&profile.rate_limit, endpoint_name, endpoint.weight).await;
+            let _ = client.request(endpoint.method(), &link).send().await;
+        }
+    }
+}
+
+/// Waits for the cool-down delay of the profile.
+///
+/// # Arguments
+///
+/// * `profile` - Settings of the environment the suite is run against
+///
+pub async fn cool_down(profile: &Profile) {
+    if profile.monitor.cool_down_seconds > 0 {
+        tokio::time::sleep(Duration::from_secs(profile.monitor.cool_down_seconds)).await;
+    }
+}
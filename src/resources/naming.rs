@@ -0,0 +1,37 @@
+/// Converts endpoint name, as present in its path (e.g. "OpenPositions"),
+/// into snake case (e.g. "open_positions").
+///
+/// # Arguments
+///
+/// * `endpoint_name` - Name of the endpoint in upper camel case
+///
+pub fn to_snake_case(endpoint_name: &str) -> String {
+    let mut snake_case = String::new();
+    for (index, character) in endpoint_name.chars().enumerate() {
+        if character.is_uppercase() && index > 0 {
+            snake_case.push('_');
+        }
+        snake_case.extend(character.to_lowercase());
+    }
+    snake_case
+}
+
+/// Name of the file in "./schemas" with rules for csv responses of the endpoint.
+///
+/// # Arguments
+///
+/// * `endpoint_name` - Name of the endpoint in upper camel case
+///
+pub fn csv_rules_file(endpoint_name: &str) -> String {
+    format!("{}_csv_rules.json", to_snake_case(endpoint_name))
+}
+
+/// Name of the file in "./schemas" describing the endpoint response.
+///
+/// # Arguments
+///
+/// * `endpoint_name` - Name of the endpoint in upper camel case
+///
+pub fn schema_file(endpoint_name: &str) -> String {
+    format!("{}_schema.json", to_snake_case(endpoint_name))
+}
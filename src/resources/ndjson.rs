@@ -0,0 +1,90 @@
+use super::drift;
+use super::schema;
+use super::streaming::{self, StreamError, StreamSummary};
+use itertools::Itertools;
+
+/// Result of validating every line of newline-delimited json response.
+#[derive(Debug)]
+pub struct NdjsonReport {
+    /// Number of non-empty lines
+    pub line_count: usize,
+    /// Numbers of lines which failed validation, along with the reason
+    pub failures: Vec<(usize, String)>,
+    pub summary: StreamSummary,
+}
+
+/// Streams the response and validates each non-empty line against the schema,
+/// so the whole body never has to be parsed as a single json value.
+///
+/// # Arguments
+///
+/// * `response` - Response whose body has not been read yet
+/// * `schema_file` - Name of the file in "./schemas" describing a single line
+/// * `max_bytes` - Maximal accepted size of the body
+///
+pub async fn validate_ndjson(
+    response: reqwest::Response,
+    schema_file: &str,
+    max_bytes: u64,
+) -> Result<NdjsonReport, StreamError> {
+    let schema = schema::load_schema(schema_file);
+    let compiled = schema::compile(&schema);
+    let mut line_count = 0;
+    let mut failures = Vec::new();
+    let summary = streaming::stream_lines(response, max_bytes, |line_number, line| {
+        if line.iter().all(u8::is_ascii_whitespace) {
+            return;
+        }
+        line_count += 1;
+        match serde_json::from_slice::<serde_json::Value>(line) {
+            Ok(json_line) => {
+                drift::observe(schema_file, &schema, &json_line);
+                if let Some(errors) = schema::validation_errors(&compiled, &json_line) {
+                    failures.push((line_number, errors));
+                }
+            }
+            Err(err) => failures.push((line_number, format!("invalid json: {}", err))),
+        }
+    })
+    .await?;
+    Ok(NdjsonReport {
+        line_count,
+        failures,
+        summary,
+    })
+}
+
+impl NdjsonReport {
+    /// Panics with numbers of all lines which failed validation.
+    pub fn verify_no_failures(&self) {
+        if !self.failures.is_empty() {
+            let joined_failures = self
+                .failures
+                .iter()
+                .map(|(line_number, errors)| format!("line {}: {}", line_number, errors))
+                .join("\n");
+            panic!("The following lines are incorrect:\n{}", joined_failures)
+        }
+    }
+
+    /// Panics if the number of non-empty lines does not meet the expectation.
+    ///
+    /// # Arguments
+    ///
+    /// * `comparison` - One of "at least", "at most" or "exactly"
+    /// * `expected_count` - Number of lines the comparison refers to
+    ///
+    pub fn verify_line_count(&self, comparison: &str, expected_count: usize) {
+        let is_met = match comparison {
+            "at least" => self.line_count >= expected_count,
+            "at most" => self.line_count <= expected_count,
+            "exactly" => self.line_count == expected_count,
+            _ => unreachable!(),
+        };
+        assert!(
+            is_met,
+            "Expected {} {} lines, got {}",
+            comparison, expected_count, self.line_count
+        );
+    }
+}
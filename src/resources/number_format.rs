@@ -0,0 +1,111 @@
+use jsonschema::CompilationOptions;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::Value;
+
+/// Decimal number as sent by the API, e.g. "-0.0001", without exponent or separators other than the dot.
+static PLAIN_DECIMAL: Lazy<Regex> = Lazy::new(|| Regex::new(r"^-?\d+(\.\d+)?$").unwrap());
+
+/// Value which would be read as a number by a lenient, locale-aware parser, e.g. "1.234,5" or "1e-4".
+static NUMBER_LIKE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[-+]?[\d.,' \u{a0}]*\d[\d.,' \u{a0}]*([eE][-+]?\d+)?$").unwrap());
+
+/// Whether the value is a decimal number without locale artifacts.
+///
+/// # Arguments
+///
+/// * `value` - String value of a field
+///
+pub fn is_plain_decimal(value: &str) -> bool {
+    PLAIN_DECIMAL.is_match(value)
+}
+
+/// Whether the value is an integer without locale artifacts.
+///
+/// # Arguments
+///
+/// * `value` - String value of a field
+///
+pub fn is_plain_integer(value: &str) -> bool {
+    is_plain_decimal(value) && !value.contains('.')
+}
+
+/// Describes the locale artifact found in a number-like value, if any.
+///
+/// # Arguments
+///
+/// * `value` - String value of a field
+///
+pub fn locale_artifact(value: &str) -> Option<&'static str> {
+    if !NUMBER_LIKE.is_match(value) || is_plain_decimal(value) {
+        return None;
+    }
+    let artifact = if value.contains(|c: char| c == 'e' || c == 'E') {
+        "exponent"
+    } else if value.matches(',').count() == 1
+        && !value.contains(|c: char| !c.is_ascii_digit() && c != ',' && c != '-')
+    {
+        "comma as decimal separator"
+    } else if value.contains(|c: char| c == ',' || c == ' ' || c == '\'' || c == '\u{a0}')
+        || value.matches('.').count() > 1
+    {
+        "thousands separator"
+    } else if value.starts_with('+') {
+        "explicit plus sign"
+    } else {
+        "malformed number"
+    };
+    Some(artifact)
+}
+
+/// Registers validators as formats of the json schema, so schemas may declare
+/// `"format": "decimal"` or `"format": "integer-string"` for numeric string fields.
+///
+/// # Arguments
+///
+/// * `options` - Options the schema is compiled with
+///
+pub fn register_formats(options: &mut CompilationOptions) {
+    options
+        .with_format("decimal", is_plain_decimal)
+        .with_format("integer-string", is_plain_integer);
+}
+
+/// Finds locale artifacts in all number-like string values of the response,
+/// returning their paths along with the artifact, e.g. "result.XXBTZUSD.ordermin: comma as decimal separator".
+///
+/// # Arguments
+///
+/// * `json_response` - Response body parsed to json
+///
+pub fn locale_violations(json_response: &Value) -> Vec<String> {
+    let mut violations = Vec::new();
+    collect_violations(json_response, "", &mut violations);
+    violations
+}
+
+fn collect_violations(value: &Value, path: &str, violations: &mut Vec<String>) {
+    match value {
+        Value::String(text) => {
+            if let Some(artifact) = locale_artifact(text) {
+                violations.push(format!("{}: {} in {:?}", path, artifact, text));
+            }
+        }
+        Value::Object(fields) => {
+            for (key, field) in fields {
+                let field_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                collect_violations(field, &field_path, violations);
+            }
+        }
+        Value::Array(elements) => {
+            for (index, element) in elements.iter().enumerate() {
+                collect_violations(element, &format!("{}[{}]", path, index), violations);
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,136 @@
+/// Interval of candles returned when the request does not set one, in minutes.
+pub const DEFAULT_INTERVAL_MINUTES: i64 = 1;
+
+/// Single candle, as listed by the OHLC endpoint:
+/// `[time, open, high, low, close, vwap, volume, count]` with decimals sent as strings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candle {
+    pub time: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+}
+
+fn decimal(value: &serde_json::Value) -> Option<f64> {
+    match value {
+        serde_json::Value::String(value) => value.parse().ok(),
+        value => value.as_f64(),
+    }
+}
+
+impl Candle {
+    /// Reads the candle from its array, or None when the array is malformed.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Array describing the candle
+    ///
+    pub fn from_json(value: &serde_json::Value) -> Option<Self> {
+        let fields = value.as_array()?;
+        Some(Self {
+            time: fields.get(0)?.as_i64()?,
+            open: decimal(fields.get(1)?)?,
+            high: decimal(fields.get(2)?)?,
+            low: decimal(fields.get(3)?)?,
+            close: decimal(fields.get(4)?)?,
+            volume: decimal(fields.get(6)?)?,
+        })
+    }
+}
+
+/// Interval of candles set by the "interval" parameter of the request, in minutes.
+///
+/// # Arguments
+///
+/// * `api_link` - Link the candles were requested from, along with its query
+///
+pub fn interval_of(api_link: &str) -> i64 {
+    url::Url::parse(api_link)
+        .ok()
+        .and_then(|url| {
+            url.query_pairs()
+                .find(|(name, _)| name == "interval")
+                .and_then(|(_, interval)| interval.parse().ok())
+        })
+        .unwrap_or(DEFAULT_INTERVAL_MINUTES)
+}
+
+/// Number of pairs the OHLC result lists candles of, not counting the "last" timestamp.
+///
+/// # Arguments
+///
+/// * `result` - Result of the OHLC endpoint
+///
+pub fn series_count(result: &serde_json::Value) -> usize {
+    result
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(_, candles)| candles.is_array())
+        .count()
+}
+
+/// Describes candles which contradict each other or themselves: candles out of time order,
+/// gaps longer than the interval, prices outside of the range between low and high, and negative volumes.
+///
+/// # Arguments
+///
+/// * `result` - Result of the OHLC endpoint, with candles listed by pair and the "last" timestamp
+/// * `interval_minutes` - Interval of the requested candles
+///
+pub fn inconsistencies(result: &serde_json::Value, interval_minutes: i64) -> Vec<String> {
+    let interval_seconds = interval_minutes * 60;
+    let mut inconsistencies = Vec::new();
+    let series = result
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter_map(|(pair, candles)| Some((pair, candles.as_array()?)));
+    for (pair, candles) in series {
+        let mut previous_time: Option<i64> = None;
+        for (index, value) in candles.iter().enumerate() {
+            let candle = match Candle::from_json(value) {
+                Some(candle) => candle,
+                None => {
+                    inconsistencies
+                        .push(format!("{} candle {} is malformed: {}", pair, index, value));
+                    continue;
+                }
+            };
+            if let Some(previous_time) = previous_time {
+                if candle.time <= previous_time {
+                    inconsistencies.push(format!(
+                        "{} candle at {} follows the one at {}",
+                        pair, candle.time, previous_time
+                    ));
+                } else if candle.time - previous_time > interval_seconds {
+                    inconsistencies.push(format!(
+                        "{} has a gap of {} seconds before the candle at {}, the interval is {} seconds",
+                        pair,
+                        candle.time - previous_time,
+                        candle.time,
+                        interval_seconds
+                    ));
+                }
+            }
+            previous_time = Some(candle.time);
+            let body_high = candle.open.max(candle.close);
+            let body_low = candle.open.min(candle.close);
+            if candle.high < body_high || candle.low > body_low {
+                inconsistencies.push(format!(
+                    "{} candle at {} has open {} and close {} outside of low {} and high {}",
+                    pair, candle.time, candle.open, candle.close, candle.low, candle.high
+                ));
+            }
+            if candle.volume < 0.0 {
+                inconsistencies.push(format!(
+                    "{} candle at {} has negative volume {}",
+                    pair, candle.time, candle.volume
+                ));
+            }
+        }
+    }
+    inconsistencies
+}
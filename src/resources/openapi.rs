@@ -0,0 +1,575 @@
+use super::schema;
+use once_cell::sync::Lazy;
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::sync::{Arc, Mutex};
+
+/// OpenAPI mode of the suite: with a spec set, responses are also checked against the operations it describes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OpenApiSettings {
+    /// OpenAPI 3 spec of the API, in json or yaml, e.g. "openapi.yaml"
+    pub spec: Option<String>,
+    /// Whether requests are checked against the operation of the spec before they are sent
+    pub validate_requests: bool,
+    /// Whether headers sent by the server but not declared by the spec are reported
+    pub report_undeclared_headers: bool,
+    /// Headers never reported as undeclared, besides standard ones such as Date or Content-Length
+    pub ignored_headers: Vec<String>,
+}
+
+impl Default for OpenApiSettings {
+    fn default() -> Self {
+        Self {
+            spec: None,
+            validate_requests: true,
+            report_undeclared_headers: true,
+            ignored_headers: Vec::new(),
+        }
+    }
+}
+
+/// Headers of the transport or of caching, which specs do not declare per operation.
+const STANDARD_HEADERS: &[&str] = &[
+    "accept-ranges",
+    "age",
+    "alt-svc",
+    "cache-control",
+    "connection",
+    "content-encoding",
+    "content-length",
+    "content-security-policy",
+    "content-type",
+    "date",
+    "etag",
+    "expires",
+    "keep-alive",
+    "last-modified",
+    "pragma",
+    "referrer-policy",
+    "server",
+    "set-cookie",
+    "strict-transport-security",
+    "transfer-encoding",
+    "vary",
+    "via",
+    "x-content-type-options",
+    "x-frame-options",
+];
+
+/// Depth at which references of recursive schemas are no longer inlined, leaving any value allowed.
+const MAX_REFERENCE_DEPTH: usize = 16;
+
+/// Specs read during the run, by file.
+static SPECS: Lazy<Mutex<HashMap<String, Arc<Value>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Parses the spec, written either as json or as yaml.
+///
+/// # Arguments
+///
+/// * `content` - Content of an OpenAPI 3 spec
+///
+pub fn parse_spec(content: &str) -> Result<Value, serde_yaml::Error> {
+    serde_yaml::from_str(content)
+}
+
+/// Spec of the file, read once per run; panics when it cannot be read.
+///
+/// # Arguments
+///
+/// * `spec_file` - OpenAPI 3 spec, in json or yaml
+///
+pub fn load_spec(spec_file: &str) -> Arc<Value> {
+    let mut specs = SPECS.lock().expect("OpenAPI specs should not be poisoned");
+    specs
+        .entry(spec_file.to_owned())
+        .or_insert_with(|| {
+            let content = fs::read_to_string(spec_file)
+                .unwrap_or_else(|err| panic!("Cannot read OpenAPI spec {}: {}", spec_file, err));
+            let spec = parse_spec(&content)
+                .unwrap_or_else(|err| panic!("{} is not a valid OpenAPI spec: {}", spec_file, err));
+            Arc::new(spec)
+        })
+        .clone()
+}
+
+/// Replaces local references, e.g. `#/components/schemas/Pair`, with the schemas they point to,
+/// and `nullable: true` of OpenAPI 3.0 with "null" among the allowed types, making a json schema of the spec schema.
+///
+/// # Arguments
+///
+/// * `schema` - Schema within the spec
+/// * `spec` - The whole spec
+///
+pub fn inline_references(schema: &Value, spec: &Value) -> Value {
+    inline_references_within(schema, spec, 0)
+}
+
+fn inline_references_within(schema: &Value, spec: &Value, depth: usize) -> Value {
+    match schema {
+        Value::Object(fields) => {
+            if fields.contains_key("$ref") {
+                if depth >= MAX_REFERENCE_DEPTH {
+                    return Value::Object(Default::default());
+                }
+                return match referenced(schema, spec) {
+                    Some(target) => inline_references_within(target, spec, depth + 1),
+                    None => Value::Object(Default::default()),
+                };
+            }
+            let mut inlined: serde_json::Map<String, Value> = fields
+                .iter()
+                .filter(|(key, _)| key.as_str() != "nullable")
+                .map(|(key, value)| (key.clone(), inline_references_within(value, spec, depth)))
+                .collect();
+            if fields.get("nullable") == Some(&Value::Bool(true)) {
+                if let Some(Value::String(schema_type)) = inlined.get("type").cloned() {
+                    inlined.insert(
+                        "type".to_owned(),
+                        Value::Array(vec![Value::String(schema_type), Value::from("null")]),
+                    );
+                }
+            }
+            Value::Object(inlined)
+        }
+        Value::Array(elements) => Value::Array(
+            elements
+                .iter()
+                .map(|element| inline_references_within(element, spec, depth))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Target of a local `$ref` of the object.
+fn referenced<'a>(object: &Value, spec: &'a Value) -> Option<&'a Value> {
+    object
+        .get("$ref")?
+        .as_str()?
+        .strip_prefix('#')
+        .and_then(|pointer| spec.pointer(pointer))
+}
+
+/// The object itself, or the object it refers to, e.g. a response of `#/components/responses`.
+fn resolved<'a>(object: &'a Value, spec: &'a Value) -> &'a Value {
+    let mut object = object;
+    for _ in 0..MAX_REFERENCE_DEPTH {
+        match referenced(object, spec) {
+            Some(target) => object = target,
+            None => break,
+        }
+    }
+    object
+}
+
+/// Base path of the first server of the spec, e.g. "/0" of "https://api.example.com/0".
+pub fn base_path_of(spec: &Value) -> String {
+    let server_url = spec
+        .pointer("/servers/0/url")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let path = match url::Url::parse(server_url) {
+        Ok(url) => url.path().to_owned(),
+        Err(_) => server_url.to_owned(),
+    };
+    path.trim_end_matches('/').to_owned()
+}
+
+fn matches_template(template: &str, path: &str) -> bool {
+    let template_segments: Vec<&str> = template.trim_matches('/').split('/').collect();
+    let path_segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    template_segments.len() == path_segments.len()
+        && template_segments
+            .iter()
+            .zip(&path_segments)
+            .all(|(template_segment, path_segment)| {
+                template_segment == path_segment
+                    || (template_segment.starts_with('{')
+                        && template_segment.ends_with('}')
+                        && !path_segment.is_empty())
+            })
+}
+
+/// Operation of the spec along with the path item and the path template it is described under.
+struct MatchedOperation<'a> {
+    template: &'a str,
+    path_item: &'a Value,
+    operation: &'a Value,
+}
+
+fn matched_operation<'a>(
+    spec: &'a Value,
+    method: &reqwest::Method,
+    path: &str,
+) -> Option<MatchedOperation<'a>> {
+    let base_path = base_path_of(spec);
+    let path = path.strip_prefix(base_path.as_str()).unwrap_or(path);
+    let method = method.as_str().to_lowercase();
+    let paths = spec.get("paths")?.as_object()?;
+    // Literal paths take precedence over templated ones, e.g. "/orders/open" over "/orders/{id}"
+    let mut candidates: Vec<(&String, &Value)> = paths
+        .iter()
+        .filter(|(template, _)| matches_template(template, path))
+        .collect();
+    candidates.sort_by_key(|(template, _)| template.matches('{').count());
+    candidates.into_iter().find_map(|(template, path_item)| {
+        let path_item = resolved(path_item, spec);
+        Some(MatchedOperation {
+            template,
+            path_item,
+            operation: path_item.get(&method)?,
+        })
+    })
+}
+
+/// Operation of the spec serving the request, matched by method and path template, e.g. `/orders/{id}`;
+/// the base path of the server is stripped from the request path when present.
+///
+/// # Arguments
+///
+/// * `spec` - OpenAPI 3 spec
+/// * `method` - Method of the request
+/// * `path` - Path of the request, e.g. "/0/public/Time"
+///
+pub fn operation_of<'a>(
+    spec: &'a Value,
+    method: &reqwest::Method,
+    path: &str,
+) -> Option<&'a Value> {
+    matched_operation(spec, method, path).map(|matched| matched.operation)
+}
+
+/// Response of the operation documented for the status, falling back to its range, e.g. "2XX", and "default".
+fn response_of<'a>(operation: &'a Value, spec: &'a Value, status: u16) -> Option<&'a Value> {
+    let responses = operation.get("responses")?;
+    let range = format!("{}XX", status / 100);
+    responses
+        .get(status.to_string())
+        .or_else(|| responses.get(&range))
+        .or_else(|| responses.get(range.to_lowercase()))
+        .or_else(|| responses.get("default"))
+        .map(|response| resolved(response, spec))
+}
+
+/// Value of the header as the json value its schema describes, for the simple style of serialization.
+fn typed_value(text: &str, schema: &Value) -> Value {
+    let schema_type = schema
+        .get("type")
+        .and_then(|schema_type| match schema_type {
+            Value::Array(types) => types.iter().find_map(Value::as_str),
+            schema_type => schema_type.as_str(),
+        });
+    match schema_type {
+        Some("integer") => text.parse::<i64>().map_or(Value::from(text), Value::from),
+        Some("number") => text.parse::<f64>().map_or(Value::from(text), Value::from),
+        Some("boolean") => text.parse::<bool>().map_or(Value::from(text), Value::from),
+        Some("array") => Value::Array(
+            text.split(',')
+                .map(|element| typed_value(element.trim(), &schema["items"]))
+                .collect(),
+        ),
+        _ => Value::from(text),
+    }
+}
+
+/// Media type of the Content-Type header, without parameters such as charset.
+fn media_type_of(content_type: &str) -> String {
+    content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase()
+}
+
+fn media_type_matches(declared: &str, actual: &str) -> bool {
+    let declared = media_type_of(declared);
+    match declared.split_once('/') {
+        Some(("*", "*")) => true,
+        Some((declared_type, "*")) => actual
+            .split_once('/')
+            .map_or(false, |(actual_type, _)| actual_type == declared_type),
+        _ => declared == actual,
+    }
+}
+
+/// Describes how headers and the content type of the response differ from the operation of the spec:
+/// required headers missing, headers not matching their schemas or formats, content types not declared
+/// for the status and, unless disabled, headers the spec does not declare.
+///
+/// # Arguments
+///
+/// * `spec` - OpenAPI 3 spec
+/// * `method` - Method of the request
+/// * `path` - Path of the request
+/// * `status` - Status of the response
+/// * `headers` - Headers of the response
+/// * `settings` - OpenAPI settings of the profile
+///
+pub fn header_problems(
+    spec: &Value,
+    method: &reqwest::Method,
+    path: &str,
+    status: u16,
+    headers: &HeaderMap,
+    settings: &OpenApiSettings,
+) -> Vec<String> {
+    let operation = match operation_of(spec, method, path) {
+        Some(operation) => operation,
+        None => return vec![format!("{} {} is not described by the spec", method, path)],
+    };
+    let response = match response_of(operation, spec, status) {
+        Some(response) => response,
+        None => {
+            return vec![format!(
+                "Status {} is not declared by the operation",
+                status
+            )]
+        }
+    };
+    let mut problems = Vec::new();
+
+    let declared_headers = response
+        .get("headers")
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_default();
+    for (name, header) in &declared_headers {
+        // Content-Type is described by the content of the response
+        if name.eq_ignore_ascii_case("content-type") {
+            continue;
+        }
+        let header = resolved(header, spec);
+        let value = match headers.get(name.as_str()) {
+            Some(value) => value,
+            None => {
+                if header.get("required") == Some(&Value::Bool(true)) {
+                    problems.push(format!("Required header {} is missing", name));
+                }
+                continue;
+            }
+        };
+        let value = match value.to_str() {
+            Ok(value) => value.trim(),
+            Err(_) => {
+                problems.push(format!("Header {} is not visible ASCII", name));
+                continue;
+            }
+        };
+        if let Some(header_schema) = header.get("schema") {
+            let header_schema = inline_references(header_schema, spec);
+            let compiled = schema::compile(&header_schema);
+            if let Some(errors) =
+                schema::validation_errors(&compiled, &typed_value(value, &header_schema))
+            {
+                problems.push(format!(
+                    "Header {} \"{}\" does not match its schema: {}",
+                    name, value, errors
+                ));
+            }
+        }
+    }
+
+    let declared_types: Vec<&String> = response
+        .get("content")
+        .and_then(Value::as_object)
+        .map(|content| content.keys().collect())
+        .unwrap_or_default();
+    if !declared_types.is_empty() {
+        let declared_list = declared_types
+            .iter()
+            .map(|declared| declared.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        match headers
+            .get("content-type")
+            .and_then(|value| value.to_str().ok())
+        {
+            None => problems.push(format!(
+                "Content-Type is missing, the spec declares {}",
+                declared_list
+            )),
+            Some(content_type) => {
+                let actual = media_type_of(content_type);
+                if !declared_types
+                    .iter()
+                    .any(|declared| media_type_matches(declared, &actual))
+                {
+                    problems.push(format!(
+                        "Content-Type {} is not declared, the spec declares {}",
+                        actual, declared_list
+                    ));
+                }
+            }
+        }
+    }
+
+    if settings.report_undeclared_headers {
+        let undeclared: Vec<&str> = headers
+            .keys()
+            .map(|name| name.as_str())
+            .filter(|name| {
+                !STANDARD_HEADERS.contains(name)
+                    && !name.starts_with("access-control-")
+                    && !declared_headers
+                        .keys()
+                        .any(|declared| declared.eq_ignore_ascii_case(name))
+                    && !settings
+                        .ignored_headers
+                        .iter()
+                        .any(|ignored| ignored.eq_ignore_ascii_case(name))
+            })
+            .collect();
+        if !undeclared.is_empty() {
+            problems.push(format!(
+                "Headers not declared by the spec: {}",
+                undeclared.join(", ")
+            ));
+        }
+    }
+    problems
+}
+
+/// Values of path parameters of the request, by the names of the template, e.g. "id" of `/orders/{id}`.
+fn path_parameters<'a>(template: &str, path: &'a str) -> HashMap<String, &'a str> {
+    template
+        .trim_matches('/')
+        .split('/')
+        .zip(path.trim_matches('/').split('/'))
+        .filter_map(|(template_segment, path_segment)| {
+            let name = template_segment.strip_prefix('{')?.strip_suffix('}')?;
+            Some((name.to_owned(), path_segment))
+        })
+        .collect()
+}
+
+fn value_problem(description: &str, value: &Value, schema: &Value, spec: &Value) -> Option<String> {
+    let json_schema = inline_references(schema, spec);
+    let compiled = schema::compile(&json_schema);
+    schema::validation_errors(&compiled, value)
+        .map(|errors| format!("{} does not match its schema: {}", description, errors))
+}
+
+/// Describes how the request breaks the operation of the spec, before it is sent: query and path parameters
+/// missing, undeclared or not matching their schemas, and a form encoded body not matching the schema of
+/// the request body. Header and cookie parameters are not checked, as the suite sets its own headers.
+///
+/// # Arguments
+///
+/// * `spec` - OpenAPI 3 spec
+/// * `method` - Method of the request
+/// * `path` - Path of the request
+/// * `query` - Query parameters of the request
+/// * `body` - Parameters of the form encoded body, if the request has one
+///
+pub fn request_problems(
+    spec: &Value,
+    method: &reqwest::Method,
+    path: &str,
+    query: &[(String, String)],
+    body: Option<&[(String, String)]>,
+) -> Vec<String> {
+    let matched = match matched_operation(spec, method, path) {
+        Some(matched) => matched,
+        None => return vec![format!("{} {} is not described by the spec", method, path)],
+    };
+    let base_path = base_path_of(spec);
+    let path_values = path_parameters(
+        matched.template,
+        path.strip_prefix(base_path.as_str()).unwrap_or(path),
+    );
+    let parameters: Vec<&Value> = [matched.path_item, matched.operation]
+        .into_iter()
+        .filter_map(|object| object.get("parameters")?.as_array())
+        .flatten()
+        .map(|parameter| resolved(parameter, spec))
+        .collect();
+    let mut problems = Vec::new();
+
+    for parameter in &parameters {
+        let name = parameter["name"].as_str().unwrap_or_default();
+        let location = parameter["in"].as_str().unwrap_or_default();
+        let value = match location {
+            "query" => query
+                .iter()
+                .find(|(query_name, _)| query_name == name)
+                .map(|(_, value)| value.as_str()),
+            "path" => path_values.get(name).copied(),
+            _ => continue,
+        };
+        let value = match value {
+            Some(value) => value,
+            None => {
+                if location == "path" || parameter["required"] == Value::Bool(true) {
+                    problems.push(format!(
+                        "Required {} parameter {} is missing",
+                        location, name
+                    ));
+                }
+                continue;
+            }
+        };
+        if let Some(parameter_schema) = parameter.get("schema") {
+            let description = format!("{} parameter {} \"{}\"", location, name, value);
+            let typed = typed_value(value, &inline_references(parameter_schema, spec));
+            problems.extend(value_problem(&description, &typed, parameter_schema, spec));
+        }
+    }
+    for (name, _) in query {
+        let declared = parameters.iter().any(|parameter| {
+            parameter["in"].as_str() == Some("query") && parameter["name"].as_str() == Some(name)
+        });
+        if !declared {
+            problems.push(format!("query parameter {} is not declared", name));
+        }
+    }
+
+    let request_body = matched
+        .operation
+        .get("requestBody")
+        .map(|request_body| resolved(request_body, spec));
+    match (request_body, body) {
+        (None, Some(body)) if !body.is_empty() => {
+            problems.push("The operation declares no request body".to_owned())
+        }
+        (Some(request_body), None) => {
+            if request_body.get("required") == Some(&Value::Bool(true)) {
+                problems.push("The request body is required".to_owned());
+            }
+        }
+        (Some(request_body), Some(body)) => {
+            let body_schema = request_body
+                .get("content")
+                .and_then(|content| content.get("application/x-www-form-urlencoded"))
+                .and_then(|media_type| media_type.get("schema"));
+            match body_schema {
+                Some(body_schema) => {
+                    let json_schema = inline_references(body_schema, spec);
+                    let fields: serde_json::Map<String, Value> = body
+                        .iter()
+                        .map(|(name, value)| {
+                            let field_schema = &json_schema["properties"][name.as_str()];
+                            (name.clone(), typed_value(value, field_schema))
+                        })
+                        .collect();
+                    problems.extend(value_problem(
+                        "The form body",
+                        &Value::Object(fields),
+                        body_schema,
+                        spec,
+                    ));
+                }
+                None => problems.push(
+                    "The operation declares no application/x-www-form-urlencoded request body"
+                        .to_owned(),
+                ),
+            }
+        }
+        _ => {}
+    }
+    problems
+}
@@ -0,0 +1,95 @@
+use super::reference::ReferenceData;
+
+/// Share of the tick size by which a price may differ from a multiple of it, absorbing float rounding.
+const TICK_TOLERANCE: f64 = 1e-6;
+
+/// Price of a level of the order book, listed by the Depth endpoint as `[price, volume, timestamp]`.
+fn price_of(level: &serde_json::Value) -> Option<f64> {
+    match level.get(0)? {
+        serde_json::Value::String(price) => price.parse().ok(),
+        price => price.as_f64(),
+    }
+}
+
+fn prices_of(
+    pair: &str,
+    book: &serde_json::Value,
+    side: &str,
+    problems: &mut Vec<String>,
+) -> Vec<f64> {
+    let levels = match book[side].as_array() {
+        Some(levels) => levels,
+        None => {
+            problems.push(format!("{} order book has no {} list", pair, side));
+            return Vec::new();
+        }
+    };
+    levels
+        .iter()
+        .enumerate()
+        .filter_map(|(index, level)| {
+            let price = price_of(level);
+            if price.is_none() {
+                problems.push(format!(
+                    "{} {} level {} is malformed: {}",
+                    pair, side, index, level
+                ));
+            }
+            price
+        })
+        .collect()
+}
+
+/// Describes problems of the order books listed by pair: bids not strictly descending, asks not strictly
+/// ascending, a crossed book with the best bid at or above the best ask, and prices off the tick size of the pair.
+/// Tick sizes are not checked for pairs missing from the reference data.
+///
+/// # Arguments
+///
+/// * `result` - Result of the Depth endpoint, with "bids" and "asks" of every pair
+/// * `reference` - Asset pairs, providing their tick sizes
+///
+pub fn problems(result: &serde_json::Value, reference: &ReferenceData) -> Vec<String> {
+    let mut problems = Vec::new();
+    for (pair, book) in result.as_object().into_iter().flatten() {
+        let bids = prices_of(pair, book, "bids", &mut problems);
+        let asks = prices_of(pair, book, "asks", &mut problems);
+        for window in bids.windows(2) {
+            if window[1] >= window[0] {
+                problems.push(format!(
+                    "{} bid {} follows bid {}, bids should be strictly descending",
+                    pair, window[1], window[0]
+                ));
+            }
+        }
+        for window in asks.windows(2) {
+            if window[1] <= window[0] {
+                problems.push(format!(
+                    "{} ask {} follows ask {}, asks should be strictly ascending",
+                    pair, window[1], window[0]
+                ));
+            }
+        }
+        if let (Some(best_bid), Some(best_ask)) = (bids.first(), asks.first()) {
+            if best_bid >= best_ask {
+                problems.push(format!(
+                    "{} best bid {} is not below best ask {}",
+                    pair, best_bid, best_ask
+                ));
+            }
+        }
+        if let Some(pair_info) = reference.pair(pair) {
+            let tick_size = pair_info.tick_size();
+            for price in bids.iter().chain(&asks) {
+                let ticks = price / tick_size;
+                if (ticks - ticks.round()).abs() > TICK_TOLERANCE {
+                    problems.push(format!(
+                        "{} price {} is not a multiple of the tick size {}",
+                        pair, price, tick_size
+                    ));
+                }
+            }
+        }
+    }
+    problems
+}
@@ -0,0 +1,56 @@
+use super::capture;
+
+/// Fields of an order compared between its representations, as dot separated paths.
+pub const COMPARED_FIELDS: &[&str] = &[
+    "status",
+    "vol",
+    "descr.pair",
+    "descr.type",
+    "descr.ordertype",
+    "descr.price",
+];
+
+/// Largest number of txids the API accepts in a single query of orders.
+pub const MAX_TXIDS_PER_QUERY: usize = 50;
+
+/// Differences between open orders and the same orders queried by their txids,
+/// e.g. `order OABC-123: status is "open" in OpenOrders, "closed" in QueryOrders`.
+///
+/// # Arguments
+///
+/// * `open_orders` - Orders listed by the open orders endpoint, by txid
+/// * `queried_orders` - Orders returned by the query endpoint, by txid
+/// * `open_endpoint` - Name of the endpoint listing open orders
+/// * `query_endpoint` - Name of the endpoint querying orders by txid
+///
+pub fn inconsistencies(
+    open_orders: &serde_json::Map<String, serde_json::Value>,
+    queried_orders: &serde_json::Map<String, serde_json::Value>,
+    open_endpoint: &str,
+    query_endpoint: &str,
+) -> Vec<String> {
+    let mut inconsistencies = Vec::new();
+    for (txid, open_order) in open_orders {
+        let queried_order = match queried_orders.get(txid) {
+            Some(queried_order) => queried_order,
+            None => {
+                inconsistencies.push(format!(
+                    "order {}: listed by {}, missing from {}",
+                    txid, open_endpoint, query_endpoint
+                ));
+                continue;
+            }
+        };
+        for field in COMPARED_FIELDS {
+            let open_value = capture::extract_field(open_order, field);
+            let queried_value = capture::extract_field(queried_order, field);
+            if open_value != queried_value {
+                inconsistencies.push(format!(
+                    "order {}: {} is {:?} in {}, {:?} in {}",
+                    txid, field, open_value, open_endpoint, queried_value, query_endpoint
+                ));
+            }
+        }
+    }
+    inconsistencies
+}
@@ -0,0 +1,167 @@
+use super::config::{Auth, EndpointRegistry, Profile};
+use super::parameters::OrderSide;
+use super::reference::PairInfo;
+use super::{access, http_client, rate_limit};
+use serde::{Deserialize, Serialize};
+use std::env;
+
+/// Composition of validate-only test orders from metadata of their pair.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OrderSettings {
+    /// Private endpoint orders are validated with
+    pub endpoint: String,
+    /// Public endpoint with the last trade price of a pair, e.g. "Ticker"; prices are derived from 1.0 when not set
+    pub ticker_endpoint: Option<String>,
+    /// Distance of the order price from the last trade price, as a fraction of it,
+    /// below for buy and above for sell orders, so the orders would rest on the book
+    pub price_offset: f64,
+}
+
+impl Default for OrderSettings {
+    fn default() -> Self {
+        Self {
+            endpoint: "AddOrder".to_owned(),
+            ticker_endpoint: None,
+            price_offset: 0.5,
+        }
+    }
+}
+
+/// Limit order compliant with the trading rules of its pair.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DerivedOrder {
+    pub pair: String,
+    pub side: OrderSide,
+    /// Price with the precision of the pair, a multiple of its tick size
+    pub price: String,
+    /// Volume with the lot precision of the pair, at least its minimal volume and cost
+    pub volume: String,
+}
+
+impl DerivedOrder {
+    /// Cost of the order in the quote currency of the pair.
+    pub fn cost(&self) -> f64 {
+        self.price.parse::<f64>().unwrap_or_default()
+            * self.volume.parse::<f64>().unwrap_or_default()
+    }
+
+    /// Parameters of the request validating the order without placing it.
+    pub fn parameters(&self) -> Vec<(String, String)> {
+        vec![
+            ("pair".to_owned(), self.pair.clone()),
+            ("type".to_owned(), self.side.to_string()),
+            ("ordertype".to_owned(), "limit".to_owned()),
+            ("price".to_owned(), self.price.clone()),
+            ("volume".to_owned(), self.volume.clone()),
+            ("validate".to_owned(), "true".to_owned()),
+        ]
+    }
+}
+
+/// Rounds the value up to the decimal places, ignoring representation errors of floats.
+fn round_up(value: f64, decimals: u32) -> String {
+    let scale = 10f64.powi(decimals as i32);
+    format!(
+        "{:.*}",
+        decimals as usize,
+        (value * scale - 1e-6).ceil() / scale
+    )
+}
+
+/// Derives a limit order from the trading rules of the pair: the price is offset from the last trade price
+/// and aligned with the tick size, the volume is the smallest one satisfying both minimal volume and minimal cost.
+///
+/// # Arguments
+///
+/// * `pair` - Trading rules of the pair, from the reference data
+/// * `side` - Side of the order
+/// * `last_price` - Last trade price of the pair, if known
+/// * `settings` - Composition of orders of the profile
+///
+pub fn derive_order(
+    pair: &PairInfo,
+    side: OrderSide,
+    last_price: Option<f64>,
+    settings: &OrderSettings,
+) -> DerivedOrder {
+    let tick_size = pair.tick_size();
+    let (target_price, up) = match (last_price, side) {
+        (Some(last_price), OrderSide::Buy) => (last_price * (1.0 - settings.price_offset), false),
+        (Some(last_price), OrderSide::Sell) => (last_price * (1.0 + settings.price_offset), true),
+        (None, _) => (1.0, false),
+    };
+    let ticks = if up {
+        (target_price / tick_size - 1e-6).ceil()
+    } else {
+        (target_price / tick_size + 1e-6).floor()
+    };
+    let price = ticks.max(1.0) * tick_size;
+    let volume = pair.min_cost().map_or(pair.min_volume(), |min_cost| {
+        pair.min_volume().max(min_cost / price)
+    });
+    DerivedOrder {
+        pair: if pair.altname.is_empty() {
+            pair.name.clone()
+        } else {
+            pair.altname.clone()
+        },
+        side,
+        price: format!("{:.*}", pair.pair_decimals as usize, price),
+        volume: round_up(volume, pair.lot_decimals),
+    }
+}
+
+/// Last trade price of the pair, read from the ticker endpoint of the settings, if set.
+///
+/// # Arguments
+///
+/// * `profile` - Profile of the run
+/// * `endpoints` - Endpoints known to the suite
+/// * `pair` - Name of the pair, e.g. "XBTUSD"
+///
+pub async fn last_price(
+    profile: &Profile,
+    endpoints: &EndpointRegistry,
+    pair: &str,
+) -> Option<f64> {
+    let endpoint_name = profile.orders.ticker_endpoint.as_ref()?;
+    let endpoint = endpoints
+        .get(endpoint_name)
+        .unwrap_or_else(|err| panic!("{}", err));
+    assert_eq!(
+        endpoint.auth,
+        Auth::Public,
+        "Ticker endpoint {} should be public",
+        endpoint_name
+    );
+    access::check(profile, endpoint_name, endpoint).unwrap_or_else(|err| panic!("{}", err));
+    let api_link = env::var("API_LINK").expect("Missing secret value: API_LINK");
+    let link = [api_link.as_str(), endpoint.path.as_str()].concat();
+    let (client, _) = http_client::build_client(profile);
+    rate_limit::acquire(&profile.rate_limit, endpoint_name, endpoint.weight).await;
+    let json_response: serde_json::Value = client
+        .request(endpoint.method(), &link)
+        .query(&[("pair", pair)])
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .unwrap_or_else(|err| panic!("Last price of {} cannot be read: {}", pair, err))
+        .json()
+        .await
+        .unwrap_or_else(|err| panic!("Response of {} is not valid json: {}", endpoint_name, err));
+    // Ticker lists the pair under its own name, with the last trade as [price, volume] in "c"
+    let last_trade = json_response
+        .get("result")
+        .and_then(serde_json::Value::as_object)
+        .and_then(|pairs| pairs.values().next())
+        .and_then(|ticker| ticker.pointer("/c/0"))
+        .and_then(serde_json::Value::as_str)
+        .and_then(|price| price.parse().ok());
+    Some(last_trade.unwrap_or_else(|| {
+        panic!(
+            "Response of {} has no last trade price of {}: {}",
+            endpoint_name, pair, json_response
+        )
+    }))
+}
@@ -0,0 +1,117 @@
+use super::config::{ConfigError, EndpointRegistry, Profile};
+use cucumber::Parameter;
+use once_cell::sync::Lazy;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Endpoints of the profile selected for the run, loaded once for validation of step parameters.
+static REGISTRY: Lazy<Result<EndpointRegistry, ConfigError>> =
+    Lazy::new(|| Profile::load().and_then(|profile| EndpointRegistry::load(&profile.api_version)));
+
+/// Name of an endpoint of the registry, e.g. `AssetPair`; unknown names fail the step when it is matched.
+#[derive(Debug, Clone, PartialEq, Eq, Parameter)]
+#[param(name = "endpoint", regex = "[A-Z][A-Za-z0-9]*")]
+pub struct EndpointName(pub String);
+
+impl FromStr for EndpointName {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, String> {
+        let registry = REGISTRY.as_ref().map_err(|err| err.to_string())?;
+        registry.get(name).map_err(|err| err.to_string())?;
+        Ok(Self(name.to_owned()))
+    }
+}
+
+impl fmt::Display for EndpointName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Asset pair, e.g. `XBTUSD` or `XBT/USD`.
+#[derive(Debug, Clone, PartialEq, Eq, Parameter)]
+#[param(name = "asset-pair", regex = "[A-Z0-9]+(?:/[A-Z0-9]+)?")]
+pub struct AssetPair(pub String);
+
+impl FromStr for AssetPair {
+    type Err = String;
+
+    fn from_str(pair: &str) -> Result<Self, String> {
+        let codes: Vec<&str> = pair.split('/').collect();
+        let is_valid = codes.iter().all(|code| {
+            (2..=12).contains(&code.len()) && code.chars().all(|c| c.is_ascii_alphanumeric())
+        });
+        if !is_valid {
+            return Err(format!("{} is not a valid asset pair", pair));
+        }
+        Ok(Self(pair.to_owned()))
+    }
+}
+
+impl fmt::Display for AssetPair {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Side of an order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Parameter)]
+#[param(name = "order-side", regex = "buy|sell")]
+pub enum OrderSide {
+    Buy,
+    Sell,
+}
+
+impl FromStr for OrderSide {
+    type Err = String;
+
+    fn from_str(side: &str) -> Result<Self, String> {
+        match side {
+            "buy" => Ok(OrderSide::Buy),
+            "sell" => Ok(OrderSide::Sell),
+            _ => Err(format!("{} is not an order side", side)),
+        }
+    }
+}
+
+impl fmt::Display for OrderSide {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OrderSide::Buy => write!(f, "buy"),
+            OrderSide::Sell => write!(f, "sell"),
+        }
+    }
+}
+
+/// Duration written in words, e.g. `500 milliseconds`, `1 second` or `2 minutes`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Parameter)]
+#[param(
+    name = "duration",
+    regex = r"\d+ (?:milliseconds?|seconds?|minutes?|hours?)"
+)]
+pub struct StepDuration(pub Duration);
+
+impl FromStr for StepDuration {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, String> {
+        let (amount, unit) = text
+            .split_once(' ')
+            .ok_or_else(|| format!("{} is not a duration", text))?;
+        let amount: u64 = amount
+            .parse()
+            .map_err(|_| format!("{} is not a duration", text))?;
+        let duration = match unit.trim_end_matches('s') {
+            "millisecond" => Some(Duration::from_millis(amount)),
+            "second" => Some(Duration::from_secs(amount)),
+            "minute" => amount.checked_mul(60).map(Duration::from_secs),
+            "hour" => amount.checked_mul(3600).map(Duration::from_secs),
+            _ => return Err(format!("{} is not a duration", text)),
+        };
+        duration
+            .map(Self)
+            .ok_or_else(|| format!("{} is too long a duration", text))
+    }
+}
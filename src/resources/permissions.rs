@@ -0,0 +1,45 @@
+use super::skipping;
+use serde::{Deserialize, Serialize};
+
+/// Handling of requests the API key lacks permissions for, e.g. "Query Funds".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PermissionSettings {
+    /// Whether scenarios rejected for insufficient key permissions are reported as skipped instead of failed
+    pub skip_denied: bool,
+    /// Errors of the API meaning that the key lacks permissions
+    pub denied_errors: Vec<String>,
+}
+
+impl Default for PermissionSettings {
+    fn default() -> Self {
+        Self {
+            skip_denied: false,
+            denied_errors: vec!["EGeneral:Permission denied".to_owned()],
+        }
+    }
+}
+
+/// Skips the scenario when the API rejected its request for insufficient key permissions
+/// and the settings turn such rejections into skips.
+///
+/// # Arguments
+///
+/// * `settings` - Handling of insufficient permissions of the profile
+/// * `scenario_key` - Key of the scenario which sent the request
+/// * `errors` - Errors reported by the API in the response
+///
+pub fn skip_if_denied(settings: &PermissionSettings, scenario_key: &str, errors: &[String]) {
+    if !settings.skip_denied {
+        return;
+    }
+    if let Some(error) = errors
+        .iter()
+        .find(|error| settings.denied_errors.contains(error))
+    {
+        skipping::skip_scenario(
+            scenario_key,
+            &format!("insufficient key permissions ({})", error),
+        )
+    }
+}
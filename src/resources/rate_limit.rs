@@ -0,0 +1,113 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Call counter of the API, modelled after the one used by Kraken:
+/// every request increases the counter by the weight of the endpoint,
+/// and the counter decreases steadily over time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimit {
+    /// Maximal value of the counter; requests wait until they fit below it
+    pub max_counter: f64,
+    /// Decrease of the counter per second
+    pub decay_per_second: f64,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            max_counter: 15.0,
+            decay_per_second: 0.33,
+        }
+    }
+}
+
+/// Weight consumed during the run, by endpoint name.
+#[derive(Debug, Clone, Default)]
+pub struct WeightReport {
+    pub consumed: BTreeMap<String, u64>,
+}
+
+impl WeightReport {
+    pub fn total(&self) -> u64 {
+        self.consumed.values().sum()
+    }
+}
+
+impl fmt::Display for WeightReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Rate limit weight consumed: {}", self.total())?;
+        for (endpoint_name, weight) in &self.consumed {
+            writeln!(f, "  {}: {}", endpoint_name, weight)?;
+        }
+        Ok(())
+    }
+}
+
+struct Limiter {
+    counter: f64,
+    updated_at: Instant,
+    report: WeightReport,
+}
+
+impl Limiter {
+    fn decay(&mut self, rate_limit: &RateLimit) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.updated_at).as_secs_f64();
+        self.counter = (self.counter - elapsed * rate_limit.decay_per_second).max(0.0);
+        self.updated_at = now;
+    }
+}
+
+/// Limiter shared by all scenarios of the run, as they share the call counter of the account.
+static LIMITER: Lazy<Mutex<Limiter>> = Lazy::new(|| {
+    Mutex::new(Limiter {
+        counter: 0.0,
+        updated_at: Instant::now(),
+        report: WeightReport::default(),
+    })
+});
+
+/// Waits until the request fits within the rate limit, then consumes its weight.
+/// Weight greater than the maximal counter is consumed once the counter drops to 0.
+///
+/// # Arguments
+///
+/// * `rate_limit` - Call counter settings of the profile
+/// * `endpoint_name` - Name of the requested endpoint, used in the report
+/// * `weight` - Cost of the request
+///
+pub async fn acquire(rate_limit: &RateLimit, endpoint_name: &str, weight: u32) {
+    loop {
+        let wait = {
+            let mut limiter = LIMITER.lock().expect("Rate limiter should not be poisoned");
+            limiter.decay(rate_limit);
+            let excess = limiter.counter + weight as f64 - rate_limit.max_counter;
+            if excess <= 0.0 || limiter.counter == 0.0 || rate_limit.decay_per_second <= 0.0 {
+                limiter.counter += weight as f64;
+                *limiter
+                    .report
+                    .consumed
+                    .entry(endpoint_name.to_owned())
+                    .or_insert(0) += weight as u64;
+                return;
+            }
+            let wait_seconds = excess.min(limiter.counter) / rate_limit.decay_per_second;
+            Duration::from_secs_f64(wait_seconds)
+        };
+        tokio::time::sleep(wait).await;
+    }
+}
+
+/// Weight consumed so far in the run.
+pub fn weight_report() -> WeightReport {
+    LIMITER
+        .lock()
+        .expect("Rate limiter should not be poisoned")
+        .report
+        .clone()
+}
@@ -0,0 +1,129 @@
+use super::config::{Auth, EndpointRegistry, Profile};
+use super::{access, http_client, rate_limit};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Result of the lightweight request sent to a single endpoint.
+#[derive(Debug)]
+pub enum Reachability {
+    Responded {
+        status: reqwest::StatusCode,
+        latency: Duration,
+    },
+    Failed(String),
+}
+
+impl Reachability {
+    /// Endpoint is reachable when it responds with any status other than a server error;
+    /// private endpoints are requested without signature, so they are expected to reject it.
+    pub fn is_reachable(&self) -> bool {
+        match self {
+            Reachability::Responded { status, .. } => !status.is_server_error(),
+            Reachability::Failed(_) => false,
+        }
+    }
+}
+
+impl fmt::Display for Reachability {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Reachability::Responded { status, latency } => {
+                write!(f, "{} in {} ms", status, latency.as_millis())
+            }
+            Reachability::Failed(error) => write!(f, "failed: {}", error),
+        }
+    }
+}
+
+/// Reachability of the registered endpoints, in the order of their names.
+#[derive(Debug)]
+pub struct ReachabilityMatrix {
+    pub endpoints: Vec<(String, Auth, Reachability)>,
+}
+
+impl ReachabilityMatrix {
+    /// Panics if any of the endpoints is unreachable.
+    pub fn verify(&self) {
+        assert!(
+            self.endpoints
+                .iter()
+                .all(|(_, _, reachability)| reachability.is_reachable()),
+            "Some of the endpoints are unreachable:\n{}",
+            self
+        );
+    }
+}
+
+impl fmt::Display for ReachabilityMatrix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (endpoint_name, auth, reachability) in &self.endpoints {
+            let mark = if reachability.is_reachable() {
+                "ok"
+            } else {
+                "UNREACHABLE"
+            };
+            writeln!(
+                f,
+                "{:<11} {:<30} {:?}: {}",
+                mark, endpoint_name, auth, reachability
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Sends a single unsigned request to every registered endpoint the profile allows, concurrently.
+///
+/// # Arguments
+///
+/// * `profile` - Settings of the environment the suite is run against
+/// * `endpoints` - Endpoints known to the suite
+/// * `api_link` - Link to the API the endpoint paths are appended to
+/// * `auth` - Restricts the check to endpoints of the given API, all are checked when not set
+///
+pub async fn check_reachability(
+    profile: &Profile,
+    endpoints: &EndpointRegistry,
+    api_link: &str,
+    auth: Option<Auth>,
+) -> ReachabilityMatrix {
+    let (client, _) = http_client::build_client(profile);
+    let mut selected: Vec<_> = endpoints
+        .iter()
+        .filter(|(_, endpoint)| auth.map_or(true, |auth| endpoint.auth == auth))
+        .filter(|(endpoint_name, endpoint)| access::check(profile, endpoint_name, endpoint).is_ok())
+        .collect();
+    selected.sort_by(|(first, _), (second, _)| first.cmp(second));
+
+    let checks: Vec<_> = selected
+        .into_iter()
+        .map(|(endpoint_name, endpoint)| {
+            let client = client.clone();
+            let rate_limit = profile.rate_limit.clone();
+            let endpoint_name = endpoint_name.clone();
+            let endpoint = endpoint.clone();
+            let link = [api_link, endpoint.path.as_str()].concat();
+            tokio::spawn(async move {
+                rate_limit::acquire(&rate_limit, &endpoint_name, endpoint.weight).await;
+                let start = Instant::now();
+                let reachability = match client.request(endpoint.method(), &link).send().await {
+                    Ok(response) => Reachability::Responded {
+                        status: response.status(),
+                        latency: start.elapsed(),
+                    },
+                    Err(err) => Reachability::Failed(err.to_string()),
+                };
+                (endpoint_name, endpoint.auth, reachability)
+            })
+        })
+        .collect();
+
+    let mut matrix = ReachabilityMatrix {
+        endpoints: Vec::new(),
+    };
+    for check in checks {
+        let result = check.await.expect("Reachability check should not panic");
+        matrix.endpoints.push(result);
+    }
+    matrix
+}
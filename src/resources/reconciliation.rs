@@ -0,0 +1,188 @@
+use super::reference::ReferenceData;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::Mutex;
+
+/// Private endpoints whose listings are reconciled, and the precision amounts are compared with.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReconciliationSettings {
+    /// Endpoint listing trades of the account in the "trades" object of its result
+    pub trades_endpoint: String,
+    /// Endpoint listing ledger entries of the account in the "ledger" object of its result
+    pub ledgers_endpoint: String,
+    /// Largest difference between amounts of a trade and of its ledger entries considered equal
+    pub tolerance: f64,
+}
+
+impl Default for ReconciliationSettings {
+    fn default() -> Self {
+        Self {
+            trades_endpoint: "TradesHistory".to_owned(),
+            ledgers_endpoint: "Ledgers".to_owned(),
+            tolerance: 1e-8,
+        }
+    }
+}
+
+/// Outcome of matching trades with ledger entries of the same window.
+#[derive(Debug, Clone, Default)]
+pub struct ReconciliationReport {
+    pub trades: usize,
+    pub ledger_entries: usize,
+    /// Descriptions of trades whose ledger entries are missing or do not add up
+    pub mismatches: Vec<String>,
+}
+
+impl fmt::Display for ReconciliationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Reconciled {} trades with {} ledger entries, {} mismatches",
+            self.trades,
+            self.ledger_entries,
+            self.mismatches.len()
+        )?;
+        for mismatch in &self.mismatches {
+            writeln!(f, "  {}", mismatch)?;
+        }
+        Ok(())
+    }
+}
+
+fn decimal(entry: &serde_json::Value, field: &str) -> f64 {
+    match &entry[field] {
+        serde_json::Value::String(value) => value.parse().unwrap_or_default(),
+        value => value.as_f64().unwrap_or_default(),
+    }
+}
+
+fn text<'a>(entry: &'a serde_json::Value, field: &str) -> &'a str {
+    entry[field].as_str().unwrap_or_default()
+}
+
+/// Matches every trade with the ledger entries referring to it: the entries should move the base asset
+/// by the traded volume and the quote asset by the cost, in the direction of the trade, and their fees
+/// should add up to the fee of the trade. Assets of a pair missing from the reference data are not compared.
+///
+/// # Arguments
+///
+/// * `trades` - Trades by txid, as listed by the trades endpoint
+/// * `ledger` - Ledger entries by id, as listed by the ledgers endpoint
+/// * `reference` - Asset pairs, resolving the assets of a trade
+/// * `tolerance` - Largest difference of amounts considered equal
+///
+pub fn reconcile(
+    trades: &serde_json::Map<String, serde_json::Value>,
+    ledger: &serde_json::Map<String, serde_json::Value>,
+    reference: &ReferenceData,
+    tolerance: f64,
+) -> ReconciliationReport {
+    let mut entries_by_refid: HashMap<&str, Vec<(&String, &serde_json::Value)>> = HashMap::new();
+    for (ledger_id, entry) in ledger {
+        entries_by_refid
+            .entry(text(entry, "refid"))
+            .or_default()
+            .push((ledger_id, entry));
+    }
+    let mut mismatches = Vec::new();
+    for (txid, trade) in trades {
+        let entries = match entries_by_refid.get(txid.as_str()) {
+            Some(entries) => entries,
+            None => {
+                mismatches.push(format!("trade {} has no ledger entries", txid));
+                continue;
+            }
+        };
+        let fee: f64 = entries.iter().map(|(_, entry)| decimal(entry, "fee")).sum();
+        if (fee - decimal(trade, "fee")).abs() > tolerance {
+            mismatches.push(format!(
+                "trade {} has fee {}, its ledger entries {}",
+                txid,
+                decimal(trade, "fee"),
+                fee
+            ));
+        }
+        let pair = match reference.pair(text(trade, "pair")) {
+            Some(pair) => pair,
+            None => continue,
+        };
+        let direction = if text(trade, "type") == "sell" {
+            -1.0
+        } else {
+            1.0
+        };
+        let expected_amounts = [
+            (&pair.base, direction * decimal(trade, "vol")),
+            (&pair.quote, -direction * decimal(trade, "cost")),
+        ];
+        for (asset, expected_amount) in expected_amounts {
+            let amount: f64 = entries
+                .iter()
+                .filter(|(_, entry)| text(entry, "asset") == asset.as_str())
+                .map(|(_, entry)| decimal(entry, "amount"))
+                .sum();
+            if (amount - expected_amount).abs() > tolerance {
+                let ledger_ids: Vec<&str> = entries.iter().map(|(id, _)| id.as_str()).collect();
+                mismatches.push(format!(
+                    "trade {} should move {} {}, its ledger entries {} move {}",
+                    txid,
+                    expected_amount,
+                    asset,
+                    ledger_ids.join(", "),
+                    amount
+                ));
+            }
+        }
+    }
+    ReconciliationReport {
+        trades: trades.len(),
+        ledger_entries: ledger.len(),
+        mismatches,
+    }
+}
+
+/// Reports of reconciliations verified during the run, by scenario key.
+static REPORTS: Lazy<Mutex<BTreeMap<String, ReconciliationReport>>> =
+    Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+/// Keeps the report of the verified reconciliation for the report of the run.
+///
+/// # Arguments
+///
+/// * `scenario_key` - Key of the scenario verifying the reconciliation
+/// * `report` - The verified reconciliation
+///
+pub fn record(scenario_key: &str, report: &ReconciliationReport) {
+    REPORTS
+        .lock()
+        .expect("Reconciliation reports should not be poisoned")
+        .insert(scenario_key.to_owned(), report.clone());
+}
+
+/// Reconciliations verified during the run, by scenario key.
+#[derive(Debug, Clone, Default)]
+pub struct RunReconciliationReport {
+    pub scenarios: BTreeMap<String, ReconciliationReport>,
+}
+
+impl fmt::Display for RunReconciliationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (scenario_key, report) in &self.scenarios {
+            write!(f, "{}: {}", scenario_key, report)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reconciliations verified so far in the run.
+pub fn run_report() -> RunReconciliationReport {
+    RunReconciliationReport {
+        scenarios: REPORTS
+            .lock()
+            .expect("Reconciliation reports should not be poisoned")
+            .clone(),
+    }
+}
@@ -0,0 +1,258 @@
+use super::{report_encryption, scrubbing};
+use once_cell::sync::Lazy;
+use std::collections::{HashSet, VecDeque};
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+/// Value replacing every occurrence of a secret in the reports.
+const MASK: &str = "***";
+
+/// Environment variables whose values must never be present in the reports.
+const SECRET_ENV_VARS: [&str; 3] = ["API_KEY", "API_SECRET", "OTP_SECRET"];
+
+/// Number of derived secrets kept; the oldest ones are forgotten first,
+/// so long runs neither grow the registry nor slow down redaction without bounds.
+const MAX_DERIVED_SECRETS: usize = 4096;
+
+/// Secrets created during the run, e.g. signatures of sent requests, in the order of registration.
+#[derive(Default)]
+struct DerivedSecrets {
+    known: HashSet<String>,
+    order: VecDeque<String>,
+}
+
+impl DerivedSecrets {
+    fn insert(&mut self, secret: &str) {
+        if !self.known.insert(secret.to_owned()) {
+            return;
+        }
+        self.order.push_back(secret.to_owned());
+        if self.order.len() > MAX_DERIVED_SECRETS {
+            if let Some(oldest) = self.order.pop_front() {
+                self.known.remove(&oldest);
+            }
+        }
+    }
+}
+
+static DERIVED_SECRETS: Lazy<Mutex<DerivedSecrets>> =
+    Lazy::new(|| Mutex::new(DerivedSecrets::default()));
+
+/// Marks a value created during the run as secret, so it is masked in the reports
+/// as long as it is among the latest `MAX_DERIVED_SECRETS` ones.
+///
+/// # Arguments
+///
+/// * `secret` - Value derived from the keys, e.g. request signature
+///
+pub fn register_derived_secret(secret: &str) {
+    DERIVED_SECRETS
+        .lock()
+        .expect("Secrets registry should not be poisoned")
+        .insert(secret);
+}
+
+/// Collects configured and derived secrets, longest first,
+/// so secrets containing other secrets are masked as a whole.
+fn known_secrets() -> Vec<String> {
+    // Keys of subaccounts are kept in the same variables with a prefix, e.g. SUBA_API_KEY
+    let mut secrets: Vec<String> = env::vars_os()
+        .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)))
+        .filter(|(name, _)| {
+            SECRET_ENV_VARS
+                .iter()
+                .any(|env_var| name == env_var || name.ends_with(&format!("_{}", env_var)))
+        })
+        .map(|(_, value)| value)
+        .collect();
+    secrets.extend(
+        DERIVED_SECRETS
+            .lock()
+            .expect("Secrets registry should not be poisoned")
+            .order
+            .iter()
+            .cloned(),
+    );
+    secrets.retain(|secret| !secret.trim().is_empty());
+    secrets.sort_by_key(|secret| std::cmp::Reverse(secret.len()));
+    secrets.dedup();
+    secrets
+}
+
+/// Replaces all known secrets present in the content with the mask.
+/// Returns redacted content along with information whether any secret was found.
+///
+/// # Arguments
+///
+/// * `content` - Text which is about to be written to a report
+///
+pub fn redact(content: &str) -> (String, bool) {
+    let mut redacted = content.to_owned();
+    let mut leak_detected = false;
+    for secret in known_secrets() {
+        if redacted.contains(&secret) {
+            leak_detected = true;
+            redacted = redacted.replace(&secret, MASK);
+        }
+    }
+    (redacted, leak_detected)
+}
+
+/// Strict mode turns every detected leak into a failure of the run.
+fn is_strict_mode() -> bool {
+    env::var("STRICT_MODE")
+        .map(|value| value == "true" || value == "1")
+        .unwrap_or(false)
+}
+
+/// In-memory output for report writers; the content is redacted before reaching the disk.
+#[derive(Clone, Default)]
+pub struct ReportBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for ReportBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0
+            .lock()
+            .expect("Report buffer should not be poisoned")
+            .write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl ReportBuffer {
+    /// Inserts the comments after the XML declaration of the buffered report, before its root element.
+    ///
+    /// # Arguments
+    ///
+    /// * `comments` - Comments, e.g. "<!-- SEED=42 -->"
+    ///
+    pub fn insert_comments(&self, comments: &[String]) {
+        let mut content = self.0.lock().expect("Report buffer should not be poisoned");
+        let position = if content.starts_with(b"<?xml") {
+            content
+                .windows(2)
+                .position(|window| window == b"?>")
+                .map_or(0, |end| {
+                    let end = end + 2;
+                    end + usize::from(content.get(end) == Some(&b'\n'))
+                })
+        } else {
+            0
+        };
+        let inserted: String = comments
+            .iter()
+            .map(|comment| format!("{}\n", comment))
+            .collect();
+        content.splice(position..position, inserted.into_bytes());
+    }
+}
+
+/// Writes the buffered report to the file with all secrets masked and sensitive values scrubbed,
+/// encrypted when a report key is set.
+/// In strict mode the run fails if any secret was found.
+///
+/// # Arguments
+///
+/// * `path` - Path to the report file
+/// * `report` - Buffer filled by the report writer
+///
+pub fn write_redacted_report(path: &str, report: &ReportBuffer) {
+    let content = String::from_utf8_lossy(
+        &report
+            .0
+            .lock()
+            .expect("Report buffer should not be poisoned"),
+    )
+    .into_owned();
+    let (redacted, leak_detected) = redact(&scrubbing::scrub_known_values(&content));
+    fs::write(path, report_encryption::seal(redacted.into_bytes()))
+        .expect("Report should be possible to write");
+    report_leak(path, leak_detected);
+}
+
+/// Reports secrets found in the written file; in strict mode the run fails.
+///
+/// # Arguments
+///
+/// * `path` - Path to the written file
+/// * `leak_detected` - Whether `redact` found any secret in its content
+///
+pub fn report_leak(path: &str, leak_detected: bool) {
+    if leak_detected {
+        eprintln!("Secret values were found in {} and have been masked", path);
+        if is_strict_mode() {
+            panic!(
+                "Secret values leaked into {} while running in strict mode",
+                path
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redact_masks_derived_secrets_and_reports_the_leak() {
+        register_derived_secret("signature-of-a-leaked-request");
+        let (redacted, leak_detected) = redact("API-Sign: signature-of-a-leaked-request");
+        assert_eq!(redacted, "API-Sign: ***");
+        assert!(leak_detected);
+    }
+
+    #[test]
+    fn redact_masks_keys_of_subaccounts() {
+        env::set_var("REDACTION_TEST_API_KEY", "key-of-a-subaccount");
+        let (redacted, leak_detected) = redact("API-Key: key-of-a-subaccount");
+        assert_eq!(redacted, "API-Key: ***");
+        assert!(leak_detected);
+    }
+
+    #[test]
+    fn redact_masks_secrets_containing_other_secrets_as_a_whole() {
+        register_derived_secret("nested-secret");
+        register_derived_secret("nested-secret-with-suffix");
+        let (redacted, _) = redact("nested-secret-with-suffix");
+        assert_eq!(redacted, MASK);
+    }
+
+    #[test]
+    fn redact_leaves_content_without_secrets_intact() {
+        let (redacted, leak_detected) = redact("{\"result\": {\"unixtime\": 1700000000}}");
+        assert_eq!(redacted, "{\"result\": {\"unixtime\": 1700000000}}");
+        assert!(!leak_detected);
+    }
+
+    #[test]
+    fn derived_secrets_forget_the_oldest_ones_beyond_the_limit() {
+        let mut secrets = DerivedSecrets::default();
+        for index in 0..=MAX_DERIVED_SECRETS {
+            secrets.insert(&format!("signature-{}", index));
+        }
+        secrets.insert("signature-1");
+        assert_eq!(secrets.order.len(), MAX_DERIVED_SECRETS);
+        assert_eq!(secrets.known.len(), MAX_DERIVED_SECRETS);
+        assert!(!secrets.known.contains("signature-0"));
+        assert!(secrets
+            .known
+            .contains(&format!("signature-{}", MAX_DERIVED_SECRETS)));
+    }
+
+    #[test]
+    fn report_buffer_inserts_comments_after_the_xml_declaration() {
+        let mut report = ReportBuffer::default();
+        write!(report, "<?xml version=\"1.0\"?>\n<testsuites/>").unwrap();
+        report.insert_comments(&["<!-- SEED=42 -->".to_owned()]);
+        let content = report.0.lock().unwrap().clone();
+        assert_eq!(
+            String::from_utf8(content).unwrap(),
+            "<?xml version=\"1.0\"?>\n<!-- SEED=42 -->\n<testsuites/>"
+        );
+    }
+}
@@ -0,0 +1,245 @@
+use super::config::{Auth, EndpointRegistry, Profile};
+use super::{access, http_client, rate_limit};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::sync::{Arc, Mutex};
+
+/// Public endpoints reference data is preloaded from before the suite.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReferenceSettings {
+    /// Endpoint listing asset pairs, e.g. "AssetPair"; pairs are not preloaded when not set
+    pub pairs_endpoint: Option<String>,
+    /// Endpoint listing assets, e.g. "Assets"; assets are not preloaded when not set
+    pub assets_endpoint: Option<String>,
+}
+
+/// Trading rules of an asset pair, as listed by the API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairInfo {
+    /// Name of the pair in the listing, e.g. "XXBTZUSD"
+    #[serde(skip_deserializing)]
+    pub name: String,
+    /// Alternate name, e.g. "XBTUSD"
+    #[serde(default)]
+    pub altname: String,
+    /// Name used by the websocket API, e.g. "XBT/USD"
+    #[serde(default)]
+    pub wsname: Option<String>,
+    pub base: String,
+    pub quote: String,
+    /// Decimal places of prices
+    pub pair_decimals: u32,
+    /// Decimal places of volumes
+    pub lot_decimals: u32,
+    /// Multiplier of volumes, 1 when volumes are traded as they are
+    #[serde(default = "default_lot_multiplier")]
+    pub lot_multiplier: u32,
+    /// Minimal volume of an order, as a decimal
+    #[serde(default)]
+    pub ordermin: Option<String>,
+    /// Minimal cost of an order in the quote currency, as a decimal
+    #[serde(default)]
+    pub costmin: Option<String>,
+    /// Smallest step of prices, as a decimal
+    #[serde(default)]
+    pub tick_size: Option<String>,
+    /// Trading status, e.g. "online"
+    #[serde(default)]
+    pub status: Option<String>,
+}
+
+fn default_lot_multiplier() -> u32 {
+    1
+}
+
+impl PairInfo {
+    /// Minimal volume of an order; the smallest volume of lot precision when not listed.
+    pub fn min_volume(&self) -> f64 {
+        decimal_of(&self.ordermin).unwrap_or_else(|| 10f64.powi(-(self.lot_decimals as i32)))
+    }
+
+    /// Minimal cost of an order in the quote currency, if listed.
+    pub fn min_cost(&self) -> Option<f64> {
+        decimal_of(&self.costmin)
+    }
+
+    /// Smallest step of prices; the one of price precision when not listed.
+    pub fn tick_size(&self) -> f64 {
+        decimal_of(&self.tick_size).unwrap_or_else(|| 10f64.powi(-(self.pair_decimals as i32)))
+    }
+}
+
+/// Precision of an asset, as listed by the API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssetInfo {
+    /// Name of the asset in the listing, e.g. "XXBT"
+    #[serde(skip_deserializing)]
+    pub name: String,
+    /// Alternate name, e.g. "XBT"
+    #[serde(default)]
+    pub altname: String,
+    /// Decimal places used to record amounts
+    pub decimals: u32,
+    /// Decimal places used to display amounts
+    #[serde(default)]
+    pub display_decimals: u32,
+}
+
+fn decimal_of(value: &Option<String>) -> Option<f64> {
+    value.as_deref().and_then(|value| value.parse().ok())
+}
+
+/// Asset pairs and assets preloaded before the suite, looked up by steps composing valid requests.
+#[derive(Debug, Clone, Default)]
+pub struct ReferenceData {
+    pairs: Vec<PairInfo>,
+    assets: Vec<AssetInfo>,
+}
+
+impl ReferenceData {
+    /// Asset pair by its name, alternate name or websocket name, e.g. "XXBTZUSD", "XBTUSD" or "XBT/USD".
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Any name of the pair
+    ///
+    pub fn pair(&self, name: &str) -> Option<&PairInfo> {
+        self.pairs.iter().find(|pair| {
+            pair.name == name || pair.altname == name || pair.wsname.as_deref() == Some(name)
+        })
+    }
+
+    /// Asset by its name or alternate name, e.g. "XXBT" or "XBT".
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Any name of the asset
+    ///
+    pub fn asset(&self, name: &str) -> Option<&AssetInfo> {
+        self.assets
+            .iter()
+            .find(|asset| asset.name == name || asset.altname == name)
+    }
+
+    /// Asset pair like `pair`, failing the step when the pair was not preloaded.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Any name of the pair
+    ///
+    pub fn require_pair(&self, name: &str) -> &PairInfo {
+        self.pair(name).unwrap_or_else(|| {
+            panic!(
+                "Asset pair {} is not in the reference data; {} pairs were preloaded",
+                name,
+                self.pairs.len()
+            )
+        })
+    }
+}
+
+/// Reference data shared by the worlds of all scenarios; empty until preloaded.
+static REFERENCE: Lazy<Mutex<Arc<ReferenceData>>> =
+    Lazy::new(|| Mutex::new(Arc::new(ReferenceData::default())));
+
+/// Reference data preloaded for the run, to be kept in the world of a scenario.
+pub fn loaded() -> Arc<ReferenceData> {
+    REFERENCE
+        .lock()
+        .expect("Reference data should not be poisoned")
+        .clone()
+}
+
+/// Reads entries of the "result" object of a listing, named by their keys;
+/// malformed entries are skipped with a warning, so a single unusual entry does not stop the run.
+fn listing_of<T: serde::de::DeserializeOwned>(
+    endpoint_name: &str,
+    json_response: &serde_json::Value,
+) -> Vec<(String, T)> {
+    let entries = json_response
+        .get("result")
+        .and_then(serde_json::Value::as_object)
+        .unwrap_or_else(|| panic!("Response of {} has no result object", endpoint_name));
+    entries
+        .iter()
+        .filter_map(
+            |(name, entry)| match serde_json::from_value(entry.clone()) {
+                Ok(info) => Some((name.clone(), info)),
+                Err(err) => {
+                    eprintln!(
+                        "Warning: entry {} of {} is malformed and skipped: {}",
+                        name, endpoint_name, err
+                    );
+                    None
+                }
+            },
+        )
+        .collect()
+}
+
+async fn fetch_listing(
+    client: &reqwest::Client,
+    profile: &Profile,
+    endpoints: &EndpointRegistry,
+    endpoint_name: &str,
+) -> serde_json::Value {
+    let endpoint = endpoints
+        .get(endpoint_name)
+        .unwrap_or_else(|err| panic!("{}", err));
+    if endpoint.auth != Auth::Public {
+        panic!("Reference data endpoint {} should be public", endpoint_name);
+    }
+    access::check(profile, endpoint_name, endpoint).unwrap_or_else(|err| panic!("{}", err));
+    let api_link = env::var("API_LINK").expect("Missing secret value: API_LINK");
+    let link = [api_link.as_str(), endpoint.path.as_str()].concat();
+    rate_limit::acquire(&profile.rate_limit, endpoint_name, endpoint.weight).await;
+    let response = client
+        .request(endpoint.method(), &link)
+        .send()
+        .await
+        .unwrap_or_else(|err| panic!("Reference data cannot be preloaded: {}", err));
+    response
+        .json()
+        .await
+        .unwrap_or_else(|err| panic!("Response of {} is not valid json: {}", endpoint_name, err))
+}
+
+/// Preloads asset pairs and assets from the endpoints named by the profile,
+/// so steps can look up minimal order sizes and precision without requesting them.
+///
+/// # Arguments
+///
+/// * `profile` - Profile of the run
+/// * `endpoints` - Endpoints known to the suite
+///
+pub async fn preload(profile: &Profile, endpoints: &EndpointRegistry) {
+    let settings = &profile.reference;
+    let (client, _) = http_client::build_client(profile);
+    let mut reference = ReferenceData::default();
+    if let Some(endpoint_name) = &settings.pairs_endpoint {
+        let json_response = fetch_listing(&client, profile, endpoints, endpoint_name).await;
+        reference.pairs = listing_of(endpoint_name, &json_response)
+            .into_iter()
+            .map(|(name, pair)| PairInfo { name, ..pair })
+            .collect();
+    }
+    if let Some(endpoint_name) = &settings.assets_endpoint {
+        let json_response = fetch_listing(&client, profile, endpoints, endpoint_name).await;
+        reference.assets = listing_of(endpoint_name, &json_response)
+            .into_iter()
+            .map(|(name, asset)| AssetInfo { name, ..asset })
+            .collect();
+    }
+    if settings.pairs_endpoint.is_some() || settings.assets_endpoint.is_some() {
+        println!(
+            "Reference data: {} asset pairs, {} assets",
+            reference.pairs.len(),
+            reference.assets.len()
+        );
+    }
+    *REFERENCE
+        .lock()
+        .expect("Reference data should not be poisoned") = Arc::new(reference);
+}
@@ -0,0 +1,112 @@
+use super::config::ConfigError;
+use aes_gcm::aead::{Aead, NewAead};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Environment variable with the base64 encoded 256-bit key report artifacts are encrypted with.
+pub const KEY_ENV_VAR: &str = "REPORT_ENCRYPTION_KEY";
+
+/// Prefix of encrypted artifacts, followed by the nonce and the AES-256-GCM ciphertext.
+const MAGIC: &[u8] = b"api-verify-aes256gcm\n";
+
+const NONCE_LENGTH: usize = 12;
+
+/// Reads the key from REPORT_ENCRYPTION_KEY; artifacts are written in plain text when it is not set.
+pub fn key_from_env() -> Result<Option<Vec<u8>>, ConfigError> {
+    let value = match env::var(KEY_ENV_VAR) {
+        Ok(value) => value,
+        Err(_) => return Ok(None),
+    };
+    let malformed = |reason: String| ConfigError::Malformed {
+        variable: KEY_ENV_VAR.to_owned(),
+        reason,
+    };
+    let key = base64::decode(value.trim())
+        .map_err(|err| malformed(format!("value is not valid base64 ({})", err)))?;
+    if key.len() != 32 {
+        return Err(malformed(format!(
+            "key should have 32 bytes, got {}",
+            key.len()
+        )));
+    }
+    Ok(Some(key))
+}
+
+fn key() -> Option<Vec<u8>> {
+    key_from_env().unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// Whether artifacts are encrypted, i.e. REPORT_ENCRYPTION_KEY is set.
+pub fn is_enabled() -> bool {
+    key().is_some()
+}
+
+/// Encrypts the content of an artifact when REPORT_ENCRYPTION_KEY is set, returning it unchanged otherwise.
+///
+/// # Arguments
+///
+/// * `content` - Content of the artifact, already redacted
+///
+pub fn seal(content: Vec<u8>) -> Vec<u8> {
+    let key = match key() {
+        Some(key) => key,
+        None => return content,
+    };
+    let mut nonce = [0u8; NONCE_LENGTH];
+    getrandom::getrandom(&mut nonce).expect("Random nonce should be possible to draw");
+    let cipher = Aes256Gcm::new(Key::from_slice(&key));
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), content.as_slice())
+        .expect("Artifact should be possible to encrypt");
+    [MAGIC, &nonce, &ciphertext].concat()
+}
+
+/// Decrypts the content of an artifact written by `seal`; content which is not encrypted is returned unchanged.
+///
+/// # Arguments
+///
+/// * `content` - Content of the artifact file
+///
+pub fn open(content: Vec<u8>) -> io::Result<Vec<u8>> {
+    if !content.starts_with(MAGIC) {
+        return Ok(content);
+    }
+    let sealed = &content[MAGIC.len()..];
+    if sealed.len() < NONCE_LENGTH {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Encrypted artifact is truncated",
+        ));
+    }
+    let key = key_from_env()
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err.to_string()))?
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Artifact is encrypted; set {} to read it", KEY_ENV_VAR),
+            )
+        })?;
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LENGTH);
+    Aes256Gcm::new(Key::from_slice(&key))
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Artifact cannot be decrypted with the key, or it was modified",
+            )
+        })
+}
+
+/// Reads an artifact as text, decrypting it if needed.
+///
+/// # Arguments
+///
+/// * `path` - Path of the artifact
+///
+pub fn read_to_string(path: impl AsRef<Path>) -> io::Result<String> {
+    let content = open(fs::read(path)?)?;
+    String::from_utf8(content).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
@@ -0,0 +1,409 @@
+use super::redaction;
+use super::signing::{PayloadHash, SignatureEncoding, SignerConfig};
+use reqwest;
+use reqwest::header::HeaderMap;
+
+/// HTTP method used for every request to private API.
+pub const PRIVATE_API_METHOD: &str = "POST";
+
+/// Request to private API with all values needed to authenticate it.
+/// Once signed, it can be sent any number of times without changing the nonce or signature.
+#[derive(Debug, Clone)]
+pub struct SignedRequest {
+    pub full_link: String,
+    pub nonce: String,
+    pub url_encoded_payload: String,
+    pub api_key: String,
+    pub signature: String,
+    /// Headers which are a part of the signature, sent along with it
+    pub signed_headers: Vec<(String, String)>,
+}
+
+/// Assembles and sends a request to private api.
+/// The function supports 2FA and needs to be given an OTP secret key.
+/// The procedure of assembling a get request to private API consists of:
+/// * Preparing "nonce" and "otp" values, which are contained in key-value vector
+/// * Using the vector to create url encoded payload
+/// * Using the paylaod and nonce value along with the private key to create the signature
+/// * Sending the request using public key and signature as headers and url encoded payload as body
+///
+/// # Arguments
+///
+/// * `api_key` - Public key for API
+/// * `api_secret` - Private key for API
+/// * `otp_secret` - Secret serving as private key to generate one time password
+/// * `api_link` - Basic link to API, without any predefined endpoint
+/// * `endpoint_path` - Specific endpoint which is the target of sent requests
+/// * `signer_config` - Conventions used by the API to verify signatures
+///
+pub async fn private_api_request(
+    api_key: &str,
+    api_secret: &str,
+    otp_secret: &str,
+    api_link: &str,
+    endpoint_path: &str,
+    signer_config: &SignerConfig,
+) -> reqwest::Result<reqwest::Response> {
+    let signed_request = sign_private_request(
+        api_key,
+        api_secret,
+        otp_secret,
+        api_link,
+        endpoint_path,
+        signer_config,
+        0,
+    );
+    send_signed_request(&reqwest::Client::new(), &signed_request).await
+}
+
+/// Assembles a request to private api without sending it;
+/// see `private_api_request` for the description of the procedure and arguments.
+/// Additionally, the nonce can be shifted by `clock_skew_seconds`, simulating a client
+/// whose clock is behind (negative values) or ahead (positive values) of the API clock.
+pub fn sign_private_request(
+    api_key: &str,
+    api_secret: &str,
+    otp_secret: &str,
+    api_link: &str,
+    endpoint_path: &str,
+    signer_config: &SignerConfig,
+    clock_skew_seconds: i64,
+) -> SignedRequest {
+    sign_private_request_with(
+        api_key,
+        api_secret,
+        otp_secret,
+        api_link,
+        endpoint_path,
+        signer_config,
+        clock_skew_seconds,
+        &[],
+    )
+}
+
+/// Assembles a request to private api like `sign_private_request`,
+/// with `parameters` appended to the payload after "nonce" and "otp" values.
+#[allow(clippy::too_many_arguments)]
+pub fn sign_private_request_with(
+    api_key: &str,
+    api_secret: &str,
+    otp_secret: &str,
+    api_link: &str,
+    endpoint_path: &str,
+    signer_config: &SignerConfig,
+    clock_skew_seconds: i64,
+    parameters: &[(&str, &str)],
+) -> SignedRequest {
+    let otp = properties::get_otp_code(&otp_secret.into());
+    let nonce = properties::get_nonce(clock_skew_seconds);
+    let mut body_data: Vec<(&str, &str)> = vec![("nonce", &nonce), ("otp", &otp)];
+    body_data.extend_from_slice(parameters);
+    let url_encoded_payload: String = url_encoding::url_encode(&body_data);
+    let signature = encryption::get_signature(
+        &nonce,
+        &url_encoded_payload,
+        PRIVATE_API_METHOD,
+        &host_of(api_link),
+        endpoint_path,
+        api_secret,
+        signer_config,
+    );
+    redaction::register_derived_secret(&signature);
+    let full_link = [api_link, endpoint_path].concat();
+
+    SignedRequest {
+        full_link,
+        nonce,
+        url_encoded_payload,
+        api_key: api_key.to_owned(),
+        signature,
+        signed_headers: signer_config.signed_headers.clone(),
+    }
+}
+
+/// Sends the signed request verbatim, so it can be used to replay earlier requests.
+///
+/// # Arguments
+///
+/// * `client` - Client configured according to the profile
+/// * `signed_request` - Request assembled by `sign_private_request`
+///
+pub async fn send_signed_request(
+    client: &reqwest::Client,
+    signed_request: &SignedRequest,
+) -> reqwest::Result<reqwest::Response> {
+    send_signed_request_with_headers(client, signed_request, HeaderMap::new()).await
+}
+
+/// Sends the signed request like `send_signed_request`, with additional headers,
+/// e.g. the ID of the request; unlike the signed headers of the request, these are not signed.
+///
+/// # Arguments
+///
+/// * `client` - Client configured according to the profile
+/// * `signed_request` - Request assembled by `sign_private_request`
+/// * `headers` - Headers added to the request
+///
+pub async fn send_signed_request_with_headers(
+    client: &reqwest::Client,
+    signed_request: &SignedRequest,
+    headers: HeaderMap,
+) -> reqwest::Result<reqwest::Response> {
+    let result = requesting::send_request(
+        client,
+        &signed_request.full_link,
+        &signed_request.url_encoded_payload,
+        &signed_request.api_key,
+        &signed_request.signature,
+        &signed_request.signed_headers,
+        headers,
+    )
+    .await;
+    result
+}
+
+/// Extracts host, along with non-default port, from the link to API,
+/// as it is sent in the "Host" header.
+fn host_of(api_link: &str) -> String {
+    match url::Url::parse(api_link) {
+        Ok(url) => match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_owned(),
+            (None, _) => String::new(),
+        },
+        Err(_) => String::new(),
+    }
+}
+
+mod properties {
+    use boringauth::oath::TOTPBuilder;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    /// Creates otp code from otp secret.
+    ///
+    /// # Arguments
+    ///
+    /// * `otp_secret` - Secret serving as private key to generate one time password
+    ///
+    pub fn get_otp_code(otp_secret: &String) -> String {
+        let code = TOTPBuilder::new()
+            .base32_key(otp_secret)
+            .finalize()
+            .expect("Incorrect OTP secret")
+            .generate();
+        code
+    }
+
+    /// Creates nonce from current time timestamp;
+    /// It serves as a value that increases across the request sent to API.
+    ///
+    /// # Arguments
+    ///
+    /// * `clock_skew_seconds` - Offset added to the current time, usually 0
+    ///
+    pub fn get_nonce(clock_skew_seconds: i64) -> String {
+        let start = SystemTime::now();
+        let since_the_epoch = start
+            .duration_since(UNIX_EPOCH)
+            .expect("Time interval from unix epoch should be positive");
+        let skewed_millis = since_the_epoch.as_millis() as i128 + clock_skew_seconds as i128 * 1000;
+        skewed_millis.to_string()
+    }
+}
+
+mod url_encoding {
+    use url::form_urlencoded::Serializer;
+    /// Parses the vector of key-value pairs into urlencoded payload serving as request body.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Vector of key-value pairs
+    ///    
+    pub fn url_encode(data: &[(&str, &str)]) -> String {
+        Serializer::new(String::new())
+            .extend_pairs(data.iter())
+            .finish()
+    }
+}
+
+pub mod encryption {
+    use super::{PayloadHash, SignatureEncoding, SignerConfig};
+    use hmac::{Hmac, Mac};
+    use sha2::{Digest, Sha256, Sha512};
+    type HmacSha512 = Hmac<Sha512>;
+    /// Hashes the payload prefixed by nonce, or returns `None` if the API signs the payload directly.
+    ///
+    /// # Arguments
+    ///
+    /// * `nonce` - A timestamp or value which increases per every request sent
+    /// * `url_encoded_payload` - Data ready to be sent as request body
+    /// * `payload_hash` - Digest used to hash the payload
+    ///    
+    fn hash_payload(
+        nonce: &str,
+        url_encoded_payload: &str,
+        payload_hash: PayloadHash,
+    ) -> Option<Vec<u8>> {
+        let nonce_prepended_payload = [nonce, url_encoded_payload].concat().into_bytes();
+        match payload_hash {
+            PayloadHash::Sha256 => Some(
+                Sha256::new()
+                    .chain_update(nonce_prepended_payload)
+                    .finalize()
+                    .to_vec(),
+            ),
+            PayloadHash::Sha512 => Some(
+                Sha512::new()
+                    .chain_update(nonce_prepended_payload)
+                    .finalize()
+                    .to_vec(),
+            ),
+            PayloadHash::None => None,
+        }
+    }
+    /// Bytes of the request component if the API signs it, empty otherwise.
+    fn signed_component(is_signed: bool, component: &str) -> &[u8] {
+        if is_signed {
+            component.as_bytes()
+        } else {
+            &[]
+        }
+    }
+    /// Creates a message which is signed with the API secret.
+    /// When the payload is hashed, the message consists of hashed payload
+    /// prefixed by HTTP method, host, endpoint path and signed headers; otherwise it is nonce,
+    /// HTTP method, host, endpoint path, signed headers and payload concatenated directly.
+    /// HTTP method, host and endpoint path are skipped if the API does not sign them;
+    /// each signed header is "name:value", with the name in lowercase.
+    ///
+    /// # Arguments
+    ///
+    /// * `nonce` - A timestamp or value which increases per every request sent
+    /// * `url_encoded_payload` - Data ready to be sent as request body
+    /// * `method` - HTTP method of the request, e.g. POST
+    /// * `host` - Host of the API, as sent in the "Host" header
+    /// * `endpoint_path` - Path to an endpoint, NOT prefixed by link to API
+    /// * `signer_config` - Conventions used by the API to verify signatures
+    ///
+    pub fn build_message(
+        nonce: &str,
+        url_encoded_payload: &str,
+        method: &str,
+        host: &str,
+        endpoint_path: &str,
+        signer_config: &SignerConfig,
+    ) -> Vec<u8> {
+        let signed_headers: String = signer_config
+            .signed_headers
+            .iter()
+            .map(|(name, value)| format!("{}:{}", name.to_lowercase(), value))
+            .collect();
+        let request_target = [
+            signed_component(signer_config.sign_method, method),
+            signed_component(signer_config.sign_host, host),
+            signed_component(signer_config.sign_path, endpoint_path),
+            signed_headers.as_bytes(),
+        ]
+        .concat();
+        let message = match hash_payload(nonce, url_encoded_payload, signer_config.payload_hash) {
+            Some(hashed_payload) => [request_target.as_slice(), hashed_payload.as_slice()].concat(),
+            None => [
+                nonce.as_bytes(),
+                request_target.as_slice(),
+                url_encoded_payload.as_bytes(),
+            ]
+            .concat(),
+        };
+        message
+    }
+
+    fn get_mac(secret_bytes: &[u8], message: &[u8]) -> Vec<u8> {
+        let mut mac =
+            HmacSha512::new_from_slice(secret_bytes).expect("HMAC can take key of any size");
+        mac.update(message.as_ref());
+        mac.finalize().into_bytes().to_vec()
+    }
+
+    fn encrypt_message(
+        message: &[u8],
+        secret_bytes: &[u8],
+        signature_encoding: SignatureEncoding,
+    ) -> String {
+        let mac_bytes = get_mac(secret_bytes, message);
+        let signature = signature_encoding.encode(&mac_bytes);
+        signature
+    }
+    /// Creates signature used for authentication.
+    ///
+    /// # Arguments
+    ///
+    /// * `nonce` - A timestamp or value which increases per every request sent
+    /// * `url_encoded_payload` - Data ready to be sent as request body
+    /// * `method` - HTTP method of the request, e.g. POST
+    /// * `host` - Host of the API, as sent in the "Host" header
+    /// * `endpoint_path` - Path to an endpoint, NOT prefixed by link to API
+    /// * `api_secret` - Private key for API
+    /// * `signer_config` - Conventions used by the API to verify signatures
+    ///
+    pub fn get_signature(
+        nonce: &str,
+        url_encoded_payload: &str,
+        method: &str,
+        host: &str,
+        endpoint_path: &str,
+        api_secret: &str,
+        signer_config: &SignerConfig,
+    ) -> String {
+        let message = build_message(
+            nonce,
+            url_encoded_payload,
+            method,
+            host,
+            endpoint_path,
+            signer_config,
+        );
+        let api_secret_bytes = signer_config.secret_encoding.decode(api_secret);
+        let signature = encrypt_message(
+            &message,
+            &api_secret_bytes,
+            signer_config.signature_encoding,
+        );
+        signature
+    }
+}
+
+mod requesting {
+    /// Sends GET request built from prepared payload and assembled signature
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - Client used to send the request
+    /// * `full_link` - Link to API combined with specified endpoint
+    /// * `url_encoded_payload` - Data ready to be sent as request body
+    /// * `api_key` - Public key to API
+    /// * `signature` - Signature used for authentication
+    /// * `signed_headers` - Headers which are a part of the signature
+    /// * `headers` - Additional headers, which are not signed
+    ///
+    pub async fn send_request(
+        client: &reqwest::Client,
+        full_link: &str,
+        url_encoded_payload: &str,
+        api_key: &str,
+        signature: &str,
+        signed_headers: &[(String, String)],
+        headers: reqwest::header::HeaderMap,
+    ) -> reqwest::Result<reqwest::Response> {
+        let request = signed_headers.iter().fold(
+            client.post(full_link).headers(headers),
+            |request, (name, value)| request.header(name.as_str(), value.as_str()),
+        );
+        let result = request
+            .body(url_encoded_payload.to_owned())
+            .header("API-Key", api_key)
+            .header("API-Sign", signature)
+            .send()
+            .await;
+        result
+    }
+}
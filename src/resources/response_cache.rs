@@ -0,0 +1,109 @@
+use super::http_client::RedirectChain;
+use once_cell::sync::Lazy;
+use reqwest::header::HeaderMap;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Response of a reference-data endpoint kept for reuse within the run.
+#[derive(Debug, Clone)]
+struct CachedResponse {
+    status: u16,
+    headers: HeaderMap,
+    body: Vec<u8>,
+    request_id: Option<String>,
+    redirect_chain: RedirectChain,
+    stored_at: Instant,
+}
+
+/// Response reused from the cache, along with the request ID and redirects of the request which received it.
+pub struct CacheHit {
+    pub response: reqwest::Response,
+    pub request_id: Option<String>,
+    pub redirect_chain: RedirectChain,
+}
+
+impl CachedResponse {
+    fn to_response(&self) -> reqwest::Response {
+        let mut response = http::Response::builder()
+            .status(self.status)
+            .body(self.body.clone())
+            .expect("Cached status should be valid");
+        *response.headers_mut() = self.headers.clone();
+        reqwest::Response::from(response)
+    }
+}
+
+/// Responses of endpoints with `cache_ttl_seconds`, by method and full link, including parameters.
+static RESPONSES: Lazy<Mutex<HashMap<String, CachedResponse>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Key of the cached response of a request.
+///
+/// # Arguments
+///
+/// * `method` - HTTP method of the request
+/// * `url` - Full link of the request, including its query parameters
+///
+pub fn key_of(method: &str, url: &str) -> String {
+    format!("{} {}", method, url)
+}
+
+/// Response cached for the request less than `ttl_seconds` ago, if any;
+/// it is rebuilt for every lookup, so it can be read like a response of the API.
+///
+/// # Arguments
+///
+/// * `key` - Key of the request, see `key_of`
+/// * `ttl_seconds` - Time for which the endpoint allows reusing its responses
+///
+pub fn lookup(key: &str, ttl_seconds: u64) -> Option<CacheHit> {
+    let responses = RESPONSES
+        .lock()
+        .expect("Response cache should not be poisoned");
+    responses
+        .get(key)
+        .filter(|cached| cached.stored_at.elapsed() < Duration::from_secs(ttl_seconds))
+        .map(|cached| CacheHit {
+            response: cached.to_response(),
+            request_id: cached.request_id.clone(),
+            redirect_chain: cached.redirect_chain.clone(),
+        })
+}
+
+/// Reads the response of the API and keeps successful ones for reuse,
+/// returning a response with the same status, headers and body in place of the consumed one.
+///
+/// # Arguments
+///
+/// * `key` - Key of the request, see `key_of`
+/// * `response` - Response received from the API
+/// * `request_id` - Request ID sent with the request, if any
+/// * `redirect_chain` - Redirects the request followed
+///
+pub async fn store(
+    key: &str,
+    response: reqwest::Response,
+    request_id: Option<String>,
+    redirect_chain: RedirectChain,
+) -> reqwest::Result<reqwest::Response> {
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body = response.bytes().await?.to_vec();
+    let cached = CachedResponse {
+        status: status.as_u16(),
+        headers,
+        body,
+        request_id,
+        redirect_chain,
+        stored_at: Instant::now(),
+    };
+    let response = cached.to_response();
+    if status.is_success() {
+        RESPONSES
+            .lock()
+            .expect("Response cache should not be poisoned")
+            .insert(key.to_owned(), cached);
+    }
+    Ok(response)
+}
@@ -0,0 +1,430 @@
+use super::config::DEFAULT_PROFILE;
+use super::correlation;
+use super::redaction;
+use super::report_encryption;
+use super::scrubbing;
+use super::sharding::Shard;
+use super::status_page::{self, ProviderStatus, StatusPageSettings};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Outcomes of scenarios finished in the run, in the order they finished.
+static OUTCOMES: Lazy<Mutex<Vec<ScenarioOutcome>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Status of a finished scenario.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScenarioStatus {
+    Passed,
+    Failed,
+    Skipped,
+    /// Failed, but listed in the baseline of known failures
+    #[serde(rename = "known_issue")]
+    KnownIssue,
+}
+
+/// Outcome of a finished scenario.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioOutcome {
+    pub feature: String,
+    pub scenario: String,
+    pub status: ScenarioStatus,
+    pub duration_ms: u64,
+    /// Description of the failed step or hook, if any
+    pub failure: Option<String>,
+    /// ID of the run the scenario was run in; differs from the one of a resumed result
+    pub run_id: String,
+    /// Reason given in the baseline, when the failure is a known issue
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub known_issue: Option<String>,
+    /// Whether the scenario failed and is reported with the outcome of its second attempt
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub retried: bool,
+    /// Problems found by checks configured as warnings
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// Why the scenario was skipped instead of run, e.g. its endpoint is denied by the profile
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skip_reason: Option<String>,
+}
+
+/// Result of a run of a suite, kept as json next to its JUnit report.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunResult {
+    pub suite: String,
+    /// Profile the suite was run with
+    #[serde(default = "default_profile")]
+    pub profile: String,
+    pub seed: u64,
+    pub run_id: String,
+    /// Part of the scenarios run, e.g. "2/5", when the suite was split across executors
+    #[serde(default)]
+    pub shard: Option<String>,
+    /// Whether the run was interrupted before all scenarios were run
+    #[serde(default)]
+    pub cancelled: bool,
+    pub scenarios: Vec<ScenarioOutcome>,
+    /// State of the provider reported by its status page, queried when scenarios failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_status: Option<ProviderStatus>,
+    /// Reports about the run as a whole, e.g. integrity violations across scenarios
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sections: Vec<RunSection>,
+}
+
+/// Report about the run as a whole, kept in the json result and as output of the "run" test suite of the JUnit report.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunSection {
+    /// Name of the report, e.g. "integrity"
+    pub name: String,
+    pub lines: Vec<String>,
+}
+
+fn default_profile() -> String {
+    DEFAULT_PROFILE.to_owned()
+}
+
+/// Adds the outcome of a finished scenario to the result of the run.
+pub fn record_outcome(outcome: ScenarioOutcome) {
+    OUTCOMES
+        .lock()
+        .expect("Scenario outcomes should not be poisoned")
+        .push(outcome);
+}
+
+/// Number of outcomes of scenarios finished so far in the run.
+pub fn recorded_outcomes() -> usize {
+    OUTCOMES
+        .lock()
+        .expect("Scenario outcomes should not be poisoned")
+        .len()
+}
+
+/// Result of the scenarios finished so far in the run.
+///
+/// # Arguments
+///
+/// * `suite` - Name of the suite, e.g. public
+/// * `seed` - Seed of the run
+/// * `shard` - Part of the scenarios run, if the suite is split across executors
+///
+pub fn run_result(suite: &str, seed: u64, shard: Option<Shard>) -> RunResult {
+    RunResult {
+        suite: suite.to_owned(),
+        profile: env::var("API_PROFILE").unwrap_or_else(|_| DEFAULT_PROFILE.to_owned()),
+        seed,
+        run_id: correlation::run_id().to_owned(),
+        shard: shard.map(|shard| shard.to_string()),
+        cancelled: false,
+        scenarios: OUTCOMES
+            .lock()
+            .expect("Scenario outcomes should not be poisoned")
+            .clone(),
+        provider_status: None,
+        sections: Vec::new(),
+    }
+}
+
+impl RunResult {
+    /// Reads the result written by a previous run.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the json file
+    ///
+    pub fn read(path: &str) -> io::Result<Self> {
+        let content = report_encryption::read_to_string(path)?;
+        serde_json::from_str(&content).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} is not a valid run result: {}", path, err),
+            )
+        })
+    }
+
+    /// Writes the result as json with all secrets masked and sensitive values scrubbed,
+    /// encrypted when a report key is set;
+    /// failures to write are reported without failing the run, secrets found fail it in strict mode.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the json file
+    ///
+    pub fn write(&self, path: &str) {
+        let content =
+            serde_json::to_string_pretty(self).expect("Run result should be serializable");
+        let (redacted, leak_detected) = redaction::redact(&scrubbing::scrub_known_values(&content));
+        let written = Path::new(path)
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(path, report_encryption::seal(redacted.into_bytes())));
+        if let Err(err) = written {
+            eprintln!("Writing run result {} failed: {}", path, err);
+        }
+        redaction::report_leak(path, leak_detected);
+    }
+
+    /// Whether any scenario recorded warnings, which the JUnit report of cucumber does not contain.
+    pub fn has_warnings(&self) -> bool {
+        self.scenarios
+            .iter()
+            .any(|outcome| !outcome.warnings.is_empty())
+    }
+
+    /// Whether any scenario failed, not counting known issues.
+    pub fn has_failures(&self) -> bool {
+        self.scenarios
+            .iter()
+            .any(|outcome| outcome.status == ScenarioStatus::Failed)
+    }
+
+    /// Attaches the state of the provider to the result when any scenario failed,
+    /// so outages of the provider can be told apart from broken contracts.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - Status page of the profile
+    ///
+    pub async fn attach_provider_status(&mut self, settings: &StatusPageSettings) {
+        if self.has_failures() {
+            self.provider_status = status_page::provider_status(settings).await;
+        }
+    }
+
+    /// Whether any scenario was skipped with a reason, which the JUnit report of cucumber reports as failed.
+    pub fn has_skip_reasons(&self) -> bool {
+        self.scenarios
+            .iter()
+            .any(|outcome| outcome.skip_reason.is_some())
+    }
+
+    /// Feature and scenario names of the scenarios which passed; a scenario reported many times,
+    /// e.g. for every example of an outline, passed only if all its runs passed.
+    pub fn passed_scenarios(&self) -> HashSet<(String, String)> {
+        let not_passed: HashSet<(&str, &str)> = self
+            .scenarios
+            .iter()
+            .filter(|outcome| outcome.status != ScenarioStatus::Passed)
+            .map(|outcome| (outcome.feature.as_str(), outcome.scenario.as_str()))
+            .collect();
+        let passed = self
+            .scenarios
+            .iter()
+            .filter(|outcome| {
+                !not_passed.contains(&(outcome.feature.as_str(), outcome.scenario.as_str()))
+            })
+            .map(|outcome| (outcome.feature.clone(), outcome.scenario.clone()))
+            .collect();
+        passed
+    }
+
+    /// Adds the report about the run as its section, unless the report is empty.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the section, e.g. "integrity"
+    /// * `report` - Report printed as lines of the section
+    ///
+    pub fn add_section(&mut self, name: &str, report: &impl fmt::Display) {
+        let lines: Vec<String> = report
+            .to_string()
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(str::to_owned)
+            .collect();
+        if !lines.is_empty() {
+            self.sections.push(RunSection {
+                name: name.to_owned(),
+                lines,
+            });
+        }
+    }
+
+    pub fn has_sections(&self) -> bool {
+        !self.sections.is_empty()
+    }
+
+    /// Result of the run resuming this one:
+    /// passed scenarios of this result followed by the ones run again.
+    ///
+    /// # Arguments
+    ///
+    /// * `resumed` - Result of the scenarios run again
+    ///
+    pub fn resumed_with(&self, resumed: RunResult) -> RunResult {
+        let passed = self.passed_scenarios();
+        let mut scenarios: Vec<ScenarioOutcome> = self
+            .scenarios
+            .iter()
+            .filter(|outcome| passed.contains(&(outcome.feature.clone(), outcome.scenario.clone())))
+            .cloned()
+            .collect();
+        scenarios.extend(resumed.scenarios);
+        RunResult {
+            scenarios,
+            ..resumed
+        }
+    }
+
+    /// Writes the result as a JUnit report, with a test suite per feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `report` - Output of the report, e.g. a `ReportBuffer`
+    ///
+    pub fn write_junit(&self, report: &mut impl Write) -> io::Result<()> {
+        let test_suites = group_outcomes(&self.scenarios, |outcome| outcome.feature.clone());
+        let name = if self.cancelled {
+            format!("{} (cancelled)", self.suite)
+        } else {
+            self.suite.clone()
+        };
+        let mut comments = Vec::new();
+        if self.cancelled {
+            comments.push("CANCELLED".to_owned());
+        }
+        if let Some(provider_status) = &self.provider_status {
+            comments.push(format!("PROVIDER_STATUS={}", provider_status));
+        }
+        comments.push(format!("SEED={}", self.seed));
+        comments.push(format!("RUN_ID={}", self.run_id));
+        write_junit_suites(report, &name, &comments, &test_suites, &self.sections)
+    }
+}
+
+/// Groups the outcomes by the key, keeping the order in which the keys first occur.
+///
+/// # Arguments
+///
+/// * `outcomes` - Outcomes of scenarios
+/// * `key` - Name of the group of an outcome, e.g. its feature
+///
+pub fn group_outcomes<'a>(
+    outcomes: impl IntoIterator<Item = &'a ScenarioOutcome>,
+    key: impl Fn(&ScenarioOutcome) -> String,
+) -> Vec<(String, Vec<&'a ScenarioOutcome>)> {
+    let mut groups: Vec<(String, Vec<&ScenarioOutcome>)> = Vec::new();
+    for outcome in outcomes {
+        let name = key(outcome);
+        match groups.iter_mut().find(|(group, _)| *group == name) {
+            Some((_, group_outcomes)) => group_outcomes.push(outcome),
+            None => groups.push((name, vec![outcome])),
+        }
+    }
+    groups
+}
+
+/// XML comment of the text, e.g. "<!-- SEED=42 -->", with "--", which may not occur in comments, shortened.
+pub fn xml_comment(text: &str) -> String {
+    format!("<!-- {} -->", text.replace("--", "-"))
+}
+
+/// Writes the outcomes as a JUnit document, with a test suite per group,
+/// followed by a "run" test suite with the sections about the run, if there are any.
+/// Comments about the run precede the root element, so readers of the report find them first.
+///
+/// # Arguments
+///
+/// * `report` - Output of the report
+/// * `name` - Name of the whole document
+/// * `comments` - Texts of comments about the run, e.g. "SEED=42"
+/// * `test_suites` - Outcomes grouped into test suites, by name of the test suite
+/// * `sections` - Reports about the run as a whole
+///
+pub fn write_junit_suites(
+    report: &mut impl Write,
+    name: &str,
+    comments: &[String],
+    test_suites: &[(String, Vec<&ScenarioOutcome>)],
+    sections: &[RunSection],
+) -> io::Result<()> {
+    writeln!(report, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    for comment in comments {
+        writeln!(report, "{}", xml_comment(comment))?;
+    }
+    writeln!(report, r#"<testsuites name="{}">"#, escape(name))?;
+    for (test_suite, scenarios) in test_suites {
+        let count = |status| {
+            scenarios
+                .iter()
+                .filter(|outcome| outcome.status == status)
+                .count()
+        };
+        writeln!(
+            report,
+            r#"  <testsuite name="{}" tests="{}" failures="{}" skipped="{}">"#,
+            escape(test_suite),
+            scenarios.len(),
+            count(ScenarioStatus::Failed),
+            count(ScenarioStatus::Skipped) + count(ScenarioStatus::KnownIssue)
+        )?;
+        for outcome in scenarios {
+            write!(
+                report,
+                r#"    <testcase name="{}" classname="{}" time="{:.3}""#,
+                escape(&outcome.scenario),
+                escape(test_suite),
+                outcome.duration_ms as f64 / 1000.0
+            )?;
+            match outcome.status {
+                ScenarioStatus::Passed if outcome.warnings.is_empty() => writeln!(report, "/>")?,
+                ScenarioStatus::Passed => writeln!(
+                    report,
+                    ">\n      <system-out>{}</system-out>\n    </testcase>",
+                    escape(&outcome.warnings.join("\n"))
+                )?,
+                ScenarioStatus::Failed => writeln!(
+                    report,
+                    ">\n      <failure message=\"{}\"/>\n    </testcase>",
+                    escape(outcome.failure.as_deref().unwrap_or_default())
+                )?,
+                ScenarioStatus::Skipped => match &outcome.skip_reason {
+                    Some(reason) => writeln!(
+                        report,
+                        ">\n      <skipped message=\"{}\"/>\n    </testcase>",
+                        escape(reason)
+                    )?,
+                    None => writeln!(report, ">\n      <skipped/>\n    </testcase>")?,
+                },
+                ScenarioStatus::KnownIssue => writeln!(
+                    report,
+                    ">\n      <skipped message=\"known issue: {}\"/>\n    </testcase>",
+                    escape(outcome.known_issue.as_deref().unwrap_or_default())
+                )?,
+            }
+        }
+        writeln!(report, "  </testsuite>")?;
+    }
+    if !sections.is_empty() {
+        writeln!(
+            report,
+            r#"  <testsuite name="run" tests="{}" failures="0" skipped="0">"#,
+            sections.len()
+        )?;
+        for section in sections {
+            writeln!(
+                report,
+                "    <testcase name=\"{}\" classname=\"run\" time=\"0.000\">\n      <system-out>{}</system-out>\n    </testcase>",
+                escape(&section.name),
+                escape(&section.lines.join("\n"))
+            )?;
+        }
+        writeln!(report, "  </testsuite>")?;
+    }
+    writeln!(report, "</testsuites>")
+}
+
+/// Escapes text placed in XML or HTML documents.
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
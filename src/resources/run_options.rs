@@ -0,0 +1,50 @@
+use super::sharding::Shard;
+use clap::Args;
+
+/// Options of a run of the suite, given after `--` to `cargo test`, besides the ones of cucumber.
+#[derive(Debug, Clone, Default, Args)]
+pub struct RunOptions {
+    /// Runs only the scenarios which did not pass in the previous run, merging their results with it
+    #[clap(long)]
+    pub resume: bool,
+    /// Runs only the part of the scenarios assigned to the executor, e.g. 2/5;
+    /// its reports are written to files with the "-shard-2-of-5" suffix
+    #[clap(long)]
+    pub shard: Option<Shard>,
+    /// Runs private scenarios with the keys of the account from `accounts` of the profile;
+    /// its reports are written to files with the "-<account>" suffix and reported as a separate suite
+    #[clap(long)]
+    pub account: Option<String>,
+}
+
+impl RunOptions {
+    /// Name of the report files of the suite, without extension, e.g. "public-shard-2-of-5" or "private-subA".
+    ///
+    /// # Arguments
+    ///
+    /// * `suite` - Name of the suite, e.g. public
+    ///
+    pub fn report_name(&self, suite: &str) -> String {
+        let suite = match &self.account {
+            Some(account) => format!("{}-{}", suite, account),
+            None => suite.to_owned(),
+        };
+        match &self.shard {
+            Some(shard) => format!("{}{}", suite, shard.file_suffix()),
+            None => suite,
+        }
+    }
+
+    /// Name of the suite in the results, e.g. "private[subA]" when run with the keys of an account.
+    ///
+    /// # Arguments
+    ///
+    /// * `suite` - Name of the suite, e.g. private
+    ///
+    pub fn suite_name(&self, suite: &str) -> String {
+        match &self.account {
+            Some(account) => format!("{}[{}]", suite, account),
+            None => suite.to_owned(),
+        }
+    }
+}
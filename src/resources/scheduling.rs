@@ -0,0 +1,99 @@
+use super::tags::{all_tags, tag_argument};
+use cucumber::gherkin;
+use cucumber::runner::ScenarioType;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+
+/// Locks of the resources named in `@exclusive(resource)` tags, by resource name.
+static RESOURCE_LOCKS: Lazy<Mutex<HashMap<String, Arc<AsyncMutex<()>>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Locks held by the scenarios in progress, by scenario key.
+static HELD_LOCKS: Lazy<Mutex<HashMap<String, Vec<OwnedMutexGuard<()>>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Scenarios tagged `@serial`, directly or through their rule or feature,
+/// are run when no other scenario is running; the rest are run concurrently.
+///
+/// # Arguments
+///
+/// * `feature` - Feature containing the scenario
+/// * `rule` - Rule containing the scenario, if any
+/// * `scenario` - The scenario
+///
+pub fn scenario_type(
+    feature: &gherkin::Feature,
+    rule: Option<&gherkin::Rule>,
+    scenario: &gherkin::Scenario,
+) -> ScenarioType {
+    if all_tags(feature, rule, scenario).any(|tag| tag == "serial") {
+        ScenarioType::Serial
+    } else {
+        ScenarioType::Concurrent
+    }
+}
+
+/// Names of the resources from `@exclusive(resource)` tags, sorted,
+/// so every scenario locks them in the same order.
+///
+/// # Arguments
+///
+/// * `feature` - Feature containing the scenario
+/// * `rule` - Rule containing the scenario, if any
+/// * `scenario` - The scenario
+///
+pub fn exclusive_resources(
+    feature: &gherkin::Feature,
+    rule: Option<&gherkin::Rule>,
+    scenario: &gherkin::Scenario,
+) -> Vec<String> {
+    let mut resources: Vec<String> = all_tags(feature, rule, scenario)
+        .filter_map(|tag| tag_argument(tag, "exclusive"))
+        .map(str::to_owned)
+        .collect();
+    resources.sort();
+    resources.dedup();
+    resources
+}
+
+/// Waits until none of the resources is used by another scenario, then marks them as used
+/// by the scenario, until `release_resources` is called with its key.
+///
+/// # Arguments
+///
+/// * `scenario_key` - Key of the scenario
+/// * `resources` - Sorted names of the resources
+///
+pub async fn lock_resources(scenario_key: &str, resources: &[String]) {
+    let mut guards = Vec::new();
+    for resource in resources {
+        let lock = RESOURCE_LOCKS
+            .lock()
+            .expect("Resource locks should not be poisoned")
+            .entry(resource.clone())
+            .or_insert_with(|| Arc::new(AsyncMutex::new(())))
+            .clone();
+        guards.push(lock.lock_owned().await);
+    }
+    if !guards.is_empty() {
+        HELD_LOCKS
+            .lock()
+            .expect("Held locks should not be poisoned")
+            .insert(scenario_key.to_owned(), guards);
+    }
+}
+
+/// Releases resources used by the scenario.
+///
+/// # Arguments
+///
+/// * `scenario_key` - Key of the scenario
+///
+pub fn release_resources(scenario_key: &str) {
+    HELD_LOCKS
+        .lock()
+        .expect("Held locks should not be poisoned")
+        .remove(scenario_key);
+}
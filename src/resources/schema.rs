@@ -0,0 +1,121 @@
+use super::drift;
+use super::number_format;
+use itertools::Itertools;
+use jsonschema::{Draft, JSONSchema};
+use std::fs;
+
+/// Reads json schema from the "./schemas" catalogue.
+///
+/// # Arguments
+///
+/// * `schema_file` - Name of the file containing the schema
+///
+pub fn load_schema(schema_file: &str) -> serde_json::Value {
+    let schema: serde_json::Value = serde_json::from_str(
+        fs::read_to_string(format!("./schemas/{}", schema_file))
+            .expect("Something went wrong reading the file")
+            .as_str(),
+    )
+    .expect("Schema secret should be possible to parse to json");
+    schema
+}
+
+/// Reads json schema from the "./schemas" catalogue like `load_schema`, returning errors instead of panicking.
+///
+/// # Arguments
+///
+/// * `schema_file` - Name of the file containing the schema
+///
+pub fn read_schema(schema_file: &str) -> Result<serde_json::Value, String> {
+    let path = format!("./schemas/{}", schema_file);
+    let content =
+        fs::read_to_string(&path).map_err(|err| format!("Cannot read {}: {}", path, err))?;
+    serde_json::from_str(&content).map_err(|err| format!("{} is not valid json: {}", path, err))
+}
+
+/// Validates json response against the schema, panicking with all found errors.
+/// Fields of the response are also compared with the schema, for the drift report of the run.
+///
+/// # Arguments
+///
+/// * `json_response` - Response body parsed to json
+/// * `schema_file` - Name of the file in "./schemas" containing the schema
+///
+pub fn verify_against_schema(json_response: &serde_json::Value, schema_file: &str) {
+    let schema = load_schema(schema_file);
+    drift::observe(schema_file, &schema, json_response);
+    let compiled = compile(&schema);
+    if let Some(joined_errors) = validation_errors(&compiled, json_response) {
+        panic!("The following errors occured: {}", joined_errors)
+    }
+}
+
+/// Validates json response against the schema like `verify_against_schema`,
+/// returning found errors instead of panicking with them.
+///
+/// # Arguments
+///
+/// * `json_response` - Response body parsed to json
+/// * `schema_file` - Name of the file in "./schemas" containing the schema
+///
+pub fn schema_errors(json_response: &serde_json::Value, schema_file: &str) -> Vec<String> {
+    let schema = load_schema(schema_file);
+    drift::observe(schema_file, &schema, json_response);
+    let compiled = compile(&schema);
+    let errors = match compiled.validate(json_response) {
+        Ok(_) => Vec::new(),
+        Err(errors) => errors.map(|err| format!("{}", err)).collect(),
+    };
+    errors
+}
+
+/// Reads and compiles json schema, so it can be used to validate many values;
+/// fails when the file cannot be read or does not hold a valid schema.
+///
+/// # Arguments
+///
+/// * `schema_file` - Name of the file in "./schemas" containing the schema
+///
+pub fn compile_schema(schema_file: &str) -> Result<JSONSchema, String> {
+    let schema = read_schema(schema_file)?;
+    try_compile(&schema).map_err(|err| format!("Schema {} is invalid: {}", schema_file, err))
+}
+
+/// Compiles json schema, so it can be used to validate many values.
+/// Besides the standard ones, schemas may use formats of `number_format`, e.g. "decimal".
+///
+/// # Arguments
+///
+/// * `schema` - Content of the schema
+///
+pub fn compile(schema: &serde_json::Value) -> JSONSchema {
+    try_compile(schema).expect("Schema should be valid")
+}
+
+/// Compiles json schema like `compile`, returning the error instead of panicking when the schema is invalid.
+///
+/// # Arguments
+///
+/// * `schema` - Content of the schema
+///
+pub fn try_compile(schema: &serde_json::Value) -> Result<JSONSchema, String> {
+    let mut options = JSONSchema::options();
+    options.with_draft(Draft::Draft7);
+    number_format::register_formats(&mut options);
+    options.compile(schema).map_err(|err| err.to_string())
+}
+
+/// Validates json value against compiled schema, returning joined errors if any were found.
+///
+/// # Arguments
+///
+/// * `compiled` - Schema compiled by `compile_schema`
+/// * `json_value` - Value to validate
+///
+pub fn validation_errors(compiled: &JSONSchema, json_value: &serde_json::Value) -> Option<String> {
+    let result = compiled.validate(json_value);
+    match result {
+        Ok(_) => None,
+        Err(errors) => Some(errors.map(|err| format!("{}", err)).join("\n, ")),
+    }
+}
@@ -0,0 +1,234 @@
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Single difference between the old and the new version of schemas.
+#[derive(Debug)]
+pub struct SchemaChange {
+    pub is_breaking: bool,
+    /// Schema file, followed by the location within the schema, e.g. "result.unixtime"
+    pub location: String,
+    pub description: String,
+}
+
+impl fmt::Display for SchemaChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let kind = if self.is_breaking {
+            "BREAKING"
+        } else {
+            "non-breaking"
+        };
+        write!(f, "{:<12} {}: {}", kind, self.location, self.description)
+    }
+}
+
+/// Compares json schemas of the same names in two catalogues and prints the changes.
+/// Changes are breaking when the new schemas take back something the old ones promised:
+/// removed schemas or fields, fields which stopped being required, types or enumerations
+/// which were narrowed, and values newly restricted to an enumeration.
+/// Widened types and enumerations, added fields and newly required ones only promise more,
+/// so they are non-breaking. Fails when any breaking change is found.
+///
+/// # Arguments
+///
+/// * `old_dir` - Catalogue with the current schemas
+/// * `new_dir` - Catalogue with the updated schemas
+///
+pub fn diff_schema_dirs(old_dir: &Path, new_dir: &Path) -> io::Result<()> {
+    let old_schemas = read_schemas(old_dir)?;
+    let new_schemas = read_schemas(new_dir)?;
+
+    let mut changes = Vec::new();
+    for (file_name, old_schema) in &old_schemas {
+        match new_schemas.get(file_name) {
+            Some(new_schema) => diff_schemas(file_name, old_schema, new_schema, &mut changes),
+            None => changes.push(SchemaChange {
+                is_breaking: true,
+                location: file_name.clone(),
+                description: "schema was removed".to_owned(),
+            }),
+        }
+    }
+    for file_name in new_schemas.keys() {
+        if !old_schemas.contains_key(file_name) {
+            changes.push(SchemaChange {
+                is_breaking: false,
+                location: file_name.clone(),
+                description: "schema was added".to_owned(),
+            });
+        }
+    }
+
+    for change in &changes {
+        println!("{}", change);
+    }
+    let breaking_changes = changes.iter().filter(|change| change.is_breaking).count();
+    println!(
+        "{} changes, {} of them breaking",
+        changes.len(),
+        breaking_changes
+    );
+    if breaking_changes > 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("Found {} breaking schema changes", breaking_changes),
+        ));
+    }
+    Ok(())
+}
+
+/// Reads all json files of the catalogue, by file name.
+fn read_schemas(dir: &Path) -> io::Result<BTreeMap<String, Value>> {
+    let mut schemas = BTreeMap::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path
+            .extension()
+            .map_or(true, |extension| extension != "json")
+        {
+            continue;
+        }
+        let content = fs::read_to_string(&path)?;
+        let schema = serde_json::from_str(&content).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Cannot parse {}: {}", path.display(), err),
+            )
+        })?;
+        let file_name = path
+            .file_name()
+            .expect("Read file should have a name")
+            .to_string_lossy()
+            .into_owned();
+        schemas.insert(file_name, schema);
+    }
+    Ok(schemas)
+}
+
+/// Compares two versions of the schema recursively, through object properties and array items,
+/// classifying the changes as described at `diff_schema_dirs`.
+///
+/// # Arguments
+///
+/// * `location` - Location of the schema, e.g. the name of its file
+/// * `old` - Current version of the schema
+/// * `new` - Updated version of the schema
+/// * `changes` - Changes found so far, extended with the ones of this schema
+///
+pub fn diff_schemas(location: &str, old: &Value, new: &Value, changes: &mut Vec<SchemaChange>) {
+    let mut change = |is_breaking: bool, description: String| {
+        changes.push(SchemaChange {
+            is_breaking,
+            location: location.to_owned(),
+            description,
+        })
+    };
+
+    let (old_types, new_types) = (types_of(old), types_of(new));
+    if let (Some(old_types), Some(new_types)) = (&old_types, &new_types) {
+        let removed: Vec<_> = old_types.difference(new_types).collect();
+        let added: Vec<_> = new_types.difference(old_types).collect();
+        if !removed.is_empty() {
+            change(
+                true,
+                format!("type narrowed, no longer accepts {:?}", removed),
+            );
+        }
+        if !added.is_empty() {
+            change(false, format!("type widened, accepts {:?}", added));
+        }
+    }
+
+    let (old_enum, new_enum) = (enum_of(old), enum_of(new));
+    if let (Some(old_enum), Some(new_enum)) = (&old_enum, &new_enum) {
+        let removed: Vec<_> = old_enum.difference(new_enum).collect();
+        let added: Vec<_> = new_enum.difference(old_enum).collect();
+        if !removed.is_empty() {
+            change(true, format!("enumeration narrowed, removed {:?}", removed));
+        }
+        if !added.is_empty() {
+            change(false, format!("enumeration widened, added {:?}", added));
+        }
+    } else if old_enum.is_none() && new_enum.is_some() {
+        change(true, "values were restricted to an enumeration".to_owned());
+    }
+
+    let (old_required, new_required) = (required_of(old), required_of(new));
+    for field in old_required.difference(&new_required) {
+        change(true, format!("field {} is no longer required", field));
+    }
+    for field in new_required.difference(&old_required) {
+        change(false, format!("field {} became required", field));
+    }
+
+    let empty = serde_json::Map::new();
+    let old_properties = old["properties"].as_object().unwrap_or(&empty);
+    let new_properties = new["properties"].as_object().unwrap_or(&empty);
+    for (field, old_property) in old_properties {
+        let field_location = format!("{}.{}", location, field);
+        match new_properties.get(field) {
+            Some(new_property) => {
+                diff_schemas(&field_location, old_property, new_property, changes)
+            }
+            None => changes.push(SchemaChange {
+                is_breaking: true,
+                location: field_location,
+                description: "field was removed".to_owned(),
+            }),
+        }
+    }
+    for field in new_properties.keys() {
+        if !old_properties.contains_key(field) {
+            changes.push(SchemaChange {
+                is_breaking: false,
+                location: format!("{}.{}", location, field),
+                description: "field was added".to_owned(),
+            });
+        }
+    }
+
+    for keyword in &["items", "additionalProperties"] {
+        if old[keyword].is_object() && new[keyword].is_object() {
+            let nested_location = format!("{}[{}]", location, keyword);
+            diff_schemas(&nested_location, &old[keyword], &new[keyword], changes);
+        }
+    }
+}
+
+/// Types accepted by the schema; None when the schema does not restrict the type.
+fn types_of(schema: &Value) -> Option<BTreeSet<String>> {
+    let mut types: BTreeSet<String> = match &schema["type"] {
+        Value::String(schema_type) => std::iter::once(schema_type.clone()).collect(),
+        Value::Array(schema_types) => schema_types
+            .iter()
+            .filter_map(|schema_type| schema_type.as_str().map(str::to_owned))
+            .collect(),
+        _ => return None,
+    };
+    // Integers are numbers, so replacing "integer" with "number" widens the type
+    if types.contains("number") {
+        types.insert("integer".to_owned());
+    }
+    Some(types)
+}
+
+fn enum_of(schema: &Value) -> Option<BTreeSet<String>> {
+    schema["enum"]
+        .as_array()
+        .map(|values| values.iter().map(Value::to_string).collect())
+}
+
+fn required_of(schema: &Value) -> BTreeSet<String> {
+    schema["required"]
+        .as_array()
+        .map(|fields| {
+            fields
+                .iter()
+                .filter_map(|field| field.as_str().map(str::to_owned))
+                .collect()
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,213 @@
+use super::validators::{self, ResponseValidator, ValidationContext, ValidationReport};
+use once_cell::sync::Lazy;
+use rhai::{Dynamic, Engine, Scope, AST};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Rhai scripts with custom checks, payloads and steps, referenced from the profile.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScriptSettings {
+    /// Script files, e.g. "scripts/orders.rhai"; functions of all of them are callable by name
+    pub files: Vec<String>,
+    /// Number of operations after which a script call is aborted, guarding against endless loops
+    pub max_operations: u64,
+}
+
+impl Default for ScriptSettings {
+    fn default() -> Self {
+        Self {
+            files: Vec::new(),
+            max_operations: 1_000_000,
+        }
+    }
+}
+
+/// Compiled scripts of the profile.
+struct Scripts {
+    engine: Engine,
+    ast: AST,
+}
+
+static SCRIPTS: Lazy<Mutex<Option<Arc<Scripts>>>> = Lazy::new(|| Mutex::new(None));
+
+/// Prefix of script functions registered as validators, e.g. "validate_order_totals" for "order_totals".
+const VALIDATOR_PREFIX: &str = "validate_";
+
+/// Compiles the scripts of the profile and registers every function `validate_<name>(response, context)`
+/// as the validator `<name>`; returns the names of the registered validators.
+/// Fails when a validator is already registered under one of the names, e.g. by a plugin.
+///
+/// # Arguments
+///
+/// * `settings` - Script settings of the profile
+///
+pub fn load(settings: &ScriptSettings) -> Result<Vec<String>, String> {
+    if settings.files.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut engine = Engine::new();
+    engine.set_max_operations(settings.max_operations);
+    let mut ast = AST::empty();
+    for file in &settings.files {
+        let file_ast = engine
+            .compile_file(PathBuf::from(file))
+            .map_err(|err| format!("Script {} cannot be compiled: {}", file, err))?;
+        ast = ast.merge(&file_ast);
+    }
+    let functions: Vec<String> = ast
+        .iter_functions()
+        .filter(|function| {
+            function.params.len() == 2 && function.name.starts_with(VALIDATOR_PREFIX)
+        })
+        .map(|function| function.name.to_owned())
+        .collect();
+    *SCRIPTS.lock().expect("Scripts should not be poisoned") =
+        Some(Arc::new(Scripts { engine, ast }));
+    let mut validator_names = Vec::new();
+    for function in functions {
+        let name = function[VALIDATOR_PREFIX.len()..].to_owned();
+        validators::register_new(&name, ScriptValidator { name: function })
+            .map_err(|err| format!("Script validator cannot be registered: {}", err))?;
+        validator_names.push(name);
+    }
+    Ok(validator_names)
+}
+
+fn loaded() -> Arc<Scripts> {
+    SCRIPTS
+        .lock()
+        .expect("Scripts should not be poisoned")
+        .clone()
+        .expect("The profile should list script files in scripts.files")
+}
+
+fn to_dynamic(value: &Value) -> Dynamic {
+    rhai::serde::to_dynamic(value).expect("Json should be possible to pass to scripts")
+}
+
+/// Calls the script function with a single context argument, or with the response and the context.
+fn call(name: &str, response: Option<&Value>, context: &Value) -> Result<Value, String> {
+    let scripts = loaded();
+    let mut scope = Scope::new();
+    let result: Result<Dynamic, _> = match response {
+        Some(response) => scripts.engine.call_fn(
+            &mut scope,
+            &scripts.ast,
+            name,
+            (to_dynamic(response), to_dynamic(context)),
+        ),
+        None => scripts
+            .engine
+            .call_fn(&mut scope, &scripts.ast, name, (to_dynamic(context),)),
+    };
+    let result = result.map_err(|err| format!("Script function {} failed: {}", name, err))?;
+    rhai::serde::from_dynamic(&result).map_err(|err| {
+        format!(
+            "Script function {} returned an unusable value: {}",
+            name, err
+        )
+    })
+}
+
+/// Context of the response passed to scripts, with the endpoint, status, scenario and variables of the profile.
+fn context_of(ctx: &ValidationContext) -> Value {
+    json!({
+        "endpoint": ctx.endpoint_name,
+        "status": ctx.status,
+        "scenario": ctx.scenario_key,
+        "variables": ctx.profile.variables,
+    })
+}
+
+/// Problems of the response described by the result of a check: nothing or `true` when it passes, `false`,
+/// a description or a list of descriptions when it does not.
+fn problems_of(name: &str, result: Value) -> Vec<String> {
+    match result {
+        Value::Null | Value::Bool(true) => Vec::new(),
+        Value::Bool(false) => vec![format!("{} returned false", name)],
+        Value::String(problem) if problem.is_empty() => Vec::new(),
+        Value::String(problem) => vec![problem],
+        Value::Array(problems) => problems
+            .into_iter()
+            .map(|problem| match problem {
+                Value::String(problem) => problem,
+                other => other.to_string(),
+            })
+            .collect(),
+        other => vec![format!("{} returned {} instead of problems", name, other)],
+    }
+}
+
+/// Check of responses defined by a script function `validate_<name>(response, context)`.
+pub struct ScriptValidator {
+    /// Name of the script function
+    name: String,
+}
+
+impl ResponseValidator for ScriptValidator {
+    fn validate(&self, ctx: &ValidationContext, json_response: &Value) -> ValidationReport {
+        let mut report = ValidationReport::passed();
+        match call(&self.name, Some(json_response), &context_of(ctx)) {
+            Ok(result) => problems_of(&self.name, result)
+                .into_iter()
+                .for_each(|problem| report.fail(problem)),
+            Err(err) => report.fail(err),
+        }
+        report
+    }
+}
+
+/// Context of a scenario passed to payload and step scripts: the endpoint, values remembered by the scenario
+/// and variables of the profile.
+///
+/// # Arguments
+///
+/// * `endpoint_name` - Endpoint the scenario requests, if any
+/// * `remembered` - Values remembered by earlier steps, by name
+/// * `variables` - Variables of the profile
+///
+pub fn scenario_context(
+    endpoint_name: Option<&str>,
+    remembered: &HashMap<String, String>,
+    variables: &HashMap<String, String>,
+) -> Value {
+    json!({
+        "endpoint": endpoint_name,
+        "remembered": remembered,
+        "variables": variables,
+    })
+}
+
+/// Values of a map returned by a script, as strings; strings are taken without quotes, other values as json.
+fn string_map_of(name: &str, result: Value) -> Result<Vec<(String, String)>, String> {
+    match result {
+        Value::Null => Ok(Vec::new()),
+        Value::Object(fields) => Ok(fields
+            .into_iter()
+            .map(|(key, value)| match value {
+                Value::String(text) => (key, text),
+                other => (key, other.to_string()),
+            })
+            .collect()),
+        other => Err(format!(
+            "Script function {} returned {} instead of a map",
+            name, other
+        )),
+    }
+}
+
+/// Runs the script function `name(context)` returning a map, e.g. parameters of a request
+/// or values a step remembers for later steps, and returns its entries.
+///
+/// # Arguments
+///
+/// * `name` - Name of the script function
+/// * `context` - Context made by `scenario_context`
+///
+pub fn call_for_map(name: &str, context: &Value) -> Result<Vec<(String, String)>, String> {
+    string_map_of(name, call(name, None, context)?)
+}
@@ -0,0 +1,196 @@
+use super::masking;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Values shorter than this are not scrubbed from free text, where they would match unrelated numbers.
+const MIN_SCRUBBED_TEXT_LEN: usize = 3;
+
+/// Sensitive values scrubbed during the run, along with what they were scrubbed to, so they can be
+/// scrubbed from free text as well, e.g. from assertion messages of failed steps.
+static SCRUBBED_VALUES: Lazy<Mutex<HashMap<String, String>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// How a sensitive value, e.g. a balance or an order volume, is scrubbed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScrubAction {
+    /// Replaces the value with a short digest, so equal values stay equal
+    Hash,
+    /// Keeps the first `keep` characters of the value and replaces the rest with "*"
+    Truncate,
+}
+
+/// Scrubbing of sensitive values matched by a path, e.g. `$.result.*.vol`, in the form used by masks.
+/// Unlike masks, which stabilise volatile fields, scrubbing keeps balances and order volumes out of
+/// snapshots, console output and other CI artifacts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScrubRule {
+    pub path: String,
+    pub action: ScrubAction,
+    /// Characters kept by `truncate`
+    #[serde(default)]
+    pub keep: usize,
+}
+
+/// Applies the rules to the json value.
+///
+/// # Arguments
+///
+/// * `value` - Json value, e.g. response body, which is scrubbed in place
+/// * `rules` - Rules of scrubbing
+///
+pub fn apply(value: &mut Value, rules: &[ScrubRule]) {
+    for rule in rules {
+        scrub_at(value, &masking::segments_of(&rule.path), rule);
+    }
+}
+
+/// Returns the json value scrubbed according to the rules, leaving the original intact.
+///
+/// # Arguments
+///
+/// * `value` - Json value, e.g. response body
+/// * `rules` - Rules of scrubbing
+///
+pub fn scrubbed(value: &Value, rules: &[ScrubRule]) -> Value {
+    let mut scrubbed = value.clone();
+    apply(&mut scrubbed, rules);
+    scrubbed
+}
+
+/// Scrubs a value extracted from the response by its field path, e.g. "result.open.0.vol",
+/// when the path is matched by any of the rules.
+///
+/// # Arguments
+///
+/// * `field_path` - Path of the field the value comes from
+/// * `value` - The extracted value
+/// * `rules` - Rules of scrubbing
+///
+pub fn scrub_field(field_path: &str, value: &str, rules: &[ScrubRule]) -> String {
+    let field_segments = masking::segments_of(field_path);
+    let matching_rule = rules.iter().find(|rule| {
+        let rule_segments = masking::segments_of(&rule.path);
+        rule_segments.len() == field_segments.len()
+            && rule_segments
+                .iter()
+                .zip(&field_segments)
+                .all(|(rule_segment, field_segment)| {
+                    rule_segment == "*" || rule_segment == field_segment
+                })
+    });
+    match matching_rule {
+        Some(rule) => scrub_text(value, rule),
+        None => value.to_owned(),
+    }
+}
+
+/// Replaces the sensitive values scrubbed so far wherever they stand as a whole in the text,
+/// e.g. in failure messages written to the JUnit report and the json result.
+///
+/// # Arguments
+///
+/// * `content` - Text which is about to be written to a report
+///
+pub fn scrub_known_values(content: &str) -> String {
+    let mut scrubbed_values: Vec<(String, String)> = SCRUBBED_VALUES
+        .lock()
+        .expect("Scrubbed values should not be poisoned")
+        .iter()
+        .map(|(value, scrubbed)| (value.clone(), scrubbed.clone()))
+        .collect();
+    scrubbed_values.sort_by_key(|(value, _)| std::cmp::Reverse(value.len()));
+    scrubbed_values
+        .iter()
+        .fold(content.to_owned(), |content, (value, scrubbed)| {
+            replace_whole(&content, value, scrubbed)
+        })
+}
+
+/// Replaces occurrences of the value which are not a part of a longer number or word.
+fn replace_whole(content: &str, value: &str, replacement: &str) -> String {
+    let is_word = |character: Option<char>| {
+        character.map_or(false, |character| {
+            character.is_alphanumeric() || character == '.' || character == '_'
+        })
+    };
+    let mut replaced = String::with_capacity(content.len());
+    let mut rest_start = 0;
+    for (start, _) in content.match_indices(value) {
+        let end = start + value.len();
+        if start < rest_start
+            || is_word(content[..start].chars().next_back())
+            || is_word(content[end..].chars().next())
+        {
+            continue;
+        }
+        replaced.push_str(&content[rest_start..start]);
+        replaced.push_str(replacement);
+        rest_start = end;
+    }
+    replaced.push_str(&content[rest_start..]);
+    replaced
+}
+
+fn scrub_at(value: &mut Value, segments: &[String], rule: &ScrubRule) {
+    let (segment, rest) = match segments.split_first() {
+        Some(split) => split,
+        None => {
+            let text = match value {
+                Value::String(text) => text.clone(),
+                Value::Null => return,
+                other => other.to_string(),
+            };
+            *value = Value::from(scrub_text(&text, rule));
+            return;
+        }
+    };
+    match value {
+        Value::Object(fields) => {
+            for (key, field) in fields.iter_mut() {
+                if segment == "*" || segment == key {
+                    scrub_at(field, rest, rule);
+                }
+            }
+        }
+        Value::Array(elements) => {
+            for (index, element) in elements.iter_mut().enumerate() {
+                if segment == "*" || segment.parse::<usize>().ok() == Some(index) {
+                    scrub_at(element, rest, rule);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Scrubs the text according to the rule, remembering it for `scrub_known_values`.
+fn scrub_text(text: &str, rule: &ScrubRule) -> String {
+    let scrubbed = scrubbed_text(text, rule);
+    if text.chars().count() >= MIN_SCRUBBED_TEXT_LEN && scrubbed != text {
+        SCRUBBED_VALUES
+            .lock()
+            .expect("Scrubbed values should not be poisoned")
+            .insert(text.to_owned(), scrubbed.clone());
+    }
+    scrubbed
+}
+
+fn scrubbed_text(text: &str, rule: &ScrubRule) -> String {
+    match rule.action {
+        ScrubAction::Hash => {
+            let digest = hex::encode(Sha256::digest(text.as_bytes()));
+            format!("sha256:{}", &digest[..16])
+        }
+        ScrubAction::Truncate => {
+            let kept: String = text.chars().take(rule.keep).collect();
+            let scrubbed_length = text.chars().count().saturating_sub(rule.keep);
+            format!("{}{}", kept, "*".repeat(scrubbed_length))
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+
+/// Expectations towards security headers of responses, checked by "the response has secure headers".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SecurityHeaderSettings {
+    /// Minimal max-age of Strict-Transport-Security, in seconds; 0 skips the check
+    pub hsts_min_max_age: u64,
+    /// Whether X-Content-Type-Options has to be "nosniff"
+    pub content_type_options: bool,
+    /// Whether framing has to be restricted by X-Frame-Options or frame-ancestors of Content-Security-Policy
+    pub frame_options: bool,
+    /// Whether the Server header may not disclose a version, e.g. "nginx/1.18.0"
+    pub hide_server_version: bool,
+}
+
+impl Default for SecurityHeaderSettings {
+    fn default() -> Self {
+        Self {
+            hsts_min_max_age: 15_552_000,
+            content_type_options: true,
+            frame_options: true,
+            hide_server_version: true,
+        }
+    }
+}
+
+/// Checks headers of the response against the settings, returning descriptions of the problems.
+///
+/// # Arguments
+///
+/// * `headers` - Headers of the response
+/// * `settings` - Expectations from the profile
+///
+pub fn header_problems(headers: &HeaderMap, settings: &SecurityHeaderSettings) -> Vec<String> {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+    };
+    let mut problems = Vec::new();
+
+    let min_max_age = settings.hsts_min_max_age;
+    if min_max_age > 0 {
+        match header("strict-transport-security") {
+            None => problems.push("Strict-Transport-Security is missing".to_owned()),
+            Some(hsts) => {
+                let max_age = hsts
+                    .split(';')
+                    .filter_map(|directive| directive.trim().split_once('='))
+                    .find(|(name, _)| name.eq_ignore_ascii_case("max-age"))
+                    .and_then(|(_, value)| value.trim_matches('"').parse::<u64>().ok());
+                match max_age {
+                    Some(max_age) if max_age >= min_max_age => {}
+                    _ => problems.push(format!(
+                        "Strict-Transport-Security \"{}\" has max-age lower than {}",
+                        hsts, min_max_age
+                    )),
+                }
+            }
+        }
+    }
+
+    if settings.content_type_options
+        && !header("x-content-type-options")
+            .map_or(false, |value| value.eq_ignore_ascii_case("nosniff"))
+    {
+        problems.push("X-Content-Type-Options is not \"nosniff\"".to_owned());
+    }
+
+    if settings.frame_options {
+        let frame_options = header("x-frame-options").map_or(false, |value| {
+            value.eq_ignore_ascii_case("deny") || value.eq_ignore_ascii_case("sameorigin")
+        });
+        let frame_ancestors = header("content-security-policy")
+            .map_or(false, |policy| policy.contains("frame-ancestors"));
+        if !frame_options && !frame_ancestors {
+            problems.push(
+                "framing is restricted neither by X-Frame-Options nor by frame-ancestors"
+                    .to_owned(),
+            );
+        }
+    }
+
+    if settings.hide_server_version {
+        if let Some(server) = header("server") {
+            if discloses_version(&server) {
+                problems.push(format!("Server header discloses version: {}", server));
+            }
+        }
+    }
+    problems
+}
+
+/// Whether a product of the Server header is followed by its version, e.g. "nginx/1.25.3";
+/// digits elsewhere, as in "cloudflare-nginx" or "AmazonS3", do not count.
+fn discloses_version(server: &str) -> bool {
+    server
+        .split('/')
+        .skip(1)
+        .any(|version| version.starts_with(|c: char| c.is_ascii_digit()))
+}
@@ -0,0 +1,157 @@
+use super::interpolation;
+use super::test_plan;
+use super::translation::StepTranslations;
+use cucumber::{gherkin, parser, Parser};
+use futures::stream::{self, StreamExt};
+use futures::FutureExt;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::env;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Seed of the run, controlling scenario order and any randomized data.
+#[derive(Debug, Clone, Copy)]
+pub struct Seed {
+    pub value: u64,
+    /// Whether the seed was given explicitly, to reproduce a previous run
+    pub is_fixed: bool,
+}
+
+/// Seed of the run, read or drawn once, so the scenario order and every generator follow the same one.
+static RUN_SEED: Lazy<Seed> = Lazy::new(Seed::read_env);
+
+impl Seed {
+    /// Seed of the run from the SEED environment variable;
+    /// when it is not set, a new seed is drawn once per run, so the run can still be reproduced with it.
+    pub fn from_env() -> Self {
+        *RUN_SEED
+    }
+
+    fn read_env() -> Self {
+        match env::var("SEED") {
+            Ok(value) => Self {
+                value: value.trim().parse().unwrap_or_else(|_| {
+                    panic!("SEED should be an unsigned integer, got: {}", value)
+                }),
+                is_fixed: true,
+            },
+            Err(_) => Self {
+                value: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .expect("System time should be after unix epoch")
+                    .as_nanos() as u64,
+                is_fixed: false,
+            },
+        }
+    }
+
+    /// Limit of concurrently run scenarios; a fixed seed runs them one by one,
+    /// so their order is reproduced exactly.
+    pub fn max_concurrent_scenarios(&self) -> Option<usize> {
+        if self.is_fixed {
+            Some(1)
+        } else {
+            None
+        }
+    }
+}
+
+/// Deterministic pseudo-random generator (SplitMix64), to be used for every randomized choice of the run.
+#[derive(Debug, Clone)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut mixed = self.state;
+        mixed = (mixed ^ (mixed >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        mixed = (mixed ^ (mixed >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        mixed ^ (mixed >> 31)
+    }
+
+    /// Number from the range `0..bound`; `bound` has to be positive.
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    /// Shuffles the items in place (Fisher-Yates).
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for index in (1..items.len()).rev() {
+            let other = self.below(index + 1);
+            items.swap(index, other);
+        }
+    }
+}
+
+/// Parser reading features like the default one, along with test plans of the same catalogue, then shuffling features
+/// and scenarios within them in the order determined by the seed.
+/// Translated steps are replaced with the ones they translate,
+/// then `${NAME}` placeholders in steps are replaced with variables of the profile or the environment.
+pub struct SeededParser {
+    seed: Seed,
+    variables: HashMap<String, String>,
+    translations: StepTranslations,
+}
+
+impl SeededParser {
+    pub fn new(seed: Seed) -> Self {
+        Self {
+            seed,
+            variables: HashMap::new(),
+            translations: StepTranslations::default(),
+        }
+    }
+
+    /// Sets variables placeholders in steps are resolved with before the environment.
+    ///
+    /// # Arguments
+    ///
+    /// * `variables` - Variables of the profile, by name
+    ///
+    pub fn with_variables(mut self, variables: HashMap<String, String>) -> Self {
+        self.variables = variables;
+        self
+    }
+
+    /// Sets translations of step phrases, so features may be written in other languages.
+    ///
+    /// # Arguments
+    ///
+    /// * `translations` - Compiled translations of the config file
+    ///
+    pub fn with_translations(mut self, translations: StepTranslations) -> Self {
+        self.translations = translations;
+        self
+    }
+}
+
+impl<I: AsRef<Path>> Parser<I> for SeededParser {
+    type Cli = <parser::Basic as Parser<I>>::Cli;
+    type Output = stream::Iter<std::vec::IntoIter<parser::Result<gherkin::Feature>>>;
+
+    fn parse(self, input: I, cli: Self::Cli) -> Self::Output {
+        let plans = test_plan::load_features(input.as_ref());
+        // The basic parser reads the files up front, so its stream is ready without blocking the runtime
+        let mut features: Vec<_> = parser::Basic::new()
+            .parse(input, cli)
+            .collect::<Vec<_>>()
+            .now_or_never()
+            .expect("Features should be read by the basic parser up front");
+        features.extend(plans.into_iter().map(Ok));
+        let mut rng = SeededRng::new(self.seed.value);
+        rng.shuffle(&mut features);
+        for feature in features.iter_mut().flatten() {
+            self.translations.translate_feature(feature);
+            interpolation::interpolate_feature(feature, &self.variables);
+            rng.shuffle(&mut feature.scenarios);
+        }
+        stream::iter(features)
+    }
+}
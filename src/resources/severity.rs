@@ -0,0 +1,110 @@
+use super::config::Endpoint;
+use super::tags;
+use once_cell::sync::Lazy;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+/// Severity of problems found by a check of a step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// Problems fail the step
+    Error,
+    /// Problems are recorded as warnings of the scenario, which goes on
+    Warning,
+}
+
+/// Checks of steps configured as warnings for a scenario by its tags: `@warn` turns all checks into warnings,
+/// `@warn(security_headers,enum_values)` the listed ones.
+/// Checks are named e.g. "schema", "extra_fields", "latency", "encoding", "number_format", "enum_values",
+/// "security_headers" and "csv".
+#[derive(Debug, Clone, Default)]
+pub struct WarningChecks {
+    all: bool,
+    checks: HashSet<String>,
+}
+
+impl WarningChecks {
+    /// Reads the checks from tags of the scenario, including the ones inherited from its rule and feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `tags` - Tags of the scenario, without "@"
+    ///
+    pub fn from_tags<'a>(tags: impl IntoIterator<Item = &'a String>) -> Self {
+        let mut warning_checks = WarningChecks::default();
+        for tag in tags {
+            if tag == "warn" {
+                warning_checks.all = true;
+            } else if let Some(checks) = tags::tag_argument(tag, "warn") {
+                warning_checks
+                    .checks
+                    .extend(checks.split(',').map(|check| check.trim().to_owned()));
+            }
+        }
+        warning_checks
+    }
+
+    /// Severity of the check, turned into a warning by tags of the scenario or by the `warnings` of the endpoint.
+    ///
+    /// # Arguments
+    ///
+    /// * `check` - Name of the check, e.g. "security_headers"
+    /// * `endpoint` - Endpoint the checked response comes from, if known
+    ///
+    pub fn severity(&self, check: &str, endpoint: Option<&Endpoint>) -> Severity {
+        let is_warning = self.all
+            || self.checks.contains(check)
+            || endpoint.map_or(false, |endpoint| {
+                endpoint.warnings.iter().any(|warning| warning == check)
+            });
+        if is_warning {
+            Severity::Warning
+        } else {
+            Severity::Error
+        }
+    }
+}
+
+/// Warnings of the scenarios in progress, by scenario key.
+static WARNINGS: Lazy<Mutex<HashMap<String, Vec<String>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Panics with the problems found by the check when it is an error,
+/// records them as a warning of the scenario otherwise.
+///
+/// # Arguments
+///
+/// * `scenario_key` - Key of the scenario running the check
+/// * `severity` - Severity of the check
+/// * `description` - Description of the problems, e.g. "The response headers are insecure"
+/// * `problems` - Problems found by the check; nothing is reported when there are none
+///
+pub fn report(scenario_key: &str, severity: Severity, description: &str, problems: &[String]) {
+    if problems.is_empty() {
+        return;
+    }
+    let message = format!("{}:\n{}", description, problems.join("\n"));
+    match severity {
+        Severity::Error => panic!("{}", message),
+        Severity::Warning => WARNINGS
+            .lock()
+            .expect("Warnings should not be poisoned")
+            .entry(scenario_key.to_owned())
+            .or_default()
+            .push(message),
+    }
+}
+
+/// Removes and returns warnings recorded for the scenario.
+///
+/// # Arguments
+///
+/// * `scenario_key` - Key of the finished scenario
+///
+pub fn take_warnings(scenario_key: &str) -> Vec<String> {
+    WARNINGS
+        .lock()
+        .expect("Warnings should not be poisoned")
+        .remove(scenario_key)
+        .unwrap_or_default()
+}
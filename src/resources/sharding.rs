@@ -0,0 +1,72 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Part of the scenarios run by a single executor, given as "index/count", e.g. "2/5";
+/// indexes start from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shard {
+    pub index: u64,
+    pub count: u64,
+}
+
+impl FromStr for Shard {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let malformed = || {
+            format!(
+                "Shard should be given as index/count, e.g. 2/5; got: {}",
+                value
+            )
+        };
+        let (index, count) = value.split_once('/').ok_or_else(malformed)?;
+        let index: u64 = index.trim().parse().map_err(|_| malformed())?;
+        let count: u64 = count.trim().parse().map_err(|_| malformed())?;
+        if count == 0 || index == 0 || index > count {
+            return Err(format!(
+                "Shard index should be between 1 and the number of shards; got: {}",
+                value
+            ));
+        }
+        Ok(Self { index, count })
+    }
+}
+
+impl fmt::Display for Shard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.index, self.count)
+    }
+}
+
+impl Shard {
+    /// Whether the scenario belongs to the shard; the assignment depends only on the names
+    /// of the feature and scenario, so every executor computes the same partition.
+    ///
+    /// # Arguments
+    ///
+    /// * `feature_name` - Name of the feature containing the scenario
+    /// * `scenario_name` - Name of the scenario
+    ///
+    pub fn contains(&self, feature_name: &str, scenario_name: &str) -> bool {
+        let hash = stable_hash(&[feature_name, scenario_name]);
+        hash % self.count == self.index - 1
+    }
+
+    /// Suffix distinguishing result files of the shard, e.g. "-shard-2-of-5".
+    pub fn file_suffix(&self) -> String {
+        format!("-shard-{}-of-{}", self.index, self.count)
+    }
+}
+
+/// FNV-1a hash of the parts; unlike the hasher of the standard library,
+/// it does not change between Rust versions or runs.
+fn stable_hash(parts: &[&str]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in parts {
+        for byte in part.bytes().chain(std::iter::once(0)) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
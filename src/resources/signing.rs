@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Way in which the API secret has to be turned into bytes of the HMAC key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretEncoding {
+    /// Secret is base64 encoded and the decoded bytes are the key
+    Base64,
+    /// Secret is used as is
+    Raw,
+    /// Secret is hex encoded and the decoded bytes are the key
+    Hex,
+}
+
+impl Default for SecretEncoding {
+    fn default() -> Self {
+        SecretEncoding::Base64
+    }
+}
+
+impl FromStr for SecretEncoding {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "base64" => Ok(SecretEncoding::Base64),
+            "raw" => Ok(SecretEncoding::Raw),
+            "hex" => Ok(SecretEncoding::Hex),
+            _ => Err(format!(
+                "unknown secret encoding {:?}, expected one of: base64, raw, hex",
+                value
+            )),
+        }
+    }
+}
+
+impl SecretEncoding {
+    /// Turns API secret into bytes of the HMAC key.
+    ///
+    /// # Arguments
+    ///
+    /// * `api_secret` - Private key for API, validated when loading config
+    ///
+    pub fn decode(&self, api_secret: &str) -> Vec<u8> {
+        match self {
+            SecretEncoding::Base64 => {
+                base64::decode(api_secret).expect("API secret is validated when loading config")
+            }
+            SecretEncoding::Raw => api_secret.as_bytes().to_vec(),
+            SecretEncoding::Hex => {
+                hex::decode(api_secret).expect("API secret is validated when loading config")
+            }
+        }
+    }
+}
+
+/// Way in which bytes of the computed MAC are presented to the API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureEncoding {
+    Base64,
+    HexLower,
+    HexUpper,
+}
+
+impl Default for SignatureEncoding {
+    fn default() -> Self {
+        SignatureEncoding::Base64
+    }
+}
+
+impl FromStr for SignatureEncoding {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "base64" => Ok(SignatureEncoding::Base64),
+            "hexlower" | "hex" => Ok(SignatureEncoding::HexLower),
+            "hexupper" => Ok(SignatureEncoding::HexUpper),
+            _ => Err(format!(
+                "unknown signature encoding {:?}, expected one of: base64, hexlower, hexupper",
+                value
+            )),
+        }
+    }
+}
+
+impl SignatureEncoding {
+    /// Turns bytes of the computed MAC into signature sent in the request header.
+    ///
+    /// # Arguments
+    ///
+    /// * `mac_bytes` - Result of signing the message with the API secret
+    ///
+    pub fn encode(&self, mac_bytes: &[u8]) -> String {
+        match self {
+            SignatureEncoding::Base64 => base64::encode(mac_bytes),
+            SignatureEncoding::HexLower => hex::encode(mac_bytes),
+            SignatureEncoding::HexUpper => hex::encode_upper(mac_bytes),
+        }
+    }
+}
+
+/// Digest applied to the nonce prefixed payload before it becomes a part of signed message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PayloadHash {
+    Sha256,
+    Sha512,
+    /// Payload is signed directly, without an inner digest
+    None,
+}
+
+impl Default for PayloadHash {
+    fn default() -> Self {
+        PayloadHash::Sha256
+    }
+}
+
+impl FromStr for PayloadHash {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "sha256" => Ok(PayloadHash::Sha256),
+            "sha512" => Ok(PayloadHash::Sha512),
+            "none" => Ok(PayloadHash::None),
+            _ => Err(format!(
+                "unknown payload hash {:?}, expected one of: sha256, sha512, none",
+                value
+            )),
+        }
+    }
+}
+
+/// Signing conventions of the API, selected per profile in its `signing` table;
+/// unset conventions default to the ones used by Kraken.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SigningSettings {
+    pub secret_encoding: SecretEncoding,
+    pub signature_encoding: SignatureEncoding,
+    pub payload_hash: PayloadHash,
+    /// Whether endpoint path is a part of signed message
+    pub sign_path: bool,
+    /// Whether HTTP method is a part of signed message
+    pub sign_method: bool,
+    /// Whether host of the API is a part of signed message
+    pub sign_host: bool,
+}
+
+impl Default for SigningSettings {
+    fn default() -> Self {
+        Self {
+            secret_encoding: SecretEncoding::default(),
+            signature_encoding: SignatureEncoding::default(),
+            payload_hash: PayloadHash::default(),
+            sign_path: true,
+            sign_method: false,
+            sign_host: false,
+        }
+    }
+}
+
+/// Conventions used to sign requests to private API.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignerConfig {
+    pub secret_encoding: SecretEncoding,
+    pub signature_encoding: SignatureEncoding,
+    pub payload_hash: PayloadHash,
+    /// Whether endpoint path is a part of signed message
+    pub sign_path: bool,
+    /// Whether HTTP method is a part of signed message
+    pub sign_method: bool,
+    /// Whether host of the API is a part of signed message
+    pub sign_host: bool,
+    /// Headers sent with every request and signed after the endpoint path as "name:value",
+    /// e.g. the one selecting a subaccount; set from `signed_headers` of the profile
+    #[serde(default)]
+    pub signed_headers: Vec<(String, String)>,
+}
+
+impl Default for SignerConfig {
+    fn default() -> Self {
+        Self {
+            secret_encoding: SecretEncoding::default(),
+            signature_encoding: SignatureEncoding::default(),
+            payload_hash: PayloadHash::default(),
+            sign_path: true,
+            sign_method: false,
+            sign_host: false,
+            signed_headers: Vec::new(),
+        }
+    }
+}
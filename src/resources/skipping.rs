@@ -0,0 +1,49 @@
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Reasons of skipping the scenarios in progress, by scenario key.
+static SKIP_REASONS: Lazy<Mutex<HashMap<String, String>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Stops the scenario, which is reported as skipped with the reason instead of failed.
+///
+/// # Arguments
+///
+/// * `scenario_key` - Key of the scenario
+/// * `reason` - Why the scenario cannot be run, e.g. "endpoint AddOrder is denied by the profile"
+///
+pub fn skip_scenario(scenario_key: &str, reason: &str) -> ! {
+    SKIP_REASONS
+        .lock()
+        .expect("Skip reasons should not be poisoned")
+        .insert(scenario_key.to_owned(), reason.to_owned());
+    panic!("Scenario skipped: {}", reason)
+}
+
+/// Reason the scenario was skipped for, if it was.
+///
+/// # Arguments
+///
+/// * `scenario_key` - Key of the scenario
+///
+pub fn skip_reason(scenario_key: &str) -> Option<String> {
+    SKIP_REASONS
+        .lock()
+        .expect("Skip reasons should not be poisoned")
+        .get(scenario_key)
+        .cloned()
+}
+
+/// Removes and returns the reason the finished scenario was skipped for, if it was.
+///
+/// # Arguments
+///
+/// * `scenario_key` - Key of the finished scenario
+///
+pub fn take_skip_reason(scenario_key: &str) -> Option<String> {
+    SKIP_REASONS
+        .lock()
+        .expect("Skip reasons should not be poisoned")
+        .remove(scenario_key)
+}
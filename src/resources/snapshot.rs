@@ -0,0 +1,250 @@
+use super::correlation;
+use super::masking::{self, MaskRule};
+use super::redaction;
+use super::report_encryption;
+use super::scrubbing::{self, ScrubRule};
+use super::streaming::StreamSummary;
+use cucumber::gherkin;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Request sent during the scenario, as seen by the suite.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestTranscript {
+    pub endpoint: String,
+    pub method: String,
+    pub url: String,
+    pub status: Option<u16>,
+    pub error: Option<String>,
+    /// Whether the response was reused from the cache of reference data instead of being requested
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cached: bool,
+}
+
+/// State of the scenario kept for post-hoc debugging of its failure.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ScenarioSnapshot {
+    pub run_id: String,
+    pub feature: String,
+    pub scenario: String,
+    pub line: usize,
+    pub captured_values: BTreeMap<String, String>,
+    pub requests: Vec<RequestTranscript>,
+    /// Body of the last response read by the scenario, with volatile fields masked
+    pub last_response: Option<serde_json::Value>,
+    /// Size and digest of the last response streamed within a size limit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_body_summary: Option<StreamSummary>,
+}
+
+/// Snapshots of the scenarios in progress, by scenario key.
+/// They are kept outside of the world, since the world of a failed scenario
+/// is consumed by the failure event and is not available afterwards.
+static SNAPSHOTS: Lazy<Mutex<HashMap<String, ScenarioSnapshot>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Key identifying the scenario within the run.
+///
+/// # Arguments
+///
+/// * `feature` - Feature containing the scenario
+/// * `scenario` - The scenario
+///
+pub fn scenario_key(feature: &gherkin::Feature, scenario: &gherkin::Scenario) -> String {
+    format!(
+        "{}:{}:{}",
+        feature.name, scenario.name, scenario.position.line
+    )
+}
+
+/// Starts tracking state of the scenario.
+///
+/// # Arguments
+///
+/// * `feature` - Feature containing the scenario
+/// * `scenario` - The scenario
+///
+pub fn start(feature: &gherkin::Feature, scenario: &gherkin::Scenario) -> String {
+    let key = scenario_key(feature, scenario);
+    let snapshot = ScenarioSnapshot {
+        feature: feature.name.clone(),
+        scenario: scenario.name.clone(),
+        line: scenario.position.line,
+        run_id: correlation::run_id().to_owned(),
+        ..ScenarioSnapshot::default()
+    };
+    SNAPSHOTS
+        .lock()
+        .expect("Snapshots should not be poisoned")
+        .insert(key.clone(), snapshot);
+    key
+}
+
+/// Describes the request along with its result.
+///
+/// # Arguments
+///
+/// * `endpoint` - Name of the requested endpoint
+/// * `method` - HTTP method of the request
+/// * `url` - Full link the request was sent to
+/// * `result` - Response or error of the request
+///
+pub fn transcript_of(
+    endpoint: &str,
+    method: &str,
+    url: &str,
+    result: &reqwest::Result<reqwest::Response>,
+) -> RequestTranscript {
+    RequestTranscript {
+        endpoint: endpoint.to_owned(),
+        method: method.to_owned(),
+        url: url.to_owned(),
+        status: result
+            .as_ref()
+            .ok()
+            .map(|response| response.status().as_u16()),
+        error: result.as_ref().err().map(|err| err.to_string()),
+        cached: false,
+    }
+}
+
+/// Adds the request to the transcript of the scenario.
+///
+/// # Arguments
+///
+/// * `key` - Key of the scenario
+/// * `transcript` - Description of the sent request
+///
+pub fn record_request(key: &str, transcript: RequestTranscript) {
+    if let Some(snapshot) = SNAPSHOTS
+        .lock()
+        .expect("Snapshots should not be poisoned")
+        .get_mut(key)
+    {
+        snapshot.requests.push(transcript);
+    }
+}
+
+/// Keeps the response body read by the scenario, masked and scrubbed according to the rules.
+///
+/// # Arguments
+///
+/// * `key` - Key of the scenario
+/// * `json_response` - Response body parsed to json
+/// * `masks` - Rules of masking volatile fields
+/// * `scrub_rules` - Rules of scrubbing sensitive values
+///
+pub fn record_response(
+    key: &str,
+    json_response: &serde_json::Value,
+    masks: &[MaskRule],
+    scrub_rules: &[ScrubRule],
+) {
+    if let Some(snapshot) = SNAPSHOTS
+        .lock()
+        .expect("Snapshots should not be poisoned")
+        .get_mut(key)
+    {
+        let mut masked = json_response.clone();
+        masking::apply(&mut masked, masks);
+        scrubbing::apply(&mut masked, scrub_rules);
+        snapshot.last_response = Some(masked);
+    }
+}
+
+/// Keeps size and digest of the response body streamed by the scenario.
+///
+/// # Arguments
+///
+/// * `key` - Key of the scenario
+/// * `summary` - Summary of the streamed body
+///
+pub fn record_body_summary(key: &str, summary: &StreamSummary) {
+    if let Some(snapshot) = SNAPSHOTS
+        .lock()
+        .expect("Snapshots should not be poisoned")
+        .get_mut(key)
+    {
+        snapshot.last_body_summary = Some(summary.clone());
+    }
+}
+
+/// Keeps the value remembered by the scenario.
+///
+/// # Arguments
+///
+/// * `key` - Key of the scenario
+/// * `name` - Name the value is remembered as
+/// * `value` - The remembered value
+///
+pub fn record_captured_value(key: &str, name: &str, value: &str) {
+    if let Some(snapshot) = SNAPSHOTS
+        .lock()
+        .expect("Snapshots should not be poisoned")
+        .get_mut(key)
+    {
+        snapshot
+            .captured_values
+            .insert(name.to_owned(), value.to_owned());
+    }
+}
+
+/// Requests sent so far in the scenario; empty when the scenario is not tracked.
+///
+/// # Arguments
+///
+/// * `key` - Key of the scenario
+///
+pub fn requests_of(key: &str) -> Vec<RequestTranscript> {
+    SNAPSHOTS
+        .lock()
+        .expect("Snapshots should not be poisoned")
+        .get(key)
+        .map(|snapshot| snapshot.requests.clone())
+        .unwrap_or_default()
+}
+
+/// Stops tracking state of the scenario; when it failed,
+/// its redacted snapshot is written to a json file in the given catalogue, encrypted when a report key is set.
+///
+/// # Arguments
+///
+/// * `key` - Key of the scenario
+/// * `failed` - Whether the scenario failed
+/// * `snapshot_dir` - Catalogue the snapshots are written to
+///
+pub fn finish(key: &str, failed: bool, snapshot_dir: &str) {
+    let snapshot = match SNAPSHOTS
+        .lock()
+        .expect("Snapshots should not be poisoned")
+        .remove(key)
+    {
+        Some(snapshot) => snapshot,
+        None => return,
+    };
+    if !failed {
+        return;
+    }
+    let content = serde_json::to_string_pretty(&snapshot).expect("Snapshot should be serializable");
+    let (redacted, _) = redaction::redact(&content);
+    let file_name: String = key
+        .chars()
+        .map(|character| {
+            if character.is_ascii_alphanumeric() {
+                character.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let path = Path::new(snapshot_dir).join(format!("{}.json", file_name));
+    let written = fs::create_dir_all(snapshot_dir)
+        .and_then(|_| fs::write(&path, report_encryption::seal(redacted.into_bytes())));
+    if let Err(err) = written {
+        eprintln!("Writing snapshot {} failed: {}", path.display(), err);
+    }
+}
@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+/// Status page of the provider, queried when scenarios fail, so outages of the provider
+/// can be told apart from broken contracts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StatusPageSettings {
+    /// Base of the status API in the Statuspage format, e.g. "https://status.kraken.com/api/v2"; not queried when not set
+    pub url: Option<String>,
+    /// Longest time the status API is awaited
+    pub timeout_seconds: u64,
+}
+
+impl Default for StatusPageSettings {
+    fn default() -> Self {
+        Self {
+            url: None,
+            timeout_seconds: 10,
+        }
+    }
+}
+
+/// Incident of the provider not resolved yet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Incident {
+    pub name: String,
+    /// Stage of the incident, e.g. "investigating" or "monitoring"
+    pub status: String,
+    /// Impact of the incident, e.g. "minor" or "major"
+    #[serde(default)]
+    pub impact: String,
+}
+
+/// State of the provider reported by its status page when the run finished.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderStatus {
+    /// Overall state, e.g. "none", "minor", "major" or "critical"
+    pub indicator: String,
+    /// Overall state in words, e.g. "All Systems Operational"
+    pub description: String,
+    #[serde(default)]
+    pub incidents: Vec<Incident>,
+}
+
+impl fmt::Display for ProviderStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({})", self.description, self.indicator)?;
+        for incident in &self.incidents {
+            write!(
+                f,
+                "; incident: {} ({}, {})",
+                incident.name, incident.status, incident.impact
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+struct StatusResponse {
+    status: StatusIndicator,
+}
+
+#[derive(Deserialize)]
+struct StatusIndicator {
+    indicator: String,
+    description: String,
+}
+
+#[derive(Deserialize)]
+struct IncidentsResponse {
+    incidents: Vec<Incident>,
+}
+
+async fn get_json<T: serde::de::DeserializeOwned>(
+    client: &reqwest::Client,
+    url: &str,
+) -> Result<T, String> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|err| format!("{} cannot be read: {}", url, err))?;
+    response
+        .json()
+        .await
+        .map_err(|err| format!("{} is not a Statuspage response: {}", url, err))
+}
+
+/// Queries the overall state and the unresolved incidents of the provider;
+/// a status page which cannot be read is reported without failing the run.
+///
+/// # Arguments
+///
+/// * `settings` - Status page of the profile
+///
+pub async fn provider_status(settings: &StatusPageSettings) -> Option<ProviderStatus> {
+    let url = settings.url.as_deref()?.trim_end_matches('/');
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(settings.timeout_seconds))
+        .build()
+        .expect("Client for the status page should be possible to build");
+    let status = async {
+        let status: StatusResponse = get_json(&client, &format!("{}/status.json", url)).await?;
+        let incidents: IncidentsResponse =
+            get_json(&client, &format!("{}/incidents/unresolved.json", url)).await?;
+        Ok::<_, String>(ProviderStatus {
+            indicator: status.status.indicator,
+            description: status.status.description,
+            incidents: incidents.incidents,
+        })
+    };
+    match status.await {
+        Ok(status) => Some(status),
+        Err(err) => {
+            eprintln!("Status of the provider is unknown: {}", err);
+            None
+        }
+    }
+}
@@ -0,0 +1,268 @@
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+/// Limit of the decompressed body size used by steps which do not state it explicitly.
+pub const DEFAULT_MAX_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Problems which may occur while streaming the response body.
+#[derive(Debug)]
+pub enum StreamError {
+    Transport(reqwest::Error),
+    /// Decompressed body exceeds the limit, e.g. because of a compression bomb
+    TooLarge {
+        max_bytes: u64,
+    },
+    /// Number of received bytes differs from Content-Length, e.g. because the body was truncated
+    LengthMismatch {
+        declared: u64,
+        received: u64,
+    },
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StreamError::Transport(err) => write!(f, "Reading response body failed: {}", err),
+            StreamError::TooLarge { max_bytes } => {
+                write!(f, "Response body exceeds the limit of {} bytes", max_bytes)
+            }
+            StreamError::LengthMismatch { declared, received } => write!(
+                f,
+                "Response body has {} bytes, but Content-Length declares {}",
+                received, declared
+            ),
+        }
+    }
+}
+
+impl From<reqwest::Error> for StreamError {
+    fn from(err: reqwest::Error) -> Self {
+        StreamError::Transport(err)
+    }
+}
+
+/// Size and digest of the body, computed while the body was streamed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StreamSummary {
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// Whole response body read within the size limit.
+#[derive(Debug)]
+pub struct CappedBody {
+    pub bytes: Vec<u8>,
+    pub summary: StreamSummary,
+}
+
+/// Keeps track of size and digest of consecutive chunks, enforcing the size limit
+/// and the length declared by the server.
+struct ChunkTracker {
+    size: u64,
+    hasher: Sha256,
+    max_bytes: u64,
+    declared_length: Option<u64>,
+}
+
+impl ChunkTracker {
+    /// Content-Length is known only for bodies which are not decompressed by the client,
+    /// so chunks of compressed bodies are limited only by `max_bytes`, after decompression.
+    fn new(response: &reqwest::Response, max_bytes: u64) -> Result<Self, StreamError> {
+        let declared_length = response.content_length();
+        if declared_length.map_or(false, |length| length > max_bytes) {
+            return Err(StreamError::TooLarge { max_bytes });
+        }
+        Ok(Self {
+            size: 0,
+            hasher: Sha256::new(),
+            max_bytes,
+            declared_length,
+        })
+    }
+
+    fn track(&mut self, chunk: &[u8]) -> Result<(), StreamError> {
+        self.size += chunk.len() as u64;
+        if self.size > self.max_bytes {
+            return Err(StreamError::TooLarge {
+                max_bytes: self.max_bytes,
+            });
+        }
+        self.hasher.update(chunk);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<StreamSummary, StreamError> {
+        if let Some(declared) = self.declared_length {
+            if declared != self.size {
+                return Err(StreamError::LengthMismatch {
+                    declared,
+                    received: self.size,
+                });
+            }
+        }
+        Ok(StreamSummary {
+            size: self.size,
+            sha256: hex::encode(self.hasher.finalize()),
+        })
+    }
+}
+
+/// Reads the whole response body chunk by chunk, failing as soon as it exceeds the limit,
+/// so validation can be done once the body is fully received.
+/// Fails as well when the received body is shorter or longer than its Content-Length.
+///
+/// # Arguments
+///
+/// * `response` - Response whose body has not been read yet
+/// * `max_bytes` - Maximal accepted size of the body
+///
+pub async fn read_capped(
+    mut response: reqwest::Response,
+    max_bytes: u64,
+) -> Result<CappedBody, StreamError> {
+    let mut tracker = ChunkTracker::new(&response, max_bytes)?;
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        tracker.track(&chunk)?;
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(CappedBody {
+        bytes,
+        summary: tracker.finish()?,
+    })
+}
+
+/// Streams the response body line by line, keeping only the current line in memory.
+/// Lines are numbered from 1; trailing carriage returns are removed.
+///
+/// # Arguments
+///
+/// * `response` - Response whose body has not been read yet
+/// * `max_bytes` - Maximal accepted size of the body
+/// * `on_line` - Called with number and content of every line
+///
+pub async fn stream_lines<F>(
+    mut response: reqwest::Response,
+    max_bytes: u64,
+    mut on_line: F,
+) -> Result<StreamSummary, StreamError>
+where
+    F: FnMut(usize, &[u8]),
+{
+    let mut tracker = ChunkTracker::new(&response, max_bytes)?;
+    let mut current_line = Vec::new();
+    let mut line_number = 0;
+    while let Some(chunk) = response.chunk().await? {
+        tracker.track(&chunk)?;
+        for byte in chunk.iter() {
+            if *byte == b'\n' {
+                line_number += 1;
+                on_line(line_number, trim_carriage_return(&current_line));
+                current_line.clear();
+            } else {
+                current_line.push(*byte);
+            }
+        }
+    }
+    if !current_line.is_empty() {
+        line_number += 1;
+        on_line(line_number, trim_carriage_return(&current_line));
+    }
+    tracker.finish()
+}
+
+fn trim_carriage_return(line: &[u8]) -> &[u8] {
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker(max_bytes: u64, declared_length: Option<u64>) -> ChunkTracker {
+        ChunkTracker {
+            size: 0,
+            hasher: Sha256::new(),
+            max_bytes,
+            declared_length,
+        }
+    }
+
+    #[test]
+    fn tracker_summarizes_size_and_digest_of_all_chunks() {
+        let mut tracker = tracker(16, Some(6));
+        tracker.track(b"abc").unwrap();
+        tracker.track(b"def").unwrap();
+        let summary = tracker.finish().unwrap();
+        assert_eq!(summary.size, 6);
+        assert_eq!(summary.sha256, hex::encode(Sha256::digest(b"abcdef")));
+    }
+
+    #[test]
+    fn tracker_fails_as_soon_as_the_limit_is_exceeded() {
+        let mut tracker = tracker(4, None);
+        tracker.track(b"abc").unwrap();
+        assert!(matches!(
+            tracker.track(b"de"),
+            Err(StreamError::TooLarge { max_bytes: 4 })
+        ));
+    }
+
+    #[test]
+    fn tracker_fails_when_the_body_differs_from_its_content_length() {
+        let mut tracker = tracker(16, Some(5));
+        tracker.track(b"abc").unwrap();
+        assert!(matches!(
+            tracker.finish(),
+            Err(StreamError::LengthMismatch {
+                declared: 5,
+                received: 3
+            })
+        ));
+    }
+
+    #[test]
+    fn carriage_returns_are_trimmed_from_lines() {
+        assert_eq!(trim_carriage_return(b"line\r"), b"line");
+        assert_eq!(trim_carriage_return(b"line"), b"line");
+    }
+
+    #[tokio::test]
+    async fn read_capped_reads_the_whole_body() {
+        let response = reqwest::Response::from(http::Response::new("{\"error\":[]}"));
+        let body = read_capped(response, 64).await.unwrap();
+        assert_eq!(body.bytes, b"{\"error\":[]}");
+        assert_eq!(body.summary.size, 12);
+    }
+
+    #[tokio::test]
+    async fn read_capped_refuses_bodies_declared_above_the_limit() {
+        let response = reqwest::Response::from(http::Response::new("{\"error\":[]}"));
+        assert!(matches!(
+            read_capped(response, 4).await,
+            Err(StreamError::TooLarge { max_bytes: 4 })
+        ));
+    }
+
+    #[tokio::test]
+    async fn stream_lines_numbers_every_line() {
+        let response =
+            reqwest::Response::from(http::Response::new("{\"a\":1}\r\n{\"a\":2}\n{\"a\":3}"));
+        let mut lines = Vec::new();
+        let summary = stream_lines(response, 64, |number, line| {
+            lines.push((number, String::from_utf8_lossy(line).into_owned()))
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            lines,
+            vec![
+                (1, "{\"a\":1}".to_owned()),
+                (2, "{\"a\":2}".to_owned()),
+                (3, "{\"a\":3}".to_owned())
+            ]
+        );
+        assert_eq!(summary.size, 24);
+    }
+}
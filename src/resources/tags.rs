@@ -0,0 +1,82 @@
+use super::config::Profile;
+use cucumber::gherkin;
+use std::net::IpAddr;
+
+/// Extracts argument of a tag in the form of `name(argument)`; tags are given without "@".
+///
+/// # Arguments
+///
+/// * `tag` - Tag of a scenario or feature
+/// * `name` - Name of the tag the argument is looked for
+///
+pub fn tag_argument<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    tag.strip_prefix(name)?.strip_prefix('(')?.strip_suffix(')')
+}
+
+/// Tags of the scenario along with the ones inherited from its rule and feature.
+///
+/// # Arguments
+///
+/// * `feature` - Feature containing the scenario
+/// * `rule` - Rule containing the scenario, if any
+/// * `scenario` - The scenario
+///
+pub fn all_tags<'a>(
+    feature: &'a gherkin::Feature,
+    rule: Option<&'a gherkin::Rule>,
+    scenario: &'a gherkin::Scenario,
+) -> impl Iterator<Item = &'a String> {
+    feature
+        .tags
+        .iter()
+        .chain(rule.into_iter().flat_map(|rule| rule.tags.iter()))
+        .chain(scenario.tags.iter())
+}
+
+/// Scenarios tagged with API versions, e.g. `@v1`, directly or through their rule or feature,
+/// are run only against profiles selecting one of these versions; untagged ones are always run.
+///
+/// # Arguments
+///
+/// * `api_version` - Version of the API selected by the profile
+/// * `feature` - Feature containing the scenario
+/// * `rule` - Rule containing the scenario, if any
+/// * `scenario` - The scenario
+///
+pub fn runs_on_version(
+    api_version: &str,
+    feature: &gherkin::Feature,
+    rule: Option<&gherkin::Rule>,
+    scenario: &gherkin::Scenario,
+) -> bool {
+    let mut versions = all_tags(feature, rule, scenario)
+        .filter_map(|tag| tag.strip_prefix('v'))
+        .filter(|version| !version.is_empty() && version.chars().all(|c| c.is_ascii_digit()))
+        .peekable();
+    versions.peek().is_none() || versions.any(|version| version == api_version)
+}
+
+/// Parses `resolve(host=ip)` tag into the host and the address it should be resolved to.
+fn parse_resolve_tag(tag: &str) -> Option<(String, IpAddr)> {
+    let argument = tag_argument(tag, "resolve")?;
+    let (host, address) = argument
+        .split_once('=')
+        .unwrap_or_else(|| panic!("Tag @{} should have the form @resolve(host=ip)", tag));
+    let address = address
+        .parse()
+        .unwrap_or_else(|_| panic!("Tag @{} contains invalid ip address", tag));
+    Some((host.to_owned(), address))
+}
+
+/// Adjusts the profile according to tags of the scenario, e.g. `@resolve(host=ip)`.
+///
+/// # Arguments
+///
+/// * `tags` - Tags of the scenario, without "@"
+/// * `profile` - Profile used by the scenario
+///
+pub fn apply_to_profile(tags: &[String], profile: &mut Profile) {
+    for (host, address) in tags.iter().filter_map(|tag| parse_resolve_tag(tag)) {
+        profile.resolve.insert(host, address);
+    }
+}
@@ -0,0 +1,218 @@
+use super::config::Profile;
+use super::correlation;
+use super::generators::{self, GeneratorSettings};
+use super::reference;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::env;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tera::{Context, Tera};
+
+/// Tera templates of request parameters, referenced from the profile.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TemplateSettings {
+    /// Catalogue with templates, e.g. "add_order.yaml", referred to by steps without the extension
+    /// and includable by doc strings of steps
+    pub dir: String,
+}
+
+impl Default for TemplateSettings {
+    fn default() -> Self {
+        Self {
+            dir: "./templates".to_owned(),
+        }
+    }
+}
+
+static TEMPLATES: Lazy<Mutex<Option<Arc<Tera>>>> = Lazy::new(|| Mutex::new(None));
+
+/// Integer argument of a template function.
+fn integer_arg(args: &HashMap<String, Value>, function: &str, name: &str) -> tera::Result<i64> {
+    args.get(name)
+        .and_then(Value::as_i64)
+        .ok_or_else(|| format!("{} needs an integer argument {}", function, name).into())
+}
+
+/// Number from `start` (inclusive) to `end` (exclusive).
+fn random_in_range(args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let start = integer_arg(args, "random_in_range", "start")?;
+    let end = integer_arg(args, "random_in_range", "end")?;
+    if end <= start {
+        return Err(format!(
+            "random_in_range needs start below end, got {}..{}",
+            start, end
+        )
+        .into());
+    }
+    Ok(json!(generators::integer_in_range(start, end)?))
+}
+
+fn uuid(_: &HashMap<String, Value>) -> tera::Result<Value> {
+    Ok(Value::String(correlation::new_id()))
+}
+
+/// Registers the synthetic data generators: `client_order_id()`, `reference()`,
+/// `amount(pair="XBTUSD", price=30000)` with an optional price and `timestamp(format="rfc3339")`
+/// returning unix seconds unless formatted.
+fn register_generators(tera: &mut Tera, settings: &GeneratorSettings) {
+    let client_order_settings = settings.clone();
+    tera.register_function("client_order_id", move |_: &HashMap<String, Value>| {
+        Ok(json!(generators::client_order_id(&client_order_settings)))
+    });
+    let reference_settings = settings.clone();
+    tera.register_function("reference", move |_: &HashMap<String, Value>| {
+        Ok(json!(generators::reference(&reference_settings)))
+    });
+    let amount_settings = settings.clone();
+    tera.register_function("amount", move |args: &HashMap<String, Value>| {
+        let pair = args
+            .get("pair")
+            .and_then(Value::as_str)
+            .ok_or("amount needs a string argument pair")?;
+        let price = args.get("price").and_then(|price| match price {
+            Value::String(price) => price.parse().ok(),
+            price => price.as_f64(),
+        });
+        let reference_data = reference::loaded();
+        let pair = reference_data
+            .pair(pair)
+            .ok_or_else(|| format!("Asset pair {} is not in the reference data", pair))?;
+        Ok(json!(generators::amount(pair, price, &amount_settings)?))
+    });
+    let timestamp_settings = settings.clone();
+    tera.register_function("timestamp", move |args: &HashMap<String, Value>| {
+        let seconds = generators::timestamp(&timestamp_settings)?;
+        match args.get("format").and_then(Value::as_str) {
+            None | Some("unix") => Ok(json!(seconds)),
+            Some("rfc3339") => Ok(json!(generators::rfc3339_of(seconds))),
+            Some(format) => Err(format!("timestamp cannot be formatted as {}", format).into()),
+        }
+    });
+}
+
+/// Compiles the templates of the catalogue and registers the helper functions; a missing catalogue holds
+/// no templates, so doc strings of steps can be rendered without one.
+///
+/// # Arguments
+///
+/// * `settings` - Template settings of the profile
+/// * `generator_settings` - Generator settings of the profile
+///
+pub fn load(
+    settings: &TemplateSettings,
+    generator_settings: &GeneratorSettings,
+) -> Result<(), String> {
+    let mut tera = if Path::new(&settings.dir).is_dir() {
+        Tera::new(&format!("{}/**/*", settings.dir.trim_end_matches('/'))).map_err(|err| {
+            format!(
+                "Templates in {} cannot be compiled: {:?}",
+                settings.dir, err
+            )
+        })?
+    } else {
+        Tera::default()
+    };
+    tera.register_function("uuid", uuid);
+    tera.register_function("random_in_range", random_in_range);
+    register_generators(&mut tera, generator_settings);
+    *TEMPLATES.lock().expect("Templates should not be poisoned") = Some(Arc::new(tera));
+    Ok(())
+}
+
+fn loaded() -> Arc<Tera> {
+    TEMPLATES
+        .lock()
+        .expect("Templates should not be poisoned")
+        .clone()
+        .expect("Templates should be loaded before the run")
+}
+
+/// Values templates are rendered with: `captured` values of the scenario, `config` with the profile,
+/// its `variables` and the `env`ironment.
+///
+/// # Arguments
+///
+/// * `captured` - Values captured by earlier steps, by name
+/// * `profile` - Profile of the run
+///
+pub fn context_of(captured: &HashMap<String, String>, profile: &Profile) -> Context {
+    let mut context = Context::new();
+    context.insert("captured", captured);
+    context.insert("config", profile);
+    context.insert("variables", &profile.variables);
+    context.insert("env", &env::vars().collect::<HashMap<String, String>>());
+    context
+}
+
+/// Parameters of a rendered yaml or json map; strings are taken without quotes, other values as json.
+/// Errors leave out the rendered text, since it may contain secrets of the profile or the environment.
+fn parameters_of(rendered: &str) -> Result<Vec<(String, String)>, String> {
+    let mapping: serde_yaml::Mapping = serde_yaml::from_str(rendered)
+        .map_err(|err| format!("Rendered parameters are not a map: {}", err))?;
+    mapping
+        .into_iter()
+        .map(|(key, value)| {
+            let key = key
+                .as_str()
+                .map(str::to_owned)
+                .ok_or_else(|| format!("Parameter names should be strings, got {:?}", key))?;
+            let value = match serde_json::to_value(&value).map_err(|err| err.to_string())? {
+                Value::String(text) => text,
+                other => other.to_string(),
+            };
+            Ok((key, value))
+        })
+        .collect()
+}
+
+/// Renders the parameters written in a doc string of a step.
+///
+/// # Arguments
+///
+/// * `template` - Doc string, a yaml map with template expressions, e.g. `nonce_id: "{{ uuid() }}"`
+/// * `context` - Context made by `context_of`
+///
+pub fn render_parameters(
+    template: &str,
+    context: &Context,
+) -> Result<Vec<(String, String)>, String> {
+    let mut tera = (*loaded()).clone();
+    let rendered = tera
+        .render_str(template, context)
+        .map_err(|err| format!("Parameters cannot be rendered: {:?}", err))?;
+    parameters_of(&rendered)
+}
+
+/// Renders the parameters of a template of the catalogue.
+///
+/// # Arguments
+///
+/// * `name` - Name of the template file, with or without the extension, e.g. "add_order"
+/// * `context` - Context made by `context_of`
+///
+pub fn render_template_parameters(
+    name: &str,
+    context: &Context,
+) -> Result<Vec<(String, String)>, String> {
+    let tera = loaded();
+    let template_name = tera
+        .get_template_names()
+        .find(|template| {
+            *template == name || Path::new(template).with_extension("") == Path::new(name)
+        })
+        .map(str::to_owned)
+        .ok_or_else(|| {
+            format!(
+                "Template {} is not found in the catalogue of templates",
+                name
+            )
+        })?;
+    let rendered = tera
+        .render(&template_name, context)
+        .map_err(|err| format!("Template {} cannot be rendered: {:?}", name, err))?;
+    parameters_of(&rendered)
+}
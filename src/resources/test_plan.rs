@@ -0,0 +1,320 @@
+use super::capture;
+use super::config::{EndpointRegistry, Profile};
+use super::http_file;
+use cucumber::gherkin;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Declarative alternative to a feature file, kept as "<plan>.yaml" next to the feature files of its suite.
+/// Every test becomes a scenario of a feature named after the plan, run and reported like written ones.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TestPlan {
+    /// Name of the feature the plan becomes
+    pub name: String,
+    /// Tags of the feature, without "@"
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub tests: Vec<PlanTest>,
+}
+
+/// Requests sent one after another by a single scenario.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlanTest {
+    /// Name of the scenario
+    pub name: String,
+    /// Tags of the scenario, without "@", e.g. "warn(schema)"
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub requests: Vec<PlanRequest>,
+}
+
+/// Request of a test along with the expectations towards its response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlanRequest {
+    /// Name of the endpoint in the registry
+    pub endpoint: String,
+    /// Parameters of the request; `{{name}}` is replaced with the value extracted as "name" by an earlier request
+    #[serde(default)]
+    pub parameters: BTreeMap<String, String>,
+    /// Expected HTTP status of the response
+    pub status: Option<u16>,
+    /// Whether the response should match the schema of the endpoint
+    #[serde(default)]
+    pub schema: bool,
+    #[serde(default)]
+    pub assertions: Vec<Assertion>,
+    /// Fields of the response kept for later requests of the test, by the name they are kept under,
+    /// e.g. `txid: result.txid.0`
+    #[serde(default)]
+    pub extract: BTreeMap<String, String>,
+}
+
+/// Expectation towards a field of the response, pointed by a dot separated path, e.g. "result.unixtime".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Assertion {
+    pub field: String,
+    /// Whether the field should be present; only other expectations are checked when not set
+    pub exists: Option<bool>,
+    /// Expected value; strings are compared without quotes, other values as json
+    pub equals: Option<String>,
+    /// Regular expression the value should match
+    pub matches: Option<String>,
+}
+
+impl Assertion {
+    /// Describes how the response breaks the assertion, if it does.
+    ///
+    /// # Arguments
+    ///
+    /// * `json_response` - Response body parsed to json
+    ///
+    pub fn failure(&self, json_response: &serde_json::Value) -> Option<String> {
+        let value = capture::extract_field(json_response, &self.field);
+        match (&value, self.exists) {
+            (Some(_), Some(false)) => {
+                return Some(format!(
+                    "{} should be absent, found {:?}",
+                    self.field, value
+                ))
+            }
+            (None, Some(true)) => return Some(format!("{} should be present", self.field)),
+            (None, _) if self.equals.is_some() || self.matches.is_some() => {
+                return Some(format!("{} is missing", self.field))
+            }
+            _ => {}
+        }
+        let value = value?;
+        if let Some(expected) = &self.equals {
+            if &value != expected {
+                return Some(format!(
+                    "{} should equal {:?}, found {:?}",
+                    self.field, expected, value
+                ));
+            }
+        }
+        if let Some(pattern) = &self.matches {
+            let regex = Regex::new(pattern)
+                .unwrap_or_else(|err| panic!("Pattern {} is invalid: {}", pattern, err));
+            if !regex.is_match(&value) {
+                return Some(format!(
+                    "{} should match {}, found {:?}",
+                    self.field, pattern, value
+                ));
+            }
+        }
+        None
+    }
+}
+
+/// Plans read by the parser, by their identifier, i.e. the name of the file without extension.
+static PLANS: Lazy<Mutex<HashMap<String, TestPlan>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Identifiers of plans, used within steps.
+static PLAN_ID: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[\w-]+$").unwrap());
+
+/// Reads the plans of the suite, i.e. ".yaml" files of the catalogue, as well as ".http" and ".rest" files,
+/// as features; panics when a plan is malformed.
+/// Scenarios of plans in a "private" catalogue set up the properties of the private API first.
+///
+/// # Arguments
+///
+/// * `dir` - Catalogue with feature files of the suite, e.g. "features/public"
+///
+pub fn load_features(dir: &Path) -> Vec<gherkin::Feature> {
+    let mut paths: Vec<_> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension()
+                    .and_then(|extension| extension.to_str())
+                    .map_or(false, |extension| {
+                        ["yaml", "yml", "http", "rest"].contains(&extension)
+                    })
+            })
+            .collect(),
+        Err(_) => return Vec::new(),
+    };
+    paths.sort();
+    let is_private = dir.ends_with("private");
+    let mut endpoints = None;
+    paths
+        .iter()
+        .map(|path| {
+            let plan_id = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .filter(|stem| PLAN_ID.is_match(stem))
+                .unwrap_or_else(|| {
+                    panic!(
+                        "Test plan {:?} should be named with letters, digits, _ or -",
+                        path
+                    )
+                })
+                .to_owned();
+            let content = fs::read_to_string(path)
+                .unwrap_or_else(|err| panic!("Cannot read test plan {:?}: {}", path, err));
+            let plan = match path.extension().and_then(|extension| extension.to_str()) {
+                Some("http") | Some("rest") => {
+                    let endpoints = endpoints.get_or_insert_with(|| {
+                        Profile::load()
+                            .and_then(|profile| EndpointRegistry::load(&profile.api_version))
+                            .unwrap_or_else(|err| panic!("{}", err))
+                    });
+                    http_file::plan_of(&plan_id, &content, endpoints)
+                        .unwrap_or_else(|err| panic!("HTTP file {:?} is malformed: {}", path, err))
+                }
+                _ => serde_yaml::from_str(&content)
+                    .unwrap_or_else(|err| panic!("Test plan {:?} is malformed: {}", path, err)),
+            };
+            let mut feature = gherkin::Feature::parse(
+                feature_text(&plan_id, &plan, is_private),
+                Default::default(),
+            )
+            .unwrap_or_else(|err| panic!("Test plan {:?} cannot become a feature: {}", path, err));
+            feature.path = Some(path.clone());
+            PLANS
+                .lock()
+                .expect("Test plans should not be poisoned")
+                .insert(plan_id, plan);
+            feature
+        })
+        .collect()
+}
+
+fn single_line(text: &str) -> String {
+    text.replace(|c: char| c == '\n' || c == '\r', " ")
+}
+
+fn tag_line(tags: &[String], indent: &str) -> String {
+    if tags.is_empty() {
+        return String::new();
+    }
+    let tags: Vec<String> = tags.iter().map(|tag| format!("@{}", tag)).collect();
+    format!("{}{}\n", indent, tags.join(" "))
+}
+
+/// Gherkin text of the feature running the plan, with a pair of steps per request.
+fn feature_text(plan_id: &str, plan: &TestPlan, is_private: bool) -> String {
+    let mut text = format!(
+        "{}Feature: {}\n  Declared by the \"{}\" test plan.\n",
+        tag_line(&plan.tags, ""),
+        single_line(&plan.name),
+        plan_id
+    );
+    for (test_index, test) in plan.tests.iter().enumerate() {
+        text.push_str(&format!(
+            "\n{}  Scenario: {}\n",
+            tag_line(&test.tags, "  "),
+            single_line(&test.name)
+        ));
+        if is_private {
+            text.push_str("    Given I have some properties concerning a private API\n");
+        }
+        for request_index in 0..test.requests.len() {
+            let reference = format!(
+                "request {} of test {} of the \"{}\" plan",
+                request_index + 1,
+                test_index + 1,
+                plan_id
+            );
+            text.push_str(&format!("    When I send {}\n", reference));
+            text.push_str(&format!("    Then the response meets {}\n", reference));
+        }
+    }
+    text
+}
+
+/// Request of a plan read by the parser, by its position counted from 1.
+///
+/// # Arguments
+///
+/// * `plan_id` - Identifier of the plan
+/// * `test` - Position of the test within the plan
+/// * `request` - Position of the request within the test
+///
+pub fn request_of(plan_id: &str, test: usize, request: usize) -> PlanRequest {
+    let plans = PLANS.lock().expect("Test plans should not be poisoned");
+    plans
+        .get(plan_id)
+        .and_then(|plan| plan.tests.get(test.checked_sub(1)?))
+        .and_then(|plan_test| plan_test.requests.get(request.checked_sub(1)?))
+        .cloned()
+        .unwrap_or_else(|| {
+            panic!(
+                "Test plan {} has no request {} in test {}",
+                plan_id, request, test
+            )
+        })
+}
+
+/// Parameters of the request, with `{{name}}` replaced by the values extracted by earlier requests.
+///
+/// # Arguments
+///
+/// * `request` - Request of a plan
+/// * `extracted` - Values extracted by earlier requests of the test, by name
+///
+pub fn parameters_of(
+    request: &PlanRequest,
+    extracted: &HashMap<String, String>,
+) -> Vec<(String, String)> {
+    request
+        .parameters
+        .iter()
+        .map(|(name, value)| {
+            let value = extracted
+                .iter()
+                .fold(value.clone(), |value, (key, extracted)| {
+                    value.replace(&format!("{{{{{}}}}}", key), extracted)
+                });
+            (name.clone(), value)
+        })
+        .collect()
+}
+
+/// Describes the assertions of the request broken by its response.
+///
+/// # Arguments
+///
+/// * `request` - Request of a plan
+/// * `json_response` - Response body parsed to json
+///
+pub fn failures(request: &PlanRequest, json_response: &serde_json::Value) -> Vec<String> {
+    request
+        .assertions
+        .iter()
+        .filter_map(|assertion| assertion.failure(json_response))
+        .collect()
+}
+
+/// Names, paths and values of the fields the request extracts from its response;
+/// panics when a field is missing.
+///
+/// # Arguments
+///
+/// * `request` - Request of a plan
+/// * `json_response` - Response body parsed to json
+///
+pub fn extracted(
+    request: &PlanRequest,
+    json_response: &serde_json::Value,
+) -> Vec<(String, String, String)> {
+    request
+        .extract
+        .iter()
+        .map(|(name, field_path)| {
+            let value = capture::extract_field(json_response, field_path)
+                .unwrap_or_else(|| panic!("Response does not contain field {}", field_path));
+            (name.clone(), field_path.clone(), value)
+        })
+        .collect()
+}
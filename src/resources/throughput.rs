@@ -0,0 +1,146 @@
+use super::latency::{self, EndpointLatency};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Limits of throughput tests, which send requests at a fixed pace instead of throttling them
+/// with the rate limit of the profile, so they are meant for internal APIs only.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThroughputSettings {
+    /// Whether throughput tests may override the rate limit of the profile
+    pub allowed: bool,
+    /// Maximal number of requests of a single throughput test
+    pub max_requests: usize,
+    /// Maximal accepted share of failed requests, from 0 to 1
+    pub max_error_rate: f64,
+    /// Minimal accepted ratio of achieved to requested requests per second, from 0 to 1
+    pub min_rps_ratio: f64,
+}
+
+impl Default for ThroughputSettings {
+    fn default() -> Self {
+        Self {
+            allowed: false,
+            max_requests: 1000,
+            max_error_rate: 0.01,
+            min_rps_ratio: 0.95,
+        }
+    }
+}
+
+/// Outcome of a throughput test.
+#[derive(Debug, Clone)]
+pub struct ThroughputResult {
+    pub requests: usize,
+    /// Requests which failed or were answered with an error status
+    pub errors: usize,
+    /// Time of sending the requests, which the achieved pace is measured over
+    pub dispatch: Duration,
+    /// Time of sending the requests and waiting for all responses
+    pub elapsed: Duration,
+    /// Latency of successful requests; None when none succeeded
+    pub latency: Option<EndpointLatency>,
+}
+
+impl ThroughputResult {
+    pub fn achieved_rps(&self) -> f64 {
+        self.requests as f64 / self.dispatch.as_secs_f64().max(f64::EPSILON)
+    }
+
+    pub fn error_rate(&self) -> f64 {
+        self.errors as f64 / self.requests.max(1) as f64
+    }
+}
+
+impl fmt::Display for ThroughputResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} requests sent in {:.1} s and answered in {:.1} s: {:.1} requests per second, error rate {:.2}%",
+            self.requests,
+            self.dispatch.as_secs_f64(),
+            self.elapsed.as_secs_f64(),
+            self.achieved_rps(),
+            self.error_rate() * 100.0
+        )?;
+        if let Some(latency) = &self.latency {
+            write!(
+                f,
+                ", latency p50 / p90 / p99: {} / {} / {} ms",
+                latency.p50.as_millis(),
+                latency.p90.as_millis(),
+                latency.p99.as_millis()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Number of requests sent at `requests_per_second` for `seconds`.
+pub fn total_requests(requests_per_second: u32, seconds: u32) -> Result<usize, String> {
+    u64::from(requests_per_second)
+        .checked_mul(u64::from(seconds))
+        .and_then(|total| usize::try_from(total).ok())
+        .ok_or_else(|| {
+            format!(
+                "{} requests per second for {} s is too many requests",
+                requests_per_second, seconds
+            )
+        })
+}
+
+/// Sends requests at a fixed pace, without waiting for responses of the previous ones,
+/// then waits for all responses; the pace is measured over sending only, so slow responses
+/// do not lower it.
+///
+/// # Arguments
+///
+/// * `client` - Client the requests are sent with
+/// * `method` - HTTP method of the endpoint
+/// * `url` - Url of the endpoint
+/// * `requests_per_second` - Pace of sending the requests
+/// * `seconds` - Duration of sending the requests
+///
+pub async fn run(
+    client: &reqwest::Client,
+    method: reqwest::Method,
+    url: &str,
+    requests_per_second: u32,
+    seconds: u32,
+) -> Result<ThroughputResult, String> {
+    let total = total_requests(requests_per_second, seconds)?;
+    let mut pace = tokio::time::interval(Duration::from_secs(1) / requests_per_second);
+    let start = Instant::now();
+    let mut in_flight = Vec::with_capacity(total);
+    for _ in 0..total {
+        pace.tick().await;
+        let request = client.request(method.clone(), url);
+        in_flight.push(tokio::spawn(async move {
+            let sent = Instant::now();
+            let result = request.send().await;
+            let succeeded = matches!(&result, Ok(response) if response.status().is_success());
+            (succeeded, sent.elapsed())
+        }));
+    }
+    // The last request owns the slot up to the next tick, so the window spans a slot per request.
+    pace.tick().await;
+    let dispatch = start.elapsed();
+
+    let mut errors = 0;
+    let mut latencies = latency::new_histogram();
+    for request in in_flight {
+        match request.await {
+            Ok((true, latency)) => latency::record_into(&mut latencies, latency),
+            _ => errors += 1,
+        }
+    }
+    let elapsed = start.elapsed();
+    Ok(ThroughputResult {
+        requests: total,
+        errors,
+        dispatch,
+        elapsed,
+        latency: (!latencies.is_empty()).then(|| EndpointLatency::from_histogram(&latencies)),
+    })
+}
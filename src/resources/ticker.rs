@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+
+/// Leeway of the ticker invariants, whose fields are not updated atomically by every provider.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TickerSettings {
+    /// Largest relative difference by which the last trade price may fall outside of the best bid and ask
+    pub tolerance: f64,
+}
+
+impl Default for TickerSettings {
+    fn default() -> Self {
+        Self { tolerance: 0.001 }
+    }
+}
+
+/// Decimal at the position of a ticker field, e.g. the price of `"a": ["52609.60000", "1", "1.000"]`.
+fn decimal_at(ticker: &serde_json::Value, field: &str, index: usize) -> Option<f64> {
+    match ticker.get(field)?.get(index)? {
+        serde_json::Value::String(value) => value.parse().ok(),
+        value => value.as_f64(),
+    }
+}
+
+/// Describes tickers breaking the invariants of their fields: the last trade price between the best bid
+/// and ask within the tolerance, the last trade price within the 24h low and high, and non-negative volumes.
+///
+/// # Arguments
+///
+/// * `result` - Result of the Ticker endpoint, with the ticker of every pair
+/// * `settings` - Tolerance of the comparison with the best bid and ask
+///
+pub fn violations(result: &serde_json::Value, settings: &TickerSettings) -> Vec<String> {
+    let mut violations = Vec::new();
+    for (pair, ticker) in result.as_object().into_iter().flatten() {
+        let fields = (
+            decimal_at(ticker, "a", 0),
+            decimal_at(ticker, "b", 0),
+            decimal_at(ticker, "c", 0),
+            decimal_at(ticker, "l", 1),
+            decimal_at(ticker, "h", 1),
+        );
+        let (ask, bid, last, low, high) = match fields {
+            (Some(ask), Some(bid), Some(last), Some(low), Some(high)) => {
+                (ask, bid, last, low, high)
+            }
+            _ => {
+                violations.push(format!(
+                    "{} ticker lacks the ask, bid, last trade, low or high price: {}",
+                    pair, ticker
+                ));
+                continue;
+            }
+        };
+        let leeway = last * settings.tolerance;
+        if last < bid - leeway || last > ask + leeway {
+            violations.push(format!(
+                "{} last trade price {} is outside of bid {} and ask {}",
+                pair, last, bid, ask
+            ));
+        }
+        if last < low || last > high {
+            violations.push(format!(
+                "{} last trade price {} is outside of 24h low {} and high {}",
+                pair, last, low, high
+            ));
+        }
+        for (index, period) in ["today", "24h"].iter().enumerate() {
+            if let Some(volume) = decimal_at(ticker, "v", index) {
+                if volume < 0.0 {
+                    violations.push(format!(
+                        "{} volume of {} is negative: {}",
+                        pair, period, volume
+                    ));
+                }
+            }
+        }
+    }
+    violations
+}
@@ -0,0 +1,300 @@
+use super::config::Profile;
+use openssl::asn1::Asn1Time;
+use openssl::ssl::{SslConnector, SslMethod};
+use openssl::x509::{X509NameRef, X509Ref};
+use std::env;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::{SystemTime, UNIX_EPOCH};
+use x509_parser::extensions::GeneralName;
+
+/// Longest accepted response of a proxy to the CONNECT request, in bytes.
+const MAX_PROXY_RESPONSE_BYTES: usize = 8192;
+
+/// Properties of a certificate presented by the API host.
+#[derive(Debug, Clone)]
+pub struct CertificateInfo {
+    pub subject: String,
+    pub issuer: String,
+    /// Attributes of the issuer name by their short names, e.g. ("CN", "R3"), in the order of the name
+    pub issuer_attributes: Vec<(String, String)>,
+    /// Unix timestamp after which the certificate is no longer valid
+    pub not_after: i64,
+    pub subject_alt_names: Vec<String>,
+}
+
+/// Certificates presented by the API host, starting with its own one, each issued by the next one.
+#[derive(Debug, Clone)]
+pub struct CertificateChain {
+    pub certificates: Vec<CertificateInfo>,
+}
+
+impl CertificateChain {
+    /// Certificate of the host itself.
+    pub fn leaf(&self) -> &CertificateInfo {
+        &self.certificates[0]
+    }
+
+    /// Certificate of the chain which expires first.
+    pub fn first_to_expire(&self) -> &CertificateInfo {
+        self.certificates
+            .iter()
+            .min_by_key(|certificate| certificate.not_after)
+            .expect("Certificate chain should not be empty")
+    }
+}
+
+/// Attributes of the name by their short names, e.g. ("CN", "R3").
+fn attributes_of(name: &X509NameRef) -> Vec<(String, String)> {
+    name.entries()
+        .map(|entry| {
+            let attribute = entry
+                .object()
+                .nid()
+                .short_name()
+                .map(str::to_owned)
+                .unwrap_or_else(|_| entry.object().to_string());
+            (
+                attribute,
+                String::from_utf8_lossy(entry.data().as_slice()).into_owned(),
+            )
+        })
+        .collect()
+}
+
+/// Name written as its attributes, e.g. "CN=R3, O=Let's Encrypt, C=US".
+fn name_to_string(attributes: &[(String, String)]) -> String {
+    attributes
+        .iter()
+        .map(|(attribute, value)| format!("{}={}", attribute, value))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn certificate_info(certificate: &X509Ref) -> Result<CertificateInfo, String> {
+    let der = certificate.to_der().map_err(|err| err.to_string())?;
+    let (_, parsed) = x509_parser::parse_x509_certificate(&der).map_err(|err| err.to_string())?;
+    let subject_alt_names = parsed
+        .tbs_certificate
+        .subject_alternative_name()
+        .map(|(_, names)| {
+            names
+                .general_names
+                .iter()
+                .filter_map(|name| match name {
+                    GeneralName::DNSName(dns_name) => Some(dns_name.to_string()),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let not_after = Asn1Time::from_unix(0)
+        .and_then(|epoch| epoch.diff(certificate.not_after()))
+        .map_err(|err| err.to_string())?;
+    let issuer_attributes = attributes_of(certificate.issuer_name());
+    Ok(CertificateInfo {
+        subject: name_to_string(&attributes_of(certificate.subject_name())),
+        issuer: name_to_string(&issuer_attributes),
+        issuer_attributes,
+        not_after: i64::from(not_after.days) * 24 * 60 * 60 + i64::from(not_after.secs),
+        subject_alt_names,
+    })
+}
+
+/// Proxy of HTTPS requests to the host from the environment, as used by the HTTP client,
+/// unless NO_PROXY exempts the host.
+fn proxy_for(host: &str) -> Option<url::Url> {
+    let proxy = ["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy"]
+        .iter()
+        .find_map(|name| env::var(name).ok().filter(|value| !value.is_empty()))?;
+    let no_proxy = env::var("NO_PROXY")
+        .or_else(|_| env::var("no_proxy"))
+        .unwrap_or_default();
+    let exempt = no_proxy.split(',').map(str::trim).any(|exempt| {
+        let domain = exempt.trim_start_matches('.');
+        exempt == "*"
+            || (!domain.is_empty() && (host == domain || host.ends_with(&format!(".{}", domain))))
+    });
+    if exempt {
+        return None;
+    }
+    let proxy = if proxy.contains("://") {
+        proxy
+    } else {
+        format!("http://{}", proxy)
+    };
+    url::Url::parse(&proxy).ok()
+}
+
+/// Opens a tunnel to the host through the proxy with a CONNECT request.
+fn tunnel(proxy: &url::Url, host: &str, port: u16) -> Result<TcpStream, String> {
+    let proxy_host = proxy
+        .host_str()
+        .ok_or_else(|| format!("Proxy {} has no host", proxy))?;
+    let proxy_port = proxy.port_or_known_default().unwrap_or(8080);
+    let mut stream = TcpStream::connect((proxy_host, proxy_port))
+        .map_err(|err| format!("Connecting to proxy {} failed: {}", proxy_host, err))?;
+    let mut request = format!(
+        "CONNECT {host}:{port} HTTP/1.1\r\nHost: {host}:{port}\r\n",
+        host = host,
+        port = port
+    );
+    if !proxy.username().is_empty() {
+        let credentials = format!(
+            "{}:{}",
+            proxy.username(),
+            proxy.password().unwrap_or_default()
+        );
+        request.push_str(&format!(
+            "Proxy-Authorization: Basic {}\r\n",
+            base64::encode(credentials)
+        ));
+    }
+    request.push_str("\r\n");
+    stream
+        .write_all(request.as_bytes())
+        .map_err(|err| err.to_string())?;
+
+    let mut response = Vec::new();
+    let mut byte = [0u8; 1];
+    while !response.ends_with(b"\r\n\r\n") {
+        if response.len() > MAX_PROXY_RESPONSE_BYTES {
+            return Err(format!("Proxy {} sent an oversized response", proxy_host));
+        }
+        match stream.read(&mut byte).map_err(|err| err.to_string())? {
+            0 => return Err(format!("Proxy {} closed the connection", proxy_host)),
+            _ => response.push(byte[0]),
+        }
+    }
+    let response = String::from_utf8_lossy(&response);
+    let status_line = response.lines().next().unwrap_or_default();
+    match status_line.split_whitespace().nth(1) {
+        Some(status) if status.starts_with('2') => Ok(stream),
+        _ => Err(format!(
+            "Proxy {} refused the tunnel to {}: {}",
+            proxy_host, host, status_line
+        )),
+    }
+}
+
+/// Connection to the host as the HTTP client would open it: through the proxy of the environment,
+/// or to the address the profile resolves the host to.
+fn connect(profile: &Profile, host: &str, port: u16) -> Result<TcpStream, String> {
+    if let Some(proxy) = proxy_for(host) {
+        return tunnel(&proxy, host, port);
+    }
+    let connected = match profile.resolve.get(host) {
+        Some(address) => TcpStream::connect((*address, port)),
+        None => TcpStream::connect((host, port)),
+    };
+    connected.map_err(|err| format!("Connecting to {} failed: {}", host, err))
+}
+
+/// Connects to the host and reads the certificate chain it presents during TLS handshake,
+/// honouring the proxy of the environment and the addresses resolved by the profile.
+/// The handshake verifies the chain and the host name, so an untrusted certificate results in error,
+/// as does a presented chain whose certificates are not issued by the following ones.
+///
+/// # Arguments
+///
+/// * `profile` - Settings of the environment the suite is run against
+/// * `host` - Host of the API, without port
+/// * `port` - Port of the API, usually 443
+///
+pub fn inspect_certificate(
+    profile: &Profile,
+    host: &str,
+    port: u16,
+) -> Result<CertificateChain, String> {
+    let connector = SslConnector::builder(SslMethod::tls())
+        .map_err(|err| err.to_string())?
+        .build();
+    let stream = connect(profile, host, port)?;
+    let tls_stream = connector
+        .connect(host, stream)
+        .map_err(|err| format!("TLS handshake with {} failed: {}", host, err))?;
+    let certificates = tls_stream
+        .ssl()
+        .peer_cert_chain()
+        .filter(|chain| !chain.is_empty())
+        .ok_or_else(|| format!("{} did not present a certificate", host))?
+        .iter()
+        .map(certificate_info)
+        .collect::<Result<Vec<_>, String>>()?;
+    for pair in certificates.windows(2) {
+        if pair[0].issuer != pair[1].subject {
+            return Err(format!(
+                "Certificate chain of {} is out of order: {} is issued by {}, not by the next certificate {}",
+                host, pair[0].subject, pair[0].issuer, pair[1].subject
+            ));
+        }
+    }
+    Ok(CertificateChain { certificates })
+}
+
+impl CertificateInfo {
+    /// Number of whole days left until the certificate expires; negative if already expired.
+    pub fn days_until_expiry(&self) -> i64 {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time interval from unix epoch should be positive")
+            .as_secs() as i64;
+        (self.not_after - now).div_euclid(24 * 60 * 60)
+    }
+
+    /// Whether any subject alternative name, possibly a wildcard, covers the host.
+    pub fn covers_host(&self, host: &str) -> bool {
+        let host = host.to_lowercase();
+        self.subject_alt_names.iter().any(|name| {
+            let name = name.to_lowercase();
+            match name.strip_prefix("*.") {
+                Some(domain) => host
+                    .split_once('.')
+                    .map_or(false, |(label, rest)| !label.is_empty() && rest == domain),
+                None => name == host,
+            }
+        })
+    }
+
+    /// Whether every attribute of the expected issuer, e.g. "CN=R3, O=Let's Encrypt", equals
+    /// the attribute of the issuer name exactly; attributes left out of the expected issuer are not compared.
+    /// Values may contain commas, e.g. "O=Acme, Inc".
+    ///
+    /// # Arguments
+    ///
+    /// * `expected_issuer` - Attributes of the issuer name separated by commas
+    ///
+    pub fn is_issued_by(&self, expected_issuer: &str) -> Result<bool, String> {
+        // Parts without "=" continue the value of the previous attribute, e.g. "O=Acme, Inc"
+        let mut parts: Vec<String> = Vec::new();
+        for part in expected_issuer.split(',') {
+            match parts.last_mut() {
+                Some(previous) if !part.contains('=') => {
+                    previous.push(',');
+                    previous.push_str(part);
+                }
+                _ => parts.push(part.to_owned()),
+            }
+        }
+        let expected_attributes = parts
+            .iter()
+            .map(|attribute| {
+                attribute
+                    .split_once('=')
+                    .map(|(name, value)| (name.trim(), value.trim()))
+                    .filter(|(name, _)| !name.is_empty())
+                    .ok_or_else(|| {
+                        format!(
+                            "Issuer should be given as attributes, e.g. \"CN=R3, O=Let's Encrypt\", got {}",
+                            expected_issuer
+                        )
+                    })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(expected_attributes.iter().all(|(name, value)| {
+            self.issuer_attributes
+                .iter()
+                .any(|(attribute, actual)| attribute == name && actual == value)
+        }))
+    }
+}
@@ -0,0 +1,126 @@
+use cucumber::gherkin;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+
+/// Translated phrase of a step, kept in the `[[translations.<language>]]` tables of the config file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StepTranslation {
+    /// Regular expression matching the whole translated step, e.g. "^pytam o czas serwera$"
+    pub pattern: String,
+    /// Step the translated one is replaced with, e.g. "I request server time";
+    /// groups of the pattern are referred to as $1, $2 and so on
+    pub step: String,
+}
+
+/// Compiled translations by language, applied to steps of features written in that language
+/// before they are matched with step functions.
+#[derive(Debug, Clone, Default)]
+pub struct StepTranslations {
+    /// Translations of every language, in the order of the config file
+    rules: BTreeMap<String, Vec<(Regex, String)>>,
+}
+
+/// Language of the feature file, given by its "# language: <code>" header; English when there is none.
+fn language_of(feature: &gherkin::Feature) -> String {
+    let content = feature
+        .path
+        .as_ref()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .unwrap_or_default();
+    content
+        .lines()
+        .map(str::trim)
+        .take_while(|line| line.is_empty() || line.starts_with('#'))
+        .find_map(|line| {
+            let (key, language) = line.trim_start_matches('#').split_once(':')?;
+            (key.trim() == "language").then(|| language.trim().to_owned())
+        })
+        .unwrap_or_else(|| "en".to_owned())
+}
+
+impl StepTranslations {
+    /// Compiles translations, panicking when a pattern is not a valid regular expression.
+    ///
+    /// # Arguments
+    ///
+    /// * `translations` - Translations of the config file, by language
+    ///
+    pub fn compile(translations: &HashMap<String, Vec<StepTranslation>>) -> Self {
+        let rules = translations
+            .iter()
+            .map(|(language, steps)| {
+                let steps = steps
+                    .iter()
+                    .map(|translation| {
+                        let pattern = Regex::new(&translation.pattern).unwrap_or_else(|err| {
+                            panic!(
+                                "Translation pattern {} of language {} is invalid: {}",
+                                translation.pattern, language, err
+                            )
+                        });
+                        (pattern, translation.step.clone())
+                    })
+                    .collect();
+                (language.clone(), steps)
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// Translates the step using the first matching translation of the language, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `language` - Gherkin language of the feature, e.g. "pl"
+    /// * `step` - Text of the step, without its keyword
+    ///
+    pub fn translate(&self, language: &str, step: &str) -> Option<String> {
+        let rules = self.rules.get(language)?;
+        rules.iter().find_map(|(pattern, translated)| {
+            let captures = pattern.captures(step)?;
+            let mut expanded = String::new();
+            captures.expand(translated, &mut expanded);
+            Some(expanded)
+        })
+    }
+
+    /// Translates all steps of the feature with the translations of its language;
+    /// steps without matching translation are left as they are.
+    ///
+    /// # Arguments
+    ///
+    /// * `feature` - Parsed feature
+    ///
+    pub fn translate_feature(&self, feature: &mut gherkin::Feature) {
+        if self.rules.is_empty() {
+            return;
+        }
+        let language = language_of(feature);
+        if !self.rules.contains_key(&language) {
+            return;
+        }
+        let mut steps: Vec<&mut gherkin::Step> = Vec::new();
+        if let Some(background) = feature.background.as_mut() {
+            steps.extend(background.steps.iter_mut());
+        }
+        for scenario in feature.scenarios.iter_mut() {
+            steps.extend(scenario.steps.iter_mut());
+        }
+        for rule in feature.rules.iter_mut() {
+            if let Some(background) = rule.background.as_mut() {
+                steps.extend(background.steps.iter_mut());
+            }
+            for scenario in rule.scenarios.iter_mut() {
+                steps.extend(scenario.steps.iter_mut());
+            }
+        }
+        for step in steps {
+            if let Some(translated) = self.translate(&language, &step.value) {
+                step.value = translated;
+            }
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use super::config::Profile;
+use once_cell::sync::Lazy;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// What a validator knows about the response besides its body.
+#[derive(Debug, Clone, Copy)]
+pub struct ValidationContext<'a> {
+    /// Name of the endpoint which returned the response, if it was requested through the registry
+    pub endpoint_name: Option<&'a str>,
+    /// HTTP status of the response
+    pub status: u16,
+    pub scenario_key: &'a str,
+    /// Profile of the run, e.g. for `variables` the validator depends on
+    pub profile: &'a Profile,
+}
+
+/// Problems a validator found in the response; the response passes when there are none.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationReport {
+    pub problems: Vec<String>,
+}
+
+impl ValidationReport {
+    /// Report of a response which passed the validator.
+    pub fn passed() -> Self {
+        Self::default()
+    }
+
+    /// Adds the problem to the report.
+    ///
+    /// # Arguments
+    ///
+    /// * `problem` - Description of the problem, e.g. "result.total differs from the sum of result.items"
+    ///
+    pub fn fail(&mut self, problem: impl Into<String>) {
+        self.problems.push(problem.into());
+    }
+
+    pub fn is_passed(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// Check of responses defined outside the suite, e.g. a proprietary business rule of a downstream crate,
+/// run by "the response passes the <name> validator" once registered under the name.
+pub trait ResponseValidator: Send + Sync {
+    /// Validates the response body.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - Endpoint, status and scenario of the response
+    /// * `json_response` - Response body parsed to json
+    ///
+    fn validate(&self, ctx: &ValidationContext, json_response: &Value) -> ValidationReport;
+}
+
+/// Validators registered for the run, by name.
+static VALIDATORS: Lazy<Mutex<HashMap<String, Arc<dyn ResponseValidator>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Registers the validator under the name, replacing a validator registered under it before.
+/// Validators are registered before the run, e.g. in `main` of the test binary of a downstream crate.
+///
+/// # Arguments
+///
+/// * `name` - Name steps refer to the validator by, e.g. "order_totals"
+/// * `validator` - The validator
+///
+pub fn register(name: &str, validator: impl ResponseValidator + 'static) {
+    VALIDATORS
+        .lock()
+        .expect("Validators should not be poisoned")
+        .insert(name.to_owned(), Arc::new(validator));
+}
+
+/// Registers the validator under the name, failing when a validator is already registered under it,
+/// so validators loaded at startup, e.g. from scripts, do not silently replace others.
+///
+/// # Arguments
+///
+/// * `name` - Name steps refer to the validator by, e.g. "order_totals"
+/// * `validator` - The validator
+///
+pub fn register_new(name: &str, validator: impl ResponseValidator + 'static) -> Result<(), String> {
+    let mut validators = VALIDATORS
+        .lock()
+        .expect("Validators should not be poisoned");
+    if validators.contains_key(name) {
+        return Err(format!("A validator is already registered as {}", name));
+    }
+    validators.insert(name.to_owned(), Arc::new(validator));
+    Ok(())
+}
+
+/// The validator registered under the name.
+///
+/// # Arguments
+///
+/// * `name` - Name the validator was registered under
+///
+pub fn validator(name: &str) -> Option<Arc<dyn ResponseValidator>> {
+    VALIDATORS
+        .lock()
+        .expect("Validators should not be poisoned")
+        .get(name)
+        .cloned()
+}
+
+/// Names of all registered validators, sorted.
+pub fn registered_names() -> Vec<String> {
+    let mut names: Vec<String> = VALIDATORS
+        .lock()
+        .expect("Validators should not be poisoned")
+        .keys()
+        .cloned()
+        .collect();
+    names.sort();
+    names
+}
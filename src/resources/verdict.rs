@@ -0,0 +1,153 @@
+use super::drift::DriftReport;
+use super::results::{RunResult, ScenarioStatus};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Policy deciding whether the run as a whole fails, so monitoring runs tolerate benign additive changes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VerdictPolicy {
+    /// Number of failed scenarios tolerated
+    pub max_failed_scenarios: usize,
+    /// Failures containing any of these texts, e.g. transport or authentication errors, are never tolerated
+    pub critical_failures: Vec<String>,
+    /// Number of fields absent from schemas tolerated; not judged when not set
+    pub max_undeclared_fields: Option<usize>,
+    /// Number of values outside of declared enumerations tolerated; not judged when not set
+    pub max_values_outside_enum: Option<usize>,
+}
+
+impl Default for VerdictPolicy {
+    fn default() -> Self {
+        Self {
+            max_failed_scenarios: 0,
+            critical_failures: vec![
+                "error sending request".to_owned(),
+                "EAPI:Invalid key".to_owned(),
+                "EAPI:Invalid signature".to_owned(),
+                "EGeneral:Permission denied".to_owned(),
+            ],
+            max_undeclared_fields: None,
+            max_values_outside_enum: None,
+        }
+    }
+}
+
+/// Verdict of the run along with the counts it was based on.
+#[derive(Debug, Clone, Default)]
+pub struct Verdict {
+    pub failed_scenarios: usize,
+    pub critical_failures: Vec<String>,
+    pub undeclared_fields: usize,
+    pub values_outside_enum: usize,
+    /// Limits of the policy which were exceeded
+    pub violations: Vec<String>,
+}
+
+impl Verdict {
+    pub fn is_passed(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Panics with the violations of the policy, failing the run, unless the verdict is passed.
+    pub fn enforce(&self) {
+        if !self.is_passed() {
+            panic!("The run failed:\n{}", self.violations.join("\n"))
+        }
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Verdict: {} ({} failed scenarios, {} undeclared fields, {} values outside enumerations)",
+            if self.is_passed() { "passed" } else { "failed" },
+            self.failed_scenarios,
+            self.undeclared_fields,
+            self.values_outside_enum
+        )
+    }
+}
+
+/// Judges the run according to the policy.
+///
+/// # Arguments
+///
+/// * `run_result` - Result of the run; known issues are not counted as failures
+/// * `drift_report` - Schema drift of the run
+/// * `policy` - Policy of the profile
+///
+pub fn judge(
+    run_result: &RunResult,
+    drift_report: &DriftReport,
+    policy: &VerdictPolicy,
+) -> Verdict {
+    let failed: Vec<_> = run_result
+        .scenarios
+        .iter()
+        .filter(|outcome| outcome.status == ScenarioStatus::Failed)
+        .collect();
+    let critical_failures: Vec<String> = failed
+        .iter()
+        .filter(|outcome| {
+            let failure = outcome.failure.as_deref().unwrap_or_default();
+            policy
+                .critical_failures
+                .iter()
+                .any(|critical| failure.contains(critical.as_str()))
+        })
+        .map(|outcome| format!("{}: {}", outcome.feature, outcome.scenario))
+        .collect();
+    let undeclared_fields = drift_report
+        .schemas
+        .values()
+        .map(|drift| drift.undeclared.len())
+        .sum();
+    let values_outside_enum = drift_report
+        .schemas
+        .values()
+        .flat_map(|drift| drift.outside_enum.values())
+        .map(|values| values.len())
+        .sum();
+
+    let mut violations = Vec::new();
+    for scenario in &critical_failures {
+        violations.push(format!("critical failure of {}", scenario));
+    }
+    if failed.len() > policy.max_failed_scenarios {
+        let scenarios: Vec<String> = failed
+            .iter()
+            .map(|outcome| format!("  {}: {}", outcome.feature, outcome.scenario))
+            .collect();
+        violations.push(format!(
+            "{} failed scenarios exceed the {} tolerated:\n{}",
+            failed.len(),
+            policy.max_failed_scenarios,
+            scenarios.join("\n")
+        ));
+    }
+    if let Some(max_undeclared_fields) = policy.max_undeclared_fields {
+        if undeclared_fields > max_undeclared_fields {
+            violations.push(format!(
+                "{} undeclared fields exceed the {} tolerated",
+                undeclared_fields, max_undeclared_fields
+            ));
+        }
+    }
+    if let Some(max_values_outside_enum) = policy.max_values_outside_enum {
+        if values_outside_enum > max_values_outside_enum {
+            violations.push(format!(
+                "{} values outside enumerations exceed the {} tolerated",
+                values_outside_enum, max_values_outside_enum
+            ));
+        }
+    }
+    Verdict {
+        failed_scenarios: failed.len(),
+        critical_failures,
+        undeclared_fields,
+        values_outside_enum,
+        violations,
+    }
+}
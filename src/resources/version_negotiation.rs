@@ -0,0 +1,68 @@
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+
+/// Headers of header-based API versioning, used by "I request the "<Endpoint>" endpoint accepting version "<version>"".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VersionNegotiationSettings {
+    /// Header of the request asking for a version, e.g. "Accept-Version"
+    pub request_header: String,
+    /// Header of the response indicating the version served, e.g. "API-Version"
+    pub response_header: String,
+}
+
+impl Default for VersionNegotiationSettings {
+    fn default() -> Self {
+        Self {
+            request_header: "Accept-Version".to_owned(),
+            response_header: "API-Version".to_owned(),
+        }
+    }
+}
+
+/// Version the response indicates: the response header of the settings or, without it,
+/// the `version` parameter of the media type, e.g. "application/vnd.api+json; version=2".
+///
+/// # Arguments
+///
+/// * `headers` - Headers of the response
+/// * `settings` - Version negotiation settings of the profile
+///
+pub fn indicated_version(
+    headers: &HeaderMap,
+    settings: &VersionNegotiationSettings,
+) -> Option<String> {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+    };
+    if let Some(version) = header(settings.response_header.as_str()) {
+        return Some(version.to_owned());
+    }
+    header("content-type")?
+        .split(';')
+        .skip(1)
+        .filter_map(|parameter| parameter.trim().split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("version"))
+        .map(|(_, version)| version.trim().trim_matches('"').to_owned())
+}
+
+/// Whether the indicated version is the expected one, ignoring a "v" prefix, e.g. "v2" and "2".
+///
+/// # Arguments
+///
+/// * `indicated` - Version indicated by the response
+/// * `expected` - Version requested by the scenario
+///
+pub fn versions_match(indicated: &str, expected: &str) -> bool {
+    let normalized = |version: &str| {
+        let version = version.trim();
+        version
+            .strip_prefix(|prefix: char| prefix == 'v' || prefix == 'V')
+            .unwrap_or(version)
+            .to_owned()
+    };
+    normalized(indicated) == normalized(expected)
+}
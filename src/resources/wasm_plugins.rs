@@ -0,0 +1,218 @@
+use super::validators::{self, ResponseValidator, ValidationContext, ValidationReport};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::io;
+use std::path::Path;
+use wasmi::core::TrapCode;
+use wasmi::{Config, Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+/// Catalogue of validators compiled to WebAssembly, registered at startup.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WasmPluginSettings {
+    /// Catalogue with "<name>.wasm" plugins, each registered as the validator "<name>"
+    pub dir: String,
+    /// Fuel of a single validation, roughly the number of executed instructions, guarding against endless loops
+    pub max_fuel: u64,
+    /// Largest memory of a plugin, in bytes
+    pub max_memory_bytes: usize,
+}
+
+impl Default for WasmPluginSettings {
+    fn default() -> Self {
+        Self {
+            dir: "./plugins".to_owned(),
+            max_fuel: 100_000_000,
+            max_memory_bytes: 64 * 1024 * 1024,
+        }
+    }
+}
+
+/// Largest list of problems read back from a plugin, which guards against corrupted output.
+const MAX_OUTPUT_BYTES: usize = 16 * 1024 * 1024;
+
+/// Validator compiled to WebAssembly. The module exports `memory`, `alloc(len: i32) -> i32` returning
+/// a buffer for the input, and `validate(ptr: i32, len: i32) -> i64` reading the input, a json object with
+/// `endpoint`, `status` and `response`, and returning the location of its output packed as `ptr << 32 | len`:
+/// a json array of problems, empty when the response passes.
+/// Modules get no imports, so they cannot reach files, network or clock, and every response is validated
+/// by a fresh instance, so nothing is kept between validations.
+/// A validation fails once it runs out of fuel or its memory grows over the limit.
+pub struct WasmValidator {
+    name: String,
+    engine: Engine,
+    module: Module,
+    max_fuel: u64,
+    max_memory_bytes: usize,
+}
+
+/// Describes a trap of the plugin, telling exceeded limits apart from its own failures.
+fn trap_description(function: &str, err: wasmi::Error) -> String {
+    match err.as_trap_code() {
+        Some(TrapCode::OutOfFuel) => format!("ran out of fuel in {}", function),
+        Some(TrapCode::GrowthOperationLimited) => {
+            format!("exceeded the memory limit in {}", function)
+        }
+        _ => format!("trapped in {}: {}", function, err),
+    }
+}
+
+impl WasmValidator {
+    /// Compiles the plugin.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the validator
+    /// * `wasm` - Content of the ".wasm" file
+    /// * `settings` - Plugin settings of the profile, limiting fuel and memory of validations
+    ///
+    pub fn new(name: &str, wasm: &[u8], settings: &WasmPluginSettings) -> Result<Self, String> {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, wasm).map_err(|err| err.to_string())?;
+        Ok(Self {
+            name: name.to_owned(),
+            engine,
+            module,
+            max_fuel: settings.max_fuel,
+            max_memory_bytes: settings.max_memory_bytes,
+        })
+    }
+
+    /// Runs the plugin on the input, returning the problems it found.
+    fn run(&self, input: &[u8]) -> Result<Vec<String>, String> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(self.max_memory_bytes)
+            .trap_on_grow_failure(true)
+            .build();
+        let mut store = Store::new(&self.engine, limits);
+        store.limiter(|limits: &mut StoreLimits| limits);
+        store
+            .set_fuel(self.max_fuel)
+            .map_err(|err| format!("cannot be given fuel: {}", err))?;
+        let linker = <Linker<StoreLimits>>::new(&self.engine);
+        let instance = linker
+            .instantiate(&mut store, &self.module)
+            .and_then(|instance| instance.start(&mut store))
+            .map_err(|err| match err.as_trap_code() {
+                Some(_) => trap_description("start", err),
+                None => format!("cannot be instantiated: {}", err),
+            })?;
+        let export = |name: &str| {
+            instance
+                .get_export(&store, name)
+                .ok_or_else(|| format!("does not export {}", name))
+        };
+        let memory = export("memory")?
+            .into_memory()
+            .ok_or("exports memory which is not a memory")?;
+        let alloc = export("alloc")?
+            .into_func()
+            .ok_or("exports alloc which is not a function")?
+            .typed::<i32, i32>(&store)
+            .map_err(|err| format!("exports alloc of a wrong type: {}", err))?;
+        let validate = export("validate")?
+            .into_func()
+            .ok_or("exports validate which is not a function")?
+            .typed::<(i32, i32), i64>(&store)
+            .map_err(|err| format!("exports validate of a wrong type: {}", err))?;
+
+        let input_len = input.len() as i32;
+        let input_ptr = alloc
+            .call(&mut store, input_len)
+            .map_err(|err| trap_description("alloc", err))?;
+        memory
+            .write(&mut store, input_ptr as u32 as usize, input)
+            .map_err(|err| format!("returned a buffer outside its memory: {}", err))?;
+        let packed = validate
+            .call(&mut store, (input_ptr, input_len))
+            .map_err(|err| trap_description("validate", err))?;
+        let output_ptr = (packed >> 32) as u32 as usize;
+        let output_len = (packed & 0xffff_ffff) as usize;
+        if output_len > MAX_OUTPUT_BYTES {
+            return Err(format!("returned {} bytes of problems", output_len));
+        }
+        let mut output = vec![0; output_len];
+        memory
+            .read(&store, output_ptr, &mut output)
+            .map_err(|err| format!("returned problems outside its memory: {}", err))?;
+        serde_json::from_slice(&output).map_err(|err| {
+            format!(
+                "returned problems which are not a json array of strings: {}",
+                err
+            )
+        })
+    }
+}
+
+impl ResponseValidator for WasmValidator {
+    fn validate(&self, ctx: &ValidationContext, json_response: &Value) -> ValidationReport {
+        let input = json!({
+            "endpoint": ctx.endpoint_name,
+            "status": ctx.status,
+            "response": json_response,
+        });
+        let input = serde_json::to_vec(&input).expect("Input of the plugin should be serializable");
+        let mut report = ValidationReport::passed();
+        match self.run(&input) {
+            Ok(problems) => problems
+                .into_iter()
+                .for_each(|problem| report.fail(problem)),
+            Err(err) => report.fail(format!("Plugin {} {}", self.name, err)),
+        }
+        report
+    }
+}
+
+/// Compiles the ".wasm" plugins of the catalogue and registers them as validators named after their files,
+/// returning the names; a missing catalogue holds no plugins.
+///
+/// # Arguments
+///
+/// * `settings` - Plugin settings of the profile, with the catalogue of the plugins
+///
+pub fn register_dir(settings: &WasmPluginSettings) -> io::Result<Vec<String>> {
+    let entries = match fs::read_dir(&settings.dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .map_or(false, |extension| extension == "wasm")
+        })
+        .collect();
+    paths.sort();
+    let mut names = Vec::new();
+    for path in paths {
+        let name = plugin_name(&path)?;
+        let validator = WasmValidator::new(&name, &fs::read(&path)?, settings).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Plugin {:?} is not a valid WebAssembly module: {}",
+                    path, err
+                ),
+            )
+        })?;
+        validators::register(&name, validator);
+        names.push(name);
+    }
+    Ok(names)
+}
+
+fn plugin_name(path: &Path) -> io::Result<String> {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .map(str::to_owned)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Plugin {:?} should be named with UTF-8", path),
+            )
+        })
+}
@@ -0,0 +1,181 @@
+use api_verify::resources::config::{Profile, CONFIG_FILE, DEFAULT_API_VERSION};
+use api_verify::resources::naming::{self, to_snake_case};
+use serde_json::json;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Generates everything needed to verify a new endpoint with the generic steps:
+/// * Feature file skeleton in "./features/public" or "./features/private"
+/// * Placeholder json schema in "./schemas", or "./schemas/v<version>" for other versions than the default one
+/// * Entry of the endpoint in the registry kept in "./api_verify.toml", for the API version of the profile
+///
+/// Nothing is written when the feature or the schema file already exists.
+///
+/// # Arguments
+///
+/// * `endpoint_name` - Name of the endpoint as present in its path, e.g. OpenPositions
+/// * `private` - Whether the endpoint is a part of private API
+///
+pub fn scaffold_endpoint(endpoint_name: &str, private: bool) -> io::Result<()> {
+    validate_endpoint_name(endpoint_name)?;
+    let profile = Profile::load()
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err.to_string()))?;
+    let api_version = &profile.api_version;
+    let snake_case_name = to_snake_case(endpoint_name);
+    let api_type = if private { "private" } else { "public" };
+
+    let feature_path = format!("./features/{}/{}.feature", api_type, snake_case_name);
+    let schema_file = naming::schema_file(endpoint_name);
+    let (schema_path, table) = if api_version == DEFAULT_API_VERSION {
+        (
+            format!("./schemas/{}", schema_file),
+            format!("endpoints.{}", endpoint_name),
+        )
+    } else {
+        (
+            format!("./schemas/v{}/{}", api_version, schema_file),
+            format!("versions.{}.endpoints.{}", api_version, endpoint_name),
+        )
+    };
+    for path in [&feature_path, &schema_path] {
+        if Path::new(path).exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("Cannot create {}: file exists", path),
+            ));
+        }
+    }
+    create_new_file(&feature_path, &feature_skeleton(endpoint_name, private))?;
+    create_new_file(&schema_path, &placeholder_schema(endpoint_name))?;
+
+    let registry_entry = format!(
+        "[{table}]\npath = \"/{version}/{api_type}/{name}\"\nauth = \"{api_type}\"\nschema = \"{schema_file}\"\n",
+        table = table,
+        version = api_version,
+        name = endpoint_name,
+        api_type = api_type,
+        schema_file = schema_file,
+    );
+    let registry_entry_added = append_registry_entry(CONFIG_FILE, &table, &registry_entry)?;
+
+    println!("Created {}", feature_path);
+    println!("Created {}", schema_path);
+    if registry_entry_added {
+        println!("Added endpoint {} to {}", endpoint_name, CONFIG_FILE);
+    } else {
+        println!(
+            "Endpoint {} is already defined in {}",
+            endpoint_name, CONFIG_FILE
+        );
+    }
+    Ok(())
+}
+
+/// Endpoint names are used in file names and config tables,
+/// so only alphanumeric upper camel case names are accepted.
+fn validate_endpoint_name(endpoint_name: &str) -> io::Result<()> {
+    let starts_uppercase = endpoint_name
+        .chars()
+        .next()
+        .map_or(false, |character| character.is_ascii_uppercase());
+    let is_alphanumeric = endpoint_name
+        .chars()
+        .all(|character| character.is_ascii_alphanumeric());
+    if starts_uppercase && is_alphanumeric {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Endpoint name should be in upper camel case, e.g. OpenPositions; got: {}",
+                endpoint_name
+            ),
+        ))
+    }
+}
+
+pub fn feature_skeleton(endpoint_name: &str, private: bool) -> String {
+    let (api_type, given_step) = if private {
+        (
+            "private",
+            "I have some properties concerning a private API".to_owned(),
+        )
+    } else {
+        (
+            "public",
+            format!("I have link to the public \"{}\" endpoint", endpoint_name),
+        )
+    };
+    format!(
+        "Feature: {name} endpoint
+
+  Scenario: If I request {name} from {api_type} api, it returns correct format
+    Given {given_step}
+    When I request the \"{name}\" endpoint
+    Then the \"{name}\" response format is correct
+",
+        name = endpoint_name,
+        api_type = api_type,
+        given_step = given_step,
+    )
+}
+
+fn placeholder_schema(endpoint_name: &str) -> String {
+    let schema = json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": endpoint_name,
+        "type": "object",
+        "properties": {
+            "error": { "type": "array" },
+            "result": { "type": "object" }
+        },
+        "required": ["error", "result"]
+    });
+    let mut content =
+        serde_json::to_string_pretty(&schema).expect("Schema should be possible to serialize");
+    content.push('\n');
+    content
+}
+
+pub fn create_new_file(path: &str, content: &str) -> io::Result<()> {
+    if let Some(parent) = Path::new(path).parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .map_err(|err| io::Error::new(err.kind(), format!("Cannot create {}: {}", path, err)))?;
+    file.write_all(content.as_bytes())
+}
+
+/// Appends the table of the endpoint, e.g. `[endpoints.<Name>]`, to the config file,
+/// unless the endpoint is already defined there. Returns whether the table was appended.
+pub fn append_registry_entry(config_file: &str, table: &str, entry: &str) -> io::Result<bool> {
+    let existing_content = match fs::read_to_string(config_file) {
+        Ok(content) => content,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err),
+    };
+    let table_header = format!("[{}]", table);
+    if existing_content
+        .lines()
+        .any(|line| line.trim() == table_header)
+    {
+        return Ok(false);
+    }
+
+    let mut file = OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(config_file)?;
+    if !existing_content.is_empty() {
+        if !existing_content.ends_with('\n') {
+            writeln!(file)?;
+        }
+        writeln!(file)?;
+    }
+    write!(file, "{}", entry)?;
+    Ok(true)
+}
@@ -0,0 +1,6 @@
+//! Step definitions of the suites, registered for their worlds; downstream crates may define
+//! their own steps for the same worlds and run them together in their own cucumber binaries.
+pub mod private;
+pub mod public;
+pub mod shared;
+pub mod suite;
@@ -0,0 +1,971 @@
+//! Steps verifying private endpoints, along with the world they share.
+use super::shared::{self, check_severity, take_json_response, StepWorld};
+use super::suite::SuiteWorld;
+use crate::resources::config::{
+    self, Auth, ConfigError, EndpointRegistry, PrivateApiProperties, Profile,
+};
+use crate::resources::parameters::{AssetPair, OrderSide, StepDuration};
+use crate::resources::run_options::RunOptions;
+use crate::resources::{
+    access, accounts, api_errors, attachment, balance_guard, budget, capture, correlation,
+    fixtures, http_client, key_permissions, ndjson, openapi, order_consistency, orders,
+    permissions, rate_limit, reconciliation, reference, request_handler, scripting, scrubbing,
+    severity, snapshot, tags, test_plan,
+};
+use async_trait::async_trait;
+use cucumber::gherkin::{self, Step};
+use cucumber::{given, then, when, World, WorldInit};
+use reqwest::header::{HeaderMap, HeaderValue};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[given("I have some properties concerning a private API")]
+pub fn setup_api_properties(world: &mut ApiWorld) {
+    let properties = accounts::properties_for(&world.profile, world.account.as_deref())
+        .unwrap_or_else(|err| panic!("{}", err));
+    world.private_api_properties = Some(properties);
+}
+
+/// Signs and sends a request to the registered endpoint, once the profile allows it, it fits within
+/// the request budget and its weight fits within the rate limit, keeping the signed request in the world.
+pub async fn request_private_endpoint(
+    world: &mut ApiWorld,
+    properties: &PrivateApiProperties,
+    endpoint_name: &str,
+    clock_skew_seconds: i64,
+) -> reqwest::Result<()> {
+    request_private_endpoint_with(world, properties, endpoint_name, clock_skew_seconds, &[]).await
+}
+
+/// Signs and sends a request to the registered endpoint like `request_private_endpoint`,
+/// with `parameters` appended to its payload.
+pub async fn request_private_endpoint_with(
+    world: &mut ApiWorld,
+    properties: &PrivateApiProperties,
+    endpoint_name: &str,
+    clock_skew_seconds: i64,
+    parameters: &[(&str, &str)],
+) -> reqwest::Result<()> {
+    let (endpoint_path, weight) = private_endpoint_of(world, endpoint_name);
+    budget::consume(&world.profile.request_budget, &mut world.scenario_requests);
+    rate_limit::acquire(&world.profile.rate_limit, endpoint_name, weight).await;
+    let signed_request = request_handler::sign_private_request_with(
+        &properties.api_key,
+        &properties.api_secret,
+        &properties.otp_secret,
+        &properties.api_link,
+        &endpoint_path,
+        &properties.signer_config,
+        clock_skew_seconds,
+        parameters,
+    );
+    check_openapi_request(world, endpoint_name, &signed_request);
+    let headers = request_id_headers(world);
+    let (client, _) = http_client::build_client(&world.profile);
+    let result = shared::timed(
+        world,
+        endpoint_name,
+        request_handler::send_signed_request_with_headers(&client, &signed_request, headers),
+    )
+    .await;
+    record_request(world, endpoint_name, &signed_request, &result);
+    world.last_signed_request = Some(signed_request);
+    world.endpoint_name = Some(endpoint_name.to_owned());
+    world.raw_api_response = Some(result?);
+    Ok(())
+}
+
+/// Checks the signed request against the OpenAPI spec of the profile before it is sent,
+/// so that invalid requests of the suite are not reported as failures of the server.
+pub fn check_openapi_request(
+    world: &ApiWorld,
+    endpoint_name: &str,
+    signed_request: &request_handler::SignedRequest,
+) {
+    let settings = &world.profile.openapi;
+    let spec_file = match (&settings.spec, settings.validate_requests) {
+        (Some(spec_file), true) => spec_file,
+        _ => return,
+    };
+    let url = url::Url::parse(&signed_request.full_link).expect("API link should be a valid URL");
+    let query: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+    let body: Vec<(String, String)> =
+        url::form_urlencoded::parse(signed_request.url_encoded_payload.as_bytes())
+            .into_owned()
+            .collect();
+    let method = reqwest::Method::from_bytes(request_handler::PRIVATE_API_METHOD.as_bytes())
+        .expect("Private API method should be valid");
+    let problems = openapi::request_problems(
+        &openapi::load_spec(spec_file),
+        &method,
+        url.path(),
+        &query,
+        Some(&body),
+    );
+    // The endpoint of the world is still the previous one until the response arrives
+    let severity = world
+        .warning_checks
+        .severity("openapi_request", world.endpoints.get(endpoint_name).ok());
+    severity::report(
+        &world.scenario_key,
+        severity,
+        "Our request is invalid per the OpenAPI spec",
+        &problems,
+    );
+}
+
+/// Headers with a new request ID, kept in the world, when the profile enables request IDs.
+pub fn request_id_headers(world: &mut ApiWorld) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    world.request_id = None;
+    if let Some((header_name, request_id)) =
+        correlation::request_id_header(&world.profile.correlation)
+    {
+        let value =
+            HeaderValue::from_str(&request_id).expect("Request ID should be a valid header");
+        headers.insert(header_name, value);
+        world.request_id = Some(request_id);
+    }
+    headers
+}
+
+/// Adds the request to the transcript kept for the snapshot of a failed scenario.
+pub fn record_request(
+    world: &ApiWorld,
+    endpoint_name: &str,
+    signed_request: &request_handler::SignedRequest,
+    result: &reqwest::Result<reqwest::Response>,
+) {
+    let transcript = snapshot::transcript_of(
+        endpoint_name,
+        request_handler::PRIVATE_API_METHOD,
+        &signed_request.full_link,
+        result,
+    );
+    snapshot::record_request(&world.scenario_key, transcript);
+    shared::observe_deprecation(world, endpoint_name, result);
+}
+
+#[when("I request all open orders")]
+pub async fn request_server_time(world: &mut ApiWorld) -> reqwest::Result<()> {
+    let properties = world
+        .private_api_properties
+        .take()
+        .expect("Api properties are empty");
+
+    let result = request_private_endpoint(world, &properties, "OpenOrders", 0).await;
+    world.private_api_properties = Some(properties);
+    result
+}
+
+#[when(regex = r#"^I request the "(\w+)" endpoint$"#)]
+pub async fn request_named_endpoint(
+    world: &mut ApiWorld,
+    endpoint_name: String,
+) -> reqwest::Result<()> {
+    let properties = world
+        .private_api_properties
+        .take()
+        .expect("Api properties are empty");
+
+    let result = request_private_endpoint(world, &properties, &endpoint_name, 0).await;
+    world.private_api_properties = Some(properties);
+    result
+}
+
+#[when(
+    regex = r#"^I request the "(\w+)" endpoint with the clock (\d+) (seconds|minutes) (behind|ahead)$"#
+)]
+pub async fn request_named_endpoint_with_skew(
+    world: &mut ApiWorld,
+    endpoint_name: String,
+    amount: i64,
+    unit: String,
+    direction: String,
+) -> reqwest::Result<()> {
+    let properties = world
+        .private_api_properties
+        .take()
+        .expect("Api properties are empty");
+    let seconds = if unit == "minutes" {
+        amount * 60
+    } else {
+        amount
+    };
+    let clock_skew_seconds = if direction == "behind" {
+        -seconds
+    } else {
+        seconds
+    };
+
+    let result =
+        request_private_endpoint(world, &properties, &endpoint_name, clock_skew_seconds).await;
+    world.private_api_properties = Some(properties);
+    result
+}
+
+#[when(expr = "I validate a {order-side} order for {asset-pair}")]
+pub async fn validate_derived_order(
+    world: &mut ApiWorld,
+    side: OrderSide,
+    pair: AssetPair,
+) -> reqwest::Result<()> {
+    let properties = world
+        .private_api_properties
+        .take()
+        .expect("Api properties are empty");
+    let pair_info = world.reference.require_pair(&pair.0).clone();
+    let last_price = orders::last_price(&world.profile, &world.endpoints, &pair_info.altname).await;
+    let order = orders::derive_order(&pair_info, side, last_price, &world.profile.orders);
+    balance_guard::skip_if_unaffordable(
+        &world.profile,
+        &world.endpoints,
+        &world.scenario_key,
+        world.account.as_deref(),
+        &pair_info,
+        &order,
+    )
+    .await;
+    let parameters = order.parameters();
+    let parameters: Vec<(&str, &str)> = parameters
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_str()))
+        .collect();
+    let endpoint_name = world.profile.orders.endpoint.clone();
+    world.derived_order = Some(order);
+    let result =
+        request_private_endpoint_with(world, &properties, &endpoint_name, 0, &parameters).await;
+    world.private_api_properties = Some(properties);
+    result
+}
+
+/// Fetches every page of the listing kept in the `field` object of the result of the private endpoint,
+/// advancing the "ofs" offset until the "count" of the result is reached.
+async fn fetch_listing(
+    world: &mut ApiWorld,
+    properties: &PrivateApiProperties,
+    endpoint_name: &str,
+    field: &str,
+    window: &[(&str, &str)],
+) -> reqwest::Result<serde_json::Map<String, serde_json::Value>> {
+    let mut listing = serde_json::Map::new();
+    loop {
+        let offset = listing.len().to_string();
+        let mut parameters = window.to_vec();
+        parameters.push(("ofs", &offset));
+        request_private_endpoint_with(world, properties, endpoint_name, 0, &parameters).await?;
+        let json_response = take_json_response(world).await?;
+        let errors = api_errors::errors_of(&json_response);
+        assert!(
+            errors.is_empty(),
+            "Listing {} failed: {:?}",
+            endpoint_name,
+            errors
+        );
+        let page = json_response["result"][field]
+            .as_object()
+            .cloned()
+            .unwrap_or_else(|| panic!("Response of {} has no {} object", endpoint_name, field));
+        // Without a count, pages are read until one adds no entries
+        let count = json_response["result"]["count"]
+            .as_u64()
+            .map(|count| count as usize);
+        let listed_before = listing.len();
+        listing.extend(page);
+        let is_last_page = listing.len() == listed_before;
+        if is_last_page || count.map_or(false, |count| listing.len() >= count) {
+            return Ok(listing);
+        }
+    }
+}
+
+#[when(expr = "I reconcile trades with ledger entries of the last {duration}")]
+pub async fn reconcile_trades(world: &mut ApiWorld, window: StepDuration) -> reqwest::Result<()> {
+    let properties = world
+        .private_api_properties
+        .take()
+        .expect("Api properties are empty");
+    let settings = world.profile.reconciliation.clone();
+    let end = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("System time should be after unix epoch");
+    let start = end.saturating_sub(window.0).as_secs().to_string();
+    let end = end.as_secs().to_string();
+    let window = [("start", start.as_str()), ("end", end.as_str())];
+    let listings = async {
+        let trades = fetch_listing(
+            world,
+            &properties,
+            &settings.trades_endpoint,
+            "trades",
+            &window,
+        )
+        .await?;
+        let ledger = fetch_listing(
+            world,
+            &properties,
+            &settings.ledgers_endpoint,
+            "ledger",
+            &window,
+        )
+        .await?;
+        Ok::<_, reqwest::Error>((trades, ledger))
+    }
+    .await;
+    world.private_api_properties = Some(properties);
+    let (trades, ledger) = listings?;
+    world.reconciliation = Some(reconciliation::reconcile(
+        &trades,
+        &ledger,
+        &world.reference,
+        settings.tolerance,
+    ));
+    Ok(())
+}
+
+#[then("every trade matches its ledger entries")]
+pub fn verify_reconciliation(world: &mut ApiWorld) {
+    let report = world
+        .reconciliation
+        .take()
+        .expect("Trades should be reconciled before verifying them");
+    reconciliation::record(&world.scenario_key, &report);
+    assert!(report.mismatches.is_empty(), "{}", report);
+}
+
+#[when(regex = r#"^I query each order listed by the "(\w+)" endpoint with the "(\w+)" endpoint$"#)]
+pub async fn query_open_orders(
+    world: &mut ApiWorld,
+    open_endpoint: String,
+    query_endpoint: String,
+) -> reqwest::Result<()> {
+    let properties = world
+        .private_api_properties
+        .take()
+        .expect("Api properties are empty");
+    let result = query_open_orders_with(world, &properties, &open_endpoint, &query_endpoint).await;
+    world.private_api_properties = Some(properties);
+    result
+}
+
+/// Lists open orders with one endpoint and queries them in batches with the other,
+/// keeping the inconsistencies between the two in the world.
+async fn query_open_orders_with(
+    world: &mut ApiWorld,
+    properties: &PrivateApiProperties,
+    open_endpoint: &str,
+    query_endpoint: &str,
+) -> reqwest::Result<()> {
+    request_private_endpoint_with(world, properties, open_endpoint, 0, &[]).await?;
+    let json_response = take_json_response(world).await?;
+    let errors = api_errors::errors_of(&json_response);
+    assert!(
+        errors.is_empty(),
+        "Listing {} failed: {:?}",
+        open_endpoint,
+        errors
+    );
+    let open_orders = json_response["result"]["open"]
+        .as_object()
+        .cloned()
+        .unwrap_or_else(|| panic!("Response of {} has no open object", open_endpoint));
+    let txids: Vec<&str> = open_orders.keys().map(String::as_str).collect();
+    let mut queried_orders = serde_json::Map::new();
+    for batch in txids.chunks(order_consistency::MAX_TXIDS_PER_QUERY) {
+        let batch = batch.join(",");
+        request_private_endpoint_with(world, properties, query_endpoint, 0, &[("txid", &batch)])
+            .await?;
+        let json_response = take_json_response(world).await?;
+        let errors = api_errors::errors_of(&json_response);
+        assert!(
+            errors.is_empty(),
+            "Querying {} failed: {:?}",
+            query_endpoint,
+            errors
+        );
+        if let Some(orders) = json_response["result"].as_object() {
+            queried_orders.extend(orders.clone());
+        }
+    }
+    world.order_inconsistencies = Some(order_consistency::inconsistencies(
+        &open_orders,
+        &queried_orders,
+        open_endpoint,
+        query_endpoint,
+    ));
+    Ok(())
+}
+
+#[then("every queried order matches its open order")]
+pub fn verify_order_consistency(world: &mut ApiWorld) {
+    let inconsistencies = world
+        .order_inconsistencies
+        .take()
+        .expect("Orders should be queried before verifying them");
+    assert!(
+        inconsistencies.is_empty(),
+        "Orders differ between their representations:\n  {}",
+        inconsistencies.join("\n  ")
+    );
+}
+
+/// Reads path and weight of the endpoint from the registry, making sure it belongs to private API
+/// and the profile allows sending requests to it.
+pub fn private_endpoint_of(world: &ApiWorld, endpoint_name: &str) -> (String, u32) {
+    let endpoint = world
+        .endpoints
+        .get(endpoint_name)
+        .unwrap_or_else(|err| panic!("{}", err));
+    assert_eq!(
+        endpoint.auth,
+        Auth::Private,
+        "Endpoint {} is not a part of private API",
+        endpoint_name
+    );
+    access::ensure_allowed(&world.profile, &world.scenario_key, endpoint_name, endpoint);
+    (endpoint.path.clone(), endpoint.weight)
+}
+
+#[then(regex = r"^the request is (accepted|rejected)$")]
+pub async fn verify_request_outcome(
+    world: &mut ApiWorld,
+    expected_outcome: String,
+) -> reqwest::Result<()> {
+    let raw_api_response = world
+        .raw_api_response
+        .take()
+        .expect("World should contain api response at this point");
+
+    let outcome = api_errors::RequestOutcome::from_response(raw_api_response).await?;
+    let should_be_accepted = expected_outcome == "accepted";
+    if should_be_accepted {
+        permissions::skip_if_denied(
+            &world.profile.permissions,
+            &world.scenario_key,
+            &outcome.errors,
+        );
+    }
+    assert_eq!(
+        outcome.is_accepted(),
+        should_be_accepted,
+        "Expected the request to be {}, got status {} with errors {:?}",
+        expected_outcome,
+        outcome.status,
+        outcome.errors
+    );
+    Ok(())
+}
+
+#[then(regex = r#"^the request is rejected with error "([^"]+)"$"#)]
+pub async fn verify_request_error(
+    world: &mut ApiWorld,
+    expected_error: String,
+) -> reqwest::Result<()> {
+    let raw_api_response = world
+        .raw_api_response
+        .take()
+        .expect("World should contain api response at this point");
+
+    let outcome = api_errors::RequestOutcome::from_response(raw_api_response).await?;
+    outcome.verify_rejected_with(&expected_error);
+    Ok(())
+}
+
+#[when("I freeze the signed request")]
+pub fn freeze_signed_request(world: &mut ApiWorld) {
+    let signed_request = world
+        .last_signed_request
+        .clone()
+        .expect("World should contain signed request at this point");
+    let endpoint_name = world
+        .endpoint_name
+        .clone()
+        .expect("World should contain requested endpoint at this point");
+    world.frozen_request = Some((endpoint_name, signed_request));
+}
+
+#[when("I resend the frozen request")]
+pub async fn resend_frozen_request(world: &mut ApiWorld) -> reqwest::Result<()> {
+    let (endpoint_name, frozen_request) = world
+        .frozen_request
+        .clone()
+        .expect("Signed request should be frozen before resending it");
+    let (_, weight) = private_endpoint_of(world, &endpoint_name);
+    budget::consume(&world.profile.request_budget, &mut world.scenario_requests);
+    // The request is resent verbatim, without an ID
+    world.request_id = None;
+    world.endpoint_name = Some(endpoint_name.clone());
+    rate_limit::acquire(&world.profile.rate_limit, &endpoint_name, weight).await;
+    let (client, _) = http_client::build_client(&world.profile);
+    let result = shared::timed(
+        world,
+        &endpoint_name,
+        request_handler::send_signed_request(&client, &frozen_request),
+    )
+    .await;
+    record_request(world, &endpoint_name, &frozen_request, &result);
+    world.raw_api_response = Some(result?);
+    Ok(())
+}
+
+#[then(regex = r#"^the "(\w+)" response is correctly encoded$"#)]
+pub async fn verify_encoding(world: &mut ApiWorld, endpoint_name: String) -> reqwest::Result<()> {
+    shared::verify_encoding(world, &endpoint_name).await
+}
+
+#[then("the numeric strings of the response contain no locale artifacts")]
+pub async fn verify_number_formats(world: &mut ApiWorld) -> reqwest::Result<()> {
+    shared::verify_number_formats(world).await
+}
+
+#[then("the responses of the scenario satisfy the integrity rules")]
+pub fn verify_integrity(world: &mut ApiWorld) {
+    shared::verify_integrity(world);
+}
+
+#[when(regex = r#"^I send request (\d+) of test (\d+) of the "([\w-]+)" plan$"#)]
+pub async fn send_plan_request(
+    world: &mut ApiWorld,
+    request: usize,
+    test: usize,
+    plan_id: String,
+) -> reqwest::Result<()> {
+    let properties = world
+        .private_api_properties
+        .take()
+        .expect("Api properties are empty");
+    let plan_request = test_plan::request_of(&plan_id, test, request);
+    let parameters = test_plan::parameters_of(&plan_request, &world.captured_values);
+    let parameters: Vec<(&str, &str)> = parameters
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_str()))
+        .collect();
+    let result =
+        request_private_endpoint_with(world, &properties, &plan_request.endpoint, 0, &parameters)
+            .await;
+    // Later requests of the test are signed with the same properties
+    world.private_api_properties = Some(properties);
+    result
+}
+
+#[when(regex = r#"^I request the "(\w+)" endpoint with the payload built by the "(\w+)" script$"#)]
+pub async fn request_endpoint_with_scripted_payload(
+    world: &mut ApiWorld,
+    endpoint_name: String,
+    script: String,
+) -> reqwest::Result<()> {
+    let properties = world
+        .private_api_properties
+        .take()
+        .expect("Api properties are empty");
+    let context = scripting::scenario_context(
+        Some(&endpoint_name),
+        &world.captured_values,
+        &world.profile.variables,
+    );
+    let parameters =
+        scripting::call_for_map(&script, &context).unwrap_or_else(|err| panic!("{}", err));
+    let parameters: Vec<(&str, &str)> = parameters
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_str()))
+        .collect();
+    let result =
+        request_private_endpoint_with(world, &properties, &endpoint_name, 0, &parameters).await;
+    world.private_api_properties = Some(properties);
+    result
+}
+
+/// Signs and sends a request to the registered endpoint with the parameters appended to its payload.
+async fn request_private_endpoint_with_parameters(
+    world: &mut ApiWorld,
+    endpoint_name: &str,
+    parameters: &[(String, String)],
+) -> reqwest::Result<()> {
+    let properties = world
+        .private_api_properties
+        .take()
+        .expect("Api properties are empty");
+    let parameters: Vec<(&str, &str)> = parameters
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_str()))
+        .collect();
+    let result =
+        request_private_endpoint_with(world, &properties, endpoint_name, 0, &parameters).await;
+    world.private_api_properties = Some(properties);
+    result
+}
+
+#[when(regex = r#"^I request the "(\w+)" endpoint with the parameters$"#)]
+pub async fn request_endpoint_with_rendered_parameters(
+    world: &mut ApiWorld,
+    endpoint_name: String,
+    step: &Step,
+) -> reqwest::Result<()> {
+    let parameters = shared::rendered_parameters(world, step);
+    request_private_endpoint_with_parameters(world, &endpoint_name, &parameters).await
+}
+
+#[when(
+    regex = r#"^I request the "(\w+)" endpoint with the parameters of the "([\w-]+)" template$"#
+)]
+pub async fn request_endpoint_with_template_parameters(
+    world: &mut ApiWorld,
+    endpoint_name: String,
+    template: String,
+) -> reqwest::Result<()> {
+    let parameters = shared::template_parameters(world, &template);
+    request_private_endpoint_with_parameters(world, &endpoint_name, &parameters).await
+}
+
+#[given(
+    regex = r#"^I generate (a client order ID|a reference|a timestamp|an amount of [\w/]+) as "(\w+)"$"#
+)]
+pub fn generate_value(world: &mut ApiWorld, kind: String, name: String) {
+    shared::generate_value(world, &kind, name);
+}
+
+#[when(regex = r#"^I run the "(\w+)" script$"#)]
+pub fn run_script(world: &mut ApiWorld, script: String) {
+    shared::run_script(world, &script);
+}
+
+#[then(regex = r"^the response passes the ([\w-]+) validator$")]
+pub async fn verify_with_validator(world: &mut ApiWorld, name: String) -> reqwest::Result<()> {
+    shared::verify_with_validator(world, &name).await
+}
+
+#[then("the response meets the expectations")]
+pub async fn verify_expectations(world: &mut ApiWorld, step: &Step) -> reqwest::Result<()> {
+    shared::verify_expectations(world, step).await
+}
+
+#[then(regex = r#"^the response meets request (\d+) of test (\d+) of the "([\w-]+)" plan$"#)]
+pub async fn verify_plan_request(
+    world: &mut ApiWorld,
+    request: usize,
+    test: usize,
+    plan_id: String,
+) -> reqwest::Result<()> {
+    shared::verify_plan_request(world, request, test, &plan_id).await
+}
+
+#[then(regex = r#"^the "(\w+)" responses contain only documented enum values$"#)]
+pub fn verify_enum_values(world: &mut ApiWorld, endpoint_name: String) {
+    shared::verify_enum_values(world, &endpoint_name);
+}
+
+#[then("the response echoes the request ID")]
+pub fn verify_request_id_echoed(world: &mut ApiWorld) {
+    shared::verify_request_id_in_header(world, &world.profile.correlation.request_header);
+}
+
+#[then(regex = r#"^the response echoes the request ID in the "([\w-]+)" header$"#)]
+pub fn verify_request_id_in_header(world: &mut ApiWorld, header_name: String) {
+    shared::verify_request_id_in_header(world, &header_name);
+}
+
+#[then(regex = r#"^the response echoes the request ID in the "([\w.]+)" field$"#)]
+pub async fn verify_request_id_in_field(
+    world: &mut ApiWorld,
+    field_path: String,
+) -> reqwest::Result<()> {
+    shared::verify_request_id_in_field(world, &field_path).await
+}
+
+#[then("the open orders list is presented to me")]
+pub async fn verify_open_orders(world: &mut ApiWorld) -> reqwest::Result<()> {
+    let json_response = take_json_response(world).await?;
+    let json_response = scrubbing::scrubbed(&json_response, &world.profile.scrub);
+    println!("List of open orders:");
+    if let Some(content) = json_response["result"]["open"].as_object() {
+        for (key, value) in content {
+            println!("{:?}: {:?}", key, value);
+        }
+    }
+
+    Ok(())
+}
+
+#[then(regex = r#"^the "(\w+)" response format is correct$"#)]
+pub async fn verify_named_response(
+    world: &mut ApiWorld,
+    endpoint_name: String,
+) -> reqwest::Result<()> {
+    shared::verify_named_response(world, &endpoint_name).await
+}
+
+#[then("the response headers match the OpenAPI spec")]
+pub fn verify_openapi_headers(world: &mut ApiWorld) {
+    shared::verify_openapi_headers(world);
+}
+
+#[then(regex = r#"^the "(\w+)" response of at most (\d+) bytes has correct format$"#)]
+pub async fn verify_streamed_response(world: &mut ApiWorld, endpoint_name: String, max_bytes: u64) {
+    shared::verify_streamed_response(world, &endpoint_name, max_bytes).await;
+}
+
+#[then(regex = r#"^each line of the "(\w+)" NDJSON response has correct format$"#)]
+pub async fn verify_ndjson_response(world: &mut ApiWorld, endpoint_name: String) {
+    shared::verify_ndjson_response(world, &endpoint_name).await;
+}
+
+#[then(regex = r"^the NDJSON response has (at least|at most|exactly) (\d+) lines$")]
+pub fn verify_ndjson_line_count(world: &mut ApiWorld, comparison: String, expected_count: usize) {
+    shared::verify_ndjson_line_count(world, &comparison, expected_count);
+}
+
+#[then(regex = r#"^the "(\w+)" CSV response matches its rules$"#)]
+pub async fn verify_csv_response(world: &mut ApiWorld, endpoint_name: String) {
+    shared::verify_csv_response(world, &endpoint_name).await;
+}
+
+#[then(regex = r#"^I remember the "([\w.]+)" field of the response as "(\w+)"$"#)]
+pub async fn capture_field(
+    world: &mut ApiWorld,
+    field_path: String,
+    name: String,
+) -> reqwest::Result<()> {
+    let json_response = take_json_response(world).await?;
+    let value = capture::extract_field(&json_response, &field_path)
+        .unwrap_or_else(|| panic!("Response does not contain field {}", field_path));
+    let recorded_value = scrubbing::scrub_field(&field_path, &value, &world.profile.scrub);
+    snapshot::record_captured_value(&world.scenario_key, &name, &recorded_value);
+    world.captured_values.insert(name, value);
+    Ok(())
+}
+
+#[then(regex = r#"^the response is an attachment of type "([^"]+)" of (\d+) to (\d+) bytes$"#)]
+pub async fn verify_attachment(
+    world: &mut ApiWorld,
+    content_type: String,
+    min_bytes: u64,
+    max_bytes: u64,
+) {
+    let raw_api_response = world
+        .raw_api_response
+        .take()
+        .expect("World should contain api response at this point");
+
+    let attachment = attachment::read_attachment(raw_api_response, max_bytes)
+        .await
+        .unwrap_or_else(|err| panic!("{}", err));
+    attachment.verify_content_type(&content_type);
+    attachment.verify_size_between(min_bytes, max_bytes);
+    world.attachment = Some(attachment);
+}
+
+#[then(regex = r#"^the attachment starts with bytes "([0-9a-fA-F]+)"$"#)]
+pub fn verify_attachment_magic_bytes(world: &mut ApiWorld, magic_bytes_hex: String) {
+    attachment_of(world).verify_magic_bytes(&magic_bytes_hex);
+}
+
+#[then(regex = r#"^the attachment SHA-256 is "([0-9a-fA-F]{64})"$"#)]
+pub fn verify_attachment_checksum(world: &mut ApiWorld, expected_sha256: String) {
+    attachment_of(world).verify_sha256(&expected_sha256);
+}
+
+#[then(regex = r#"^the attachment SHA-256 is equal to the remembered "(\w+)"$"#)]
+pub fn verify_attachment_captured_checksum(world: &mut ApiWorld, name: String) {
+    let expected_sha256 = world
+        .captured_values
+        .get(&name)
+        .unwrap_or_else(|| panic!("No value was remembered as {}", name));
+    attachment_of(world).verify_sha256(expected_sha256);
+}
+
+pub fn attachment_of(world: &ApiWorld) -> &attachment::Attachment {
+    world
+        .attachment
+        .as_ref()
+        .expect("World should contain attachment at this point")
+}
+
+#[derive(Debug, WorldInit)]
+pub struct ApiWorld {
+    pub profile: Profile,
+    pub endpoints: EndpointRegistry,
+    pub fixtures: HashMap<String, fixtures::FixtureDefinition>,
+    pub endpoint_name: Option<String>,
+    pub scenario_key: String,
+    pub scenario_requests: usize,
+    pub private_api_properties: Option<PrivateApiProperties>,
+    pub raw_api_response: Option<reqwest::Response>,
+    pub ndjson_report: Option<ndjson::NdjsonReport>,
+    pub attachment: Option<attachment::Attachment>,
+    pub captured_values: HashMap<String, String>,
+    pub last_signed_request: Option<request_handler::SignedRequest>,
+    /// Request frozen by "I freeze the signed request", along with the endpoint it was signed for
+    pub frozen_request: Option<(String, request_handler::SignedRequest)>,
+    /// ID sent with the last request, when the profile enables request IDs
+    pub request_id: Option<String>,
+    /// Checks reported as warnings in the scenario, according to its tags
+    pub warning_checks: severity::WarningChecks,
+    /// Asset pairs and assets preloaded before the suite
+    pub reference: Arc<reference::ReferenceData>,
+    /// Order composed from the trading rules of its pair by the last order step
+    pub derived_order: Option<orders::DerivedOrder>,
+    /// Trades matched with ledger entries by the last reconciliation step
+    pub reconciliation: Option<reconciliation::ReconciliationReport>,
+    /// Differences between open orders and the same orders queried by txid, found by the last order query step
+    pub order_inconsistencies: Option<Vec<String>>,
+    /// Account selected by the `@account(name)` tag of the scenario, whose keys sign its requests
+    pub account: Option<String>,
+}
+
+#[async_trait(?Send)]
+impl World for ApiWorld {
+    type Error = ConfigError;
+
+    async fn new() -> Result<Self, ConfigError> {
+        let profile = Profile::load()?;
+        let endpoints = EndpointRegistry::load(&profile.api_version)?;
+        Ok(Self {
+            profile,
+            endpoints,
+            fixtures: config::load_fixture_definitions()?,
+            endpoint_name: None,
+            scenario_key: String::new(),
+            scenario_requests: 0,
+            private_api_properties: None,
+            raw_api_response: None,
+            ndjson_report: None,
+            attachment: None,
+            captured_values: HashMap::new(),
+            last_signed_request: None,
+            frozen_request: None,
+            request_id: None,
+            warning_checks: severity::WarningChecks::default(),
+            reference: reference::loaded(),
+            derived_order: None,
+            reconciliation: None,
+            order_inconsistencies: None,
+            account: None,
+        })
+    }
+}
+
+impl StepWorld for ApiWorld {
+    fn profile(&self) -> &Profile {
+        &self.profile
+    }
+
+    fn endpoints(&self) -> &EndpointRegistry {
+        &self.endpoints
+    }
+
+    fn endpoint_name(&self) -> Option<&str> {
+        self.endpoint_name.as_deref()
+    }
+
+    fn scenario_key(&self) -> &str {
+        &self.scenario_key
+    }
+
+    fn request_id(&self) -> Option<&str> {
+        self.request_id.as_deref()
+    }
+
+    fn warning_checks(&self) -> &severity::WarningChecks {
+        &self.warning_checks
+    }
+
+    fn raw_api_response(&self) -> Option<&reqwest::Response> {
+        self.raw_api_response.as_ref()
+    }
+
+    fn take_raw_api_response(&mut self) -> Option<reqwest::Response> {
+        self.raw_api_response.take()
+    }
+
+    fn captured_values(&self) -> &HashMap<String, String> {
+        &self.captured_values
+    }
+
+    fn captured_values_mut(&mut self) -> &mut HashMap<String, String> {
+        &mut self.captured_values
+    }
+
+    fn ndjson_report_mut(&mut self) -> &mut Option<ndjson::NdjsonReport> {
+        &mut self.ndjson_report
+    }
+
+    fn default_method(&self) -> reqwest::Method {
+        reqwest::Method::POST
+    }
+
+    /// The scenario is skipped when the response reports insufficient key permissions and the profile skips them.
+    fn inspect_json_response(&self, json_response: &serde_json::Value) {
+        permissions::skip_if_denied(
+            &self.profile.permissions,
+            &self.scenario_key,
+            &api_errors::errors_of(json_response),
+        );
+    }
+}
+
+/// Private scenarios run with the keys of the selected account, once the permissions of the keys
+/// are checked, and with the fixtures they need; fixtures are torn down after the run.
+#[async_trait(?Send)]
+impl SuiteWorld for ApiWorld {
+    const SUITE: &'static str = "private";
+
+    fn steps() -> cucumber::step::Collection<Self> {
+        Self::collection()
+    }
+
+    fn profile_mut(&mut self) -> &mut Profile {
+        &mut self.profile
+    }
+
+    fn scenario_key_mut(&mut self) -> &mut String {
+        &mut self.scenario_key
+    }
+
+    fn warning_checks_mut(&mut self) -> &mut severity::WarningChecks {
+        &mut self.warning_checks
+    }
+
+    async fn prepare_run(profile: &Profile, endpoints: &EndpointRegistry, options: &RunOptions) {
+        accounts::set_run_account(profile, options.account.as_deref())
+            .unwrap_or_else(|err| panic!("{}", err));
+        if let Some(account) = &options.account {
+            println!("Running with the keys of account {}", account);
+        }
+        key_permissions::preflight(profile, endpoints).await;
+    }
+
+    /// Scenarios requiring permissions the API key lacks are skipped.
+    async fn prepare_scenario(
+        &mut self,
+        feature: &gherkin::Feature,
+        rule: Option<&gherkin::Rule>,
+        scenario: &gherkin::Scenario,
+    ) {
+        self.account = accounts::account_of(tags::all_tags(feature, rule, scenario));
+        key_permissions::skip_unless_permitted(
+            &self.scenario_key,
+            tags::all_tags(feature, rule, scenario),
+        );
+        for name in fixtures::required_fixtures(tags::all_tags(feature, rule, scenario)) {
+            let id = fixtures::ensure(&name, &self.fixtures, &self.profile, &self.endpoints)
+                .await
+                .unwrap_or_else(|err| {
+                    panic!("Scenario {} cannot be set up: {}", self.scenario_key, err)
+                });
+            if let Some(id) = id {
+                self.captured_values.insert(name, id);
+            }
+        }
+    }
+
+    fn run_reports() -> Vec<(&'static str, String)> {
+        vec![("reconciliation", reconciliation::run_report().to_string())]
+    }
+
+    async fn finish_run(profile: &Profile, endpoints: &EndpointRegistry) {
+        let fixture_definitions =
+            config::load_fixture_definitions().unwrap_or_else(|err| panic!("{}", err));
+        fixtures::teardown_all(&fixture_definitions, profile, endpoints).await;
+    }
+}
@@ -0,0 +1,1270 @@
+//! Steps verifying public endpoints, along with the world they share.
+use super::shared::{self, check_severity, response_schema_file, take_json_response, StepWorld};
+use super::suite::SuiteWorld;
+use crate::resources::config::{Auth, ConfigError, EndpointRegistry, Profile};
+use crate::resources::parameters::{AssetPair, EndpointName};
+use crate::resources::{
+    access, api_errors, budget, conditional, console, correlation, cors, cursor, dual_stack, feed,
+    http_client, ndjson, ohlc, openapi, order_book, rate_limit, reachability, reference,
+    response_cache, schema, scripting, security_headers, severity, snapshot, test_plan, throughput,
+    ticker, tls, version_negotiation,
+};
+use async_trait::async_trait;
+use cucumber::gherkin::Step;
+use cucumber::{given, then, when, World, WorldInit};
+use std::collections::HashMap;
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+
+// Custom world struct for shared state
+#[derive(Debug, WorldInit)]
+pub struct ApiWorld {
+    pub profile: Profile,
+    pub endpoints: EndpointRegistry,
+    pub endpoint_name: Option<String>,
+    pub scenario_key: String,
+    pub scenario_requests: usize,
+    pub api_link: Option<String>,
+    pub redirect_chain: Option<http_client::RedirectChain>,
+    pub certificate: Option<tls::CertificateChain>,
+    pub raw_api_response: Option<reqwest::Response>,
+    pub ndjson_report: Option<ndjson::NdjsonReport>,
+    pub cache_validators: Option<conditional::CacheValidators>,
+    pub cors_preflight: Option<cors::CorsPreflight>,
+    pub connection_counter: Option<http_client::ConnectionCounter>,
+    /// ID sent with the last request, when the profile enables request IDs
+    pub request_id: Option<String>,
+    /// Checks reported as warnings in the scenario, according to its tags
+    pub warning_checks: severity::WarningChecks,
+    /// Asset pairs and assets preloaded before the suite
+    pub reference: Arc<reference::ReferenceData>,
+    /// The "last" cursor of the feed response checked by the last recency step
+    pub feed_cursor: Option<u128>,
+    /// Values extracted by earlier requests of a test plan, by name
+    pub captured_values: HashMap<String, String>,
+}
+
+#[async_trait(?Send)]
+impl World for ApiWorld {
+    type Error = ConfigError;
+
+    async fn new() -> Result<Self, ConfigError> {
+        let profile = Profile::load()?;
+        let endpoints = EndpointRegistry::load(&profile.api_version)?;
+        Ok(Self {
+            profile,
+            endpoints,
+            endpoint_name: None,
+            scenario_key: String::new(),
+            scenario_requests: 0,
+            api_link: None,
+            redirect_chain: None,
+            certificate: None,
+            raw_api_response: None,
+            ndjson_report: None,
+            cache_validators: None,
+            cors_preflight: None,
+            connection_counter: None,
+            request_id: None,
+            warning_checks: severity::WarningChecks::default(),
+            reference: reference::loaded(),
+            feed_cursor: None,
+            captured_values: HashMap::new(),
+        })
+    }
+}
+
+impl StepWorld for ApiWorld {
+    fn profile(&self) -> &Profile {
+        &self.profile
+    }
+
+    fn endpoints(&self) -> &EndpointRegistry {
+        &self.endpoints
+    }
+
+    fn endpoint_name(&self) -> Option<&str> {
+        self.endpoint_name.as_deref()
+    }
+
+    fn scenario_key(&self) -> &str {
+        &self.scenario_key
+    }
+
+    fn request_id(&self) -> Option<&str> {
+        self.request_id.as_deref()
+    }
+
+    fn warning_checks(&self) -> &severity::WarningChecks {
+        &self.warning_checks
+    }
+
+    fn raw_api_response(&self) -> Option<&reqwest::Response> {
+        self.raw_api_response.as_ref()
+    }
+
+    fn take_raw_api_response(&mut self) -> Option<reqwest::Response> {
+        self.raw_api_response.take()
+    }
+
+    fn captured_values(&self) -> &HashMap<String, String> {
+        &self.captured_values
+    }
+
+    fn captured_values_mut(&mut self) -> &mut HashMap<String, String> {
+        &mut self.captured_values
+    }
+
+    fn ndjson_report_mut(&mut self) -> &mut Option<ndjson::NdjsonReport> {
+        &mut self.ndjson_report
+    }
+
+    fn default_method(&self) -> reqwest::Method {
+        reqwest::Method::GET
+    }
+}
+
+impl SuiteWorld for ApiWorld {
+    const SUITE: &'static str = "public";
+
+    fn steps() -> cucumber::step::Collection<Self> {
+        Self::collection()
+    }
+
+    fn profile_mut(&mut self) -> &mut Profile {
+        &mut self.profile
+    }
+
+    fn scenario_key_mut(&mut self) -> &mut String {
+        &mut self.scenario_key
+    }
+
+    fn warning_checks_mut(&mut self) -> &mut severity::WarningChecks {
+        &mut self.warning_checks
+    }
+}
+
+/// Name of the registered endpoint the legacy steps refer to.
+pub fn legacy_endpoint_name(endpoint_type: &str) -> &'static str {
+    match endpoint_type {
+        "server time" => "ServerTime",
+        "asset pair info" => "AssetPair",
+        _ => unreachable!(),
+    }
+}
+
+/// Joins link to API with the path of the registered endpoint.
+pub fn select_endpoint(world: &mut ApiWorld, endpoint_name: &str) {
+    let endpoint = world
+        .endpoints
+        .get(endpoint_name)
+        .unwrap_or_else(|err| panic!("{}", err));
+    let api_link = env::var("API_LINK").expect("Missing secret value: API_LINK");
+
+    world.api_link = Some([api_link.as_str(), endpoint.path.as_str()].concat());
+    world.endpoint_name = Some(endpoint_name.to_owned());
+}
+
+/// Waits until a request to the selected endpoint fits within the request budget
+/// and its weight fits within the rate limit, failing when the profile does not allow it.
+pub async fn throttle_request(world: &mut ApiWorld) {
+    let endpoint_name = world
+        .endpoint_name
+        .as_ref()
+        .expect("Endpoint should be selected before requesting it");
+    let endpoint = world
+        .endpoints
+        .get(endpoint_name)
+        .unwrap_or_else(|err| panic!("{}", err));
+    access::ensure_allowed(&world.profile, &world.scenario_key, endpoint_name, endpoint);
+    budget::consume(&world.profile.request_budget, &mut world.scenario_requests);
+    rate_limit::acquire(&world.profile.rate_limit, endpoint_name, endpoint.weight).await;
+}
+
+/// Builds request to the selected endpoint, once it fits within the request budget
+/// and its weight fits within the rate limit.
+/// The request carries a new request ID, kept in the world.
+pub async fn prepare_request(
+    world: &mut ApiWorld,
+    client: &reqwest::Client,
+) -> reqwest::RequestBuilder {
+    check_openapi_request(world);
+    throttle_request(world).await;
+    let endpoint = world
+        .endpoints
+        .get(
+            world
+                .endpoint_name
+                .as_ref()
+                .expect("Endpoint should be selected before requesting it"),
+        )
+        .unwrap_or_else(|err| panic!("{}", err));
+    let mut request = client.request(
+        endpoint.method(),
+        world
+            .api_link
+            .as_ref()
+            .expect("Link should be selected before requesting it"),
+    );
+    world.request_id = None;
+    if let Some((header_name, request_id)) =
+        correlation::request_id_header(&world.profile.correlation)
+    {
+        request = request.header(header_name, &request_id);
+        world.request_id = Some(request_id);
+    }
+    request
+}
+
+/// Checks the request to the selected endpoint against the OpenAPI spec of the profile before it is sent,
+/// so that invalid requests of the suite are not reported as failures of the server.
+pub fn check_openapi_request(world: &ApiWorld) {
+    let settings = &world.profile.openapi;
+    let spec_file = match (&settings.spec, settings.validate_requests) {
+        (Some(spec_file), true) => spec_file,
+        _ => return,
+    };
+    let endpoint = world
+        .endpoints
+        .get(
+            world
+                .endpoint_name
+                .as_ref()
+                .expect("Endpoint should be selected before requesting it"),
+        )
+        .unwrap_or_else(|err| panic!("{}", err));
+    let url = url::Url::parse(
+        world
+            .api_link
+            .as_ref()
+            .expect("Link should be selected before requesting it"),
+    )
+    .expect("API link should be a valid URL");
+    let query: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+    let problems = openapi::request_problems(
+        &openapi::load_spec(spec_file),
+        &endpoint.method(),
+        url.path(),
+        &query,
+        None,
+    );
+    let severity = check_severity(world, "openapi_request");
+    severity::report(
+        &world.scenario_key,
+        severity,
+        "Our request is invalid per the OpenAPI spec",
+        &problems,
+    );
+}
+
+#[given(regex = r"I have link to a public api endpoint returning (server time|asset pair info)")]
+pub fn get_link_to_api(world: &mut ApiWorld, endpoint_type: String) {
+    select_endpoint(world, legacy_endpoint_name(&endpoint_type));
+}
+
+#[given(regex = r#"^I have link to the public "(\w+)" endpoint$"#)]
+pub fn get_link_to_named_endpoint(world: &mut ApiWorld, endpoint_name: String) {
+    select_endpoint(world, &endpoint_name);
+}
+
+/// Key of the cached response of the selected endpoint and its time to live,
+/// when the endpoint allows reusing its responses.
+fn cache_of(world: &ApiWorld) -> Option<(String, u64)> {
+    let endpoint = world
+        .endpoints
+        .get(world.endpoint_name.as_ref()?)
+        .unwrap_or_else(|err| panic!("{}", err));
+    let ttl_seconds = endpoint.cache_ttl_seconds?;
+    let key = response_cache::key_of(endpoint.method().as_str(), world.api_link.as_ref()?);
+    Some((key, ttl_seconds))
+}
+
+#[when(regex = r#"^I request (server time|asset pair info|the "\w+" endpoint)$"#)]
+pub async fn send_selected_request(world: &mut ApiWorld) -> reqwest::Result<()> {
+    let (client, redirect_chain) = http_client::build_client(&world.profile);
+    let cache = cache_of(world);
+    if let Some((key, ttl_seconds)) = &cache {
+        let endpoint_name = world
+            .endpoint_name
+            .as_ref()
+            .expect("Endpoint should be selected before requesting it");
+        let endpoint = world
+            .endpoints
+            .get(endpoint_name)
+            .unwrap_or_else(|err| panic!("{}", err));
+        access::ensure_allowed(&world.profile, &world.scenario_key, endpoint_name, endpoint);
+        if let Some(hit) = response_cache::lookup(key, *ttl_seconds) {
+            world.request_id = hit.request_id;
+            let result = Ok(hit.response);
+            record_request_from_cache(world, &result);
+            world.raw_api_response = Some(result?);
+            world.redirect_chain = Some(hit.redirect_chain);
+            return Ok(());
+        }
+    }
+    let request = prepare_request(world, &client).await;
+    let endpoint_name = world
+        .endpoint_name
+        .as_ref()
+        .expect("Endpoint should be selected before requesting it");
+    let result = shared::timed(world, endpoint_name, request.send()).await;
+    record_request(world, &result);
+    let response = match cache {
+        Some((key, _)) => {
+            let request_id = world.request_id.clone();
+            response_cache::store(&key, result?, request_id, redirect_chain.clone()).await?
+        }
+        None => result?,
+    };
+    world.raw_api_response = Some(response);
+    world.redirect_chain = Some(redirect_chain);
+    Ok(())
+}
+
+#[when(expr = "I request {endpoint} for {asset-pair}")]
+pub async fn request_endpoint_for_pair(
+    world: &mut ApiWorld,
+    endpoint_name: EndpointName,
+    pair: AssetPair,
+) -> reqwest::Result<()> {
+    let parameters = [("pair".to_owned(), pair.0)];
+    request_endpoint_with_parameters(world, &endpoint_name.0, &parameters).await
+}
+
+/// Sends the request to the endpoint with the version in the header, bypassing cached responses.
+async fn request_with_version_header(
+    world: &mut ApiWorld,
+    endpoint_name: &str,
+    header_name: &str,
+    version: &str,
+) -> reqwest::Result<()> {
+    select_endpoint(world, endpoint_name);
+    let (client, redirect_chain) = http_client::build_client(&world.profile);
+    let request = prepare_request(world, &client)
+        .await
+        .header(header_name, version);
+    let result = shared::timed(world, endpoint_name, request.send()).await;
+    record_request(world, &result);
+    world.raw_api_response = Some(result?);
+    world.redirect_chain = Some(redirect_chain);
+    Ok(())
+}
+
+#[when(regex = r#"^I request the "(\w+)" endpoint accepting version "([^"]+)"$"#)]
+pub async fn request_endpoint_accepting_version(
+    world: &mut ApiWorld,
+    endpoint_name: String,
+    version: String,
+) -> reqwest::Result<()> {
+    let header_name = world.profile.version_negotiation.request_header.clone();
+    request_with_version_header(world, &endpoint_name, &header_name, &version).await
+}
+
+#[when(
+    regex = r#"^I request the "(\w+)" endpoint with the "([\w-]+)" header set to version "([^"]+)"$"#
+)]
+pub async fn request_endpoint_with_version_header(
+    world: &mut ApiWorld,
+    endpoint_name: String,
+    header_name: String,
+    version: String,
+) -> reqwest::Result<()> {
+    request_with_version_header(world, &endpoint_name, &header_name, &version).await
+}
+
+#[then(expr = "the reference data lists {asset-pair} with its trading rules")]
+pub fn verify_reference_pair(world: &mut ApiWorld, pair: AssetPair) {
+    let pair_info = world.reference.require_pair(&pair.0);
+    assert!(
+        pair_info.min_volume() > 0.0 && pair_info.tick_size() > 0.0,
+        "Asset pair {} has no positive minimal volume and tick size: {:?}",
+        pair,
+        pair_info
+    );
+}
+
+/// Adds the request to the transcript kept for the snapshot of a failed scenario.
+pub fn record_request(world: &ApiWorld, result: &reqwest::Result<reqwest::Response>) {
+    snapshot::record_request(&world.scenario_key, transcript_of(world, result));
+    observe_deprecation(world, result);
+}
+
+/// Collects the deprecation headers of the response to the selected endpoint.
+fn observe_deprecation(world: &ApiWorld, result: &reqwest::Result<reqwest::Response>) {
+    let endpoint_name = world
+        .endpoint_name
+        .as_ref()
+        .expect("Endpoint should be selected before requesting it");
+    shared::observe_deprecation(world, endpoint_name, result);
+}
+
+/// Adds the request answered from the cache of reference data to the transcript, marked as a cache hit.
+fn record_request_from_cache(world: &ApiWorld, result: &reqwest::Result<reqwest::Response>) {
+    let transcript = snapshot::RequestTranscript {
+        cached: true,
+        ..transcript_of(world, result)
+    };
+    snapshot::record_request(&world.scenario_key, transcript);
+    observe_deprecation(world, result);
+}
+
+fn transcript_of(
+    world: &ApiWorld,
+    result: &reqwest::Result<reqwest::Response>,
+) -> snapshot::RequestTranscript {
+    let endpoint_name = world
+        .endpoint_name
+        .as_ref()
+        .expect("Endpoint should be selected before requesting it");
+    let method = world
+        .endpoints
+        .get(endpoint_name)
+        .map(|endpoint| endpoint.method().to_string())
+        .unwrap_or_default();
+    snapshot::transcript_of(
+        endpoint_name,
+        &method,
+        world
+            .api_link
+            .as_ref()
+            .expect("Link should be selected before requesting it"),
+        result,
+    )
+}
+
+#[then(regex = r#"^the "(\w+)" response is correctly encoded$"#)]
+pub async fn verify_encoding(world: &mut ApiWorld, endpoint_name: String) -> reqwest::Result<()> {
+    shared::verify_encoding(world, &endpoint_name).await
+}
+
+#[then("the numeric strings of the response contain no locale artifacts")]
+pub async fn verify_number_formats(world: &mut ApiWorld) -> reqwest::Result<()> {
+    shared::verify_number_formats(world).await
+}
+
+/// Asserts the market data response reports no errors and lists at least one entry,
+/// so checks of its entries cannot pass on an empty result.
+///
+/// # Arguments
+///
+/// * `json_response` - Response of the market data endpoint
+/// * `entries` - Kind of the listed entries, e.g. "OHLC series"
+/// * `count` - Number of the listed entries
+///
+fn assert_listed(json_response: &serde_json::Value, entries: &str, count: usize) {
+    let errors = api_errors::errors_of(json_response);
+    assert!(
+        errors.is_empty(),
+        "The response listing {} reports errors: {:?}",
+        entries,
+        errors
+    );
+    assert!(count > 0, "The response lists no {}", entries);
+}
+
+#[then("the OHLC data is internally consistent")]
+pub async fn verify_ohlc(world: &mut ApiWorld) -> reqwest::Result<()> {
+    let json_response = take_json_response(world).await?;
+    assert_listed(
+        &json_response,
+        "OHLC series",
+        ohlc::series_count(&json_response["result"]),
+    );
+    let interval_minutes = ohlc::interval_of(
+        world
+            .api_link
+            .as_ref()
+            .expect("Link should be selected before requesting it"),
+    );
+    let inconsistencies = ohlc::inconsistencies(&json_response["result"], interval_minutes);
+    let severity = check_severity(world, "ohlc");
+    severity::report(
+        &world.scenario_key,
+        severity,
+        "The OHLC data is inconsistent",
+        &inconsistencies,
+    );
+    Ok(())
+}
+
+#[then("the order book is well-formed")]
+pub async fn verify_order_book(world: &mut ApiWorld) -> reqwest::Result<()> {
+    let json_response = take_json_response(world).await?;
+    assert_listed(
+        &json_response,
+        "order books",
+        json_response["result"]
+            .as_object()
+            .map_or(0, |books| books.len()),
+    );
+    let problems = order_book::problems(&json_response["result"], &world.reference);
+    let severity = check_severity(world, "order_book");
+    severity::report(
+        &world.scenario_key,
+        severity,
+        "The order book is malformed",
+        &problems,
+    );
+    Ok(())
+}
+
+#[then("the ticker fields are consistent with each other")]
+pub async fn verify_ticker(world: &mut ApiWorld) -> reqwest::Result<()> {
+    let json_response = take_json_response(world).await?;
+    assert_listed(
+        &json_response,
+        "tickers",
+        json_response["result"]
+            .as_object()
+            .map_or(0, |tickers| tickers.len()),
+    );
+    let violations = ticker::violations(&json_response["result"], &world.profile.ticker);
+    let severity = check_severity(world, "ticker");
+    severity::report(
+        &world.scenario_key,
+        severity,
+        "The ticker breaks its invariants",
+        &violations,
+    );
+    Ok(())
+}
+
+/// Server time read from the server time endpoint of the profile, in seconds;
+/// the endpoint selected before is selected again afterwards.
+async fn server_time(world: &mut ApiWorld) -> reqwest::Result<f64> {
+    let endpoint_name = world.endpoint_name.take();
+    let api_link = world.api_link.take();
+    let server_time_endpoint = world.profile.feeds.server_time_endpoint.clone();
+    select_endpoint(world, &server_time_endpoint);
+    let response = async {
+        send_selected_request(world).await?;
+        take_json_response(world).await
+    }
+    .await;
+    world.endpoint_name = endpoint_name;
+    world.api_link = api_link;
+    Ok(response?["result"]["unixtime"]
+        .as_f64()
+        .unwrap_or_else(|| panic!("Response of {} has no unixtime", server_time_endpoint)))
+}
+
+#[then(regex = r"^the newest entry of the feed is at most (\d+) minutes behind server time$")]
+pub async fn verify_feed_recency(world: &mut ApiWorld, max_minutes: u64) -> reqwest::Result<()> {
+    let json_response = take_json_response(world).await?;
+    let endpoint_name = world
+        .endpoint_name
+        .clone()
+        .expect("Endpoint should be selected before requesting it");
+    let timestamp_index = *world
+        .profile
+        .feeds
+        .timestamp_index
+        .get(&endpoint_name)
+        .unwrap_or_else(|| {
+            panic!(
+                "Timestamp position of {} is not set in feeds",
+                endpoint_name
+            )
+        });
+    world.feed_cursor = feed::cursor_of(&json_response["result"]);
+    let newest = feed::newest_timestamp(&json_response["result"], timestamp_index)
+        .unwrap_or_else(|| panic!("Response of {} has no timestamped entries", endpoint_name));
+    let server_time = server_time(world).await?;
+    let lag_seconds = server_time - newest;
+    let max_seconds = max_minutes
+        .checked_mul(60)
+        .unwrap_or_else(|| panic!("{} minutes is too long a lag to verify", max_minutes));
+    assert!(
+        lag_seconds <= max_seconds as f64,
+        "The newest entry of {} is {} seconds behind server time, at most {} minutes expected",
+        endpoint_name,
+        lag_seconds,
+        max_minutes
+    );
+    Ok(())
+}
+
+#[then(regex = r"^the feed cursor advances when polled again after (\d+) seconds$")]
+pub async fn verify_feed_cursor(world: &mut ApiWorld, delay_seconds: u64) -> reqwest::Result<()> {
+    let first_cursor = if world.raw_api_response.is_some() {
+        feed::cursor_of(&take_json_response(world).await?["result"])
+    } else {
+        world.feed_cursor.take()
+    };
+    let endpoint_name = world
+        .endpoint_name
+        .clone()
+        .expect("Endpoint should be selected before requesting it");
+    let first_cursor =
+        first_cursor.unwrap_or_else(|| panic!("Response of {} has no last cursor", endpoint_name));
+    tokio::time::sleep(Duration::from_secs(delay_seconds)).await;
+    send_selected_request(world).await?;
+    let json_response = take_json_response(world).await?;
+    let second_cursor = feed::cursor_of(&json_response["result"])
+        .unwrap_or_else(|| panic!("Response of {} has no last cursor", endpoint_name));
+    assert!(
+        second_cursor > first_cursor,
+        "The last cursor of {} did not advance within {} seconds: {} then {}",
+        endpoint_name,
+        delay_seconds,
+        first_cursor,
+        second_cursor
+    );
+    Ok(())
+}
+
+#[then("the next batch follows the cursor of the response without overlap or gap")]
+pub async fn verify_cursor_continuity(world: &mut ApiWorld) -> reqwest::Result<()> {
+    let endpoint_name = world
+        .endpoint_name
+        .clone()
+        .expect("Endpoint should be selected before requesting it");
+    let config = world
+        .endpoints
+        .get(&endpoint_name)
+        .unwrap_or_else(|err| panic!("{}", err))
+        .cursor
+        .clone()
+        .unwrap_or_else(|| panic!("Endpoint {} has no cursor in the registry", endpoint_name));
+    let first = take_json_response(world).await?;
+    let cursor = cursor::cursor_of(&first["result"], &config).unwrap_or_else(|| {
+        panic!(
+            "Response of {} has no {} cursor",
+            endpoint_name, config.field
+        )
+    });
+    let api_link = world
+        .api_link
+        .take()
+        .expect("Link should be selected before requesting it");
+    world.api_link = Some(cursor::link_after(&api_link, &config, &cursor));
+    send_selected_request(world).await?;
+    let second = take_json_response(world).await?;
+    let discontinuities = cursor::discontinuities(&first["result"], &second["result"], &config);
+    assert!(
+        discontinuities.is_empty(),
+        "Batches of {} following cursor {} are discontinuous:\n  {}",
+        endpoint_name,
+        cursor,
+        discontinuities.join("\n  ")
+    );
+    Ok(())
+}
+
+#[then("the responses of the scenario satisfy the integrity rules")]
+pub fn verify_integrity(world: &mut ApiWorld) {
+    shared::verify_integrity(world);
+}
+
+#[when(regex = r#"^I send request (\d+) of test (\d+) of the "([\w-]+)" plan$"#)]
+pub async fn send_plan_request(
+    world: &mut ApiWorld,
+    request: usize,
+    test: usize,
+    plan_id: String,
+) -> reqwest::Result<()> {
+    let plan_request = test_plan::request_of(&plan_id, test, request);
+    let parameters = test_plan::parameters_of(&plan_request, &world.captured_values);
+    request_endpoint_with_parameters(world, &plan_request.endpoint, &parameters).await
+}
+
+#[when(regex = r#"^I request the "(\w+)" endpoint with the payload built by the "(\w+)" script$"#)]
+pub async fn request_endpoint_with_scripted_payload(
+    world: &mut ApiWorld,
+    endpoint_name: String,
+    script: String,
+) -> reqwest::Result<()> {
+    let context = scripting::scenario_context(
+        Some(&endpoint_name),
+        &world.captured_values,
+        &world.profile.variables,
+    );
+    let parameters =
+        scripting::call_for_map(&script, &context).unwrap_or_else(|err| panic!("{}", err));
+    request_endpoint_with_parameters(world, &endpoint_name, &parameters).await
+}
+
+/// Requests the registered endpoint with the parameters in its query.
+async fn request_endpoint_with_parameters(
+    world: &mut ApiWorld,
+    endpoint_name: &str,
+    parameters: &[(String, String)],
+) -> reqwest::Result<()> {
+    select_endpoint(world, endpoint_name);
+    if !parameters.is_empty() {
+        let query = serde_urlencoded::to_string(parameters)
+            .expect("Parameters should be possible to encode");
+        let api_link = world
+            .api_link
+            .take()
+            .expect("Link should be selected before requesting it");
+        world.api_link = Some(format!("{}?{}", api_link, query));
+    }
+    send_selected_request(world).await
+}
+
+#[when(regex = r#"^I request the "(\w+)" endpoint with the parameters$"#)]
+pub async fn request_endpoint_with_rendered_parameters(
+    world: &mut ApiWorld,
+    endpoint_name: String,
+    step: &Step,
+) -> reqwest::Result<()> {
+    let parameters = shared::rendered_parameters(world, step);
+    request_endpoint_with_parameters(world, &endpoint_name, &parameters).await
+}
+
+#[when(
+    regex = r#"^I request the "(\w+)" endpoint with the parameters of the "([\w-]+)" template$"#
+)]
+pub async fn request_endpoint_with_template_parameters(
+    world: &mut ApiWorld,
+    endpoint_name: String,
+    template: String,
+) -> reqwest::Result<()> {
+    let parameters = shared::template_parameters(world, &template);
+    request_endpoint_with_parameters(world, &endpoint_name, &parameters).await
+}
+
+#[given(
+    regex = r#"^I generate (a client order ID|a reference|a timestamp|an amount of [\w/]+) as "(\w+)"$"#
+)]
+pub fn generate_value(world: &mut ApiWorld, kind: String, name: String) {
+    shared::generate_value(world, &kind, name);
+}
+
+#[when(regex = r#"^I run the "(\w+)" script$"#)]
+pub fn run_script(world: &mut ApiWorld, script: String) {
+    shared::run_script(world, &script);
+}
+
+#[then(regex = r"^the response passes the ([\w-]+) validator$")]
+pub async fn verify_with_validator(world: &mut ApiWorld, name: String) -> reqwest::Result<()> {
+    shared::verify_with_validator(world, &name).await
+}
+
+#[then("the response meets the expectations")]
+pub async fn verify_expectations(world: &mut ApiWorld, step: &Step) -> reqwest::Result<()> {
+    shared::verify_expectations(world, step).await
+}
+
+#[then(regex = r#"^the response meets request (\d+) of test (\d+) of the "([\w-]+)" plan$"#)]
+pub async fn verify_plan_request(
+    world: &mut ApiWorld,
+    request: usize,
+    test: usize,
+    plan_id: String,
+) -> reqwest::Result<()> {
+    shared::verify_plan_request(world, request, test, &plan_id).await
+}
+
+#[then(regex = r#"^the "(\w+)" responses contain only documented enum values$"#)]
+pub fn verify_enum_values(world: &mut ApiWorld, endpoint_name: String) {
+    shared::verify_enum_values(world, &endpoint_name);
+}
+
+#[then(regex = r"the (server time|asset pair info) format is correct")]
+pub async fn verify_response(world: &mut ApiWorld, endpoint_type: String) -> reqwest::Result<()> {
+    let json_response = take_json_response(world).await?;
+
+    let schema_file = world
+        .endpoints
+        .schema_file(legacy_endpoint_name(&endpoint_type));
+    schema::verify_against_schema(&json_response, &schema_file);
+    Ok(())
+}
+
+#[then(regex = r#"^the "(\w+)" response format is correct$"#)]
+pub async fn verify_named_response(
+    world: &mut ApiWorld,
+    endpoint_name: String,
+) -> reqwest::Result<()> {
+    shared::verify_named_response(world, &endpoint_name).await
+}
+
+#[then("the response headers match the OpenAPI spec")]
+pub fn verify_openapi_headers(world: &mut ApiWorld) {
+    shared::verify_openapi_headers(world);
+}
+
+#[then(regex = r#"^the response indicates version "([^"]+)"$"#)]
+pub fn verify_indicated_version(world: &mut ApiWorld, version: String) {
+    let raw_api_response = world
+        .raw_api_response
+        .as_ref()
+        .expect("World should contain api response at this point");
+    let settings = &world.profile.version_negotiation;
+    match version_negotiation::indicated_version(raw_api_response.headers(), settings) {
+        Some(indicated) => assert!(
+            version_negotiation::versions_match(&indicated, &version),
+            "The response indicates version {} instead of {}",
+            indicated,
+            version
+        ),
+        None => panic!(
+            "The response indicates no version, neither in {} nor in its Content-Type",
+            settings.response_header
+        ),
+    }
+}
+
+#[then(regex = r#"^the "(\w+)" response matches the schema of version "([^"]+)"$"#)]
+pub async fn verify_versioned_response(
+    world: &mut ApiWorld,
+    endpoint_name: String,
+    version: String,
+) -> reqwest::Result<()> {
+    let endpoints = EndpointRegistry::load(&version).unwrap_or_else(|err| panic!("{}", err));
+    let schema_file = response_schema_file(world, &endpoints, &endpoint_name);
+    let json_response = take_json_response(world).await?;
+    let errors = schema::schema_errors(&json_response, &schema_file);
+    let severity = check_severity(world, "schema");
+    severity::report(
+        &world.scenario_key,
+        severity,
+        &format!(
+            "The response does not match the schema of version {}",
+            version
+        ),
+        &errors,
+    );
+    Ok(())
+}
+
+#[then(regex = r#"^the "(\w+)" response of at most (\d+) bytes has correct format$"#)]
+pub async fn verify_streamed_response(world: &mut ApiWorld, endpoint_name: String, max_bytes: u64) {
+    shared::verify_streamed_response(world, &endpoint_name, max_bytes).await;
+}
+
+#[then(regex = r#"^each line of the "(\w+)" NDJSON response has correct format$"#)]
+pub async fn verify_ndjson_response(world: &mut ApiWorld, endpoint_name: String) {
+    shared::verify_ndjson_response(world, &endpoint_name).await;
+}
+
+#[then(regex = r"^the NDJSON response has (at least|at most|exactly) (\d+) lines$")]
+pub fn verify_ndjson_line_count(world: &mut ApiWorld, comparison: String, expected_count: usize) {
+    shared::verify_ndjson_line_count(world, &comparison, expected_count);
+}
+
+#[then(regex = r#"^the "(\w+)" CSV response matches its rules$"#)]
+pub async fn verify_csv_response(world: &mut ApiWorld, endpoint_name: String) {
+    shared::verify_csv_response(world, &endpoint_name).await;
+}
+
+#[then("I remember the cache validators of the response")]
+pub fn capture_cache_validators(world: &mut ApiWorld) {
+    let raw_api_response = world
+        .raw_api_response
+        .as_ref()
+        .expect("World should contain api response at this point");
+
+    let validators = conditional::CacheValidators::from_response(raw_api_response);
+    assert!(
+        !validators.is_empty(),
+        "Response contains neither ETag nor Last-Modified header"
+    );
+    world.cache_validators = Some(validators);
+}
+
+#[when("I request it again conditionally")]
+pub async fn request_conditionally(world: &mut ApiWorld) -> reqwest::Result<()> {
+    let (client, redirect_chain) = http_client::build_client(&world.profile);
+    let request = prepare_request(world, &client).await;
+    let validators = world
+        .cache_validators
+        .as_ref()
+        .expect("Cache validators should be remembered before a conditional request");
+    let endpoint_name = world
+        .endpoint_name
+        .as_ref()
+        .expect("Endpoint should be selected before requesting it");
+    let result = shared::timed(world, endpoint_name, validators.apply(request).send()).await;
+    record_request(world, &result);
+    world.raw_api_response = Some(result?);
+    world.redirect_chain = Some(redirect_chain);
+    Ok(())
+}
+
+#[then(regex = r"^the response status is (\d{3})$")]
+pub fn verify_status(world: &mut ApiWorld, expected_status: u16) {
+    let raw_api_response = world
+        .raw_api_response
+        .as_ref()
+        .expect("World should contain api response at this point");
+    assert_eq!(raw_api_response.status().as_u16(), expected_status);
+}
+
+#[then("the response echoes the request ID")]
+pub fn verify_request_id_echoed(world: &mut ApiWorld) {
+    shared::verify_request_id_in_header(world, &world.profile.correlation.request_header);
+}
+
+#[then(regex = r#"^the response echoes the request ID in the "([\w-]+)" header$"#)]
+pub fn verify_request_id_in_header(world: &mut ApiWorld, header_name: String) {
+    shared::verify_request_id_in_header(world, &header_name);
+}
+
+#[then(regex = r#"^the response echoes the request ID in the "([\w.]+)" field$"#)]
+pub async fn verify_request_id_in_field(
+    world: &mut ApiWorld,
+    field_path: String,
+) -> reqwest::Result<()> {
+    shared::verify_request_id_in_field(world, &field_path).await
+}
+
+#[then("the response has secure headers")]
+pub fn verify_security_headers(world: &mut ApiWorld) {
+    let raw_api_response = world
+        .raw_api_response
+        .as_ref()
+        .expect("World should contain api response at this point");
+    let problems = security_headers::header_problems(
+        raw_api_response.headers(),
+        &world.profile.security_headers,
+    );
+    let severity = check_severity(world, "security_headers");
+    severity::report(
+        &world.scenario_key,
+        severity,
+        "The response headers are insecure",
+        &problems,
+    );
+}
+
+/// Sends the preflight to the selected endpoint, keeping the response in the world.
+pub async fn send_cors_preflight(
+    world: &mut ApiWorld,
+    preflight: cors::CorsPreflight,
+) -> reqwest::Result<()> {
+    throttle_request(world).await;
+    let (client, _) = http_client::build_client(&world.profile);
+    let api_link = world
+        .api_link
+        .as_ref()
+        .expect("Link should be selected before requesting it");
+    let result = preflight.request(&client, api_link).send().await;
+    let endpoint_name = world
+        .endpoint_name
+        .as_ref()
+        .expect("Endpoint should be selected before requesting it");
+    let transcript = snapshot::transcript_of(endpoint_name, "OPTIONS", api_link, &result);
+    snapshot::record_request(&world.scenario_key, transcript);
+    world.raw_api_response = Some(result?);
+    world.cors_preflight = Some(preflight);
+    Ok(())
+}
+
+#[when("I send a CORS preflight request")]
+pub async fn request_cors_preflight(world: &mut ApiWorld) -> reqwest::Result<()> {
+    let preflight = world.profile.cors.clone();
+    send_cors_preflight(world, preflight).await
+}
+
+#[when(regex = r#"^I send a CORS preflight request from "([^"]+)" for "(\w+)"$"#)]
+pub async fn request_cors_preflight_from(
+    world: &mut ApiWorld,
+    origin: String,
+    method: String,
+) -> reqwest::Result<()> {
+    let preflight = cors::CorsPreflight {
+        origin,
+        method,
+        ..world.profile.cors.clone()
+    };
+    send_cors_preflight(world, preflight).await
+}
+
+#[then(regex = r"^the CORS preflight is (allowed|rejected)$")]
+pub fn verify_cors_preflight(world: &mut ApiWorld, outcome: String) {
+    let raw_api_response = world
+        .raw_api_response
+        .as_ref()
+        .expect("World should contain api response at this point");
+    let preflight = world
+        .cors_preflight
+        .as_ref()
+        .expect("CORS preflight should be sent before verifying it");
+    let denials = preflight.denials(raw_api_response.status(), raw_api_response.headers());
+    match outcome.as_str() {
+        "allowed" if !denials.is_empty() => {
+            panic!("The CORS preflight is rejected:\n{}", denials.join("\n"))
+        }
+        "rejected" if denials.is_empty() => {
+            panic!("The CORS preflight from {} is allowed", preflight.origin)
+        }
+        _ => {}
+    }
+}
+
+#[given(regex = r"^the client (forces HTTP/1.1|prefers HTTP/2|forces HTTP/2)$")]
+pub fn set_http_version(world: &mut ApiWorld, preference: String) {
+    world.profile.http_version = match preference.as_str() {
+        "forces HTTP/1.1" => http_client::HttpVersionPreference::Http1Only,
+        "prefers HTTP/2" => http_client::HttpVersionPreference::PreferHttp2,
+        _ => http_client::HttpVersionPreference::Http2Only,
+    };
+}
+
+#[then(regex = r"^the response is served over (HTTP/1.0|HTTP/1.1|HTTP/2|HTTP/3)$")]
+pub fn verify_http_version(world: &mut ApiWorld, expected_version: String) {
+    let raw_api_response = world
+        .raw_api_response
+        .as_ref()
+        .expect("World should contain api response at this point");
+    let version = match raw_api_response.version() {
+        reqwest::Version::HTTP_09 => "HTTP/0.9",
+        reqwest::Version::HTTP_10 => "HTTP/1.0",
+        reqwest::Version::HTTP_11 => "HTTP/1.1",
+        reqwest::Version::HTTP_2 => "HTTP/2",
+        reqwest::Version::HTTP_3 => "HTTP/3",
+        _ => "unknown",
+    };
+    assert_eq!(version, expected_version);
+}
+
+#[then(regex = r"^the endpoint sustains (\d+) requests per second for (\d+) seconds$")]
+pub async fn verify_throughput(world: &mut ApiWorld, requests_per_second: u32, seconds: u32) {
+    let settings = world.profile.throughput.clone();
+    assert!(
+        settings.allowed,
+        "Throughput tests override the rate limit, so they have to be allowed by the profile"
+    );
+    assert!(
+        requests_per_second > 0,
+        "Requests per second should be positive"
+    );
+    let total = throughput::total_requests(requests_per_second, seconds)
+        .unwrap_or_else(|err| panic!("{}", err));
+    assert!(
+        total <= settings.max_requests,
+        "Throughput test of {} requests exceeds the limit of {} requests",
+        total,
+        settings.max_requests
+    );
+    let endpoint_name = world
+        .endpoint_name
+        .as_ref()
+        .expect("Endpoint should be selected before requesting it");
+    let endpoint = world
+        .endpoints
+        .get(endpoint_name)
+        .unwrap_or_else(|err| panic!("{}", err));
+    access::ensure_allowed(&world.profile, &world.scenario_key, endpoint_name, endpoint);
+    for _ in 0..total {
+        budget::consume(&world.profile.request_budget, &mut world.scenario_requests);
+    }
+    let (client, _) = http_client::build_client(&world.profile);
+    let result = throughput::run(
+        &client,
+        endpoint.method(),
+        world
+            .api_link
+            .as_ref()
+            .expect("Link should be selected before requesting it"),
+        requests_per_second,
+        seconds,
+    )
+    .await
+    .unwrap_or_else(|err| panic!("{}", err));
+    console::print_report(&result);
+    assert!(
+        result.error_rate() <= settings.max_error_rate,
+        "Error rate {:.2}% exceeds {:.2}%",
+        result.error_rate() * 100.0,
+        settings.max_error_rate * 100.0
+    );
+    assert!(
+        result.achieved_rps() >= requests_per_second as f64 * settings.min_rps_ratio,
+        "Achieved {:.1} requests per second instead of {}",
+        result.achieved_rps(),
+        requests_per_second
+    );
+}
+
+#[when(regex = r"^I request it (\d+) times with the same client$")]
+pub async fn request_with_same_client(world: &mut ApiWorld, times: usize) -> reqwest::Result<()> {
+    let (client, counter) = http_client::build_counting_client(&world.profile);
+    for _ in 0..times {
+        let request = prepare_request(world, &client).await;
+        let endpoint_name = world
+            .endpoint_name
+            .as_ref()
+            .expect("Endpoint should be selected before requesting it");
+        let result = shared::timed(world, endpoint_name, request.send()).await;
+        record_request(world, &result);
+        // Connection returns to the pool only once the body is read
+        result?.bytes().await?;
+    }
+    world.connection_counter = Some(counter);
+    Ok(())
+}
+
+#[then(regex = r"^the requests open (\d+) connections?$")]
+pub fn verify_connections(world: &mut ApiWorld, expected_connections: usize) {
+    let counter = world
+        .connection_counter
+        .as_ref()
+        .expect("Requests should be sent with the same client before verifying connections");
+    assert_eq!(counter.new_connections(), expected_connections);
+}
+
+#[then(regex = r#"^the request is redirected to "([^"]+)"$"#)]
+pub fn verify_redirected_to(world: &mut ApiWorld, expected_url: String) {
+    let redirect_chain = world
+        .redirect_chain
+        .as_ref()
+        .expect("World should contain redirect chain at this point")
+        .urls();
+    let is_followed = redirect_chain
+        .iter()
+        .any(|url| url.as_str() == expected_url);
+    let is_announced = world
+        .raw_api_response
+        .as_ref()
+        .and_then(|response| response.headers().get(reqwest::header::LOCATION))
+        .map_or(false, |location| {
+            location.as_bytes() == expected_url.as_bytes()
+        });
+    assert!(
+        is_followed || is_announced,
+        "Request was not redirected to {}; redirect chain: {:?}",
+        expected_url,
+        redirect_chain
+    );
+}
+
+#[then("no redirects occur")]
+pub fn verify_no_redirects(world: &mut ApiWorld) {
+    let redirect_chain = world
+        .redirect_chain
+        .as_ref()
+        .expect("World should contain redirect chain at this point")
+        .urls();
+    assert!(
+        redirect_chain.is_empty(),
+        "Request was redirected: {:?}",
+        redirect_chain
+    );
+    if let Some(response) = world.raw_api_response.as_ref() {
+        assert!(
+            !response.status().is_redirection(),
+            "Response is a redirect with status {}",
+            response.status()
+        );
+    }
+}
+
+/// Inspects the certificate chain of the host from API_LINK once per scenario;
+/// the handshake is throttled like a request to the selected endpoint.
+pub async fn certificate_of(world: &mut ApiWorld) -> &tls::CertificateChain {
+    if world.certificate.is_none() {
+        throttle_request(world).await;
+        let api_link = env::var("API_LINK").expect("Missing secret value: API_LINK");
+        let url = url::Url::parse(&api_link).expect("API_LINK should be a valid url");
+        let host = url
+            .host_str()
+            .expect("API_LINK should contain host")
+            .to_owned();
+        let port = url.port_or_known_default().unwrap_or(443);
+        let profile = world.profile.clone();
+        let certificate =
+            tokio::task::spawn_blocking(move || tls::inspect_certificate(&profile, &host, port))
+                .await
+                .expect("Certificate inspection should not panic")
+                .unwrap_or_else(|err| panic!("{}", err));
+        world.certificate = Some(certificate);
+    }
+    world
+        .certificate
+        .as_ref()
+        .expect("Certificate should be inspected at this point")
+}
+
+#[then(regex = r"^the TLS certificate of the API expires in more than (\d+) days$")]
+pub async fn verify_certificate_expiry(world: &mut ApiWorld, min_days: i64) {
+    let certificate = certificate_of(world).await.first_to_expire();
+    let days_until_expiry = certificate.days_until_expiry();
+    assert!(
+        days_until_expiry > min_days,
+        "Certificate of {} expires in {} days",
+        certificate.subject,
+        days_until_expiry
+    );
+}
+
+#[then(regex = r#"^the TLS certificate of the API is issued by "([^"]+)"$"#)]
+pub async fn verify_certificate_issuer(world: &mut ApiWorld, expected_issuer: String) {
+    let certificate = certificate_of(world).await.leaf();
+    let issued_by = certificate
+        .is_issued_by(&expected_issuer)
+        .unwrap_or_else(|err| panic!("{}", err));
+    assert!(issued_by, "Certificate is issued by {}", certificate.issuer);
+}
+
+#[then(regex = r#"^the TLS certificate of the API covers "([^"]+)"$"#)]
+pub async fn verify_certificate_host(world: &mut ApiWorld, host: String) {
+    let certificate = certificate_of(world).await.leaf();
+    assert!(
+        certificate.covers_host(&host),
+        "Certificate does not cover {}; subject alternative names: {:?}",
+        host,
+        certificate.subject_alt_names
+    );
+}
+
+#[then(regex = r"^the endpoint responds (?:with status (\d{3}) )?over both IPv4 and IPv6$")]
+pub async fn verify_dual_stack(world: &mut ApiWorld, expected_status: String) {
+    let expected_status = (!expected_status.is_empty()).then(|| {
+        reqwest::StatusCode::from_bytes(expected_status.as_bytes())
+            .unwrap_or_else(|err| panic!("Invalid status {}: {}", expected_status, err))
+    });
+    let api_link = world
+        .api_link
+        .clone()
+        .expect("Link should be selected before requesting it");
+    let addresses = dual_stack::resolve_families(&api_link).await;
+    let mut outcomes = Vec::new();
+    for address in [addresses.ipv4, addresses.ipv6] {
+        if address.is_some() {
+            throttle_request(world).await;
+        }
+        outcomes.push(
+            dual_stack::request_over(&world.profile, &api_link, &addresses.host, address).await,
+        );
+    }
+    let ipv6 = outcomes
+        .pop()
+        .expect("Outcome over IPv6 should be recorded");
+    let ipv4 = outcomes
+        .pop()
+        .expect("Outcome over IPv4 should be recorded");
+    let report = dual_stack::DualStackReport { ipv4, ipv6 };
+    console::print_report(&report);
+    report.verify(expected_status);
+}
+
+#[then(regex = r"^all configured (public |private )?endpoints are reachable$")]
+pub async fn verify_reachability(world: &mut ApiWorld, api_type: String) {
+    let auth = match api_type.trim() {
+        "public" => Some(Auth::Public),
+        "private" => Some(Auth::Private),
+        _ => None,
+    };
+    let checked_endpoints = world
+        .endpoints
+        .iter()
+        .filter(|(_, endpoint)| auth.map_or(true, |auth| endpoint.auth == auth))
+        .filter(|(endpoint_name, endpoint)| {
+            access::check(&world.profile, endpoint_name, endpoint).is_ok()
+        })
+        .count();
+    for _ in 0..checked_endpoints {
+        budget::consume(&world.profile.request_budget, &mut world.scenario_requests);
+    }
+    let api_link = env::var("API_LINK").expect("Missing secret value: API_LINK");
+
+    let matrix =
+        reachability::check_reachability(&world.profile, &world.endpoints, &api_link, auth).await;
+    console::print_report(&matrix);
+    matrix.verify();
+}
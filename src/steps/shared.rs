@@ -0,0 +1,585 @@
+//! Steps both suites share, written once over the `StepWorld` trait their worlds implement;
+//! each suite registers thin wrappers of them for its own world.
+use crate::resources::config::{EndpointRegistry, Profile};
+use crate::resources::{
+    capture, correlation, csv_validation, deprecation, drift, encoding, enum_coverage,
+    expectations, generators, integrity, latency, ndjson, number_format, openapi, schema,
+    scripting, scrubbing, severity, snapshot, streaming, templating, test_plan, validators,
+};
+use cucumber::gherkin::Step;
+use std::collections::HashMap;
+use std::future::Future;
+
+/// State of a world the shared steps read and update.
+pub trait StepWorld {
+    fn profile(&self) -> &Profile;
+
+    fn endpoints(&self) -> &EndpointRegistry;
+
+    /// Name of the endpoint of the last request, if it was requested through the registry
+    fn endpoint_name(&self) -> Option<&str>;
+
+    fn scenario_key(&self) -> &str;
+
+    /// Request ID sent with the last request, if the profile sends them
+    fn request_id(&self) -> Option<&str>;
+
+    /// Checks reported as warnings in the scenario, according to its tags
+    fn warning_checks(&self) -> &severity::WarningChecks;
+
+    fn raw_api_response(&self) -> Option<&reqwest::Response>;
+
+    fn take_raw_api_response(&mut self) -> Option<reqwest::Response>;
+
+    /// Values remembered by earlier steps, by name
+    fn captured_values(&self) -> &HashMap<String, String>;
+
+    fn captured_values_mut(&mut self) -> &mut HashMap<String, String>;
+
+    fn ndjson_report_mut(&mut self) -> &mut Option<ndjson::NdjsonReport>;
+
+    /// Method of requests of the suite sent without the registry, e.g. to the selected URL
+    fn default_method(&self) -> reqwest::Method;
+
+    /// Inspects every response parsed to json before the steps check it, e.g. to skip the scenario.
+    fn inspect_json_response(&self, _json_response: &serde_json::Value) {}
+}
+
+/// The response stored in the world, failing the step when there is none.
+pub fn response_of<W: StepWorld>(world: &W) -> &reqwest::Response {
+    world
+        .raw_api_response()
+        .expect("World should contain api response at this point")
+}
+
+/// Takes the response stored in the world, failing the step when there is none.
+pub fn take_response<W: StepWorld>(world: &mut W) -> reqwest::Response {
+    world
+        .take_raw_api_response()
+        .expect("World should contain api response at this point")
+}
+
+/// Severity of the check in the scenario, for the endpoint of the last request.
+pub fn check_severity<W: StepWorld>(world: &W, check: &str) -> severity::Severity {
+    let endpoint = world
+        .endpoint_name()
+        .and_then(|endpoint_name| world.endpoints().get(endpoint_name).ok());
+    world.warning_checks().severity(check, endpoint)
+}
+
+/// Takes the response stored in the world and parses it to json,
+/// keeping it, with volatile fields masked, for the snapshot of a failed scenario.
+pub async fn take_json_response<W: StepWorld>(world: &mut W) -> reqwest::Result<serde_json::Value> {
+    let raw_api_response = take_response(world);
+    let url_path = raw_api_response.url().path().to_owned();
+    let max_bytes = world.profile().max_body_bytes;
+    let body = match streaming::read_capped(raw_api_response, max_bytes).await {
+        Ok(body) => body,
+        Err(streaming::StreamError::Transport(err)) => return Err(err),
+        Err(err) => panic!("{}", err),
+    };
+    let json_response =
+        serde_json::from_slice(&body.bytes).expect("Response body should be valid json");
+    observe_json_response(world, &url_path, &json_response);
+    Ok(json_response)
+}
+
+/// Observes every response parsed to json: keeps it, with volatile fields masked, for the snapshot
+/// of a failed scenario, lets the world inspect it and collects its enum values and integrity values.
+fn observe_json_response<W: StepWorld>(
+    world: &W,
+    url_path: &str,
+    json_response: &serde_json::Value,
+) {
+    snapshot::record_response(
+        world.scenario_key(),
+        json_response,
+        &world.profile().masks,
+        &world.profile().scrub,
+    );
+    world.inspect_json_response(json_response);
+    observe_enum_values(world, url_path, json_response);
+    observe_integrity(world, json_response);
+}
+
+/// Collects Deprecation, Sunset and Warning headers of the response, failing the scenario
+/// when the profile does not accept the announced removal of the endpoint.
+///
+/// # Arguments
+///
+/// * `endpoint_name` - Name of the requested endpoint in the registry
+/// * `result` - Outcome of the request; failed requests announce nothing
+///
+pub fn observe_deprecation<W: StepWorld>(
+    world: &W,
+    endpoint_name: &str,
+    result: &reqwest::Result<reqwest::Response>,
+) {
+    let response = match result {
+        Ok(response) => response,
+        Err(_) => return,
+    };
+    let signal = deprecation::observe(endpoint_name, response.headers());
+    let problems: Vec<String> =
+        deprecation::sunset_violation(endpoint_name, &signal, &world.profile().deprecation)
+            .into_iter()
+            .collect();
+    let severity = world
+        .warning_checks()
+        .severity("deprecation", world.endpoints().get(endpoint_name).ok());
+    severity::report(
+        world.scenario_key(),
+        severity,
+        "The endpoint is announced for removal",
+        &problems,
+    );
+}
+
+/// Collects values of the response of the selected endpoint referred to by integrity rules of the profile.
+pub fn observe_integrity<W: StepWorld>(world: &W, json_response: &serde_json::Value) {
+    if let Some(endpoint_name) = world.endpoint_name() {
+        integrity::observe(
+            world.scenario_key(),
+            endpoint_name,
+            &world.profile().integrity_rules,
+            json_response,
+        );
+    }
+}
+
+/// Collects values of enum-like fields of the response of the selected endpoint,
+/// or of the endpoint of the registry the URL leads to, when the request was sent by URL.
+///
+/// # Arguments
+///
+/// * `world` - World of the scenario
+/// * `url_path` - Path of the URL the response was received from
+/// * `json_response` - Response body parsed to json
+///
+pub fn observe_enum_values<W: StepWorld>(
+    world: &W,
+    url_path: &str,
+    json_response: &serde_json::Value,
+) {
+    let selected = world.endpoint_name().and_then(|endpoint_name| {
+        let endpoint = world.endpoints().get(endpoint_name).ok()?;
+        Some((endpoint_name, endpoint))
+    });
+    let endpoint = selected.or_else(|| {
+        world
+            .endpoints()
+            .find_by_path(url_path)
+            .map(|(endpoint_name, endpoint)| (endpoint_name.as_str(), endpoint))
+    });
+    if let Some((endpoint_name, endpoint)) = endpoint {
+        enum_coverage::observe(endpoint_name, &endpoint.enums, json_response);
+    }
+}
+
+/// Checks the charset of the response and identifiers the registry expects in ASCII.
+pub async fn verify_encoding<W: StepWorld>(
+    world: &mut W,
+    endpoint_name: &str,
+) -> reqwest::Result<()> {
+    let raw_api_response = take_response(world);
+    let url_path = raw_api_response.url().path().to_owned();
+    let content_type = raw_api_response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_owned);
+    let max_bytes = world.profile().max_body_bytes;
+    let body = match streaming::read_capped(raw_api_response, max_bytes).await {
+        Ok(body) => body.bytes,
+        Err(streaming::StreamError::Transport(err)) => return Err(err),
+        Err(err) => panic!("{}", err),
+    };
+    let mut problems = encoding::encoding_problems(content_type.as_deref(), &body);
+    if problems.is_empty() {
+        let json_response: serde_json::Value =
+            serde_json::from_slice(&body).expect("Response body should be valid json");
+        observe_json_response(world, &url_path, &json_response);
+        let endpoint = world
+            .endpoints()
+            .get(endpoint_name)
+            .unwrap_or_else(|err| panic!("{}", err));
+        problems = encoding::non_ascii_identifiers(&json_response, &endpoint.ascii);
+    }
+    let severity = check_severity(world, "encoding");
+    severity::report(
+        world.scenario_key(),
+        severity,
+        "The response is incorrectly encoded",
+        &problems,
+    );
+    Ok(())
+}
+
+/// Checks numeric strings of the response for locale artifacts, e.g. thousands separators.
+pub async fn verify_number_formats<W: StepWorld>(world: &mut W) -> reqwest::Result<()> {
+    let json_response = take_json_response(world).await?;
+    let violations = number_format::locale_violations(&json_response);
+    let severity = check_severity(world, "number_format");
+    severity::report(
+        world.scenario_key(),
+        severity,
+        "The following numbers are incorrect",
+        &violations,
+    );
+    Ok(())
+}
+
+/// Checks the values of enum-like fields collected from all responses of the endpoint so far.
+pub fn verify_enum_values<W: StepWorld>(world: &W, endpoint_name: &str) {
+    let endpoint = world
+        .endpoints()
+        .get(endpoint_name)
+        .unwrap_or_else(|err| panic!("{}", err));
+    let problems = enum_coverage::undocumented_problems(endpoint_name, &endpoint.enums);
+    let severity = world
+        .warning_checks()
+        .severity("enum_values", Some(endpoint));
+    severity::report(
+        world.scenario_key(),
+        severity,
+        &format!("Responses of {} contain undocumented values", endpoint_name),
+        &problems,
+    );
+}
+
+/// Validates every line of the NDJSON response, keeping the report for the line count steps.
+pub async fn verify_ndjson_response<W: StepWorld>(world: &mut W, endpoint_name: &str) {
+    let raw_api_response = take_response(world);
+    let report = ndjson::validate_ndjson(
+        raw_api_response,
+        &world.endpoints().schema_file(endpoint_name),
+        world.profile().max_body_bytes,
+    )
+    .await
+    .unwrap_or_else(|err| panic!("{}", err));
+    report.verify_no_failures();
+    *world.ndjson_report_mut() = Some(report);
+}
+
+pub fn verify_ndjson_line_count<W: StepWorld>(
+    world: &mut W,
+    comparison: &str,
+    expected_count: usize,
+) {
+    world
+        .ndjson_report_mut()
+        .as_ref()
+        .expect("World should contain NDJSON report at this point")
+        .verify_line_count(comparison, expected_count);
+}
+
+/// Checks the CSV response against the rules of the endpoint.
+pub async fn verify_csv_response<W: StepWorld>(world: &mut W, endpoint_name: &str) {
+    let raw_api_response = take_response(world);
+    let body = streaming::read_capped(raw_api_response, world.profile().max_body_bytes)
+        .await
+        .unwrap_or_else(|err| panic!("{}", err));
+    let rules = csv_validation::load_rules(&world.endpoints().csv_rules_file(endpoint_name));
+    let violations = csv_validation::validate_csv(&body.bytes, &rules);
+    let severity = check_severity(world, "csv");
+    severity::report(
+        world.scenario_key(),
+        severity,
+        "The following csv rules are violated",
+        &violations,
+    );
+}
+
+/// Checks headers and the content type of the response against the OpenAPI spec of the profile, in OpenAPI mode.
+pub fn check_openapi_headers<W: StepWorld>(world: &W) {
+    let spec_file = match &world.profile().openapi.spec {
+        Some(spec_file) => spec_file,
+        None => return,
+    };
+    let raw_api_response = response_of(world);
+    let method = world
+        .endpoint_name()
+        .and_then(|endpoint_name| world.endpoints().get(endpoint_name).ok())
+        .map_or_else(|| world.default_method(), |endpoint| endpoint.method());
+    let problems = openapi::header_problems(
+        &openapi::load_spec(spec_file),
+        &method,
+        raw_api_response.url().path(),
+        raw_api_response.status().as_u16(),
+        raw_api_response.headers(),
+        &world.profile().openapi,
+    );
+    let severity = check_severity(world, "openapi_headers");
+    severity::report(
+        world.scenario_key(),
+        severity,
+        "The response headers differ from the OpenAPI spec",
+        &problems,
+    );
+}
+
+pub fn verify_openapi_headers<W: StepWorld>(world: &W) {
+    assert!(
+        world.profile().openapi.spec.is_some(),
+        "The profile should set openapi.spec to check responses against the spec"
+    );
+    check_openapi_headers(world);
+}
+
+/// Name of the file in "./schemas" describing the response in the world, selected by its status and content type.
+pub fn response_schema_file<W: StepWorld>(
+    world: &W,
+    endpoints: &EndpointRegistry,
+    endpoint_name: &str,
+) -> String {
+    let raw_api_response = response_of(world);
+    let content_type = raw_api_response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok());
+    endpoints.response_schema_file(
+        endpoint_name,
+        raw_api_response.status().as_u16(),
+        content_type,
+    )
+}
+
+/// Checks the response against the schema the registry selects for its status and content type.
+pub async fn verify_named_response<W: StepWorld>(
+    world: &mut W,
+    endpoint_name: &str,
+) -> reqwest::Result<()> {
+    check_openapi_headers(world);
+    let schema_file = response_schema_file(world, world.endpoints(), endpoint_name);
+    let json_response = take_json_response(world).await?;
+    let errors = schema::schema_errors(&json_response, &schema_file);
+    let severity = check_severity(world, "schema");
+    severity::report(
+        world.scenario_key(),
+        severity,
+        "The response does not match its schema",
+        &errors,
+    );
+    let undeclared = drift::undeclared_fields(&schema::load_schema(&schema_file), &json_response);
+    let severity = check_severity(world, "extra_fields");
+    severity::report(
+        world.scenario_key(),
+        severity,
+        "The response contains fields absent from its schema",
+        &undeclared,
+    );
+    Ok(())
+}
+
+/// Streams the response within the size limit, keeping its size and digest for the snapshot,
+/// and checks it against the schema the registry selects.
+pub async fn verify_streamed_response<W: StepWorld>(
+    world: &mut W,
+    endpoint_name: &str,
+    max_bytes: u64,
+) {
+    let schema_file = response_schema_file(world, world.endpoints(), endpoint_name);
+    let raw_api_response = take_response(world);
+    let url_path = raw_api_response.url().path().to_owned();
+    let body = streaming::read_capped(raw_api_response, max_bytes)
+        .await
+        .unwrap_or_else(|err| panic!("{}", err));
+    snapshot::record_body_summary(world.scenario_key(), &body.summary);
+    let json_response: serde_json::Value =
+        serde_json::from_slice(&body.bytes).expect("Response body should be valid json");
+    observe_json_response(world, &url_path, &json_response);
+    schema::verify_against_schema(&json_response, &schema_file);
+}
+
+/// Checks the response echoes the request ID of the last request in the header.
+pub fn verify_request_id_in_header<W: StepWorld>(world: &W, header_name: &str) {
+    let echoed = correlation::header_value(response_of(world).headers(), header_name);
+    let location = format!("header {}", header_name);
+    if let Some(problem) = correlation::echo_problem(world.request_id(), echoed, &location) {
+        panic!("{}", problem)
+    }
+}
+
+/// Checks the response echoes the request ID of the last request in the field of its body.
+pub async fn verify_request_id_in_field<W: StepWorld>(
+    world: &mut W,
+    field_path: &str,
+) -> reqwest::Result<()> {
+    let json_response = take_json_response(world).await?;
+    let echoed = capture::extract_field(&json_response, field_path);
+    let location = format!("field {}", field_path);
+    if let Some(problem) =
+        correlation::echo_problem(world.request_id(), echoed.as_deref(), &location)
+    {
+        panic!("{}", problem)
+    }
+    Ok(())
+}
+
+/// Awaits sending of the request to the endpoint, recording its latency
+/// and reporting it when it exceeds the latency budget of the endpoint.
+pub async fn timed<W, F>(
+    world: &W,
+    endpoint_name: &str,
+    sending: F,
+) -> reqwest::Result<reqwest::Response>
+where
+    W: StepWorld,
+    F: Future<Output = reqwest::Result<reqwest::Response>>,
+{
+    let (response, latency) = latency::timed(endpoint_name, sending).await?;
+    let problems: Vec<String> = latency::budget_problem(endpoint_name, latency)
+        .into_iter()
+        .collect();
+    let severity = world
+        .warning_checks()
+        .severity("latency", world.endpoints().get(endpoint_name).ok());
+    severity::report(
+        world.scenario_key(),
+        severity,
+        "The request exceeded its latency budget",
+        &problems,
+    );
+    Ok(response)
+}
+
+/// Checks the integrity rules over the responses observed by the scenario.
+pub fn verify_integrity<W: StepWorld>(world: &W) {
+    let violations =
+        integrity::scenario_violations(world.scenario_key(), &world.profile().integrity_rules);
+    assert!(
+        violations.is_empty(),
+        "Responses of the scenario violate integrity rules:\n{}",
+        violations.join("\n")
+    );
+}
+
+/// Checks the response against the status, schema and assertions of the request of the test plan,
+/// remembering the values the request extracts.
+pub async fn verify_plan_request<W: StepWorld>(
+    world: &mut W,
+    request: usize,
+    test: usize,
+    plan_id: &str,
+) -> reqwest::Result<()> {
+    let plan_request = test_plan::request_of(plan_id, test, request);
+    if let Some(expected_status) = plan_request.status {
+        let status = response_of(world).status().as_u16();
+        assert_eq!(
+            status, expected_status,
+            "Response of {} has unexpected status",
+            plan_request.endpoint
+        );
+    }
+    let schema_file = response_schema_file(world, world.endpoints(), &plan_request.endpoint);
+    let json_response = take_json_response(world).await?;
+    if plan_request.schema {
+        let errors = schema::schema_errors(&json_response, &schema_file);
+        let severity = check_severity(world, "schema");
+        severity::report(
+            world.scenario_key(),
+            severity,
+            "The response does not match its schema",
+            &errors,
+        );
+    }
+    let failures = test_plan::failures(&plan_request, &json_response);
+    assert!(
+        failures.is_empty(),
+        "Response of {} breaks the assertions of the plan:\n{}",
+        plan_request.endpoint,
+        failures.join("\n")
+    );
+    for (name, field_path, value) in test_plan::extracted(&plan_request, &json_response) {
+        let recorded_value = scrubbing::scrub_field(&field_path, &value, &world.profile().scrub);
+        snapshot::record_captured_value(world.scenario_key(), &name, &recorded_value);
+        world.captured_values_mut().insert(name, value);
+    }
+    Ok(())
+}
+
+/// Checks the response against the expectations listed in the doc string of the step.
+pub async fn verify_expectations<W: StepWorld>(world: &mut W, step: &Step) -> reqwest::Result<()> {
+    let document = step
+        .docstring
+        .as_ref()
+        .expect("The step should be followed by a doc string listing the expectations");
+    let expectations = expectations::parse(document)
+        .unwrap_or_else(|err| panic!("Expectations of the step are malformed: {}", err));
+    let json_response = take_json_response(world).await?;
+    let failures = expectations::failures(&expectations, &json_response);
+    let severity = check_severity(world, "expectations");
+    severity::report(
+        world.scenario_key(),
+        severity,
+        "The response does not meet the expectations",
+        &failures,
+    );
+    Ok(())
+}
+
+/// Checks the response with the validator registered under the name, reporting its problems under the same name.
+pub async fn verify_with_validator<W: StepWorld>(world: &mut W, name: &str) -> reqwest::Result<()> {
+    let validator = validators::validator(name).unwrap_or_else(|| {
+        panic!(
+            "No validator is registered as {}; registered: {:?}",
+            name,
+            validators::registered_names()
+        )
+    });
+    let status = response_of(world).status().as_u16();
+    let json_response = take_json_response(world).await?;
+    let ctx = validators::ValidationContext {
+        endpoint_name: world.endpoint_name(),
+        status,
+        scenario_key: world.scenario_key(),
+        profile: world.profile(),
+    };
+    let report = validator.validate(&ctx, &json_response);
+    let severity = check_severity(world, name);
+    severity::report(
+        world.scenario_key(),
+        severity,
+        &format!("The response does not pass the {} validator", name),
+        &report.problems,
+    );
+    Ok(())
+}
+
+/// Runs the script function, remembering the entries of the map it returns for later steps.
+pub fn run_script<W: StepWorld>(world: &mut W, script: &str) {
+    let context = scripting::scenario_context(
+        world.endpoint_name(),
+        world.captured_values(),
+        &world.profile().variables,
+    );
+    let remembered =
+        scripting::call_for_map(script, &context).unwrap_or_else(|err| panic!("{}", err));
+    world.captured_values_mut().extend(remembered);
+}
+
+/// Parameters written in the doc string of the step, rendered with the values of the scenario and the profile.
+pub fn rendered_parameters<W: StepWorld>(world: &W, step: &Step) -> Vec<(String, String)> {
+    let template = step
+        .docstring
+        .as_ref()
+        .expect("The step should be followed by a doc string with the parameters");
+    let context = templating::context_of(world.captured_values(), world.profile());
+    templating::render_parameters(template, &context).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// Parameters of the template of the catalogue, rendered with the values of the scenario and the profile.
+pub fn template_parameters<W: StepWorld>(world: &W, template: &str) -> Vec<(String, String)> {
+    let context = templating::context_of(world.captured_values(), world.profile());
+    templating::render_template_parameters(template, &context)
+        .unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// Generates a synthetic value of the kind and remembers it under the name for later steps.
+pub fn generate_value<W: StepWorld>(world: &mut W, kind: &str, name: String) {
+    let generator: generators::Generator = kind.parse().unwrap_or_else(|err| panic!("{}", err));
+    let value = generator
+        .generate(&world.profile().generators)
+        .unwrap_or_else(|err| panic!("{}", err));
+    world.captured_values_mut().insert(name, value);
+}
@@ -0,0 +1,305 @@
+//! Run of a suite shared by the cucumber binaries: its setup, the retry of quarantined scenarios
+//! and the reports written once its scenarios finish.
+use super::shared::StepWorld;
+use crate::resources::config::{self, EndpointRegistry, Profile};
+use crate::resources::run_options::RunOptions;
+use crate::resources::{
+    baseline, budget, cancellation, console, correlation, deprecation, drift, enum_coverage,
+    flakiness, integrity, latency, latency_history, maintenance, monitor, rate_limit, redaction,
+    reference, report_encryption, results, scheduling, scripting, seed, severity, skipping,
+    snapshot, tags, templating, translation, verdict, wasm_plugins,
+};
+use async_trait::async_trait;
+use cucumber::{cli, gherkin, step, writer, Cucumber, World};
+use futures::future::LocalBoxFuture;
+use std::fmt::Debug;
+
+/// World of a suite run by `run_suite`, along with what its suite needs besides the shared run.
+#[async_trait(?Send)]
+pub trait SuiteWorld: StepWorld + World + Debug {
+    /// Name of the suite, e.g. "public", naming its features, reports and histories
+    const SUITE: &'static str;
+
+    /// Step definitions registered for the world
+    fn steps() -> step::Collection<Self>;
+
+    fn profile_mut(&mut self) -> &mut Profile;
+
+    fn scenario_key_mut(&mut self) -> &mut String;
+
+    fn warning_checks_mut(&mut self) -> &mut severity::WarningChecks;
+
+    /// Prepares the run once the profile and the endpoints are loaded, before any scenario.
+    async fn prepare_run(_profile: &Profile, _endpoints: &EndpointRegistry, _options: &RunOptions) {
+    }
+
+    /// Prepares the scenario once its resources are locked, before its steps.
+    async fn prepare_scenario(
+        &mut self,
+        _feature: &gherkin::Feature,
+        _rule: Option<&gherkin::Rule>,
+        _scenario: &gherkin::Scenario,
+    ) {
+    }
+
+    /// Reports about the run as a whole besides the shared ones, by section name.
+    fn run_reports() -> Vec<(&'static str, String)> {
+        Vec::new()
+    }
+
+    /// Cleans up after the run, once its results are written.
+    async fn finish_run(_profile: &Profile, _endpoints: &EndpointRegistry) {}
+}
+
+/// Prepares the scenario before its steps, in the first and the second attempt alike;
+/// scenarios started during a maintenance window are delayed or skipped.
+fn before_scenario<'a, W: SuiteWorld>(
+    feature: &'a gherkin::Feature,
+    rule: Option<&'a gherkin::Rule>,
+    scenario: &'a gherkin::Scenario,
+    world: &'a mut W,
+) -> LocalBoxFuture<'a, ()> {
+    Box::pin(async move {
+        cancellation::refuse_if_cancelled();
+        tags::apply_to_profile(&scenario.tags, world.profile_mut());
+        *world.scenario_key_mut() = snapshot::start(feature, scenario);
+        maintenance::guard(&world.profile().maintenance, world.scenario_key()).await;
+        *world.warning_checks_mut() =
+            severity::WarningChecks::from_tags(tags::all_tags(feature, rule, scenario));
+        let resources = scheduling::exclusive_resources(feature, rule, scenario);
+        scheduling::lock_resources(world.scenario_key(), &resources).await;
+        world.prepare_scenario(feature, rule, scenario).await;
+    })
+}
+
+/// Releases resources of the scenario and writes its snapshot when it failed.
+fn after_scenario<'a, W: SuiteWorld>(
+    feature: &'a gherkin::Feature,
+    _rule: Option<&'a gherkin::Rule>,
+    scenario: &'a gherkin::Scenario,
+    world: Option<&'a mut W>,
+) -> LocalBoxFuture<'a, ()> {
+    Box::pin(async move {
+        let scenario_key = snapshot::scenario_key(feature, scenario);
+        scheduling::release_resources(&scenario_key);
+        integrity::forget(&scenario_key);
+        // World of a scenario is not handed over when any of its steps failed,
+        // including the one which skipped it
+        let failed = world.is_none() && skipping::skip_reason(&scenario_key).is_none();
+        snapshot::finish(
+            &scenario_key,
+            failed,
+            &format!("/results/snapshots/{}", W::SUITE),
+        );
+    })
+}
+
+/// Runs the features of the suite with the options given after `--` to `cargo test`,
+/// retries its failed quarantined scenarios and writes the reports of the run,
+/// failing it when the verdict or the latency history says so.
+pub async fn run_suite<W: SuiteWorld>() {
+    let options = cli::Opts::<_, _, _, RunOptions>::parsed();
+    let shard = options.custom.shard;
+    let report_name = options.custom.report_name(W::SUITE);
+    let suite = options.custom.suite_name(W::SUITE);
+    let features = format!("features/{}", W::SUITE);
+    let report_path = format!("/results/{}.xml", report_name);
+    let result_path = format!("/results/{}.json", report_name);
+    let quarantine_path = format!("/results/quarantine/{}.toml", report_name);
+    let quarantine = flakiness::Quarantine::read(&quarantine_path);
+    let previous_result = options.custom.resume.then(|| {
+        results::RunResult::read(&result_path)
+            .unwrap_or_else(|err| panic!("Cannot resume the previous run: {}", err))
+    });
+    let passed_before = previous_result
+        .as_ref()
+        .map(results::RunResult::passed_scenarios)
+        .unwrap_or_default();
+    if previous_result.is_some() {
+        println!(
+            "Resuming; {} scenarios passed in the previous run are skipped",
+            passed_before.len()
+        );
+    }
+    let profile = Profile::load().unwrap_or_else(|err| panic!("{}", err));
+    // Malformed report key is reported before the run rather than when its reports are written
+    report_encryption::key_from_env().unwrap_or_else(|err| panic!("{}", err));
+    correlation::validate_run_id().unwrap_or_else(|err| panic!("{}", err));
+    let baseline = baseline::Baseline::load().unwrap_or_else(|err| panic!("{}", err));
+    let endpoints =
+        EndpointRegistry::load(&profile.api_version).unwrap_or_else(|err| panic!("{}", err));
+    latency::set_budgets(&endpoints);
+    monitor::warm_up(&profile, &endpoints).await;
+    reference::preload(&profile, &endpoints).await;
+    let plugins =
+        wasm_plugins::register_dir(&profile.wasm_plugins).unwrap_or_else(|err| panic!("{}", err));
+    if !plugins.is_empty() {
+        println!("Registered validator plugins: {}", plugins.join(", "));
+    }
+    let scripted = scripting::load(&profile.scripts).unwrap_or_else(|err| panic!("{}", err));
+    if !scripted.is_empty() {
+        println!("Registered script validators: {}", scripted.join(", "));
+    }
+    templating::load(&profile.templates, &profile.generators)
+        .unwrap_or_else(|err| panic!("{}", err));
+    W::prepare_run(&profile, &endpoints, &options.custom).await;
+
+    let api_version = profile.api_version.clone();
+    let translations = translation::StepTranslations::compile(
+        &config::load_translations().unwrap_or_else(|err| panic!("{}", err)),
+    );
+    let seed = seed::Seed::from_env();
+    println!("Running with SEED={}", seed.value);
+    println!("Running with RUN_ID={}", correlation::run_id());
+    if let Some(shard) = shard {
+        println!("Running shard {}", shard);
+    }
+
+    let report = redaction::ReportBuffer::default();
+    cancellation::install(
+        &suite,
+        &report_path,
+        &result_path,
+        seed.value,
+        shard,
+        profile.cancel_grace_seconds,
+    );
+    Cucumber::new()
+        .steps(W::steps())
+        .with_parser(
+            seed::SeededParser::new(seed)
+                .with_variables(profile.variables.clone())
+                .with_translations(translations.clone()),
+        )
+        .max_concurrent_scenarios(seed.max_concurrent_scenarios())
+        .which_scenario(scheduling::scenario_type)
+        .before(before_scenario::<W>)
+        .after(after_scenario::<W>)
+        .with_writer(console::ConsoleWriter::new(writer::JUnit::new(
+            report.clone(),
+            0,
+        )))
+        .with_cli(options)
+        .filter_run(&features, move |feature, rule, scenario| {
+            tags::runs_on_version(&api_version, feature, rule, scenario)
+                && !passed_before.contains(&(feature.name.clone(), scenario.name.clone()))
+                && shard.map_or(true, |shard| shard.contains(&feature.name, &scenario.name))
+        })
+        .await;
+    let first_attempts = results::recorded_outcomes();
+    let retried = quarantine.to_retry(
+        &profile.flakiness,
+        &results::run_result(&suite, seed.value, shard),
+    );
+    if !retried.is_empty() && !cancellation::is_cancelled() {
+        println!("Retrying {} failed quarantined scenarios", retried.len());
+        Cucumber::new()
+            .steps(W::steps())
+            .with_parser(
+                seed::SeededParser::new(seed)
+                    .with_variables(profile.variables.clone())
+                    .with_translations(translations),
+            )
+            .max_concurrent_scenarios(seed.max_concurrent_scenarios())
+            .which_scenario(scheduling::scenario_type)
+            .before(before_scenario::<W>)
+            .after(after_scenario::<W>)
+            .with_writer(console::ConsoleWriter::new(writer::JUnit::new(
+                redaction::ReportBuffer::default(),
+                0,
+            )))
+            .with_cli(cli::Opts::<_, _, _, RunOptions>::parsed())
+            .filter_run(&features, move |feature, _rule, scenario| {
+                retried.contains(&(feature.name.clone(), scenario.name.clone()))
+            })
+            .await;
+    }
+    let mut run_result = results::run_result(&suite, seed.value, shard);
+    let retried_outcomes = flakiness::apply_retries(&mut run_result, first_attempts);
+    let mut run_result = match &previous_result {
+        Some(previous_result) => previous_result.resumed_with(run_result),
+        None => run_result,
+    };
+    run_result.cancelled = cancellation::is_cancelled();
+    let baseline_report = baseline.apply(&mut run_result, &baseline::today());
+    let excused_failures = quarantine.excuse_failures(&profile.flakiness, &mut run_result);
+    run_result
+        .attach_provider_status(&profile.status_page)
+        .await;
+    let flaky = flakiness::check_flakiness(
+        &run_result,
+        &profile.flakiness,
+        &format!("/results/scenario_history/{}.jsonl", W::SUITE),
+    );
+    run_result.add_section("flakiness", &flaky);
+    let integrity_report = integrity::integrity_report(&profile.integrity_rules);
+    run_result.add_section("integrity", &integrity_report);
+    let deprecation_report = deprecation::deprecation_report();
+    run_result.add_section("deprecation", &deprecation_report);
+    let suite_reports = W::run_reports();
+    for (name, suite_report) in &suite_reports {
+        run_result.add_section(name, suite_report);
+    }
+    // Report of a resumed run covers the scenarios which passed in the previous run as well,
+    // known issues and failures of excluded flaky scenarios are reported as skipped instead of failed,
+    // retried scenarios are reported with the outcome of their second attempt,
+    // warnings are reported as output of the scenarios,
+    // scenarios stopped on purpose are reported as skipped with their reason
+    // the state of the provider is reported when scenarios failed
+    // and reports about the run as a whole are reported as the "run" test suite
+    let rewrite_report = run_result.cancelled
+        || previous_result.is_some()
+        || !baseline_report.known_issues.is_empty()
+        || excused_failures > 0
+        || retried_outcomes > 0
+        || run_result.has_warnings()
+        || run_result.has_skip_reasons()
+        || run_result.provider_status.is_some()
+        || run_result.has_sections();
+    if rewrite_report {
+        let report = redaction::ReportBuffer::default();
+        run_result
+            .write_junit(&mut report.clone())
+            .expect("Writing to report buffer should not fail");
+        redaction::write_redacted_report(&report_path, &report);
+    } else {
+        report.insert_comments(&[
+            results::xml_comment(&format!("SEED={}", seed.value)),
+            results::xml_comment(&format!("RUN_ID={}", correlation::run_id())),
+        ]);
+        redaction::write_redacted_report(&report_path, &report);
+    }
+    run_result.write(&result_path);
+    print!("{}", baseline_report);
+    if let Some(provider_status) = &run_result.provider_status {
+        println!("Provider status: {}", provider_status);
+    }
+    print!("{}", flaky);
+    flaky.write(&quarantine_path);
+    W::finish_run(&profile, &endpoints).await;
+    print!("{}", rate_limit::weight_report());
+    println!("API calls made: {}", budget::run_requests());
+    let latency_report = latency::latency_report();
+    print!("{}", latency_report);
+    latency_report.write_histograms(&format!("/results/latency_histograms/{}.json", report_name));
+    print!("{}", latency::budget_report());
+    let regressions = latency_history::check_regressions(
+        &latency_report,
+        &profile.latency_regression,
+        &format!("/results/latency_history/{}.sqlite", W::SUITE),
+    );
+    let drift_report = drift::drift_report();
+    print!("{}", drift_report);
+    print!("{}", enum_coverage::enum_coverage_report());
+    print!("{}", integrity_report);
+    for (_, suite_report) in &suite_reports {
+        print!("{}", suite_report);
+    }
+    print!("{}", deprecation_report);
+    let verdict = verdict::judge(&run_result, &drift_report, &profile.verdict);
+    print!("{}", verdict);
+    monitor::cool_down(&profile).await;
+    cancellation::exit_if_cancelled();
+    latency_history::report_regressions(&regressions, &profile.latency_regression);
+    verdict.enforce();
+}
@@ -0,0 +1,205 @@
+use api_verify::resources::correlation;
+use serde::Serialize;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::process::Command;
+use tokio::time::MissedTickBehavior;
+
+/// Outcome of a single run of a suite.
+#[derive(Debug, Clone, Serialize)]
+pub struct SuiteOutcome {
+    pub suite: String,
+    /// ID the run was started with, attached to its requests and reports
+    pub run_id: String,
+    pub passed: bool,
+    pub duration_ms: u128,
+}
+
+/// Status of the monitor, exposed by the health endpoint and sent in notifications.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WatchStatus {
+    /// Number of finished iterations
+    pub iterations: u64,
+    /// Number of finished iterations in which any suite failed
+    pub failed_iterations: u64,
+    /// Unix time of finishing the last iteration, in seconds
+    pub last_finished_at: Option<u64>,
+    pub last_outcomes: Vec<SuiteOutcome>,
+}
+
+impl WatchStatus {
+    /// Whether the last iteration passed; true before the first iteration finishes.
+    pub fn is_healthy(&self) -> bool {
+        self.last_outcomes.iter().all(|outcome| outcome.passed)
+    }
+}
+
+/// Parses interval given as a number with unit, e.g. "30s", "5m" or "1h".
+///
+/// # Arguments
+///
+/// * `interval` - Interval with the unit suffix
+///
+pub fn parse_interval(interval: &str) -> Result<Duration, String> {
+    let split_at = interval
+        .find(|character: char| !character.is_ascii_digit())
+        .unwrap_or_else(|| interval.len());
+    let (amount, unit) = interval.split_at(split_at);
+    let amount: u64 = amount
+        .parse()
+        .map_err(|_| format!("Interval should start with a number, got: {}", interval))?;
+    let unit_seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        _ => {
+            return Err(format!(
+                "Interval should end with s, m or h, got: {}",
+                interval
+            ))
+        }
+    };
+    let seconds = amount
+        .checked_mul(unit_seconds)
+        .ok_or_else(|| format!("Interval is too long: {}", interval))?;
+    if seconds == 0 {
+        return Err("Interval should be greater than 0".to_owned());
+    }
+    Ok(Duration::from_secs(seconds))
+}
+
+/// Runs the suites repeatedly, keeping the process alive and reporting status of every iteration.
+///
+/// # Arguments
+///
+/// * `interval` - Delay between starts of consecutive iterations
+/// * `suites` - Names of the test binaries to run, e.g. public
+/// * `health_port` - Port of the HTTP health endpoint
+/// * `notify_url` - Link the status is posted to after every iteration
+/// * `seed` - Seed passed to the suites, drawn by each of them when not set
+///
+pub async fn watch(
+    interval: Duration,
+    suites: &[String],
+    health_port: u16,
+    notify_url: Option<&str>,
+    seed: Option<u64>,
+) -> io::Result<()> {
+    let status = Arc::new(Mutex::new(WatchStatus::default()));
+    let listener = TcpListener::bind(("0.0.0.0", health_port)).await?;
+    tokio::spawn(serve_health(listener, status.clone()));
+    println!("Health endpoint listens on port {}", health_port);
+
+    let client = reqwest::Client::new();
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+        let mut outcomes = Vec::new();
+        for suite in suites {
+            outcomes.push(run_suite(suite, seed).await);
+        }
+
+        let snapshot = {
+            let mut status = status.lock().expect("Watch status should not be poisoned");
+            status.iterations += 1;
+            if outcomes.iter().any(|outcome| !outcome.passed) {
+                status.failed_iterations += 1;
+            }
+            status.last_finished_at = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .ok()
+                .map(|time| time.as_secs());
+            status.last_outcomes = outcomes;
+            status.clone()
+        };
+        print_metrics(&snapshot);
+        if let Some(notify_url) = notify_url {
+            if let Err(err) = client.post(notify_url).json(&snapshot).send().await {
+                eprintln!("Sending notification failed: {}", err);
+            }
+        }
+    }
+}
+
+/// Runs the suite once; a suite which cannot be started is reported as failed, so the monitor keeps going.
+async fn run_suite(suite: &str, seed: Option<u64>) -> SuiteOutcome {
+    let start = Instant::now();
+    let run_id = correlation::new_run_id();
+    let mut command = Command::new("cargo");
+    command
+        .args(["test", "--test", suite])
+        .env("RUN_ID", &run_id);
+    if let Some(seed) = seed {
+        command.env("SEED", seed.to_string());
+    }
+    let passed = match command.status().await {
+        Ok(exit_status) => exit_status.success(),
+        Err(err) => {
+            eprintln!("Starting suite {} failed: {}", suite, err);
+            false
+        }
+    };
+    SuiteOutcome {
+        suite: suite.to_owned(),
+        run_id,
+        passed,
+        duration_ms: start.elapsed().as_millis(),
+    }
+}
+
+fn print_metrics(status: &WatchStatus) {
+    for outcome in &status.last_outcomes {
+        println!(
+            "api_verify_suite_passed{{suite=\"{}\"}} {}",
+            outcome.suite, outcome.passed as u8
+        );
+        println!(
+            "api_verify_suite_duration_ms{{suite=\"{}\"}} {}",
+            outcome.suite, outcome.duration_ms
+        );
+    }
+    println!("api_verify_iterations_total {}", status.iterations);
+    println!(
+        "api_verify_failed_iterations_total {}",
+        status.failed_iterations
+    );
+}
+
+/// Answers every HTTP request with the current status as json;
+/// status code is 200 when the last iteration passed and 503 otherwise.
+async fn serve_health(listener: TcpListener, status: Arc<Mutex<WatchStatus>>) {
+    loop {
+        let (mut stream, _) = match listener.accept().await {
+            Ok(connection) => connection,
+            Err(err) => {
+                eprintln!("Accepting health check connection failed: {}", err);
+                continue;
+            }
+        };
+        let status = status
+            .lock()
+            .expect("Watch status should not be poisoned")
+            .clone();
+        tokio::spawn(async move {
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request).await;
+            let body = serde_json::to_string(&status).expect("Status should be serializable");
+            let status_line = if status.is_healthy() {
+                "200 OK"
+            } else {
+                "503 Service Unavailable"
+            };
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status_line,
+                body.len(),
+                body
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+        });
+    }
+}
@@ -0,0 +1,94 @@
+use api_verify::resources::redaction;
+use api_verify::resources::schema_diff::{self, SchemaChange};
+use async_trait::async_trait;
+use cucumber::{given, then, when, writer, World, WorldInit};
+use serde_json::Value;
+use std::convert::Infallible;
+
+/// Location the compared schemas are reported at, standing for the name of their file.
+const SCHEMA_LOCATION: &str = "schema";
+
+#[derive(Debug, WorldInit)]
+pub struct SchemaDiffWorld {
+    old_schema: Value,
+    new_schema: Value,
+    changes: Vec<SchemaChange>,
+}
+
+#[async_trait(?Send)]
+impl World for SchemaDiffWorld {
+    type Error = Infallible;
+
+    async fn new() -> Result<Self, Infallible> {
+        Ok(Self {
+            old_schema: Value::Null,
+            new_schema: Value::Null,
+            changes: Vec::new(),
+        })
+    }
+}
+
+fn parse_schema(schema: &str) -> Value {
+    serde_json::from_str(schema).unwrap_or_else(|err| panic!("Invalid schema {}: {}", schema, err))
+}
+
+#[given(regex = r"^the old schema is '(.*)'$")]
+fn setup_old_schema(world: &mut SchemaDiffWorld, schema: String) {
+    world.old_schema = parse_schema(&schema);
+}
+
+#[given(regex = r"^the new schema is '(.*)'$")]
+fn setup_new_schema(world: &mut SchemaDiffWorld, schema: String) {
+    world.new_schema = parse_schema(&schema);
+}
+
+#[when("I compare the schemas")]
+fn compare_schemas(world: &mut SchemaDiffWorld) {
+    world.changes.clear();
+    schema_diff::diff_schemas(
+        SCHEMA_LOCATION,
+        &world.old_schema,
+        &world.new_schema,
+        &mut world.changes,
+    );
+}
+
+#[then(regex = r#"^"(.+)" is reported as a (breaking|non-breaking) change: "(.+)"$"#)]
+fn verify_change(world: &mut SchemaDiffWorld, location: String, kind: String, description: String) {
+    let is_breaking = kind == "breaking";
+    let reported = world.changes.iter().any(|change| {
+        change.location == location
+            && change.is_breaking == is_breaking
+            && change.description.starts_with(&description)
+    });
+    let changes: Vec<String> = world.changes.iter().map(ToString::to_string).collect();
+    assert!(
+        reported,
+        "{} change of {} starting with {:?} is not reported; reported changes:\n{}",
+        kind,
+        location,
+        description,
+        changes.join("\n")
+    );
+}
+
+#[then(regex = r"^(\d+) changes are reported$")]
+fn verify_change_count(world: &mut SchemaDiffWorld, count: usize) {
+    let changes: Vec<String> = world.changes.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        world.changes.len(),
+        count,
+        "Reported changes:\n{}",
+        changes.join("\n")
+    );
+}
+
+#[tokio::main]
+async fn main() {
+    let report = redaction::ReportBuffer::default();
+    SchemaDiffWorld::cucumber()
+        .with_writer(writer::JUnit::new(report.clone(), 0))
+        .run("features/schema_diff")
+        .await;
+    redaction::write_redacted_report("/results/schema_diff.xml", &report);
+}
@@ -0,0 +1,91 @@
+use api_verify::resources::redaction;
+use api_verify::resources::request_handler::encryption;
+use api_verify::resources::signing::SignerConfig;
+use async_trait::async_trait;
+use cucumber::{given, then, when, writer, World, WorldInit};
+use std::convert::Infallible;
+
+/// Request components used by every scenario; only the signing conventions vary.
+const METHOD: &str = "POST";
+const HOST: &str = "api.example.com";
+const ENDPOINT_PATH: &str = "/0/private/Balance";
+
+#[derive(Debug, WorldInit)]
+pub struct SigningWorld {
+    signer_config: SignerConfig,
+    message: Option<Vec<u8>>,
+}
+
+#[async_trait(?Send)]
+impl World for SigningWorld {
+    type Error = Infallible;
+
+    async fn new() -> Result<Self, Infallible> {
+        Ok(Self {
+            signer_config: SignerConfig::default(),
+            message: None,
+        })
+    }
+}
+
+#[given(
+    regex = r#"^the API signs method "(true|false)", host "(true|false)" and path "(true|false)" with payload hash "(\w+)"$"#
+)]
+fn setup_signer_config(
+    world: &mut SigningWorld,
+    sign_method: String,
+    sign_host: String,
+    sign_path: String,
+    payload_hash: String,
+) {
+    world.signer_config.sign_method = sign_method == "true";
+    world.signer_config.sign_host = sign_host == "true";
+    world.signer_config.sign_path = sign_path == "true";
+    world.signer_config.payload_hash = payload_hash.parse().unwrap_or_else(|err| panic!("{}", err));
+}
+
+#[given(regex = r#"^the API signs header "([\w-]+)" with value "(.*)"$"#)]
+fn setup_signed_header(world: &mut SigningWorld, name: String, value: String) {
+    world.signer_config.signed_headers.push((name, value));
+}
+
+#[when(regex = r#"^I build the message for nonce "(\d+)" and payload "(.*)"$"#)]
+fn build_message(world: &mut SigningWorld, nonce: String, url_encoded_payload: String) {
+    let message = encryption::build_message(
+        &nonce,
+        &url_encoded_payload,
+        METHOD,
+        HOST,
+        ENDPOINT_PATH,
+        &world.signer_config,
+    );
+    world.message = Some(message);
+}
+
+#[then(regex = r#"^the message is "(.*)"$"#)]
+fn verify_message(world: &mut SigningWorld, expected_message: String) {
+    let message = world
+        .message
+        .take()
+        .expect("World should contain message at this point");
+    assert_eq!(String::from_utf8_lossy(&message), expected_message);
+}
+
+#[then(regex = r#"^the message in hex is "([0-9a-f]+)"$"#)]
+fn verify_message_hex(world: &mut SigningWorld, expected_message: String) {
+    let message = world
+        .message
+        .take()
+        .expect("World should contain message at this point");
+    assert_eq!(hex::encode(message), expected_message);
+}
+
+#[tokio::main]
+async fn main() {
+    let report = redaction::ReportBuffer::default();
+    SigningWorld::cucumber()
+        .with_writer(writer::JUnit::new(report.clone(), 0))
+        .run("features/signing")
+        .await;
+    redaction::write_redacted_report("/results/signing.xml", &report);
+}